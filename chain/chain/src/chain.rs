@@ -1769,7 +1769,12 @@ impl Chain {
         hash: &CryptoHash,
     ) -> Result<ExecutionOutcomeWithIdView, String> {
         match self.get_execution_outcome(hash) {
-            Ok(result) => Ok(result.clone().into()),
+            Ok(result) => {
+                let mut result: ExecutionOutcomeWithIdView = result.clone().into();
+                result.outcome.gas_profile =
+                    self.store.get_gas_profile(hash).map_err(|err| err.to_string())?;
+                Ok(result)
+            }
             Err(err) => match err.kind() {
                 ErrorKind::DBNotFoundErr(_) => Ok(ExecutionOutcomeWithIdAndProof {
                     outcome_with_id: ExecutionOutcomeWithId {
@@ -2455,6 +2460,14 @@ impl<'a> ChainUpdate<'a> {
                         apply_result.outcomes,
                         outcome_paths,
                     );
+                    self.chain_store_update.save_gas_profiles(apply_result.gas_profiles);
+                    self.chain_store_update.save_account_activity(
+                        chunk_header.height_included,
+                        &block.hash(),
+                        shard_id,
+                        &chunk.transactions,
+                        &receipts,
+                    );
                     self.chain_store_update.save_transactions(chunk.transactions);
                 } else {
                     let mut new_extra = self
@@ -3172,6 +3185,14 @@ impl<'a> ChainUpdate<'a> {
             apply_result.outcomes,
             outcome_proofs,
         );
+        self.chain_store_update.save_gas_profiles(apply_result.gas_profiles);
+        self.chain_store_update.save_account_activity(
+            chunk.header.height_included,
+            &block_header.hash,
+            shard_id,
+            &chunk.transactions,
+            &receipts,
+        );
         // Saving all incoming receipts.
         for receipt_proof_response in incoming_receipts_proofs {
             self.chain_store_update.save_incoming_receipt(
@@ -17,22 +17,22 @@ use near_primitives::sharding::{
     ChunkHash, EncodedShardChunk, PartialEncodedChunk, ReceiptProof, ShardChunk, ShardChunkHeader,
 };
 use near_primitives::transaction::{
-    ExecutionOutcomeWithId, ExecutionOutcomeWithIdAndProof, SignedTransaction,
+    ExecutionOutcomeWithId, ExecutionOutcomeWithIdAndProof, GasProfile, SignedTransaction,
 };
 use near_primitives::types::{
-    AccountId, BlockExtra, BlockHeight, ChunkExtra, EpochId, NumBlocks, ShardId, StateChangeCause,
-    StateChanges, StateChangesRequest,
+    AccountActivity, AccountId, BlockExtra, BlockHeight, ChunkExtra, EpochId, NumBlocks, ShardId,
+    StateChangeCause, StateChanges, StateChangesRequest,
 };
 use near_primitives::utils::{index_to_bytes, to_timestamp};
 use near_primitives::views::LightClientBlockView;
 use near_store::{
-    read_with_cache, ColBlock, ColBlockExtra, ColBlockHeader, ColBlockHeight, ColBlockMisc,
-    ColBlockPerHeight, ColBlocksToCatchup, ColChallengedBlocks, ColChunkExtra,
-    ColChunkPerHeightShard, ColChunks, ColEpochLightClientBlocks, ColIncomingReceipts,
-    ColInvalidChunks, ColKeyValueChanges, ColLastApprovalPerAccount, ColLastBlockWithNewChunk,
-    ColMyLastApprovalsPerChain, ColNextBlockHashes, ColNextBlockWithNewChunk, ColOutgoingReceipts,
-    ColPartialChunks, ColReceiptIdToShardId, ColStateDlInfos, ColTransactionResult,
-    ColTransactions, Store, StoreUpdate, WrappedTrieChanges,
+    read_with_cache, ColAccountActivity, ColBlock, ColBlockExtra, ColBlockHeader, ColBlockHeight,
+    ColBlockMisc, ColBlockPerHeight, ColBlocksToCatchup, ColChallengedBlocks, ColChunkExtra,
+    ColChunkPerHeightShard, ColChunks, ColEpochLightClientBlocks, ColGasProfiles,
+    ColIncomingReceipts, ColInvalidChunks, ColKeyValueChanges, ColLastApprovalPerAccount,
+    ColLastBlockWithNewChunk, ColMyLastApprovalsPerChain, ColNextBlockHashes,
+    ColNextBlockWithNewChunk, ColOutgoingReceipts, ColPartialChunks, ColReceiptIdToShardId,
+    ColStateDlInfos, ColTransactionResult, ColTransactions, Store, StoreUpdate, WrappedTrieChanges,
 };
 
 use crate::byzantine_assert;
@@ -51,6 +51,9 @@ const LARGEST_SKIPPED_HEIGHT_KEY: &[u8; 22] = b"LARGEST_SKIPPED_HEIGHT";
 /// lru cache size
 const CACHE_SIZE: usize = 100;
 const CHUNK_CACHE_SIZE: usize = 1024;
+/// Number of blocks to look for a receipt in before the block it was executed in, as it can be
+/// delayed.
+const RECEIPT_LOOKUP_DEPTH: usize = 100;
 
 #[derive(Debug, PartialEq, BorshSerialize, BorshDeserialize, Serialize)]
 pub struct ShardInfo(pub ShardId, pub ChunkHash);
@@ -69,6 +72,37 @@ fn get_height_shard_id(height: BlockHeight, shard_id: ShardId) -> Vec<u8> {
     res
 }
 
+/// Prefix of the account activity keys of the account. Account ids can't contain the separator, so
+/// the prefix of an account doesn't match the keys of its sub-accounts.
+fn get_account_activity_prefix(account_id: &AccountId) -> Vec<u8> {
+    let mut res = Vec::with_capacity(account_id.len() + 17);
+    res.extend_from_slice(account_id.as_bytes());
+    res.push(b':');
+    res
+}
+
+/// Key of the account activity in the blocks at the given height and below. The height is inverted
+/// and big endian, so the newest activity of the account comes first.
+fn get_account_activity_height_key(account_id: &AccountId, height: BlockHeight) -> Vec<u8> {
+    let mut res = get_account_activity_prefix(account_id);
+    res.extend_from_slice(&(BlockHeight::max_value() - height).to_be_bytes());
+    res
+}
+
+/// The activity is keyed by the block hash too, so the blocks of different forks at the same height
+/// don't overwrite each other.
+fn get_account_activity_key(
+    account_id: &AccountId,
+    height: BlockHeight,
+    block_hash: &CryptoHash,
+    shard_id: ShardId,
+) -> Vec<u8> {
+    let mut res = get_account_activity_height_key(account_id, height);
+    res.extend_from_slice(block_hash.as_ref());
+    res.extend_from_slice(&shard_id.to_be_bytes());
+    res
+}
+
 /// Contains the information that is used to sync state for shards as epochs switch
 #[derive(Debug, PartialEq, BorshSerialize, BorshDeserialize, Serialize)]
 pub struct StateSyncInfo {
@@ -387,6 +421,10 @@ pub struct ChainStore {
     last_block_with_new_chunk: SizedCache<Vec<u8>, CryptoHash>,
     /// Transactions
     transactions: SizedCache<Vec<u8>, SignedTransaction>,
+    /// Whether transactions and receipts of applied chunks are indexed by account.
+    account_activity_index: bool,
+    /// Whether the gas profiles of the execution outcomes are stored.
+    gas_profiling: bool,
 }
 
 pub fn option_to_not_found<T>(res: io::Result<Option<T>>, field_name: &str) -> Result<T, Error> {
@@ -424,6 +462,8 @@ impl ChainStore {
             next_block_with_new_chunk: SizedCache::with_size(CHUNK_CACHE_SIZE),
             last_block_with_new_chunk: SizedCache::with_size(CHUNK_CACHE_SIZE),
             transactions: SizedCache::with_size(CHUNK_CACHE_SIZE),
+            account_activity_index: false,
+            gas_profiling: false,
         }
     }
 
@@ -431,6 +471,114 @@ impl ChainStore {
         self.store.clone()
     }
 
+    /// Returns the receipt with the given id received by the shard, along with the proof of its
+    /// inclusion in the chunks of the block it was received in. The receipt is looked up in the
+    /// incoming receipts of the shard, starting from the block it was executed in, or from the head
+    /// if it isn't executed yet.
+    pub fn get_receipt_proof(
+        &mut self,
+        receipt_id: &CryptoHash,
+        shard_id: ShardId,
+    ) -> Result<Option<ReceiptProofResponse>, Error> {
+        let mut block_hash = match self.get_execution_outcome(receipt_id) {
+            Ok(outcome) => outcome.block_hash,
+            Err(err) => match err.kind() {
+                ErrorKind::DBNotFoundErr(_) => self.head()?.last_block_hash,
+                _ => return Err(err),
+            },
+        };
+        for _ in 0..RECEIPT_LOOKUP_DEPTH {
+            match self.get_incoming_receipts(&block_hash, shard_id) {
+                Ok(receipt_proofs) => {
+                    if let Some(receipt_proof) = receipt_proofs.iter().find(|receipt_proof| {
+                        receipt_proof.0.iter().any(|receipt| receipt.receipt_id == *receipt_id)
+                    }) {
+                        return Ok(Some(ReceiptProofResponse(
+                            block_hash,
+                            vec![receipt_proof.clone()],
+                        )));
+                    }
+                }
+                Err(err) => match err.kind() {
+                    ErrorKind::DBNotFoundErr(_) => {}
+                    _ => return Err(err),
+                },
+            }
+            block_hash = match self.get_block_header(&block_hash) {
+                Ok(header) => header.prev_hash,
+                // Reached the genesis or the garbage collected blocks.
+                Err(_) => break,
+            };
+        }
+        Ok(None)
+    }
+
+    /// Index transactions and receipts of the chunks applied from now on by account.
+    pub fn set_account_activity_index(&mut self, enabled: bool) {
+        self.account_activity_index = enabled;
+    }
+
+    /// Store the gas profiles of the outcomes of the chunks applied from now on.
+    pub fn set_gas_profiling(&mut self, enabled: bool) {
+        self.gas_profiling = enabled;
+    }
+
+    /// Returns the gas profile of the outcome with the given id, if it was recorded.
+    pub fn get_gas_profile(&self, id: &CryptoHash) -> Result<Option<GasProfile>, Error> {
+        self.store.get_ser(ColGasProfiles, id.as_ref()).map_err(|e| e.into())
+    }
+
+    /// Returns the activity of the account in at most `limit` blocks of the canonical chain below
+    /// `before_height`, starting from the newest block, together with the height to continue from
+    /// if there is more activity.
+    pub fn get_account_activity(
+        &self,
+        account_id: &AccountId,
+        before_height: Option<BlockHeight>,
+        limit: usize,
+    ) -> Result<(Vec<(BlockHeight, AccountActivity)>, Option<BlockHeight>), Error> {
+        let prefix = get_account_activity_prefix(account_id);
+        let start_key = match before_height {
+            Some(0) => return Ok((vec![], None)),
+            Some(height) => get_account_activity_height_key(account_id, height - 1),
+            None => prefix.clone(),
+        };
+        let mut result: Vec<(BlockHeight, AccountActivity)> = vec![];
+        let mut canonical_hash: Option<(BlockHeight, Option<CryptoHash>)> = None;
+        for item in self.store.iter_prefix_from_ser::<AccountActivity>(
+            ColAccountActivity,
+            &prefix,
+            &start_key,
+        ) {
+            let (key, activity) = item?;
+            let mut height_bytes = [0u8; 8];
+            height_bytes.copy_from_slice(&key[prefix.len()..prefix.len() + 8]);
+            let height = BlockHeight::max_value() - BlockHeight::from_be_bytes(height_bytes);
+            let block_hash = CryptoHash::try_from(&key[prefix.len() + 8..prefix.len() + 40])
+                .map_err(|err| ErrorKind::Other(err.to_string()))?;
+            if canonical_hash.map(|(canonical_height, _)| canonical_height) != Some(height) {
+                let hash = self.store.get_ser(ColBlockHeight, &index_to_bytes(height))?;
+                canonical_hash = Some((height, hash));
+            }
+            // Activity in the blocks of other forks.
+            if canonical_hash != Some((height, Some(block_hash))) {
+                continue;
+            }
+            match result.last_mut() {
+                // Activity in other shards of the same block.
+                Some((last_height, last_activity)) if *last_height == height => {
+                    last_activity.transactions.extend(activity.transactions);
+                    last_activity.receipts.extend(activity.receipts);
+                }
+                Some((last_height, _)) if result.len() >= limit => {
+                    return Ok((result, Some(*last_height)));
+                }
+                _ => result.push((height, activity)),
+            }
+        }
+        Ok((result, None))
+    }
+
     pub fn store_update(&mut self) -> ChainStoreUpdate {
         ChainStoreUpdate::new(self)
     }
@@ -993,6 +1141,8 @@ struct ChainStoreCacheUpdate {
     next_block_with_new_chunk: HashMap<(CryptoHash, ShardId), CryptoHash>,
     last_block_with_new_chunk: HashMap<ShardId, CryptoHash>,
     transactions: HashSet<SignedTransaction>,
+    account_activity: HashMap<(AccountId, BlockHeight, CryptoHash, ShardId), AccountActivity>,
+    gas_profiles: HashMap<CryptoHash, GasProfile>,
 }
 
 impl ChainStoreCacheUpdate {
@@ -1019,6 +1169,8 @@ impl ChainStoreCacheUpdate {
             next_block_with_new_chunk: Default::default(),
             last_block_with_new_chunk: Default::default(),
             transactions: Default::default(),
+            account_activity: Default::default(),
+            gas_profiles: Default::default(),
         }
     }
 }
@@ -1663,12 +1815,60 @@ impl<'a> ChainStoreUpdate<'a> {
         }
     }
 
+    /// Saves the gas profiles of the outcomes, if gas profiling is enabled.
+    pub fn save_gas_profiles(&mut self, gas_profiles: Vec<(CryptoHash, GasProfile)>) {
+        if !self.chain_store.gas_profiling {
+            return;
+        }
+        self.chain_store_cache_update.gas_profiles.extend(gas_profiles);
+    }
+
     pub fn save_transactions(&mut self, transactions: Vec<SignedTransaction>) {
         for transaction in transactions {
             self.chain_store_cache_update.transactions.insert(transaction);
         }
     }
 
+    /// Indexes the transactions and the incoming receipts of a chunk by the accounts involved, if
+    /// the account activity index is enabled.
+    pub fn save_account_activity(
+        &mut self,
+        height: BlockHeight,
+        block_hash: &CryptoHash,
+        shard_id: ShardId,
+        transactions: &[SignedTransaction],
+        receipts: &[Receipt],
+    ) {
+        if !self.chain_store.account_activity_index {
+            return;
+        }
+        let mut activity_by_account: HashMap<AccountId, AccountActivity> = HashMap::new();
+        for transaction in transactions {
+            let tx_hash = transaction.get_hash();
+            for account_id in
+                &[&transaction.transaction.signer_id, &transaction.transaction.receiver_id]
+            {
+                let activity = activity_by_account.entry((*account_id).clone()).or_default();
+                if activity.transactions.last() != Some(&tx_hash) {
+                    activity.transactions.push(tx_hash);
+                }
+            }
+        }
+        for receipt in receipts {
+            for account_id in &[&receipt.predecessor_id, &receipt.receiver_id] {
+                let activity = activity_by_account.entry((*account_id).clone()).or_default();
+                if activity.receipts.last() != Some(&receipt.receipt_id) {
+                    activity.receipts.push(receipt.receipt_id);
+                }
+            }
+        }
+        for (account_id, activity) in activity_by_account {
+            self.chain_store_cache_update
+                .account_activity
+                .insert((account_id, height, *block_hash, shard_id), activity);
+        }
+    }
+
     pub fn save_outcome_with_proof(
         &mut self,
         id: CryptoHash,
@@ -1988,6 +2188,9 @@ impl<'a> ChainStoreUpdate<'a> {
         for (hash, outcome) in self.chain_store_cache_update.outcomes.iter() {
             store_update.set_ser(ColTransactionResult, hash.as_ref(), outcome)?;
         }
+        for (id, gas_profile) in self.chain_store_cache_update.gas_profiles.iter() {
+            store_update.set_ser(ColGasProfiles, id.as_ref(), gas_profile)?;
+        }
         for (receipt_id, shard_id) in self.chain_store_cache_update.receipt_id_to_shard_id.iter() {
             store_update.set_ser(ColReceiptIdToShardId, receipt_id.as_ref(), shard_id)?;
         }
@@ -2011,6 +2214,15 @@ impl<'a> ChainStoreUpdate<'a> {
         for transaction in self.chain_store_cache_update.transactions.iter() {
             store_update.set_ser(ColTransactions, transaction.get_hash().as_ref(), transaction)?;
         }
+        for ((account_id, height, block_hash, shard_id), activity) in
+            self.chain_store_cache_update.account_activity.iter()
+        {
+            store_update.set_ser(
+                ColAccountActivity,
+                &get_account_activity_key(account_id, *height, block_hash, *shard_id),
+                activity,
+            )?;
+        }
         for trie_changes in self.trie_changes.drain(..) {
             trie_changes
                 .insertions_into(&mut store_update)
@@ -2125,6 +2337,8 @@ impl<'a> ChainStoreUpdate<'a> {
             next_block_with_new_chunk,
             last_block_with_new_chunk,
             transactions,
+            account_activity: _,
+            gas_profiles: _,
         } = self.chain_store_cache_update;
         for (hash, block) in blocks {
             self.chain_store.blocks.cache_set(hash.into(), block);
@@ -2217,14 +2431,17 @@ mod tests {
 
     use cached::Cached;
 
-    use near_crypto::KeyType;
+    use near_crypto::{InMemorySigner, KeyType};
     use near_primitives::block::Block;
     use near_primitives::errors::InvalidTxError;
-    use near_primitives::hash::hash;
+    use near_primitives::hash::{hash, CryptoHash};
+    use near_primitives::receipt::Receipt;
+    use near_primitives::transaction::SignedTransaction;
     use near_primitives::types::{BlockHeight, EpochId};
     use near_primitives::utils::index_to_bytes;
     use near_primitives::validator_signer::{InMemoryValidatorSigner, ValidatorSigner};
     use near_store::test_utils::create_test_store;
+    use near_store::ColBlockHeight;
 
     use crate::store::ChainStoreAccess;
     use crate::test_utils::KeyValueRuntime;
@@ -2439,6 +2656,66 @@ mod tests {
         assert_ne!(epoch_id_to_hash, epoch_id_to_hash1);
     }
 
+    #[test]
+    fn test_account_activity_pagination() {
+        let mut chain = get_chain();
+        chain.mut_store().set_account_activity_index(true);
+        let signer = InMemorySigner::from_seed("test1", KeyType::ED25519, "test1");
+        let send_money = |nonce, signer_id: &str, receiver_id: &str| {
+            SignedTransaction::send_money(
+                nonce,
+                signer_id.to_string(),
+                receiver_id.to_string(),
+                &signer,
+                1,
+                CryptoHash::default(),
+            )
+        };
+        let tx1 = send_money(1, "test1", "test2");
+        let tx2 = send_money(2, "test1", "test2");
+        let tx3 = send_money(3, "test2", "test2");
+        let tx4 = send_money(4, "test2", "test1");
+        let refund = Receipt::new_refund(&"test2".to_string(), 1);
+        let block_hashes: Vec<_> = (0..4u8).map(|i| hash(&[i])).collect();
+        let fork_hash = hash(b"fork");
+        let mut store_update = chain.mut_store().store_update();
+        store_update.save_account_activity(1, &block_hashes[1], 0, &[tx1.clone()], &[]);
+        store_update.save_account_activity(2, &block_hashes[2], 0, &[tx2.clone()], &[]);
+        store_update.save_account_activity(2, &block_hashes[2], 1, &[tx3.clone()], &[]);
+        store_update.save_account_activity(2, &fork_hash, 0, &[tx4.clone()], &[]);
+        store_update.save_account_activity(3, &block_hashes[3], 0, &[], &[refund.clone()]);
+        store_update.commit().unwrap();
+        let mut store_update = chain.store().store().store_update();
+        for height in 1..4 {
+            store_update
+                .set_ser(ColBlockHeight, &index_to_bytes(height), &block_hashes[height as usize])
+                .unwrap();
+        }
+        store_update.commit().unwrap();
+
+        let account_id = "test2".to_string();
+        let (activity, next) = chain.store().get_account_activity(&account_id, None, 2).unwrap();
+        assert_eq!(activity.iter().map(|(height, _)| *height).collect::<Vec<_>>(), vec![3, 2]);
+        assert_eq!(activity[0].1.receipts, vec![refund.receipt_id]);
+        // Activity in both shards, with the transaction to self indexed once, and without the
+        // activity in the other fork.
+        assert_eq!(activity[1].1.transactions, vec![tx2.get_hash(), tx3.get_hash()]);
+        assert_eq!(next, Some(2));
+
+        let (activity, next) = chain.store().get_account_activity(&account_id, next, 2).unwrap();
+        assert_eq!(activity.len(), 1);
+        assert_eq!(activity[0].0, 1);
+        assert_eq!(activity[0].1.transactions, vec![tx1.get_hash()]);
+        assert_eq!(next, None);
+
+        let (activity, _) = chain.store().get_account_activity(&account_id, Some(1), 2).unwrap();
+        assert!(activity.is_empty());
+
+        let (activity, _) =
+            chain.store().get_account_activity(&"test".to_string(), None, 10).unwrap();
+        assert!(activity.is_empty());
+    }
+
     #[test]
     fn test_clear_old_data_fixed_height() {
         let mut chain = get_chain();
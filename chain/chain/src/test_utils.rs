@@ -29,7 +29,7 @@ use near_primitives::types::{
 use near_primitives::validator_signer::InMemoryValidatorSigner;
 use near_primitives::views::{
     AccessKeyInfoView, AccessKeyList, CallResult, EpochValidatorInfo, QueryRequest, QueryResponse,
-    QueryResponseKind, ViewStateResult,
+    QueryResponseKind, TrackedShardsView, TransactionSimulationView, ViewStateResult,
 };
 use near_store::test_utils::create_test_store;
 use near_store::{
@@ -87,7 +87,7 @@ struct ReceiptNonce {
     nonce: Nonce,
 }
 
-fn create_receipt_nonce(from: String, to: String, amount: Balance, nonce: Nonce) -> CryptoHash {
+pub fn create_receipt_nonce(from: String, to: String, amount: Balance, nonce: Nonce) -> CryptoHash {
     hash(&ReceiptNonce { from, to, amount, nonce }.try_to_vec().unwrap())
 }
 
@@ -432,6 +432,26 @@ impl RuntimeAdapter for KeyValueRuntime {
         false
     }
 
+    fn track_accounts_and_shards(
+        &self,
+        _block_hash: &CryptoHash,
+        _accounts: Vec<AccountId>,
+        _shards: Vec<ShardId>,
+    ) -> Result<TrackedShardsView, Error> {
+        Err(ErrorKind::Other("Tracking is defined by validators in KeyValueRuntime".to_string())
+            .into())
+    }
+
+    fn untrack_accounts_and_shards(
+        &self,
+        _block_hash: &CryptoHash,
+        _accounts: Vec<AccountId>,
+        _shards: Vec<ShardId>,
+    ) -> Result<TrackedShardsView, Error> {
+        Err(ErrorKind::Other("Tracking is defined by validators in KeyValueRuntime".to_string())
+            .into())
+    }
+
     fn validate_tx(
         &self,
         _block_height: BlockHeight,
@@ -644,6 +664,7 @@ impl RuntimeAdapter for KeyValueRuntime {
             ),
             new_root: state_root,
             outcomes: tx_results,
+            gas_profiles: vec![],
             receipt_result: new_receipts,
             validator_proposals: vec![],
             total_gas_burnt: 0,
@@ -722,14 +743,16 @@ impl RuntimeAdapter for KeyValueRuntime {
                 block_height,
                 block_hash: *block_hash,
             }),
-            QueryRequest::CallFunction { .. } => Ok(QueryResponse {
-                kind: QueryResponseKind::CallResult(CallResult {
-                    result: Default::default(),
-                    logs: Default::default(),
-                }),
-                block_height,
-                block_hash: *block_hash,
-            }),
+            QueryRequest::CallFunction { .. } | QueryRequest::CallFunctionWithCaller { .. } => {
+                Ok(QueryResponse {
+                    kind: QueryResponseKind::CallResult(CallResult {
+                        result: Default::default(),
+                        logs: Default::default(),
+                    }),
+                    block_height,
+                    block_hash: *block_hash,
+                })
+            }
         }
     }
 
@@ -834,6 +857,21 @@ impl RuntimeAdapter for KeyValueRuntime {
         Ok(0)
     }
 
+    fn simulate_transaction(
+        &self,
+        _shard_id: ShardId,
+        _state_root: &StateRoot,
+        _block_height: BlockHeight,
+        _block_timestamp: u64,
+        _block_hash: &CryptoHash,
+        _gas_price: Balance,
+        _gas_limit: Gas,
+        _transaction: &SignedTransaction,
+        _verify_signature: bool,
+    ) -> Result<TransactionSimulationView, Box<dyn std::error::Error>> {
+        Err("Transaction simulation is not supported by KeyValueRuntime".into())
+    }
+
     fn get_validator_info(&self, _block_hash: &CryptoHash) -> Result<EpochValidatorInfo, Error> {
         Ok(EpochValidatorInfo {
             current_validators: vec![],
@@ -14,12 +14,14 @@ use near_primitives::hash::{hash, CryptoHash};
 use near_primitives::merkle::{merklize, MerklePath};
 use near_primitives::receipt::Receipt;
 use near_primitives::sharding::{ReceiptProof, ShardChunk, ShardChunkHeader};
-use near_primitives::transaction::{ExecutionOutcomeWithId, SignedTransaction};
+use near_primitives::transaction::{ExecutionOutcomeWithId, GasProfile, SignedTransaction};
 use near_primitives::types::{
     AccountId, Balance, BlockHeight, EpochId, Gas, MerkleHash, ShardId, StateChanges,
     StateChangesRequest, StateRoot, StateRootNode, ValidatorStake, ValidatorStats,
 };
-use near_primitives::views::{EpochValidatorInfo, QueryRequest, QueryResponse};
+use near_primitives::views::{
+    EpochValidatorInfo, QueryRequest, QueryResponse, TrackedShardsView, TransactionSimulationView,
+};
 use near_store::{PartialStorage, StoreUpdate, WrappedTrieChanges};
 
 use crate::error::Error;
@@ -86,6 +88,9 @@ pub struct ApplyTransactionResult {
     pub trie_changes: WrappedTrieChanges,
     pub new_root: StateRoot,
     pub outcomes: Vec<ExecutionOutcomeWithId>,
+    /// Gas profiles of the outcomes by outcome id. Not a part of the outcomes, so they don't
+    /// affect the outcome root and are only stored if gas profiling is enabled.
+    pub gas_profiles: Vec<(CryptoHash, GasProfile)>,
     pub receipt_result: ReceiptResult,
     pub validator_proposals: Vec<ValidatorStake>,
     pub total_gas_burnt: Gas,
@@ -281,6 +286,25 @@ pub trait RuntimeAdapter: Send + Sync {
         is_me: bool,
     ) -> bool;
 
+    /// Starts tracking the given accounts and shards, as requested at the given block. Their
+    /// state is caught up during the next epoch, so they are tracked from the epoch after it.
+    /// Returns what is tracked once all the requested changes take effect.
+    fn track_accounts_and_shards(
+        &self,
+        block_hash: &CryptoHash,
+        accounts: Vec<AccountId>,
+        shards: Vec<ShardId>,
+    ) -> Result<TrackedShardsView, Error>;
+
+    /// Stops tracking the given accounts and shards from the epoch after the given block.
+    /// Returns what is tracked once all the requested changes take effect.
+    fn untrack_accounts_and_shards(
+        &self,
+        block_hash: &CryptoHash,
+        accounts: Vec<AccountId>,
+        shards: Vec<ShardId>,
+    ) -> Result<TrackedShardsView, Error>;
+
     /// Returns true, if given hash is last block in it's epoch.
     fn is_next_block_epoch_start(&self, parent_hash: &CryptoHash) -> Result<bool, Error>;
 
@@ -397,6 +421,22 @@ pub trait RuntimeAdapter: Send + Sync {
         request: &QueryRequest,
     ) -> Result<QueryResponse, Box<dyn std::error::Error>>;
 
+    /// Executes the transaction on top of the given state of the shard and follows the receipts
+    /// it generates for the accounts of the same shard, burning at most `gas_limit` in total.
+    /// Nothing is persisted.
+    fn simulate_transaction(
+        &self,
+        shard_id: ShardId,
+        state_root: &StateRoot,
+        block_height: BlockHeight,
+        block_timestamp: u64,
+        block_hash: &CryptoHash,
+        gas_price: Balance,
+        gas_limit: Gas,
+        transaction: &SignedTransaction,
+        verify_signature: bool,
+    ) -> Result<TransactionSimulationView, Box<dyn std::error::Error>>;
+
     fn get_validator_info(&self, block_hash: &CryptoHash) -> Result<EpochValidatorInfo, Error>;
 
     /// Get the part of the state from given state root.
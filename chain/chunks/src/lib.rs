@@ -278,6 +278,10 @@ impl ShardsManager {
         self.tx_pools.get_mut(&shard_id).map(|pool| pool.pool_iterator())
     }
 
+    pub fn tx_pools(&self) -> &HashMap<ShardId, TransactionPool> {
+        &self.tx_pools
+    }
+
     pub fn cares_about_shard_this_or_next_epoch(
         &self,
         account_id: Option<&AccountId>,
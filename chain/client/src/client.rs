@@ -91,12 +91,14 @@ impl Client {
         } else {
             DoomslugThresholdMode::NoApprovals
         };
-        let chain = Chain::new(
+        let mut chain = Chain::new(
             store.clone(),
             runtime_adapter.clone(),
             &chain_genesis,
             doomslug_threshold_mode,
         )?;
+        chain.mut_store().set_account_activity_index(config.account_activity_index);
+        chain.mut_store().set_gas_profiling(config.gas_profiling);
         let shards_mgr = ShardsManager::new(
             validator_signer.as_ref().map(|x| x.validator_id().clone()),
             runtime_adapter.clone(),
@@ -5,8 +5,9 @@ use std::sync::{Arc, RwLock};
 use std::thread;
 use std::time::{Duration, Instant};
 
-use actix::{Actor, Addr, AsyncContext, Context, Handler};
+use actix::{Actor, ActorContext, Addr, AsyncContext, Context, Handler, ResponseFuture};
 use chrono::{DateTime, Utc};
+use futures::TryFutureExt;
 use log::{debug, error, info, warn};
 
 use near_chain::test_utils::format_hash;
@@ -24,6 +25,7 @@ use near_network::types::NetworkAdversarialMessage::{
 use near_network::types::{NetworkInfo, ReasonForBan, StateResponseInfo};
 use near_network::{
     NetworkAdapter, NetworkClientMessages, NetworkClientResponses, NetworkRequests,
+    NetworkResponses,
 };
 use near_primitives::hash::CryptoHash;
 use near_primitives::network::{AnnounceAccount, PeerId};
@@ -31,7 +33,9 @@ use near_primitives::types::{BlockHeight, EpochId};
 use near_primitives::unwrap_or_return;
 use near_primitives::utils::from_timestamp;
 use near_primitives::validator_signer::ValidatorSigner;
-use near_primitives::views::ValidatorInfo;
+use near_primitives::views::{
+    NetworkInfoView, SignedTransactionView, TrackedShardsView, TransactionPoolView, ValidatorInfo,
+};
 #[cfg(feature = "adversarial")]
 use near_store::ColBlock;
 use near_store::Store;
@@ -41,8 +45,8 @@ use crate::client::Client;
 use crate::info::InfoHelper;
 use crate::sync::{highest_height_peer, StateSync, StateSyncResult};
 use crate::types::{
-    Error, GetNetworkInfo, NetworkInfoResponse, ShardSyncDownload, ShardSyncStatus, Status,
-    StatusSyncInfo, SyncStatus,
+    ChangeTracking, ClearOldData, Error, GetNetworkInfo, GetTransactionPool, ManagePeers,
+    ShardSyncDownload, ShardSyncStatus, Shutdown, Status, StatusSyncInfo, SyncStatus,
 };
 use crate::StatusResponse;
 
@@ -456,26 +460,121 @@ impl Handler<Status> for ClientActor {
 }
 
 impl Handler<GetNetworkInfo> for ClientActor {
-    type Result = Result<NetworkInfoResponse, String>;
+    type Result = Result<NetworkInfoView, String>;
 
     fn handle(&mut self, _: GetNetworkInfo, _: &mut Context<Self>) -> Self::Result {
-        Ok(NetworkInfoResponse {
+        Ok(NetworkInfoView {
             active_peers: self
                 .network_info
                 .active_peers
-                .clone()
-                .into_iter()
-                .map(|a| a.peer_info.clone())
-                .collect::<Vec<_>>(),
+                .iter()
+                .map(|full_peer_info| full_peer_info.peer_info.clone().into())
+                .collect(),
             num_active_peers: self.network_info.num_active_peers,
             peer_max_count: self.network_info.peer_max_count,
             sent_bytes_per_sec: self.network_info.sent_bytes_per_sec,
             received_bytes_per_sec: self.network_info.received_bytes_per_sec,
-            known_producers: self.network_info.known_producers.clone(),
+            known_producers: self
+                .network_info
+                .known_producers
+                .iter()
+                .cloned()
+                .map(Into::into)
+                .collect(),
         })
     }
 }
 
+impl Handler<ChangeTracking> for ClientActor {
+    type Result = Result<TrackedShardsView, String>;
+
+    fn handle(&mut self, msg: ChangeTracking, _: &mut Context<Self>) -> Self::Result {
+        let head = self.client.chain.head().map_err(|err| err.to_string())?;
+        let runtime_adapter = &self.client.runtime_adapter;
+        match msg {
+            ChangeTracking::Track { accounts, shards } => {
+                info!(
+                    target: "client",
+                    "Start tracking accounts {:?} and shards {:?}",
+                    accounts,
+                    shards
+                );
+                runtime_adapter.track_accounts_and_shards(&head.last_block_hash, accounts, shards)
+            }
+            ChangeTracking::Untrack { accounts, shards } => {
+                info!(
+                    target: "client",
+                    "Stop tracking accounts {:?} and shards {:?}",
+                    accounts,
+                    shards
+                );
+                runtime_adapter.untrack_accounts_and_shards(&head.last_block_hash, accounts, shards)
+            }
+        }
+        .map_err(|err| err.to_string())
+    }
+}
+
+impl Handler<ManagePeers> for ClientActor {
+    type Result = ResponseFuture<Result<NetworkResponses, String>>;
+
+    fn handle(&mut self, msg: ManagePeers, _: &mut Context<Self>) -> Self::Result {
+        let request = match msg {
+            ManagePeers::FetchBans => NetworkRequests::FetchBans,
+            ManagePeers::Ban(target) => NetworkRequests::Ban(target),
+            ManagePeers::Unban(target) => NetworkRequests::Unban(target),
+        };
+        Box::pin(self.network_adapter.send(request).map_err(|err| err.to_string()))
+    }
+}
+
+impl Handler<ClearOldData> for ClientActor {
+    type Result = Result<(), String>;
+
+    fn handle(&mut self, _: ClearOldData, _: &mut Context<Self>) -> Self::Result {
+        if self.client.config.archive {
+            return Err("Archival nodes keep all the data".to_string());
+        }
+        info!(target: "client", "Clearing old data");
+        self.client.chain.clear_old_data().map_err(|err| err.to_string())
+    }
+}
+
+impl Handler<Shutdown> for ClientActor {
+    type Result = Result<(), String>;
+
+    fn handle(&mut self, _: Shutdown, ctx: &mut Context<Self>) -> Self::Result {
+        info!(target: "client", "Stopping the client");
+        ctx.stop();
+        self.client.chain.store().store().flush().map_err(|err| err.to_string())
+    }
+}
+
+impl Handler<GetTransactionPool> for ClientActor {
+    type Result = Result<Vec<TransactionPoolView>, String>;
+
+    fn handle(&mut self, _: GetTransactionPool, _: &mut Context<Self>) -> Self::Result {
+        let mut pools: Vec<_> = self
+            .client
+            .shards_mgr
+            .tx_pools()
+            .iter()
+            .map(|(shard_id, pool)| TransactionPoolView {
+                shard_id: *shard_id,
+                transactions: pool
+                    .transactions
+                    .values()
+                    .flatten()
+                    .cloned()
+                    .map(SignedTransactionView::from)
+                    .collect(),
+            })
+            .collect();
+        pools.sort_by_key(|pool| pool.shard_id);
+        Ok(pools)
+    }
+}
+
 impl ClientActor {
     fn sign_announce_account(&self, epoch_id: &EpochId) -> Result<Signature, ()> {
         if let Some(validator_signer) = self.client.validator_signer.as_ref() {
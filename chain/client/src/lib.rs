@@ -4,8 +4,10 @@ extern crate lazy_static;
 pub use crate::client::Client;
 pub use crate::client_actor::ClientActor;
 pub use crate::types::{
-    Error, GetBlock, GetChunk, GetGasPrice, GetKeyValueChanges, GetNetworkInfo,
-    GetNextLightClientBlock, GetValidatorInfo, Query, Status, StatusResponse, SyncStatus, TxStatus,
+    ChangeTracking, ClearOldData, Error, GetAccountActivity, GetBlock, GetChunk, GetGasPrice,
+    GetKeyValueChanges, GetNetworkInfo, GetNextLightClientBlock, GetReceipt, GetReceiptOutcome,
+    GetTransactionPool, GetValidatorInfo, ManagePeers, Query, Shutdown, SimulateTransaction,
+    Status, StatusResponse, SyncStatus, TxStatus,
 };
 pub use crate::view_client::ViewClientActor;
 
@@ -41,12 +41,13 @@ pub const STATE_SYNC_TIMEOUT: i64 = 10;
 
 pub const NS_PER_SECOND: u128 = 1_000_000_000;
 
-/// Get random peer from the hightest height peers.
+/// Get random peer from the better scored half of the highest height peers.
+/// Highest height peers are expected to be sorted by score from the best.
 pub fn highest_height_peer(highest_height_peers: &Vec<FullPeerInfo>) -> Option<FullPeerInfo> {
     if highest_height_peers.len() == 0 {
         return None;
     }
-    let index = thread_rng().gen_range(0, highest_height_peers.len());
+    let index = thread_rng().gen_range(0, (highest_height_peers.len() + 1) / 2);
     Some(highest_height_peers[index].clone())
 }
 
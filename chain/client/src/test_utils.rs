@@ -602,7 +602,7 @@ pub fn setup_mock_all_validators(
                                 aa.insert(key);
                                 for (_, view_client) in connectors1.read().unwrap().iter() {
                                     view_client.do_send(NetworkViewClientMessages::AnnounceAccount(
-                                        vec![(announce_account.clone(), None)],
+                                        vec![(announce_account.clone(), vec![])],
                                     ))
                                 }
                             }
@@ -699,6 +699,41 @@ pub fn setup_mock_all_validators(
                                 }
                             }
                         }
+                        NetworkRequests::ReceiptRequest(target, receipt_id, shard_id) => {
+                            for (i, name) in validators_clone2.iter().flatten().enumerate() {
+                                if name == target {
+                                    let connectors2 = connectors1.clone();
+                                    actix::spawn(
+                                        connectors1.read().unwrap()[i]
+                                            .1
+                                            .send(NetworkViewClientMessages::ReceiptRequest {
+                                                receipt_id: *receipt_id,
+                                                shard_id: *shard_id,
+                                            })
+                                            .then(move |response| {
+                                                let response = response.unwrap();
+                                                match response {
+                                                    NetworkViewClientResponses::ReceiptResponse(
+                                                        receipt_id,
+                                                        response,
+                                                    ) => {
+                                                        connectors2.read().unwrap()[my_ord]
+                                                            .1
+                                                            .do_send(
+                                                            NetworkViewClientMessages::ReceiptResponse(
+                                                                receipt_id, response,
+                                                            ),
+                                                        );
+                                                    }
+                                                    NetworkViewClientResponses::NoResponse => {}
+                                                    _ => assert!(false),
+                                                }
+                                                future::ready(())
+                                            }),
+                                    );
+                                }
+                            }
+                        }
                         NetworkRequests::ForwardTx(_, _)
                         | NetworkRequests::Sync { .. }
                         | NetworkRequests::FetchRoutingTable
@@ -6,17 +6,20 @@ use actix::Message;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use near_network::types::{AccountOrPeerIdOrHash, KnownProducer};
-use near_network::PeerInfo;
+use near_network::types::{AccountOrPeerIdOrHash, BanTarget};
+use near_network::NetworkResponses;
 use near_primitives::hash::CryptoHash;
 use near_primitives::sharding::ChunkHash;
+use near_primitives::transaction::SignedTransaction;
 use near_primitives::types::{
     AccountId, BlockHeight, BlockId, MaybeBlockId, ShardId, StateChanges, StateChangesRequest,
 };
 use near_primitives::utils::generate_random_string;
 use near_primitives::views::{
-    BlockView, ChunkView, EpochValidatorInfo, FinalExecutionOutcomeView, Finality, GasPriceView,
-    LightClientBlockView, QueryRequest, QueryResponse,
+    AccountActivityPageView, BlockView, ChunkView, EpochValidatorInfo, ExecutionOutcomeWithIdView,
+    FinalExecutionOutcomeView, Finality, GasPriceView, LightClientBlockView, NetworkInfoView,
+    QueryRequest, QueryResponse, ReceiptView, TrackedShardsView, TransactionPoolView,
+    TransactionSimulationView,
 };
 pub use near_primitives::views::{StatusResponse, StatusSyncInfo};
 
@@ -200,7 +203,49 @@ impl Message for GetNextLightClientBlock {
 pub struct GetNetworkInfo {}
 
 impl Message for GetNetworkInfo {
-    type Result = Result<NetworkInfoResponse, String>;
+    type Result = Result<NetworkInfoView, String>;
+}
+
+/// Changes the accounts and shards tracked by the node, starting from the current head.
+pub enum ChangeTracking {
+    Track { accounts: Vec<AccountId>, shards: Vec<ShardId> },
+    Untrack { accounts: Vec<AccountId>, shards: Vec<ShardId> },
+}
+
+impl Message for ChangeTracking {
+    type Result = Result<TrackedShardsView, String>;
+}
+
+/// Peer management requests of the node operator, forwarded to the peer manager.
+pub enum ManagePeers {
+    FetchBans,
+    Ban(BanTarget),
+    Unban(BanTarget),
+}
+
+impl Message for ManagePeers {
+    type Result = Result<NetworkResponses, String>;
+}
+
+/// Removes the data of old epochs from the storage right away instead of waiting for a new head.
+pub struct ClearOldData;
+
+impl Message for ClearOldData {
+    type Result = Result<(), String>;
+}
+
+/// Stops the actor before the node shuts down. The client flushes the store first.
+pub struct Shutdown;
+
+impl Message for Shutdown {
+    type Result = Result<(), String>;
+}
+
+/// Dumps the transactions in the pools of all the shards.
+pub struct GetTransactionPool;
+
+impl Message for GetTransactionPool {
+    type Result = Result<Vec<TransactionPoolView>, String>;
 }
 
 pub struct GetGasPrice {
@@ -211,15 +256,53 @@ impl Message for GetGasPrice {
     type Result = Result<GasPriceView, String>;
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct NetworkInfoResponse {
-    pub active_peers: Vec<PeerInfo>,
-    pub num_active_peers: usize,
-    pub peer_max_count: u32,
-    pub sent_bytes_per_sec: u64,
-    pub received_bytes_per_sec: u64,
-    /// Accounts of known block and chunk producers from routing table.
-    pub known_producers: Vec<KnownProducer>,
+/// Executes the transaction on top of the given block without applying or broadcasting it.
+pub struct SimulateTransaction {
+    pub transaction: SignedTransaction,
+    pub block_id: MaybeBlockId,
+    pub finality: Finality,
+    /// Whether the signature of the transaction has to be valid.
+    pub verify_signature: bool,
+}
+
+impl Message for SimulateTransaction {
+    type Result = Result<TransactionSimulationView, String>;
+}
+
+/// Transactions and receipts of the account, newest blocks first.
+pub struct GetAccountActivity {
+    pub account_id: AccountId,
+    /// Only blocks below this height are returned.
+    pub before_height: Option<BlockHeight>,
+    /// Maximum number of blocks returned.
+    pub limit: u64,
+}
+
+impl Message for GetAccountActivity {
+    type Result = Result<AccountActivityPageView, String>;
+}
+
+/// Receipt by id. Requested from a node tracking the shard of the receipt if this node doesn't.
+pub struct GetReceipt {
+    pub receipt_id: CryptoHash,
+    /// Used to find the shard of the receipt if the node doesn't know it.
+    pub receiver_id: Option<AccountId>,
+}
+
+impl Message for GetReceipt {
+    type Result = Result<Option<ReceiptView>, String>;
+}
+
+/// Execution outcome of the receipt with its proof and the block it was executed in.
+/// Requested from a node tracking the shard of the receipt if this node doesn't.
+pub struct GetReceiptOutcome {
+    pub receipt_id: CryptoHash,
+    /// Used to find the shard of the receipt if the node doesn't know it.
+    pub receiver_id: Option<AccountId>,
+}
+
+impl Message for GetReceiptOutcome {
+    type Result = Result<Option<ExecutionOutcomeWithIdView>, String>;
 }
 
 /// Status of given transaction including all the subsequent receipts.
@@ -1,16 +1,17 @@
 //! Readonly view of the chain and state of the database.
 //! Useful for querying from RPC.
 
-use std::cmp::Ordering;
+use std::cmp::{max, min, Ordering};
 use std::hash::Hash;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-use actix::{Actor, Context, Handler};
+use actix::{Actor, ActorContext, Context, Handler};
+use borsh::BorshSerialize;
 use cached::{Cached, SizedCache};
 use log::{error, info, warn};
 
-use near_chain::types::ShardStateSyncResponse;
+use near_chain::types::{ReceiptList, ReceiptProofResponse, ShardStateSyncResponse};
 use near_chain::{
     Chain, ChainGenesis, ChainStoreAccess, DoomslugThresholdMode, ErrorKind, RuntimeAdapter, Tip,
 };
@@ -22,23 +23,34 @@ use near_network::types::{
 };
 use near_network::{NetworkAdapter, NetworkRequests};
 use near_primitives::block::{BlockHeader, BlockScore, GenesisId};
-use near_primitives::hash::CryptoHash;
+use near_primitives::hash::{hash, CryptoHash};
 use near_primitives::merkle::verify_path;
 use near_primitives::network::AnnounceAccount;
-use near_primitives::types::{AccountId, BlockHeight, BlockId, MaybeBlockId, StateChanges};
+use near_primitives::receipt::Receipt;
+use near_primitives::sharding::{ReceiptProof, ShardProof};
+use near_primitives::types::{
+    AccountId, BlockHeight, BlockId, MaybeBlockId, ShardId, StateChanges,
+};
 use near_primitives::views::{
-    BlockView, ChunkView, EpochValidatorInfo, FinalExecutionOutcomeView, FinalExecutionStatus,
-    Finality, GasPriceView, LightClientBlockView, QueryRequest, QueryResponse,
+    AccountActivityPageView, AccountActivityView, BlockView, ChunkView, EpochValidatorInfo,
+    ExecutionOutcomeWithIdView, FinalExecutionOutcomeView, FinalExecutionStatus, Finality,
+    GasPriceView, LightClientBlockView, QueryRequest, QueryResponse, ReceiptView,
+    TransactionSimulationView,
 };
 use near_store::Store;
 
-use crate::types::{Error, GetBlock, GetGasPrice, Query, TxStatus};
+use crate::types::{
+    Error, GetAccountActivity, GetBlock, GetGasPrice, GetReceipt, GetReceiptOutcome, Query,
+    Shutdown, SimulateTransaction, TxStatus,
+};
 use crate::{sync, GetChunk, GetKeyValueChanges, GetNextLightClientBlock, GetValidatorInfo};
 
 /// Max number of queries that we keep.
 const QUERY_REQUEST_LIMIT: usize = 500;
 /// Waiting time between requests, in ms
 const REQUEST_WAIT_TIME: u64 = 1000;
+/// Max number of blocks returned by a single account activity request.
+const MAX_ACCOUNT_ACTIVITY_LIMIT: u64 = 100;
 
 /// View client provides currently committed (to the storage) view of the current chain and state.
 pub struct ViewClientActor {
@@ -61,6 +73,12 @@ pub struct ViewClientActor {
     pub query_responses: SizedCache<String, Result<QueryResponse, String>>,
     /// Receipt outcome requests
     pub receipt_outcome_requests: SizedCache<CryptoHash, Instant>,
+    /// Shards of the requested receipt outcomes that are not known to the chain
+    pub receipt_outcome_shard_ids: SizedCache<CryptoHash, ShardId>,
+    /// Receipt requests that need to be forwarded to other shards
+    pub receipt_requests: SizedCache<CryptoHash, Instant>,
+    /// Receipt responses from other nodes
+    pub receipt_responses: SizedCache<CryptoHash, Receipt>,
 }
 
 impl ViewClientActor {
@@ -102,6 +120,9 @@ impl ViewClientActor {
             query_requests: SizedCache::with_size(QUERY_REQUEST_LIMIT),
             query_responses: SizedCache::with_size(QUERY_REQUEST_LIMIT),
             receipt_outcome_requests: SizedCache::with_size(QUERY_REQUEST_LIMIT),
+            receipt_outcome_shard_ids: SizedCache::with_size(QUERY_REQUEST_LIMIT),
+            receipt_requests: SizedCache::with_size(QUERY_REQUEST_LIMIT),
+            receipt_responses: SizedCache::with_size(QUERY_REQUEST_LIMIT),
         })
     }
 
@@ -160,6 +181,7 @@ impl ViewClientActor {
             QueryRequest::ViewAccessKey { account_id, .. } => account_id,
             QueryRequest::ViewAccessKeyList { account_id, .. } => account_id,
             QueryRequest::CallFunction { account_id, .. } => account_id,
+            QueryRequest::CallFunctionWithCaller { account_id, .. } => account_id,
         };
         let shard_id = self.runtime_adapter.account_id_to_shard_id(account_id);
 
@@ -276,6 +298,121 @@ impl ViewClientActor {
         Ok(None)
     }
 
+    /// Returns the shard of the receipt and whether this node tracks it.
+    fn get_receipt_shard_id(
+        &mut self,
+        receipt_id: &CryptoHash,
+        receiver_id: Option<&AccountId>,
+    ) -> Result<(ShardId, bool), String> {
+        let shard_id = match self.chain.get_shard_id_for_receipt_id(receipt_id) {
+            Ok(&shard_id) => shard_id,
+            Err(_) => match receiver_id {
+                Some(receiver_id) => self.runtime_adapter.account_id_to_shard_id(receiver_id),
+                None => {
+                    return Err(format!(
+                        "Shard of receipt {} is unknown, receiver_id is required",
+                        receipt_id
+                    ))
+                }
+            },
+        };
+        let head = self.chain.head().map_err(|e| e.to_string())?;
+        let tracked = self.chain.get_chunk_extra(&head.last_block_hash, shard_id).is_ok();
+        Ok((shard_id, tracked))
+    }
+
+    fn get_receipt(
+        &mut self,
+        receipt_id: CryptoHash,
+        receiver_id: Option<AccountId>,
+    ) -> Result<Option<ReceiptView>, String> {
+        if let Some(receipt) = self.receipt_responses.cache_remove(&receipt_id) {
+            self.receipt_requests.cache_remove(&receipt_id);
+            return Ok(Some(receipt.into()));
+        }
+        let (shard_id, tracked) = self.get_receipt_shard_id(&receipt_id, receiver_id.as_ref())?;
+        if tracked {
+            return self
+                .chain
+                .mut_store()
+                .get_receipt_proof(&receipt_id, shard_id)
+                .map_err(|e| e.to_string())?
+                .and_then(|ReceiptProofResponse(_, receipt_proofs)| {
+                    receipt_proofs
+                        .into_iter()
+                        .flat_map(|ReceiptProof(receipts, _)| receipts)
+                        .find(|receipt| receipt.receipt_id == receipt_id)
+                })
+                .map(|receipt| Some(receipt.into()))
+                .ok_or_else(|| format!("Receipt {} is not found", receipt_id));
+        }
+        if Self::need_request(receipt_id, &mut self.receipt_requests) {
+            let validator =
+                self.chain.find_validator_for_forwarding(shard_id).map_err(|e| e.to_string())?;
+            self.network_adapter
+                .do_send(NetworkRequests::ReceiptRequest(validator, receipt_id, shard_id));
+        }
+        Ok(None)
+    }
+
+    /// Returns the requested receipt from the response of another node if the response proves
+    /// that the shard of the receipt received it in one of the known blocks.
+    fn verify_receipt_response(
+        &mut self,
+        receipt_id: &CryptoHash,
+        response: &ReceiptProofResponse,
+    ) -> Option<Receipt> {
+        let ReceiptProofResponse(block_hash, receipt_proofs) = response;
+        let block = self.chain.get_block(block_hash).ok()?;
+        for ReceiptProof(receipts, shard_proof) in receipt_proofs {
+            let receipt = match receipts.iter().find(|receipt| &receipt.receipt_id == receipt_id) {
+                Some(receipt) => receipt,
+                None => continue,
+            };
+            let ShardProof { from_shard_id, to_shard_id, proof } = shard_proof;
+            let chunk_header = block.chunks.get(*from_shard_id as usize)?;
+            let receipts_hash =
+                hash(&ReceiptList(*to_shard_id, receipts.clone()).try_to_vec().ok()?);
+            if verify_path(chunk_header.inner.outgoing_receipts_root, proof, &receipts_hash)
+                && self.runtime_adapter.account_id_to_shard_id(&receipt.receiver_id) == *to_shard_id
+            {
+                return Some(receipt.clone());
+            }
+        }
+        None
+    }
+
+    fn get_receipt_outcome(
+        &mut self,
+        receipt_id: CryptoHash,
+        receiver_id: Option<AccountId>,
+    ) -> Result<Option<ExecutionOutcomeWithIdView>, String> {
+        match self.chain.get_execution_outcome(&receipt_id) {
+            Ok(outcome) => {
+                let mut outcome: ExecutionOutcomeWithIdView = outcome.clone().into();
+                outcome.outcome.gas_profile =
+                    self.chain.store().get_gas_profile(&receipt_id).map_err(|e| e.to_string())?;
+                return Ok(Some(outcome));
+            }
+            Err(e) => match e.kind() {
+                ErrorKind::DBNotFoundErr(_) => {}
+                _ => return Err(e.to_string()),
+            },
+        }
+        let (shard_id, tracked) = self.get_receipt_shard_id(&receipt_id, receiver_id.as_ref())?;
+        if tracked {
+            return Err(format!("Execution outcome of receipt {} is not found", receipt_id));
+        }
+        if Self::need_request(receipt_id, &mut self.receipt_outcome_requests) {
+            self.receipt_outcome_shard_ids.cache_set(receipt_id, shard_id);
+            let validator =
+                self.chain.find_validator_for_forwarding(shard_id).map_err(|e| e.to_string())?;
+            self.network_adapter
+                .do_send(NetworkRequests::ReceiptOutComeRequest(validator, receipt_id));
+        }
+        Ok(None)
+    }
+
     fn retrieve_headers(
         &mut self,
         hashes: Vec<CryptoHash>,
@@ -427,6 +564,22 @@ impl Handler<TxStatus> for ViewClientActor {
     }
 }
 
+impl Handler<GetReceipt> for ViewClientActor {
+    type Result = Result<Option<ReceiptView>, String>;
+
+    fn handle(&mut self, msg: GetReceipt, _: &mut Context<Self>) -> Self::Result {
+        self.get_receipt(msg.receipt_id, msg.receiver_id)
+    }
+}
+
+impl Handler<GetReceiptOutcome> for ViewClientActor {
+    type Result = Result<Option<ExecutionOutcomeWithIdView>, String>;
+
+    fn handle(&mut self, msg: GetReceiptOutcome, _: &mut Context<Self>) -> Self::Result {
+        self.get_receipt_outcome(msg.receipt_id, msg.receiver_id)
+    }
+}
+
 impl Handler<GetValidatorInfo> for ViewClientActor {
     type Result = Result<EpochValidatorInfo, String>;
 
@@ -562,7 +715,11 @@ impl Handler<NetworkViewClientMessages> for ViewClientActor {
             }
             NetworkViewClientMessages::ReceiptOutcomeResponse(response) => {
                 if self.receipt_outcome_requests.cache_remove(response.id()).is_some() {
-                    if let Ok(&shard_id) = self.chain.get_shard_id_for_receipt_id(response.id()) {
+                    let shard_id = match self.chain.get_shard_id_for_receipt_id(response.id()) {
+                        Ok(&shard_id) => Some(shard_id),
+                        Err(_) => self.receipt_outcome_shard_ids.cache_remove(response.id()),
+                    };
+                    if let Some(shard_id) = shard_id {
                         let block_hash = response.block_hash;
                         if let Ok(Some(&next_block_hash)) =
                             self.chain.get_next_block_hash_with_new_chunk(&block_hash, shard_id)
@@ -591,6 +748,26 @@ impl Handler<NetworkViewClientMessages> for ViewClientActor {
                 }
                 NetworkViewClientResponses::NoResponse
             }
+            NetworkViewClientMessages::ReceiptRequest { receipt_id, shard_id } => {
+                if let Ok(Some(response)) =
+                    self.chain.mut_store().get_receipt_proof(&receipt_id, shard_id)
+                {
+                    NetworkViewClientResponses::ReceiptResponse(receipt_id, response)
+                } else {
+                    NetworkViewClientResponses::NoResponse
+                }
+            }
+            NetworkViewClientMessages::ReceiptResponse(receipt_id, response) => {
+                if self.receipt_requests.cache_get(&receipt_id).is_some() {
+                    match self.verify_receipt_response(&receipt_id, &response) {
+                        Some(receipt) => self.receipt_responses.cache_set(receipt_id, receipt),
+                        None => {
+                            warn!(target: "client", "Invalid proof of receipt {} in the response", receipt_id)
+                        }
+                    }
+                }
+                NetworkViewClientResponses::NoResponse
+            }
             NetworkViewClientMessages::BlockRequest(hash) => {
                 if let Ok(block) = self.chain.get_block(&hash) {
                     NetworkViewClientResponses::Block(block.clone())
@@ -688,20 +865,26 @@ impl Handler<NetworkViewClientMessages> for ViewClientActor {
             NetworkViewClientMessages::AnnounceAccount(announce_accounts) => {
                 let mut filtered_announce_accounts = Vec::new();
 
-                for (announce_account, last_epoch) in announce_accounts.into_iter() {
-                    if let Some(last_epoch) = last_epoch {
+                'announcements: for (announce_account, known_epochs) in
+                    announce_accounts.into_iter()
+                {
+                    // Known epochs that are older than the announced one are dropped once the
+                    // announcement is accepted. Announcements older than a known one are ignored.
+                    let mut stale_epochs = vec![];
+                    for known_epoch in known_epochs.into_iter() {
                         match self
                             .runtime_adapter
-                            .compare_epoch_id(&announce_account.epoch_id, &last_epoch)
+                            .compare_epoch_id(&announce_account.epoch_id, &known_epoch)
                         {
-                            Ok(Ordering::Less) => {}
-                            _ => continue,
+                            Ok(Ordering::Greater) => stale_epochs.push(known_epoch),
+                            Ok(Ordering::Equal) => {}
+                            _ => continue 'announcements,
                         }
                     }
 
                     match self.check_signature_account_announce(&announce_account) {
                         Ok(true) => {
-                            filtered_announce_accounts.push(announce_account);
+                            filtered_announce_accounts.push((announce_account, stale_epochs));
                         }
                         Ok(false) => {
                             return NetworkViewClientResponses::Ban {
@@ -721,6 +904,54 @@ impl Handler<NetworkViewClientMessages> for ViewClientActor {
     }
 }
 
+/// Executes the transaction on top of the state of the given block. Only works for the
+/// transactions whose signer belongs to a shard tracked by this node.
+/// The simulation runs on this actor, so its work is bounded: the transaction and its receipts
+/// can't burn more gas in total than the gas limit of the chunk the state comes from, and at most
+/// `node_runtime::MAX_SIMULATION_ROUNDS` rounds of receipts are executed.
+impl Handler<SimulateTransaction> for ViewClientActor {
+    type Result = Result<TransactionSimulationView, String>;
+
+    fn handle(&mut self, msg: SimulateTransaction, _: &mut Context<Self>) -> Self::Result {
+        let header = match msg.block_id {
+            Some(BlockId::Height(block_height)) => self.chain.get_header_by_height(block_height),
+            Some(BlockId::Hash(block_hash)) => self.chain.get_block_header(&block_hash),
+            None => {
+                let block_hash =
+                    self.get_block_hash_by_finality(&msg.finality).map_err(|e| e.to_string())?;
+                self.chain.get_block_header(&block_hash)
+            }
+        };
+        let header = header.map_err(|e| e.to_string())?.clone();
+
+        let shard_id =
+            self.runtime_adapter.account_id_to_shard_id(&msg.transaction.transaction.signer_id);
+        let chunk_extra =
+            self.chain.get_chunk_extra(&header.hash, shard_id).map_err(|e| match e.kind() {
+                ErrorKind::DBNotFoundErr(_) => format!(
+                    "Node doesn't have the state of shard {} at block {}",
+                    shard_id, header.hash
+                ),
+                _ => e.to_string(),
+            })?;
+        let state_root = chunk_extra.state_root;
+        let gas_limit = chunk_extra.gas_limit;
+        self.runtime_adapter
+            .simulate_transaction(
+                shard_id,
+                &state_root,
+                header.inner_lite.height,
+                header.inner_lite.timestamp,
+                &header.hash,
+                header.inner_rest.gas_price,
+                gas_limit,
+                &msg.transaction,
+                msg.verify_signature,
+            )
+            .map_err(|e| e.to_string())
+    }
+}
+
 impl Handler<GetGasPrice> for ViewClientActor {
     type Result = Result<GasPriceView, String>;
 
@@ -733,3 +964,39 @@ impl Handler<GetGasPrice> for ViewClientActor {
             .map_err(|e| e.to_string())
     }
 }
+
+impl Handler<GetAccountActivity> for ViewClientActor {
+    type Result = Result<AccountActivityPageView, String>;
+
+    fn handle(&mut self, msg: GetAccountActivity, _ctx: &mut Self::Context) -> Self::Result {
+        if !self.config.account_activity_index {
+            return Err("Account activity index is not enabled on this node".to_string());
+        }
+        let limit = max(1, min(msg.limit, MAX_ACCOUNT_ACTIVITY_LIMIT)) as usize;
+        let (activity, next_before_height) = self
+            .chain
+            .store()
+            .get_account_activity(&msg.account_id, msg.before_height, limit)
+            .map_err(|e| e.to_string())?;
+        Ok(AccountActivityPageView {
+            activity: activity
+                .into_iter()
+                .map(|(block_height, activity)| AccountActivityView {
+                    block_height,
+                    transactions: activity.transactions,
+                    receipts: activity.receipts,
+                })
+                .collect(),
+            next_before_height,
+        })
+    }
+}
+
+impl Handler<Shutdown> for ViewClientActor {
+    type Result = Result<(), String>;
+
+    fn handle(&mut self, _: Shutdown, ctx: &mut Context<Self>) -> Self::Result {
+        ctx.stop();
+        Ok(())
+    }
+}
@@ -4,10 +4,14 @@ use std::sync::{Arc, RwLock};
 use actix::{Addr, System};
 use futures::{future, FutureExt};
 
+use near_chain::test_utils::create_receipt_nonce;
 use near_client::test_utils::setup_mock_all_validators;
-use near_client::{ClientActor, Query, ViewClientActor};
-use near_network::{NetworkRequests, NetworkResponses, PeerInfo};
+use near_client::{ClientActor, GetReceipt, Query, ViewClientActor};
+use near_crypto::{InMemorySigner, KeyType};
+use near_network::test_utils::WaitOrTimeout;
+use near_network::{NetworkClientMessages, NetworkRequests, NetworkResponses, PeerInfo};
 use near_primitives::test_utils::init_test_logger;
+use near_primitives::transaction::SignedTransaction;
 use near_primitives::views::{Finality, QueryRequest, QueryResponseKind::ViewAccount};
 
 /// Tests that the KeyValueRuntime properly sets balances in genesis and makes them queriable
@@ -74,6 +78,74 @@ fn test_keyvalue_runtime_balances() {
     .unwrap();
 }
 
+/// Tests that a node finds a receipt of a shard it doesn't track by requesting it from a validator
+/// of that shard and verifying the proof of the response.
+#[test]
+fn test_get_receipt_of_untracked_shard() {
+    let validator_groups = 2;
+    init_test_logger();
+    System::run(move || {
+        let connectors: Arc<RwLock<Vec<(Addr<ClientActor>, Addr<ViewClientActor>)>>> =
+            Arc::new(RwLock::new(vec![]));
+
+        let validators = vec![vec!["test1", "test2", "test3", "test4"]];
+        let key_pairs =
+            vec![PeerInfo::random(), PeerInfo::random(), PeerInfo::random(), PeerInfo::random()];
+
+        let (genesis_block, conn) = setup_mock_all_validators(
+            validators.clone(),
+            key_pairs.clone(),
+            validator_groups,
+            true,
+            100,
+            false,
+            false,
+            5,
+            false,
+            false,
+            Arc::new(RwLock::new(move |_account_id: String, _msg: &NetworkRequests| {
+                (NetworkResponses::NoResponse, true)
+            })),
+        );
+        *connectors.write().unwrap() = conn;
+
+        // "test1" is in shard 3 and "test2" is in shard 0, which "test3" doesn't track.
+        let signer = InMemorySigner::from_seed("test1", KeyType::ED25519, "test1");
+        connectors.read().unwrap()[2].0.do_send(NetworkClientMessages::Transaction(
+            SignedTransaction::send_money(
+                1,
+                "test1".to_string(),
+                "test2".to_string(),
+                &signer,
+                1,
+                genesis_block.hash(),
+            ),
+        ));
+        let receipt_id = create_receipt_nonce("test1".to_string(), "test2".to_string(), 1, 1);
+
+        WaitOrTimeout::new(
+            Box::new(move |_| {
+                actix::spawn(
+                    connectors.read().unwrap()[2]
+                        .1
+                        .send(GetReceipt { receipt_id, receiver_id: Some("test2".to_string()) })
+                        .then(move |res| {
+                            if let Ok(Some(receipt)) = res.unwrap() {
+                                assert_eq!(receipt.receipt_id, receipt_id);
+                                System::current().stop();
+                            }
+                            future::ready(())
+                        }),
+                );
+            }),
+            100,
+            20000,
+        )
+        .start();
+    })
+    .unwrap();
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashSet;
@@ -8,20 +8,22 @@ use log::{debug, warn};
 
 use near_primitives::hash::CryptoHash;
 use near_primitives::types::{
-    AccountId, Balance, BlockChunkValidatorStats, BlockHeight, EpochId, ShardId, ValidatorId,
-    ValidatorStake, ValidatorStats,
+    AccountId, Balance, BlockChunkValidatorStats, BlockHeight, EpochHeight, EpochId, ShardId,
+    ValidatorId, ValidatorStake, ValidatorStats,
 };
 use near_primitives::views::{
     CurrentEpochValidatorInfo, EpochValidatorInfo, NextEpochValidatorInfo,
 };
 use near_store::{ColBlockInfo, ColEpochInfo, ColEpochStart, Store, StoreUpdate};
 
+pub use crate::migrations::migrate_block_info_add_epoch_height;
 use crate::proposals::proposals_to_epoch_info;
 pub use crate::reward_calculator::RewardCalculator;
 use crate::types::EpochError::EpochOutOfBounds;
 pub use crate::types::{BlockInfo, EpochConfig, EpochError, EpochInfo, RngSeed};
 use crate::types::{EpochSummary, SlashState};
 
+mod migrations;
 mod proposals;
 mod reward_calculator;
 pub mod test_utils;
@@ -322,16 +324,19 @@ impl EpochManager {
                     // This is first real block, starts the new epoch.
                     block_info.epoch_id = EpochId::default();
                     block_info.epoch_first_block = *current_hash;
+                    block_info.epoch_height = prev_block_info.epoch_height;
                     is_epoch_start = true;
                 } else if self.is_next_block_in_next_epoch(&prev_block_info)? {
                     // Current block is in the new epoch, finalize the one in prev_block.
                     block_info.epoch_id = self.get_next_epoch_id_from_info(&prev_block_info)?;
                     block_info.epoch_first_block = *current_hash;
+                    block_info.epoch_height = prev_block_info.epoch_height + 1;
                     is_epoch_start = true;
                 } else {
                     // Same epoch as parent, copy epoch_id and epoch_start_height.
                     block_info.epoch_id = prev_block_info.epoch_id;
                     block_info.epoch_first_block = prev_block_info.epoch_first_block;
+                    block_info.epoch_height = prev_block_info.epoch_height;
                 }
 
                 // Keep `slashed` from previous block if they are still in the epoch info stake change
@@ -500,6 +505,12 @@ impl EpochManager {
             .map(|idx| epoch_info.fishermen[*idx as usize].clone()))
     }
 
+    /// Returns the total stake of all validators in the given epoch. Doesn't account for slashing.
+    pub fn get_validator_total_stake(&mut self, epoch_id: &EpochId) -> Result<Balance, EpochError> {
+        let epoch_info = self.get_epoch_info(epoch_id)?;
+        Ok(epoch_info.validators.iter().map(|validator| validator.stake).sum())
+    }
+
     pub fn get_slashed_validators(
         &mut self,
         block_hash: &CryptoHash,
@@ -583,6 +594,22 @@ impl EpochManager {
         }
     }
 
+    /// Returns the height of the epoch that the block after the given block belongs to.
+    pub fn get_epoch_height_from_prev_block(
+        &mut self,
+        parent_hash: &CryptoHash,
+    ) -> Result<EpochHeight, EpochError> {
+        let block_info = self.get_block_info(parent_hash)?.clone();
+        // The first block after genesis stays in the genesis epoch.
+        if block_info.prev_hash != CryptoHash::default()
+            && self.is_next_block_in_next_epoch(&block_info)?
+        {
+            Ok(block_info.epoch_height + 1)
+        } else {
+            Ok(block_info.epoch_height)
+        }
+    }
+
     pub fn get_epoch_start_height(
         &mut self,
         block_hash: &CryptoHash,
@@ -2520,4 +2547,25 @@ mod tests {
             )
         );
     }
+
+    #[test]
+    fn test_epoch_height() {
+        let amount_staked = 1_000_000;
+        let validators = vec![("test1", amount_staked), ("test2", amount_staked)];
+        let mut epoch_manager = setup_default_epoch_manager(validators, 2, 1, 2, 0, 90, 60);
+        let h = hash_range(6);
+        record_block(&mut epoch_manager, CryptoHash::default(), h[0], 0, vec![]);
+        for i in 1..6 {
+            record_block(&mut epoch_manager, h[i - 1], h[i], i as u64, vec![]);
+        }
+        let epoch_heights: Vec<_> =
+            h.iter().map(|hash| epoch_manager.get_block_info(hash).unwrap().epoch_height).collect();
+        assert_eq!(epoch_heights, vec![0, 0, 0, 1, 1, 2]);
+        assert_eq!(epoch_manager.get_epoch_height_from_prev_block(&h[0]).unwrap(), 0);
+        assert_eq!(epoch_manager.get_epoch_height_from_prev_block(&h[2]).unwrap(), 1);
+        assert_eq!(epoch_manager.get_epoch_height_from_prev_block(&h[5]).unwrap(), 2);
+
+        let epoch_id = epoch_manager.get_epoch_id(&h[5]).unwrap();
+        assert_eq!(epoch_manager.get_validator_total_stake(&epoch_id).unwrap(), 2 * amount_staked);
+    }
 }
@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use near_primitives::hash::CryptoHash;
+use near_primitives::types::{
+    AccountId, Balance, BlockHeight, EpochHeight, EpochId, ShardId, ValidatorId, ValidatorStake,
+    ValidatorStats,
+};
+use near_store::{ColBlockInfo, Store};
+
+use crate::types::{BlockInfo, EpochError, SlashState};
+
+/// Number of block infos rewritten in one store update.
+const MIGRATION_BATCH_SIZE: usize = 10_000;
+
+/// `BlockInfo` as stored before `epoch_height` was added.
+#[derive(BorshSerialize, BorshDeserialize)]
+struct BlockInfoV0 {
+    height: BlockHeight,
+    last_finalized_height: BlockHeight,
+    prev_hash: CryptoHash,
+    epoch_first_block: CryptoHash,
+    epoch_id: EpochId,
+    proposals: Vec<ValidatorStake>,
+    chunk_mask: Vec<bool>,
+    slashed: HashMap<AccountId, SlashState>,
+    rent_paid: Balance,
+    validator_reward: Balance,
+    total_supply: Balance,
+    block_tracker: HashMap<ValidatorId, ValidatorStats>,
+    shard_tracker: HashMap<ShardId, HashMap<ValidatorId, ValidatorStats>>,
+    all_proposals: Vec<ValidatorStake>,
+    total_rent_paid: Balance,
+    total_validator_reward: Balance,
+}
+
+impl BlockInfoV0 {
+    fn into_block_info(self, epoch_height: EpochHeight) -> BlockInfo {
+        BlockInfo {
+            height: self.height,
+            last_finalized_height: self.last_finalized_height,
+            prev_hash: self.prev_hash,
+            epoch_first_block: self.epoch_first_block,
+            epoch_id: self.epoch_id,
+            epoch_height,
+            proposals: self.proposals,
+            chunk_mask: self.chunk_mask,
+            slashed: self.slashed,
+            rent_paid: self.rent_paid,
+            validator_reward: self.validator_reward,
+            total_supply: self.total_supply,
+            block_tracker: self.block_tracker,
+            shard_tracker: self.shard_tracker,
+            all_proposals: self.all_proposals,
+            total_rent_paid: self.total_rent_paid,
+            total_validator_reward: self.total_validator_reward,
+        }
+    }
+}
+
+/// Rewrites the block infos stored before `BlockInfo::epoch_height` was added.
+/// Block infos are never garbage collected, so the epoch heights are recomputed by following the
+/// first blocks of the epochs back to genesis. Block infos that are already in the new format are
+/// left as they are, so the migration can be restarted if it was interrupted.
+pub fn migrate_block_info_add_epoch_height(store: &Store) -> Result<(), EpochError> {
+    // Previous block and first block of the epoch for every legacy block.
+    let mut links = HashMap::new();
+    for (key, value) in store.iter(ColBlockInfo) {
+        let hash = CryptoHash::try_from(key.as_ref()).map_err(|err| {
+            EpochError::Other(format!("Invalid block info key {:?}: {}", key, err))
+        })?;
+        if let Ok(block_info) = BlockInfoV0::try_from_slice(&value) {
+            links.insert(hash, (block_info.prev_hash, block_info.epoch_first_block));
+        } else {
+            let block_info = BlockInfo::try_from_slice(&value)?;
+            links.insert(hash, (block_info.prev_hash, block_info.epoch_first_block));
+        }
+    }
+
+    let mut epoch_heights = HashMap::new();
+    let mut store_update = store.store_update();
+    let mut batch_size = 0;
+    for (key, value) in store.iter(ColBlockInfo) {
+        let block_info = match BlockInfoV0::try_from_slice(&value) {
+            Ok(block_info) => block_info,
+            Err(_) => continue,
+        };
+        let epoch_height =
+            epoch_height_of(&links, &mut epoch_heights, &block_info.epoch_first_block)?;
+        store_update.set_ser(ColBlockInfo, &key, &block_info.into_block_info(epoch_height))?;
+        batch_size += 1;
+        if batch_size == MIGRATION_BATCH_SIZE {
+            store_update.commit()?;
+            store_update = store.store_update();
+            batch_size = 0;
+        }
+    }
+    store_update.commit()?;
+    Ok(())
+}
+
+/// Computes the height of the epoch that starts with `epoch_first_block`, the same way
+/// `EpochManager::record_block_info` assigns it.
+fn epoch_height_of(
+    links: &HashMap<CryptoHash, (CryptoHash, CryptoHash)>,
+    epoch_heights: &mut HashMap<CryptoHash, EpochHeight>,
+    epoch_first_block: &CryptoHash,
+) -> Result<EpochHeight, EpochError> {
+    // First blocks of the epochs whose heights are not known yet, latest epoch first.
+    let mut pending = vec![];
+    let mut current = *epoch_first_block;
+    let mut base_height = loop {
+        if let Some(height) = epoch_heights.get(&current) {
+            break *height;
+        }
+        // Genesis block has no epoch first block set.
+        if current == CryptoHash::default() {
+            break 0;
+        }
+        let (prev_hash, _) = links.get(&current).ok_or(EpochError::MissingBlock(current))?;
+        if *prev_hash == CryptoHash::default() {
+            // The genesis block itself.
+            break 0;
+        }
+        let (prev_prev_hash, prev_epoch_first_block) =
+            links.get(prev_hash).ok_or(EpochError::MissingBlock(*prev_hash))?;
+        if *prev_prev_hash == CryptoHash::default() {
+            // The first block after genesis stays in the genesis epoch.
+            epoch_heights.insert(current, 0);
+            break 0;
+        }
+        pending.push(current);
+        current = *prev_epoch_first_block;
+    };
+    while let Some(first_block) = pending.pop() {
+        base_height += 1;
+        epoch_heights.insert(first_block, base_height);
+    }
+    Ok(base_height)
+}
+
+#[cfg(test)]
+mod tests {
+    use near_primitives::hash::hash;
+    use near_store::test_utils::create_test_store;
+
+    use super::*;
+
+    fn legacy_block_info(prev_hash: CryptoHash, epoch_first_block: CryptoHash) -> BlockInfoV0 {
+        BlockInfoV0 {
+            height: 0,
+            last_finalized_height: 0,
+            prev_hash,
+            epoch_first_block,
+            epoch_id: EpochId(epoch_first_block),
+            proposals: vec![],
+            chunk_mask: vec![],
+            slashed: HashMap::default(),
+            rent_paid: 0,
+            validator_reward: 0,
+            total_supply: 0,
+            block_tracker: HashMap::default(),
+            shard_tracker: HashMap::default(),
+            all_proposals: vec![],
+            total_rent_paid: 0,
+            total_validator_reward: 0,
+        }
+    }
+
+    #[test]
+    fn test_migrate_block_info_add_epoch_height() {
+        let store = create_test_store();
+        let h: Vec<_> = (0..6).map(|i| hash(&[i])).collect();
+        // Genesis, two blocks in the genesis epoch, then two epochs.
+        let chain = vec![
+            legacy_block_info(CryptoHash::default(), CryptoHash::default()),
+            legacy_block_info(h[0], h[1]),
+            legacy_block_info(h[1], h[1]),
+            legacy_block_info(h[2], h[3]),
+            legacy_block_info(h[3], h[3]),
+            legacy_block_info(h[4], h[5]),
+        ];
+        let mut store_update = store.store_update();
+        for (hash, block_info) in h.iter().zip(chain.iter()) {
+            store_update.set(ColBlockInfo, hash.as_ref(), &block_info.try_to_vec().unwrap());
+        }
+        store_update.commit().unwrap();
+
+        migrate_block_info_add_epoch_height(&store).unwrap();
+        let epoch_heights: Vec<_> = h
+            .iter()
+            .map(|hash| {
+                store
+                    .get_ser::<BlockInfo>(ColBlockInfo, hash.as_ref())
+                    .unwrap()
+                    .unwrap()
+                    .epoch_height
+            })
+            .collect();
+        assert_eq!(epoch_heights, vec![0, 0, 0, 1, 1, 2]);
+
+        // Running the migration again keeps the migrated block infos.
+        migrate_block_info_add_epoch_height(&store).unwrap();
+        let block_info: BlockInfo = store.get_ser(ColBlockInfo, h[5].as_ref()).unwrap().unwrap();
+        assert_eq!(block_info.epoch_height, 2);
+    }
+}
@@ -212,6 +212,8 @@ pub fn proposals_to_epoch_info(
 
 #[cfg(test)]
 mod tests {
+    use near_primitives::shard_layout::ShardLayout;
+
     use crate::test_utils::{change_stake, epoch_config, epoch_info, stake};
 
     use super::*;
@@ -253,7 +255,7 @@ mod tests {
             proposals_to_epoch_info(
                 &EpochConfig {
                     epoch_length: 2,
-                    num_shards: 5,
+                    shard_layout: ShardLayout::v0(5),
                     num_block_producer_seats: 6,
                     num_block_producer_seats_per_shard: vec![6, 2, 2, 2, 2],
                     avg_hidden_validator_seats_per_shard: vec![6, 2, 2, 2, 2],
@@ -2,6 +2,7 @@ use std::collections::{BTreeMap, HashMap};
 
 use near_crypto::{KeyType, SecretKey};
 use near_primitives::hash::{hash, CryptoHash};
+use near_primitives::shard_layout::ShardLayout;
 use near_primitives::types::{
     AccountId, Balance, BlockHeight, BlockHeightDelta, NumSeats, NumShards, ValidatorId,
     ValidatorStake,
@@ -85,7 +86,7 @@ pub fn epoch_config(
 ) -> EpochConfig {
     EpochConfig {
         epoch_length,
-        num_shards,
+        shard_layout: ShardLayout::v0(num_shards),
         num_block_producer_seats,
         num_block_producer_seats_per_shard: get_num_seats_per_shard(
             num_shards,
@@ -6,11 +6,13 @@ use serde::Serialize;
 
 use crate::EpochManager;
 use near_primitives::challenge::SlashedValidator;
+use near_primitives::errors::EpochInfoProviderError;
 use near_primitives::hash::CryptoHash;
 use near_primitives::serialize::to_base;
+use near_primitives::shard_layout::ShardLayout;
 use near_primitives::types::{
-    AccountId, Balance, BlockChunkValidatorStats, BlockHeight, BlockHeightDelta, EpochId, NumSeats,
-    NumShards, ShardId, ValidatorId, ValidatorStake, ValidatorStats,
+    AccountId, Balance, BlockChunkValidatorStats, BlockHeight, BlockHeightDelta, EpochHeight,
+    EpochId, NumSeats, ShardId, ValidatorId, ValidatorStake, ValidatorStats,
 };
 
 pub type RngSeed = [u8; 32];
@@ -21,8 +23,8 @@ pub type RngSeed = [u8; 32];
 pub struct EpochConfig {
     /// Epoch length in block heights.
     pub epoch_length: BlockHeightDelta,
-    /// Number of shards currently.
-    pub num_shards: NumShards,
+    /// Assignment of accounts to shards, which also defines the number of shards.
+    pub shard_layout: ShardLayout,
     /// Number of seats for block producers.
     pub num_block_producer_seats: NumSeats,
     /// Number of seats of block producers per each shard.
@@ -75,6 +77,8 @@ pub struct BlockInfo {
     pub prev_hash: CryptoHash,
     pub epoch_first_block: CryptoHash,
     pub epoch_id: EpochId,
+    /// Height of the epoch this block belongs to.
+    pub epoch_height: EpochHeight,
     pub proposals: Vec<ValidatorStake>,
     pub chunk_mask: Vec<bool>,
     pub slashed: HashMap<AccountId, SlashState>,
@@ -128,6 +132,7 @@ impl BlockInfo {
             // These values are not set. This code is suboptimal
             epoch_first_block: CryptoHash::default(),
             epoch_id: EpochId::default(),
+            epoch_height: 0,
             block_tracker: HashMap::default(),
             shard_tracker: HashMap::default(),
             all_proposals: vec![],
@@ -248,6 +253,15 @@ impl From<std::io::Error> for EpochError {
     }
 }
 
+impl From<EpochError> for EpochInfoProviderError {
+    fn from(error: EpochError) -> Self {
+        match error {
+            EpochError::EpochOutOfBounds => EpochInfoProviderError::EpochOutOfBounds,
+            err => EpochInfoProviderError::Other(err.to_string()),
+        }
+    }
+}
+
 impl From<EpochError> for near_chain::Error {
     fn from(error: EpochError) -> Self {
         match error {
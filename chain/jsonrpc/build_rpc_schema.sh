@@ -0,0 +1,3 @@
+#!/bin/bash
+cargo build --features dump_rpc_schema
+cp ../../target/rpc_schema.json ./res/rpc_schema.json
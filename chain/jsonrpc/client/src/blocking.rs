@@ -0,0 +1,44 @@
+//! Blocking wrapper of `JsonRpcClient` for the code that doesn't run an actix system, such as
+//! tools and tests driving a node from a plain thread.
+
+use std::sync::Arc;
+
+use actix::System;
+
+use crate::{create_client, Endpoints, JsonRpcClient, JsonRpcError, RetryConfig, RpcRequest};
+
+/// Runs each call of `JsonRpcClient` in its own actix system and waits for the result. The
+/// endpoint the calls are sent to is shared between the calls, so a node that couldn't be
+/// reached isn't tried first again.
+#[derive(Clone)]
+pub struct BlockingJsonRpcClient {
+    endpoints: Arc<Endpoints>,
+    retry_config: RetryConfig,
+}
+
+impl BlockingJsonRpcClient {
+    /// Creates a client that sends the calls once to the given address.
+    pub fn new(server_addr: &str) -> Self {
+        Self::with_failover(vec![server_addr.to_string()], RetryConfig::no_retries())
+    }
+
+    /// Creates a client that retries the calls according to `retry_config`, moving on to the
+    /// next of `server_addrs` when a node can't be reached.
+    pub fn with_failover(server_addrs: Vec<String>, retry_config: RetryConfig) -> Self {
+        BlockingJsonRpcClient { endpoints: Arc::new(Endpoints::new(server_addrs)), retry_config }
+    }
+
+    /// Makes the call with the async client and blocks until it's done, e.g.
+    /// `client.call(|client| client.status())`. Must not be used from within an actix system.
+    pub fn call<F, R>(&self, f: F) -> Result<R, JsonRpcError>
+    where
+        F: FnOnce(&mut JsonRpcClient) -> RpcRequest<R>,
+    {
+        let endpoints = self.endpoints.clone();
+        let retry_config = self.retry_config;
+        System::new("near-jsonrpc-client").block_on(async move {
+            let mut client = JsonRpcClient { client: create_client(), endpoints, retry_config };
+            f(&mut client).await
+        })
+    }
+}
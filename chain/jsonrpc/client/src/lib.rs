@@ -1,20 +1,31 @@
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 
 use actix_web::client::{Client, Connector};
 use futures::{future, future::LocalBoxFuture, FutureExt, TryFutureExt};
 use serde::Deserialize;
 use serde::Serialize;
+use serde_json::Value;
+use tokio::time::delay_for;
 
 use near_primitives::hash::CryptoHash;
-use near_primitives::rpc::{BlockQueryInfo, RpcQueryRequest};
-use near_primitives::types::{BlockId, MaybeBlockId, ShardId};
+use near_primitives::rpc::{
+    BlockQueryInfo, RpcAccountActivityRequest, RpcQueryRequest, RpcReceiptRequest,
+    RpcSimulateTransactionRequest, RpcTrackingRequest,
+};
+use near_primitives::types::{BlockId, MaybeBlockId, ShardId, StateChangesRequest};
 use near_primitives::views::{
-    BlockView, ChunkView, EpochValidatorInfo, FinalExecutionOutcomeView, GasPriceView,
-    QueryResponse, StateChangesView, StatusResponse,
+    AccountActivityPageView, BlockView, ChunkView, EpochValidatorInfo, ExecutionOutcomeWithIdView,
+    FinalExecutionOutcomeView, GasPriceView, LightClientBlockView, NetworkInfoView, PeersView,
+    QueryResponse, ReceiptView, ServerError, StateChangesInBlockView, StatusResponse,
+    TrackedShardsView, TransactionPoolView, TransactionSimulationView,
 };
 
-use crate::message::{from_slice, Message, RpcError};
+use crate::message::{from_slice, Message, Response, RpcError};
 
+pub mod blocking;
 pub mod message;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -27,67 +38,208 @@ pub enum ChunkId {
 /// Timeout for establishing connection.
 const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
 
-type HttpRequest<T> = LocalBoxFuture<'static, Result<T, String>>;
-type RpcRequest<T> = LocalBoxFuture<'static, Result<T, RpcError>>;
+/// Code of `RpcError::server_error`, whose data is a `ServerError` for the failed transactions.
+const SERVER_ERROR_CODE: i64 = -32_000;
+/// Code of `RpcError::too_many_requests`, returned when the rate limits of the node are exceeded.
+const TOO_MANY_REQUESTS_CODE: i64 = -32_005;
+/// Methods that change the state of the chain, so they may take effect even if the node couldn't
+/// be reached to read the response.
+const WRITE_METHODS: &[&str] = &["broadcast_tx_async", "broadcast_tx_commit"];
 
-/// Prepare a `RPCRequest` with a given client, server address, method and parameters.
-fn call_method<P, R>(client: &Client, server_addr: &str, method: &str, params: P) -> RpcRequest<R>
-where
-    P: Serialize,
-    R: serde::de::DeserializeOwned + 'static,
-{
-    let request =
-        Message::request(method.to_string(), Some(serde_json::to_value(&params).unwrap()));
-    // TODO: simplify this.
-    client
-        .post(server_addr)
-        .header("Content-Type", "application/json")
-        .send_json(&request)
-        .map_err(|err| RpcError::server_error(Some(format!("{:?}", err))))
-        .and_then(|mut response| {
-            response.body().map(|body| match body {
-                Ok(bytes) => from_slice(&bytes).map_err(|err| {
-                    RpcError::parse_error(format!("Error {:?} in {:?}", err, bytes))
-                }),
-                Err(err) => {
-                    Err(RpcError::parse_error(format!("Failed to retrieve payload: {:?}", err)))
-                }
-            })
-        })
-        .and_then(|message| {
-            future::ready(match message {
-                Message::Response(resp) => resp.result.and_then(|x| {
-                    serde_json::from_value(x)
-                        .map_err(|err| RpcError::parse_error(format!("Failed to parse: {:?}", err)))
-                }),
-                _ => Err(RpcError::invalid_request()),
-            })
-        })
-        .boxed_local()
+/// Error of a call made by the clients.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonRpcError {
+    /// The node couldn't be reached, or its response couldn't be read.
+    Transport(String),
+    /// The transaction failed or the node didn't handle it in time, as described by the
+    /// `ServerError` of `rpc_errors_schema.json`.
+    Server(ServerError),
+    /// Any other error returned by the node, or a result that doesn't match the expected type.
+    Rpc(RpcError),
+}
+
+impl JsonRpcError {
+    /// JSON-RPC error code, unless the node couldn't be reached.
+    pub fn code(&self) -> Option<i64> {
+        match self {
+            JsonRpcError::Transport(_) => None,
+            JsonRpcError::Server(_) => Some(SERVER_ERROR_CODE),
+            JsonRpcError::Rpc(err) => Some(err.code),
+        }
+    }
+
+    fn parse_error(message: String) -> Self {
+        JsonRpcError::Rpc(RpcError::parse_error(message))
+    }
+}
+
+impl From<RpcError> for JsonRpcError {
+    fn from(err: RpcError) -> Self {
+        if err.code == SERVER_ERROR_CODE {
+            if let Some(Ok(server_error)) = err.data.clone().map(serde_json::from_value) {
+                return JsonRpcError::Server(server_error);
+            }
+        }
+        JsonRpcError::Rpc(err)
+    }
+}
+
+impl fmt::Display for JsonRpcError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            JsonRpcError::Transport(err) => write!(f, "Transport error: {}", err),
+            JsonRpcError::Server(err) => write!(f, "{}", err),
+            JsonRpcError::Rpc(err) => write!(f, "{:?}", err),
+        }
+    }
+}
+
+impl std::error::Error for JsonRpcError {}
+
+/// How the client retries the calls that failed to reach the node or were rate limited. Only the
+/// reads are retried and sent to the next endpoint when the node can't be reached, while all the
+/// calls are retried on the same node when it's rate limiting the client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryConfig {
+    /// Number of retries after the first attempt.
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled after each retry.
+    pub initial_backoff: Duration,
+    /// Upper bound of the delay between the retries.
+    pub max_backoff: Duration,
+}
+
+impl RetryConfig {
+    /// Sends each call once.
+    pub fn no_retries() -> Self {
+        RetryConfig { max_retries: 0, ..Default::default() }
+    }
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        RetryConfig {
+            max_retries: 5,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
 }
 
-/// Prepare a `HttpRequest` with a given client, server address and parameters.
-fn call_http_get<R, P>(
+/// Addresses of nodes that serve the same chain. The calls are sent to one of them at a time,
+/// and move on to the next one once it can't be reached.
+struct Endpoints {
+    server_addrs: Vec<String>,
+    current: AtomicUsize,
+}
+
+impl Endpoints {
+    fn new(server_addrs: Vec<String>) -> Self {
+        assert!(!server_addrs.is_empty(), "At least one endpoint is required");
+        Endpoints { server_addrs, current: AtomicUsize::new(0) }
+    }
+
+    fn current(&self) -> (usize, String) {
+        let index = self.current.load(Ordering::Relaxed);
+        (index, self.server_addrs[index].clone())
+    }
+
+    /// The first of the addresses, regardless of the endpoints that couldn't be reached.
+    fn first(&self) -> (usize, String) {
+        (0, self.server_addrs[0].clone())
+    }
+
+    /// Moves on to the next endpoint, unless a concurrent call did it already.
+    fn fail_over(&self, index: usize) {
+        let next = (index + 1) % self.server_addrs.len();
+        let _ = self.current.compare_exchange(index, next, Ordering::Relaxed, Ordering::Relaxed);
+    }
+}
+
+type HttpRequest<T> = LocalBoxFuture<'static, Result<T, JsonRpcError>>;
+type RpcRequest<T> = LocalBoxFuture<'static, Result<T, JsonRpcError>>;
+
+/// Sends the message to the node and reads the message it responds with.
+async fn send_message(
     client: &Client,
     server_addr: &str,
-    method: &str,
-    _params: P,
-) -> HttpRequest<R>
+    admin_token: Option<&str>,
+    message: &Message,
+) -> Result<Message, JsonRpcError> {
+    let mut request = client.post(server_addr).header("Content-Type", "application/json");
+    if let Some(admin_token) = admin_token {
+        request = request.header("Authorization", format!("Bearer {}", admin_token));
+    }
+    let mut response = request
+        .send_json(message)
+        .await
+        .map_err(|err| JsonRpcError::Transport(format!("{:?}", err)))?;
+    let bytes = response
+        .body()
+        .await
+        .map_err(|err| JsonRpcError::Transport(format!("Failed to retrieve payload: {:?}", err)))?;
+    from_slice(&bytes)
+        .map_err(|err| JsonRpcError::parse_error(format!("Error {:?} in {:?}", err, bytes)))
+}
+
+/// Whether the call can be sent again, possibly to another node, when it's unknown if the node
+/// handled it. Admin calls are only meant for the node they are sent to.
+fn is_idempotent(method: &str) -> bool {
+    !method.starts_with("admin_") && !WRITE_METHODS.contains(&method)
+}
+
+/// Whether the node rejected the whole message because of its rate limits.
+fn is_rate_limited(message: &Message) -> bool {
+    match message {
+        Message::Response(Response { result: Err(err), .. }) => err.code == TOO_MANY_REQUESTS_CODE,
+        _ => false,
+    }
+}
+
+/// Parses the result of a call into the expected type.
+fn parse_result<R>(result: Result<Value, RpcError>) -> Result<R, JsonRpcError>
+where
+    R: serde::de::DeserializeOwned,
+{
+    result.map_err(JsonRpcError::from).and_then(|value| {
+        serde_json::from_value(value)
+            .map_err(|err| JsonRpcError::parse_error(format!("Failed to parse: {:?}", err)))
+    })
+}
+
+/// Prepare a `HttpRequest` with a given client, server address, path and parameters. The
+/// parameters are passed in the query string.
+fn call_http_get<R, P>(client: &Client, server_addr: &str, path: &str, params: P) -> HttpRequest<R>
 where
     P: Serialize,
     R: serde::de::DeserializeOwned + 'static,
 {
-    // TODO: url encode params.
+    let query = match serde_urlencoded::to_string(&params) {
+        Ok(query) => query,
+        Err(err) => {
+            return future::ready(Err(JsonRpcError::Rpc(RpcError::invalid_params(Some(
+                err.to_string(),
+            )))))
+            .boxed_local()
+        }
+    };
+    let url = if query.is_empty() {
+        format!("{}/{}", server_addr, path)
+    } else {
+        format!("{}/{}?{}", server_addr, path, query)
+    };
     client
-        .get(format!("{}/{}", server_addr, method))
+        .get(url)
         .send()
-        .map_err(|err| err.to_string())
+        .map_err(|err| JsonRpcError::Transport(format!("{:?}", err)))
         .and_then(|mut response| {
             response.body().map(|body| match body {
-                Ok(bytes) => String::from_utf8(bytes.to_vec())
-                    .map_err(|err| format!("Error {:?} in {:?}", err, bytes))
-                    .and_then(|s| serde_json::from_str(&s).map_err(|err| err.to_string())),
-                Err(_) => Err("Payload error: {:?}".to_string()),
+                Ok(bytes) => serde_json::from_slice(&bytes).map_err(|err| {
+                    JsonRpcError::parse_error(format!("Error {:?} in {:?}", err, bytes))
+                }),
+                Err(err) => {
+                    Err(JsonRpcError::Transport(format!("Failed to retrieve payload: {:?}", err)))
+                }
             })
         })
         .boxed_local()
@@ -131,7 +283,7 @@ macro_rules! http_client {
                     -> HttpRequest<$return_ty>
                 {
                     let method = String::from(stringify!($method));
-                    let params = expand_params!($($arg_name,)*);
+                    let params = ($((stringify!($arg_name), $arg_name),)*);
                     call_http_get(&$selff.client, &$selff.server_addr, &method, params)
                 }
             )*
@@ -152,14 +304,27 @@ macro_rules! jsonrpc_client {
     ) => (
         $(#[$struct_attr])*
         pub struct $struct_name {
-            server_addr: String,
             client: Client,
+            endpoints: Arc<Endpoints>,
+            retry_config: RetryConfig,
         }
 
         impl $struct_name {
-            /// Creates a new RPC client backed by the given transport implementation.
+            /// Creates a new RPC client backed by the given transport implementation. The calls
+            /// are sent once.
             pub fn new(server_addr: &str, client: Client) -> Self {
-                $struct_name { server_addr: server_addr.to_string(), client }
+                Self::with_failover(vec![server_addr.to_string()], client, RetryConfig::no_retries())
+            }
+
+            /// Creates a new RPC client that retries the calls according to `retry_config`,
+            /// moving on to the next of `server_addrs` when a node can't be reached. Admin calls
+            /// and transactions are never sent to another node.
+            pub fn with_failover(
+                server_addrs: Vec<String>,
+                client: Client,
+                retry_config: RetryConfig,
+            ) -> Self {
+                $struct_name { client, endpoints: Arc::new(Endpoints::new(server_addrs)), retry_config }
             }
 
             $(
@@ -169,7 +334,7 @@ macro_rules! jsonrpc_client {
                 {
                     let method = String::from(stringify!($method));
                     let params = expand_params!($($arg_name,)*);
-                    call_method(&$selff.client, &$selff.server_addr, &method, params)
+                    $selff.call_method(None, &method, params)
                 }
             )*
         }
@@ -183,7 +348,9 @@ jsonrpc_client!(pub struct JsonRpcClient {
     pub fn health(&mut self) -> RpcRequest<()>;
     pub fn tx(&mut self, hash: String, account_id: String) -> RpcRequest<FinalExecutionOutcomeView>;
     pub fn chunk(&mut self, id: ChunkId) -> RpcRequest<ChunkView>;
-    pub fn changes(&mut self, block_hash: CryptoHash, key_prefix: Vec<u8>) -> RpcRequest<StateChangesView>;
+    pub fn changes(&mut self, block_hash: CryptoHash, state_changes_request: StateChangesRequest) -> RpcRequest<StateChangesInBlockView>;
+    pub fn next_light_client_block(&mut self, last_block_hash: CryptoHash) -> RpcRequest<Option<LightClientBlockView>>;
+    pub fn network_info(&mut self) -> RpcRequest<NetworkInfoView>;
     pub fn validators(&mut self, block_id: MaybeBlockId) -> RpcRequest<EpochValidatorInfo>;
     pub fn gas_price(&mut self, block_id: MaybeBlockId) -> RpcRequest<GasPriceView>;
 });
@@ -192,19 +359,253 @@ impl JsonRpcClient {
     /// This is a soft-deprecated method to do query RPC request with a path and data positional
     /// parameters.
     pub fn query_by_path(&mut self, path: String, data: String) -> RpcRequest<QueryResponse> {
-        call_method(&self.client, &self.server_addr, "query", [path, data])
+        self.call_method(None, "query", [path, data])
     }
 
     pub fn query(&mut self, request: RpcQueryRequest) -> RpcRequest<QueryResponse> {
-        call_method(&self.client, &self.server_addr, "query", request)
+        self.call_method(None, "query", request)
     }
 
     pub fn block_by_id(&mut self, block_id: BlockId) -> RpcRequest<BlockView> {
-        call_method(&self.client, &self.server_addr, "block", [block_id])
+        self.call_method(None, "block", [block_id])
     }
 
     pub fn block(&mut self, request: BlockQueryInfo) -> RpcRequest<BlockView> {
-        call_method(&self.client, &self.server_addr, "block", request)
+        self.call_method(None, "block", request)
+    }
+
+    /// Same as `tx`, but also returns the gas profiles of the transaction and receipt outcomes.
+    pub fn tx_with_gas_profile(
+        &mut self,
+        hash: String,
+        account_id: String,
+    ) -> RpcRequest<FinalExecutionOutcomeView> {
+        self.call_method(None, "tx", (hash, account_id, true))
+    }
+
+    pub fn simulate_tx(
+        &mut self,
+        request: RpcSimulateTransactionRequest,
+    ) -> RpcRequest<TransactionSimulationView> {
+        self.call_method(None, "simulate_tx", request)
+    }
+
+    pub fn account_activity(
+        &mut self,
+        request: RpcAccountActivityRequest,
+    ) -> RpcRequest<AccountActivityPageView> {
+        self.call_method(None, "account_activity", request)
+    }
+
+    pub fn receipt(&mut self, request: RpcReceiptRequest) -> RpcRequest<ReceiptView> {
+        self.call_method(None, "receipt", request)
+    }
+
+    /// Execution outcome of the receipt with its proof and the hash of the block it executed in.
+    pub fn outcome(
+        &mut self,
+        request: RpcReceiptRequest,
+    ) -> RpcRequest<ExecutionOutcomeWithIdView> {
+        self.call_method(None, "outcome", request)
+    }
+
+    /// Sends the given `(method, params)` calls as a single JSON-RPC batch. The results are in
+    /// the order of the calls.
+    pub fn batch(
+        &mut self,
+        calls: Vec<(String, Value)>,
+    ) -> RpcRequest<Vec<Result<Value, JsonRpcError>>> {
+        let idempotent = calls.iter().all(|(method, _)| is_idempotent(method));
+        let requests: Vec<Message> = calls
+            .into_iter()
+            .map(|(method, params)| Message::request(method, Some(params)))
+            .collect();
+        let ids: Vec<Value> = requests.iter().map(Message::id).collect();
+        self.send(None, idempotent, Message::Batch(requests))
+            .and_then(move |message| {
+                future::ready(match message {
+                    Message::Batch(messages) => {
+                        let mut responses: Vec<Response> = messages
+                            .into_iter()
+                            .filter_map(|message| match message {
+                                Message::Response(resp) => Some(resp),
+                                _ => None,
+                            })
+                            .collect();
+                        ids.iter()
+                            .map(|id| {
+                                let index = responses
+                                    .iter()
+                                    .position(|resp| &resp.id == id)
+                                    .ok_or_else(|| {
+                                        JsonRpcError::parse_error(format!("No response for {}", id))
+                                    })?;
+                                Ok(responses.swap_remove(index).result.map_err(JsonRpcError::from))
+                            })
+                            .collect()
+                    }
+                    // The whole batch was rejected.
+                    Message::Response(resp) => Err(resp
+                        .result
+                        .err()
+                        .map_or_else(|| RpcError::invalid_request().into(), JsonRpcError::from)),
+                    _ => Err(RpcError::invalid_request().into()),
+                })
+            })
+            .boxed_local()
+    }
+
+    /// Retrieves the given blocks with a single batch request.
+    pub fn blocks(
+        &mut self,
+        requests: Vec<BlockQueryInfo>,
+    ) -> RpcRequest<Vec<Result<BlockView, JsonRpcError>>> {
+        self.call_batch_method("block", requests)
+    }
+
+    /// Retrieves the given chunks with a single batch request.
+    pub fn chunks(
+        &mut self,
+        ids: Vec<ChunkId>,
+    ) -> RpcRequest<Vec<Result<ChunkView, JsonRpcError>>> {
+        let params: Vec<[ChunkId; 1]> = ids.into_iter().map(|id| [id]).collect();
+        self.call_batch_method("chunk", params)
+    }
+
+    /// Starts tracking the given accounts and shards on the node.
+    pub fn admin_track(
+        &mut self,
+        admin_token: &str,
+        request: RpcTrackingRequest,
+    ) -> RpcRequest<TrackedShardsView> {
+        self.call_method(Some(admin_token), "admin_track", request)
+    }
+
+    /// Stops tracking the given accounts and shards on the node.
+    pub fn admin_untrack(
+        &mut self,
+        admin_token: &str,
+        request: RpcTrackingRequest,
+    ) -> RpcRequest<TrackedShardsView> {
+        self.call_method(Some(admin_token), "admin_untrack", request)
+    }
+
+    /// Returns the active peers and the bans of the node.
+    pub fn admin_peers(&mut self, admin_token: &str) -> RpcRequest<PeersView> {
+        self.call_method(Some(admin_token), "admin_peers", expand_params!())
+    }
+
+    /// Bans a peer id or an IP address / subnet.
+    pub fn admin_ban_peer(&mut self, admin_token: &str, target: String) -> RpcRequest<()> {
+        self.call_method(Some(admin_token), "admin_ban_peer", [target])
+    }
+
+    /// Lifts the ban of a peer id or an IP address / subnet.
+    pub fn admin_unban(&mut self, admin_token: &str, target: String) -> RpcRequest<()> {
+        self.call_method(Some(admin_token), "admin_unban", [target])
+    }
+
+    /// Replaces the log filter of the node, e.g. with `near=info,client=debug`.
+    pub fn admin_set_log_filter(&mut self, admin_token: &str, filter: String) -> RpcRequest<()> {
+        self.call_method(Some(admin_token), "admin_set_log_filter", [filter])
+    }
+
+    pub fn admin_clear_old_data(&mut self, admin_token: &str) -> RpcRequest<()> {
+        self.call_method(Some(admin_token), "admin_clear_old_data", expand_params!())
+    }
+
+    pub fn admin_tx_pool(&mut self, admin_token: &str) -> RpcRequest<Vec<TransactionPoolView>> {
+        self.call_method(Some(admin_token), "admin_tx_pool", expand_params!())
+    }
+
+    /// Flushes the store and stops the node.
+    pub fn admin_shutdown(&mut self, admin_token: &str) -> RpcRequest<()> {
+        self.call_method(Some(admin_token), "admin_shutdown", expand_params!())
+    }
+
+    /// Calls the method and parses its result. Admin methods are only served by the admin
+    /// listener of the node, so the client has to be created with its address, and they are
+    /// always sent to the first address of the client.
+    fn call_method<P, R>(&self, admin_token: Option<&str>, method: &str, params: P) -> RpcRequest<R>
+    where
+        P: Serialize,
+        R: serde::de::DeserializeOwned + 'static,
+    {
+        let request =
+            Message::request(method.to_string(), Some(serde_json::to_value(&params).unwrap()));
+        self.send(admin_token, is_idempotent(method), request)
+            .and_then(|message| {
+                future::ready(match message {
+                    Message::Response(resp) => parse_result(resp.result),
+                    _ => Err(RpcError::invalid_request().into()),
+                })
+            })
+            .boxed_local()
+    }
+
+    /// Same as `batch`, but calls the same method with each of the given parameters and parses
+    /// the results.
+    fn call_batch_method<P, R>(
+        &mut self,
+        method: &str,
+        params: Vec<P>,
+    ) -> RpcRequest<Vec<Result<R, JsonRpcError>>>
+    where
+        P: Serialize,
+        R: serde::de::DeserializeOwned + 'static,
+    {
+        let calls = params
+            .iter()
+            .map(|params| (method.to_string(), serde_json::to_value(params).unwrap()))
+            .collect();
+        self.batch(calls)
+            .map_ok(|results| {
+                results
+                    .into_iter()
+                    .map(|result| result.and_then(|value| parse_result(Ok(value))))
+                    .collect()
+            })
+            .boxed_local()
+    }
+
+    /// Sends the message to the current endpoint. The message is sent again after a backoff if
+    /// the node is rate limiting the client, or if the node can't be reached and the message is
+    /// `idempotent`, in which case the next endpoint is used.
+    fn send(
+        &self,
+        admin_token: Option<&str>,
+        idempotent: bool,
+        message: Message,
+    ) -> RpcRequest<Message> {
+        let client = self.client.clone();
+        let endpoints = self.endpoints.clone();
+        let retry_config = self.retry_config;
+        let admin_token = admin_token.map(str::to_string);
+        async move {
+            let mut backoff = retry_config.initial_backoff;
+            let mut retries = 0;
+            loop {
+                let (index, server_addr) =
+                    if admin_token.is_some() { endpoints.first() } else { endpoints.current() };
+                let token = admin_token.as_ref().map(String::as_str);
+                let result = send_message(&client, &server_addr, token, &message).await;
+                let should_retry = match &result {
+                    Ok(message) => is_rate_limited(message),
+                    Err(JsonRpcError::Transport(_)) if idempotent => {
+                        endpoints.fail_over(index);
+                        true
+                    }
+                    Err(_) => false,
+                };
+                if !should_retry || retries >= retry_config.max_retries {
+                    return result;
+                }
+                retries += 1;
+                delay_for(backoff).await;
+                backoff = std::cmp::min(backoff * 2, retry_config.max_backoff);
+            }
+        }
+            .boxed_local()
     }
 }
 
@@ -225,11 +626,59 @@ pub fn new_client(server_addr: &str) -> JsonRpcClient {
     JsonRpcClient::new(server_addr, create_client())
 }
 
+/// Create new JSON RPC client that retries the calls and fails over between the given
+/// addresses of nodes serving the same chain.
+pub fn new_client_with_failover(
+    server_addrs: Vec<String>,
+    retry_config: RetryConfig,
+) -> JsonRpcClient {
+    JsonRpcClient::with_failover(server_addrs, create_client(), retry_config)
+}
+
 http_client!(pub struct HttpClient {
     pub fn status(&mut self) -> HttpRequest<StatusResponse>;
+    pub fn network_info(&mut self) -> HttpRequest<NetworkInfoView>;
 });
 
+impl HttpClient {
+    /// OpenRPC description of the methods served by the node.
+    pub fn openrpc(&mut self) -> HttpRequest<Value> {
+        call_http_get(&self.client, &self.server_addr, "openrpc.json", ())
+    }
+}
+
 /// Create new HTTP client that connects to the given address.
 pub fn new_http_client(server_addr: &str) -> HttpClient {
     HttpClient::new(server_addr, create_client())
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use near_primitives::errors::{InvalidTxError, TxExecutionError};
+    use near_primitives::views::ServerError;
+
+    use super::*;
+
+    #[test]
+    fn test_typed_errors() {
+        let tx_error = ServerError::TxExecutionError(TxExecutionError::InvalidTxError(
+            InvalidTxError::InvalidSignature,
+        ));
+        assert_eq!(
+            JsonRpcError::from(RpcError::server_error(Some(tx_error.clone()))),
+            JsonRpcError::Server(tx_error)
+        );
+        assert_eq!(
+            JsonRpcError::from(RpcError::server_error(Some(ServerError::Timeout))),
+            JsonRpcError::Server(ServerError::Timeout)
+        );
+        let other_error = RpcError::server_error(Some("Invalid public key"));
+        assert_eq!(JsonRpcError::from(other_error.clone()), JsonRpcError::Rpc(other_error));
+        let parse_error = RpcError::parse_error("Failed to parse".to_string());
+        assert_eq!(JsonRpcError::from(parse_error.clone()).code(), Some(-32_700));
+        assert!(!is_rate_limited(&Message::response(json!(1), Ok(Value::Null))));
+        assert!(is_rate_limited(&Message::response(json!(1), Err(RpcError::too_many_requests()))));
+    }
+}
@@ -17,7 +17,7 @@ use std::fmt::{Formatter, Result as FmtResult};
 use serde::de::{Deserialize, Deserializer, Error, Unexpected, Visitor};
 use serde::ser::{Serialize, SerializeStruct, Serializer};
 use serde_derive::{Deserialize, Serialize};
-use serde_json::{to_value, Result as JsonResult, Value};
+use serde_json::{json, to_value, Result as JsonResult, Value};
 use uuid::Uuid;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -111,6 +111,18 @@ impl RpcError {
     pub fn parse_error(e: String) -> Self {
         RpcError::new(-32_700, "Parse error".to_owned(), Some(Value::String(e)))
     }
+    /// Create an error for requests rejected by the rate limits.
+    pub fn too_many_requests() -> Self {
+        RpcError::new(-32_005, "Too many requests".to_owned(), None)
+    }
+    /// Create an error for requests costing more than the rate limits ever allow at once.
+    pub fn cost_above_burst(cost: u64, burst: u64) -> Self {
+        RpcError::new(
+            -32_006,
+            "Request cost above the rate limit burst".to_owned(),
+            Some(json!({ "cost": cost, "burst": burst })),
+        )
+    }
     /// Create a method not found error.
     pub fn method_not_found(method: String) -> Self {
         RpcError::new(-32_601, "Method not found".to_owned(), Some(Value::String(method)))
@@ -1,53 +1,89 @@
 extern crate prometheus;
 
 use std::convert::TryFrom;
-use std::fmt::Display;
 use std::string::FromUtf8Error;
+use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
-use actix::{Addr, MailboxError};
+use actix::{Addr, MailboxError, System};
 use actix_cors::{Cors, CorsFactory};
-use actix_web::{http, middleware, web, App, Error as HttpError, HttpResponse, HttpServer};
+use actix_web::{
+    http, middleware, web, App, Error as HttpError, HttpRequest, HttpResponse, HttpServer,
+};
 use borsh::BorshDeserialize;
-use futures::Future;
+use futures::{future, Future};
 use futures::{FutureExt, TryFutureExt};
+use lazy_static::lazy_static;
+use log::{debug, info, warn};
 use serde::de::DeserializeOwned;
 use serde_derive::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use tokio::time::{delay_for, timeout};
 
 use message::Message;
-use message::{Request, RpcError};
+use message::{Notification, Request, RpcError};
 use near_client::{
-    ClientActor, GetBlock, GetChunk, GetGasPrice, GetKeyValueChanges, GetNetworkInfo,
-    GetNextLightClientBlock, GetValidatorInfo, Query, Status, TxStatus, ViewClientActor,
+    ChangeTracking, ClearOldData, ClientActor, GetAccountActivity, GetBlock, GetChunk, GetGasPrice,
+    GetKeyValueChanges, GetNetworkInfo, GetNextLightClientBlock, GetReceipt, GetReceiptOutcome,
+    GetTransactionPool, GetValidatorInfo, ManagePeers, Query, Shutdown, SimulateTransaction,
+    Status, TxStatus, ViewClientActor,
 };
 use near_crypto::PublicKey;
 pub use near_jsonrpc_client as client;
 use near_jsonrpc_client::{message, ChunkId};
 use near_metrics::{Encoder, TextEncoder};
+use near_network::types::BanTarget;
 #[cfg(feature = "adversarial")]
 use near_network::types::NetworkAdversarialMessage::{
     AdvDisableHeaderSync, AdvGetSavedBlocks, AdvProduceBlocks, AdvSetSyncInfo,
 };
 #[cfg(feature = "adversarial")]
 use near_network::types::NetworkViewClientMessages;
-use near_network::{NetworkClientMessages, NetworkClientResponses};
-use near_primitives::errors::{InvalidTxError, TxExecutionError};
+use near_network::{NetworkClientMessages, NetworkClientResponses, NetworkResponses};
 use near_primitives::hash::CryptoHash;
-use near_primitives::rpc::{BlockQueryInfo, RpcQueryRequest};
+use near_primitives::rpc::{
+    BlockQueryInfo, RpcAccountActivityRequest, RpcQueryRequest, RpcReceiptRequest,
+    RpcSimulateTransactionRequest, RpcTrackingRequest,
+};
 use near_primitives::serialize::{from_base, from_base64, BaseEncode};
 use near_primitives::transaction::SignedTransaction;
 use near_primitives::types::{AccountId, BlockId, MaybeBlockId, StateChangesRequest};
 use near_primitives::utils::is_valid_account_id;
-use near_primitives::views::{FinalExecutionStatus, Finality, QueryRequest};
+use near_primitives::views::{
+    FinalExecutionStatus, Finality, PeersView, QueryRequest, StateChangesByKeyView,
+    StateChangesInBlockView,
+};
 
 mod metrics;
+mod openrpc;
+mod rate_limit;
 pub mod test_utils;
 
+use crate::rate_limit::{RateLimitError, RpcRateLimiter};
+pub use crate::rate_limit::{RpcRateLimit, RpcRateLimitConfig};
+pub use near_primitives::views::ServerError;
+
 /// Maximum byte size of the json payload.
 const JSON_PAYLOAD_MAX_SIZE: usize = 2 * 1024 * 1024;
 const QUERY_DATA_MAX_SIZE: usize = 2 * 1024;
+/// Header identifying clients with their own rate limits.
+const API_KEY_HEADER: &str = "X-Api-Key";
+
+/// Replaces the log filter with the given directives.
+type LogFilterReloader = Box<dyn Fn(&str) -> Result<(), String> + Send + Sync>;
+
+lazy_static! {
+    static ref LOG_FILTER_RELOADER: RwLock<Option<LogFilterReloader>> = RwLock::new(None);
+}
+
+/// Registers the function used by `admin_set_log_filter`. Logging is set up by the binary, so
+/// the log filter can't be changed over RPC until it is registered.
+pub fn set_log_filter_reloader<F>(reloader: F)
+where
+    F: Fn(&str) -> Result<(), String> + Send + Sync + 'static,
+{
+    *LOG_FILTER_RELOADER.write().unwrap() = Some(Box::new(reloader));
+}
 
 #[derive(Serialize, Deserialize, Clone, Copy, Debug)]
 pub struct RpcPollingConfig {
@@ -69,6 +105,23 @@ pub struct RpcConfig {
     pub addr: String,
     pub cors_allowed_origins: Vec<String>,
     pub polling_config: RpcPollingConfig,
+    /// Maximum number of requests in a single JSON-RPC batch.
+    #[serde(default = "default_max_batch_size")]
+    pub max_batch_size: usize,
+    /// Per client limits on the cost of requests to the public listener. Not enforced if unset.
+    #[serde(default)]
+    pub rate_limits: Option<RpcRateLimitConfig>,
+    /// Address of the listener serving the `admin_*` methods. It's only started if the
+    /// `admin_token` is set as well.
+    #[serde(default)]
+    pub admin_addr: Option<String>,
+    /// Token that has to be passed as `Authorization: Bearer <token>` to the admin listener.
+    #[serde(default)]
+    pub admin_token: Option<String>,
+}
+
+fn default_max_batch_size() -> usize {
+    100
 }
 
 impl Default for RpcConfig {
@@ -77,6 +130,10 @@ impl Default for RpcConfig {
             addr: "0.0.0.0:3030".to_owned(),
             cors_allowed_origins: vec!["*".to_owned()],
             polling_config: Default::default(),
+            max_batch_size: default_max_batch_size(),
+            rate_limits: None,
+            admin_addr: None,
+            admin_token: None,
         }
     }
 }
@@ -117,77 +174,245 @@ fn jsonify<T: serde::Serialize>(
 
 fn parse_tx(params: Option<Value>) -> Result<SignedTransaction, RpcError> {
     let (encoded,) = parse_params::<(String,)>(params)?;
+    decode_tx(encoded)
+}
+
+fn decode_tx(encoded: String) -> Result<SignedTransaction, RpcError> {
     let bytes = from_base64_or_parse_err(encoded)?;
     SignedTransaction::try_from_slice(&bytes)
         .map_err(|e| RpcError::invalid_params(Some(format!("Failed to decode transaction: {}", e))))
 }
 
-/// A general Server Error
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, near_rpc_error_macro::RpcError)]
-pub enum ServerError {
-    TxExecutionError(TxExecutionError),
-    Timeout,
-    Closed,
-}
-
-impl Display for ServerError {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
-        match self {
-            ServerError::TxExecutionError(e) => write!(f, "ServerError: {}", e),
-            ServerError::Timeout => write!(f, "ServerError: Timeout"),
-            ServerError::Closed => write!(f, "ServerError: Closed"),
-        }
+/// Accepts either `[receipt_id]` or an object with `receipt_id` and optional `receiver_id`.
+fn parse_receipt_request(params: Option<Value>) -> Result<RpcReceiptRequest, RpcError> {
+    if let Ok((receipt_id,)) = parse_params::<(CryptoHash,)>(params.clone()) {
+        Ok(RpcReceiptRequest { receipt_id, receiver_id: None })
+    } else {
+        parse_params::<RpcReceiptRequest>(params)
     }
 }
 
-impl From<InvalidTxError> for ServerError {
-    fn from(e: InvalidTxError) -> ServerError {
-        ServerError::TxExecutionError(TxExecutionError::InvalidTxError(e))
+/// Converts the error of sending a message to one of the actors.
+fn mailbox_err(err: MailboxError) -> ServerError {
+    match err {
+        MailboxError::Closed => ServerError::Closed,
+        MailboxError::Timeout => ServerError::Timeout,
     }
 }
 
-impl From<MailboxError> for ServerError {
-    fn from(e: MailboxError) -> Self {
-        match e {
-            MailboxError::Closed => ServerError::Closed,
-            MailboxError::Timeout => ServerError::Timeout,
+/// Parses the `method_name[?key=value&...]` part of the `call/` query path. The optional keys
+/// describe the caller of the view function: `signer_account_id`, `predecessor_account_id`,
+/// `signer_public_key` and `attached_deposit`. Their values are percent-encoded.
+fn parse_call_function(
+    account_id: AccountId,
+    method: &str,
+    args: Vec<u8>,
+) -> Result<QueryRequest, RpcError> {
+    let mut method_parts = method.splitn(2, '?');
+    let method_name = method_parts.next().unwrap_or_default().to_string();
+    let params: Vec<(String, String)> =
+        serde_urlencoded::from_str(method_parts.next().unwrap_or_default()).map_err(|err| {
+            RpcError::invalid_params(Some(format!("Invalid call parameters: {}", err)))
+        })?;
+    let parse_account_id = |value: String| {
+        if is_valid_account_id(&value) {
+            Ok(value)
+        } else {
+            Err(RpcError::invalid_params(Some(format!("Invalid account id {}", value))))
+        }
+    };
+    let mut signer_account_id = None;
+    let mut predecessor_account_id = None;
+    let mut signer_public_key = None;
+    let mut attached_deposit = None;
+    for (key, value) in params {
+        match key.as_str() {
+            "signer_account_id" => signer_account_id = Some(parse_account_id(value)?),
+            "predecessor_account_id" => predecessor_account_id = Some(parse_account_id(value)?),
+            "signer_public_key" => {
+                signer_public_key = Some(PublicKey::try_from(value.as_str()).map_err(|_| {
+                    RpcError::invalid_params(Some(format!("Invalid public key {}", value)))
+                })?)
+            }
+            "attached_deposit" => {
+                attached_deposit = Some(value.parse().map_err(|_| {
+                    RpcError::invalid_params(Some(format!("Invalid attached deposit {}", value)))
+                })?)
+            }
+            _ => {
+                return Err(RpcError::invalid_params(Some(format!(
+                    "Unknown call parameter {}",
+                    key
+                ))))
+            }
         }
     }
-}
-
-impl From<ServerError> for RpcError {
-    fn from(e: ServerError) -> RpcError {
-        RpcError::server_error(Some(e))
+    if signer_account_id.is_none()
+        && predecessor_account_id.is_none()
+        && signer_public_key.is_none()
+        && attached_deposit.is_none()
+    {
+        return Ok(QueryRequest::CallFunction { account_id, method_name, args: args.into() });
     }
+    Ok(QueryRequest::CallFunctionWithCaller {
+        account_id,
+        method_name,
+        args: args.into(),
+        signer_account_id,
+        predecessor_account_id,
+        signer_public_key,
+        attached_deposit,
+    })
 }
 
 fn timeout_err() -> RpcError {
     RpcError::server_error(Some(ServerError::Timeout))
 }
 
+fn admin_only_err() -> RpcError {
+    RpcError::server_error(Some("Admin methods are only served by the admin listener".to_string()))
+}
+
+fn unexpected_response_err(response: NetworkResponses) -> RpcError {
+    RpcError::server_error(Some(format!("Unexpected network response {:?}", response)))
+}
+
+/// Compares the tokens in time that doesn't depend on the position of the first mismatch.
+fn tokens_match(expected: &str, provided: &str) -> bool {
+    expected.len() == provided.len()
+        && expected.bytes().zip(provided.bytes()).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+/// Token of the admin listener. It's only known to the admin listener, the public one never
+/// looks at the `Authorization` header.
+struct AdminToken(String);
+
+impl AdminToken {
+    /// Whether the `Authorization` header of the request carries the admin token.
+    fn is_authorized(&self, request: &HttpRequest) -> bool {
+        request
+            .headers()
+            .get(http::header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| {
+                let mut parts = value.splitn(2, ' ');
+                match (parts.next(), parts.next()) {
+                    (Some("Bearer"), Some(token)) => Some(token.trim()),
+                    _ => None,
+                }
+            })
+            .map_or(false, |token| tokens_match(&self.0, token))
+    }
+}
+
 struct JsonRpcHandler {
     client_addr: Addr<ClientActor>,
     view_client_addr: Addr<ViewClientActor>,
     polling_config: RpcPollingConfig,
+    max_batch_size: usize,
+    rate_limiter: Option<Arc<RpcRateLimiter>>,
 }
 
 impl JsonRpcHandler {
-    pub async fn process(&self, message: Message) -> Result<Message, HttpError> {
+    /// Checks whether the client sending the request is within its rate limits, and if so, charges
+    /// it with the cost of the message. Requests without a message, i.e. to the GET endpoints,
+    /// cost 1.
+    fn within_rate_limits(
+        &self,
+        request: &HttpRequest,
+        message: Option<&Message>,
+    ) -> Result<(), RateLimitError> {
+        let rate_limiter = match &self.rate_limiter {
+            Some(rate_limiter) => rate_limiter,
+            None => return Ok(()),
+        };
+        let ip = match request.peer_addr() {
+            Some(addr) => addr.ip(),
+            None => return Ok(()),
+        };
+        let api_key = request.headers().get(API_KEY_HEADER).and_then(|value| value.to_str().ok());
+        let cost = message.map_or(1, |message| rate_limiter.message_cost(message));
+        if let Err(err) = rate_limiter.allow(ip, api_key, cost) {
+            near_metrics::inc_counter(&metrics::RPC_RATE_LIMITED_COUNT);
+            return Err(err);
+        }
+        near_metrics::inc_counter_by(&metrics::RPC_REQUEST_COST, cost as i64);
+        Ok(())
+    }
+
+    /// Processes the message. `is_admin` is set for the authorized requests of the admin listener.
+    /// Returns `None` if nothing has to be answered, i.e. for notifications.
+    pub async fn process(
+        &self,
+        message: Message,
+        is_admin: bool,
+    ) -> Result<Option<Message>, HttpError> {
+        match message {
+            Message::Batch(messages) => Ok(self.process_batch(messages, is_admin).await),
+            message => Ok(self.process_message(message, is_admin).await),
+        }
+    }
+
+    /// Processes the requests of a batch concurrently. The responses are in the order of the
+    /// requests. Notifications get no response, so a batch of notifications gets none at all.
+    async fn process_batch(&self, messages: Vec<Message>, is_admin: bool) -> Option<Message> {
+        if messages.is_empty() {
+            return Some(Message::error(RpcError::invalid_request()));
+        }
+        if messages.len() > self.max_batch_size {
+            return Some(Message::error(RpcError {
+                data: Some(Value::String(format!(
+                    "Batch size {} exceeds the limit {}",
+                    messages.len(),
+                    self.max_batch_size
+                ))),
+                ..RpcError::invalid_request()
+            }));
+        }
+        near_metrics::inc_counter_by(&metrics::RPC_BATCH_REQUEST_COUNT, messages.len() as i64);
+        let responses: Vec<_> = future::join_all(
+            messages.into_iter().map(|message| self.process_message(message, is_admin)),
+        )
+        .await
+        .into_iter()
+        .filter_map(|response| response)
+        .collect();
+        if responses.is_empty() {
+            None
+        } else {
+            Some(Message::Batch(responses))
+        }
+    }
+
+    /// Processes a request or a notification. Notifications are executed like requests, but
+    /// their result is dropped.
+    async fn process_message(&self, message: Message, is_admin: bool) -> Option<Message> {
         let id = message.id();
         match message {
-            Message::Request(request) => {
-                Ok(Message::response(id, self.process_request(request).await))
+            Message::Request(Request { method, params, .. }) => {
+                Some(Message::response(id, self.process_request(method, params, is_admin).await))
             }
-            _ => Ok(Message::error(RpcError::invalid_request())),
+            Message::Notification(Notification { method, params, .. }) => {
+                if let Err(err) = self.process_request(method.clone(), params, is_admin).await {
+                    debug!(target: "jsonrpc", "Notification {} failed: {:?}", method, err);
+                }
+                None
+            }
+            _ => Some(Message::error(RpcError::invalid_request())),
         }
     }
 
-    async fn process_request(&self, request: Request) -> Result<Value, RpcError> {
+    async fn process_request(
+        &self,
+        method: String,
+        params: Option<Value>,
+        is_admin: bool,
+    ) -> Result<Value, RpcError> {
         #[cfg(feature = "adversarial")]
         {
-            let params = request.params.clone();
+            let params = params.clone();
 
-            let res = match request.method.as_ref() {
+            let res = match method.as_ref() {
                 // Adversarial controls
                 "adv_set_weight" => Some(self.adv_set_sync_info(params).await),
                 "adv_disable_header_sync" => Some(self.adv_disable_header_sync(params).await),
@@ -201,21 +426,43 @@ impl JsonRpcHandler {
             }
         }
 
-        match request.method.as_ref() {
-            "broadcast_tx_async" => self.send_tx_async(request.params).await,
-            "broadcast_tx_commit" => self.send_tx_commit(request.params).await,
-            "validators" => self.validators(request.params).await,
-            "query" => self.query(request.params).await,
+        if method.starts_with("admin_") {
+            if !is_admin {
+                return Err(admin_only_err());
+            }
+            return match method.as_ref() {
+                "admin_track" => self.admin_track(params).await,
+                "admin_untrack" => self.admin_untrack(params).await,
+                "admin_peers" => self.admin_peers().await,
+                "admin_ban_peer" => self.admin_ban_peer(params).await,
+                "admin_unban" => self.admin_unban(params).await,
+                "admin_set_log_filter" => self.admin_set_log_filter(params).await,
+                "admin_clear_old_data" => self.admin_clear_old_data().await,
+                "admin_tx_pool" => self.admin_tx_pool().await,
+                "admin_shutdown" => self.admin_shutdown().await,
+                _ => Err(RpcError::method_not_found(method)),
+            };
+        }
+
+        match method.as_ref() {
+            "broadcast_tx_async" => self.send_tx_async(params).await,
+            "broadcast_tx_commit" => self.send_tx_commit(params).await,
+            "validators" => self.validators(params).await,
+            "query" => self.query(params).await,
             "health" => self.health().await,
             "status" => self.status().await,
-            "tx" => self.tx_status(request.params).await,
-            "block" => self.block(request.params).await,
-            "chunk" => self.chunk(request.params).await,
-            "changes" => self.changes(request.params).await,
-            "next_light_client_block" => self.next_light_client_block(request.params).await,
+            "tx" => self.tx_status(params).await,
+            "block" => self.block(params).await,
+            "chunk" => self.chunk(params).await,
+            "changes" => self.changes(params).await,
+            "next_light_client_block" => self.next_light_client_block(params).await,
             "network_info" => self.network_info().await,
-            "gas_price" => self.gas_price(request.params).await,
-            _ => Err(RpcError::method_not_found(request.method)),
+            "gas_price" => self.gas_price(params).await,
+            "simulate_tx" => self.simulate_tx(params).await,
+            "account_activity" => self.account_activity(params).await,
+            "receipt" => self.receipt(params).await,
+            "outcome" => self.outcome(params).await,
+            _ => Err(RpcError::method_not_found(method)),
         }
     }
 
@@ -278,6 +525,7 @@ impl JsonRpcHandler {
         &self,
         tx_hash: CryptoHash,
         account_id: AccountId,
+        with_gas_profile: bool,
     ) -> Result<Value, RpcError> {
         timeout(self.polling_config.polling_timeout, async {
             loop {
@@ -285,12 +533,17 @@ impl JsonRpcHandler {
                     .view_client_addr
                     .send(TxStatus { tx_hash, signer_account_id: account_id.clone() })
                     .await;
-                if let Ok(Ok(Some(ref tx_result))) = final_tx {
+                if let Ok(Ok(Some(tx_result))) = final_tx {
                     match tx_result.status {
                         FinalExecutionStatus::Started | FinalExecutionStatus::NotStarted => {}
                         FinalExecutionStatus::Failure(_)
                         | FinalExecutionStatus::SuccessValue(_) => {
-                            break jsonify(final_tx);
+                            let tx_result = if with_gas_profile {
+                                tx_result
+                            } else {
+                                tx_result.without_gas_profile()
+                            };
+                            break jsonify(Ok(Ok(tx_result)));
                         }
                     }
                 }
@@ -308,11 +561,11 @@ impl JsonRpcHandler {
         let result = self
             .client_addr
             .send(NetworkClientMessages::Transaction(tx))
-            .map_err(|err| RpcError::server_error(Some(ServerError::from(err))))
+            .map_err(|err| RpcError::server_error(Some(mailbox_err(err))))
             .await?;
         match result {
             NetworkClientResponses::ValidTx | NetworkClientResponses::RequestRouted => {
-                self.tx_polling(tx_hash, signer_account_id).await
+                self.tx_polling(tx_hash, signer_account_id, false).await
             }
             NetworkClientResponses::InvalidTx(err) => {
                 Err(RpcError::server_error(Some(ServerError::TxExecutionError(err.into()))))
@@ -380,12 +633,8 @@ impl JsonRpcHandler {
                     },
                     "contract" => QueryRequest::ViewState { account_id, prefix: data.into() },
                     "call" => {
-                        if let Some(method_name) = path_parts.get(2) {
-                            QueryRequest::CallFunction {
-                                account_id,
-                                method_name: method_name.to_string(),
-                                args: data.into(),
-                            }
+                        if let Some(method) = path_parts.get(2) {
+                            parse_call_function(account_id, method, data)?
                         } else {
                             return Err(RpcError::server_error(Some(
                                 "Method name is missing".to_string(),
@@ -425,7 +674,12 @@ impl JsonRpcHandler {
     }
 
     async fn tx_status(&self, params: Option<Value>) -> Result<Value, RpcError> {
-        let (hash, account_id) = parse_params::<(String, String)>(params)?;
+        let (hash, account_id, with_gas_profile) =
+            if let Ok((hash, account_id)) = parse_params::<(String, String)>(params.clone()) {
+                (hash, account_id, false)
+            } else {
+                parse_params::<(String, String, bool)>(params)?
+            };
         if !is_valid_account_id(&account_id) {
             return Err(RpcError::invalid_params(Some(format!(
                 "Invalid account id: {}",
@@ -436,7 +690,7 @@ impl JsonRpcHandler {
             CryptoHash::try_from(bytes).map_err(|err| RpcError::parse_error(err.to_string()))
         })?;
 
-        self.tx_polling(tx_hash, account_id).await
+        self.tx_polling(tx_hash, account_id, with_gas_profile).await
     }
 
     async fn block(&self, params: Option<Value>) -> Result<Value, RpcError> {
@@ -475,30 +729,14 @@ impl JsonRpcHandler {
     async fn changes(&self, params: Option<Value>) -> Result<Value, RpcError> {
         let (block_hash, state_changes_request) =
             parse_params::<(CryptoHash, StateChangesRequest)>(params)?;
-        let block_hash_copy = block_hash.clone();
         jsonify(
             self.view_client_addr
                 .send(GetKeyValueChanges { block_hash, state_changes_request })
                 .await
                 .map(|v| {
-                    v.map(|changes| {
-                        json!({
-                            "block_hash": block_hash_copy,
-                            "changes_by_key": changes
-                            .into_iter()
-                            .map(|(key, changes)| {
-                                json!({
-                                    "key": key,
-                                    "changes": changes.into_iter().map(|(cause, value)| {
-                                        json!({
-                                            "cause": cause,
-                                            "value": value
-                                        })
-                                    }).collect::<Vec<_>>()
-                                })
-                            })
-                            .collect::<Vec<_>>()
-                        })
+                    v.map(|changes| StateChangesInBlockView {
+                        block_hash,
+                        changes_by_key: StateChangesByKeyView::from_state_changes(changes),
                     })
                 }),
         )
@@ -518,6 +756,81 @@ impl JsonRpcHandler {
         jsonify(self.view_client_addr.send(GetGasPrice { block_id }).await)
     }
 
+    async fn simulate_tx(&self, params: Option<Value>) -> Result<Value, RpcError> {
+        let request = if let Ok((signed_tx_base64,)) = parse_params::<(String,)>(params.clone()) {
+            RpcSimulateTransactionRequest {
+                signed_tx_base64,
+                block_id: None,
+                finality: Finality::default(),
+                verify_signature: false,
+            }
+        } else {
+            parse_params::<RpcSimulateTransactionRequest>(params)?
+        };
+        let transaction = decode_tx(request.signed_tx_base64)?;
+        jsonify(
+            self.view_client_addr
+                .send(SimulateTransaction {
+                    transaction,
+                    block_id: request.block_id,
+                    finality: request.finality,
+                    verify_signature: request.verify_signature,
+                })
+                .await,
+        )
+    }
+
+    async fn account_activity(&self, params: Option<Value>) -> Result<Value, RpcError> {
+        let RpcAccountActivityRequest { account_id, before_height, limit } = parse_params(params)?;
+        jsonify(
+            self.view_client_addr
+                .send(GetAccountActivity { account_id, before_height, limit })
+                .await,
+        )
+    }
+
+    async fn receipt(&self, params: Option<Value>) -> Result<Value, RpcError> {
+        let RpcReceiptRequest { receipt_id, receiver_id } = parse_receipt_request(params)?;
+        timeout(self.polling_config.polling_timeout, async {
+            loop {
+                let result = self
+                    .view_client_addr
+                    .send(GetReceipt { receipt_id, receiver_id: receiver_id.clone() })
+                    .await;
+                match result {
+                    Ok(Ok(Some(receipt))) => break jsonify(Ok(Ok(receipt))),
+                    Ok(Ok(None)) => {}
+                    Ok(Err(e)) => break Err(RpcError::server_error(Some(e))),
+                    Err(e) => break Err(RpcError::server_error(Some(e.to_string()))),
+                }
+                delay_for(self.polling_config.polling_interval).await;
+            }
+        })
+        .await
+        .map_err(|_| timeout_err())?
+    }
+
+    async fn outcome(&self, params: Option<Value>) -> Result<Value, RpcError> {
+        let RpcReceiptRequest { receipt_id, receiver_id } = parse_receipt_request(params)?;
+        timeout(self.polling_config.polling_timeout, async {
+            loop {
+                let result = self
+                    .view_client_addr
+                    .send(GetReceiptOutcome { receipt_id, receiver_id: receiver_id.clone() })
+                    .await;
+                match result {
+                    Ok(Ok(Some(outcome))) => break jsonify(Ok(Ok(outcome))),
+                    Ok(Ok(None)) => {}
+                    Ok(Err(e)) => break Err(RpcError::server_error(Some(e))),
+                    Err(e) => break Err(RpcError::server_error(Some(e.to_string()))),
+                }
+                delay_for(self.polling_config.polling_interval).await;
+            }
+        })
+        .await
+        .map_err(|_| timeout_err())?
+    }
+
     pub async fn metrics(&self) -> Result<String, FromUtf8Error> {
         // Gather metrics and return them as a String
         let mut buffer = vec![];
@@ -531,27 +844,166 @@ impl JsonRpcHandler {
         let (block_id,) = parse_params::<(MaybeBlockId,)>(params)?;
         jsonify(self.view_client_addr.send(GetValidatorInfo { block_id }).await)
     }
+
+    /// Starts tracking accounts and shards. They are tracked once their state is caught up,
+    /// which happens during the next epoch.
+    async fn admin_track(&self, params: Option<Value>) -> Result<Value, RpcError> {
+        let RpcTrackingRequest { accounts, shards } = parse_params(params)?;
+        jsonify(self.client_addr.send(ChangeTracking::Track { accounts, shards }).await)
+    }
+
+    /// Stops tracking accounts and shards starting from the next epoch.
+    async fn admin_untrack(&self, params: Option<Value>) -> Result<Value, RpcError> {
+        let RpcTrackingRequest { accounts, shards } = parse_params(params)?;
+        jsonify(self.client_addr.send(ChangeTracking::Untrack { accounts, shards }).await)
+    }
+
+    async fn manage_peers(&self, msg: ManagePeers) -> Result<NetworkResponses, RpcError> {
+        self.client_addr
+            .send(msg)
+            .await
+            .map_err(|err| RpcError::server_error(Some(mailbox_err(err))))?
+            .map_err(|err| RpcError::server_error(Some(err)))
+    }
+
+    async fn admin_peers(&self) -> Result<Value, RpcError> {
+        let network_info = self
+            .client_addr
+            .send(GetNetworkInfo {})
+            .await
+            .map_err(|err| RpcError::server_error(Some(err.to_string())))?
+            .map_err(|err| RpcError::server_error(Some(err)))?;
+        match self.manage_peers(ManagePeers::FetchBans).await? {
+            NetworkResponses::Bans(bans) => Ok(json!(PeersView {
+                active_peers: network_info.active_peers,
+                bans: bans.into_iter().map(Into::into).collect(),
+            })),
+            response => Err(unexpected_response_err(response)),
+        }
+    }
+
+    /// Bans a peer id or an IP address / subnet.
+    async fn admin_ban_peer(&self, params: Option<Value>) -> Result<Value, RpcError> {
+        let (target,) = parse_params::<(String,)>(params)?;
+        let target: BanTarget = target.parse().map_err(|err| {
+            RpcError::invalid_params(Some(format!("Invalid ban target: {}", err)))
+        })?;
+        match self.manage_peers(ManagePeers::Ban(target.clone())).await? {
+            NetworkResponses::BanResult(true) => Ok(Value::Null),
+            NetworkResponses::BanResult(false) => {
+                Err(RpcError::server_error(Some(format!("Failed to ban {}", target))))
+            }
+            response => Err(unexpected_response_err(response)),
+        }
+    }
+
+    /// Lifts the ban of a peer id or an IP address / subnet.
+    async fn admin_unban(&self, params: Option<Value>) -> Result<Value, RpcError> {
+        let (target,) = parse_params::<(String,)>(params)?;
+        let target: BanTarget = target.parse().map_err(|err| {
+            RpcError::invalid_params(Some(format!("Invalid ban target: {}", err)))
+        })?;
+        match self.manage_peers(ManagePeers::Unban(target.clone())).await? {
+            NetworkResponses::UnbanResult(true) => Ok(Value::Null),
+            NetworkResponses::UnbanResult(false) => {
+                Err(RpcError::server_error(Some(format!("Failed to unban {}", target))))
+            }
+            response => Err(unexpected_response_err(response)),
+        }
+    }
+
+    /// Replaces the log filter of the node. The filter uses the `RUST_LOG` syntax and replaces
+    /// all the directives, including the default ones.
+    async fn admin_set_log_filter(&self, params: Option<Value>) -> Result<Value, RpcError> {
+        let (filter,) = parse_params::<(String,)>(params)?;
+        let reloader = LOG_FILTER_RELOADER.read().unwrap();
+        let reloader = reloader.as_ref().ok_or_else(|| {
+            RpcError::server_error(Some("The log filter can't be changed".to_string()))
+        })?;
+        reloader(&filter).map_err(|err| RpcError::server_error(Some(err)))?;
+        info!(target: "jsonrpc", "Log filter is set to {}", filter);
+        Ok(Value::Null)
+    }
+
+    async fn admin_clear_old_data(&self) -> Result<Value, RpcError> {
+        jsonify(self.client_addr.send(ClearOldData).await)
+    }
+
+    async fn admin_tx_pool(&self) -> Result<Value, RpcError> {
+        jsonify(self.client_addr.send(GetTransactionPool).await)
+    }
+
+    /// Stops the view client, then the client, which flushes the store, and then the rest of
+    /// the node. The HTTP server finishes sending this response before it stops.
+    async fn admin_shutdown(&self) -> Result<Value, RpcError> {
+        info!(target: "jsonrpc", "Shutting down at the request of the operator");
+        jsonify(self.view_client_addr.send(Shutdown).await)?;
+        jsonify(self.client_addr.send(Shutdown).await)?;
+        System::current().stop();
+        Ok(Value::Null)
+    }
 }
 
 fn rpc_handler(
+    request: HttpRequest,
     message: web::Json<Message>,
     handler: web::Data<JsonRpcHandler>,
 ) -> impl Future<Output = Result<HttpResponse, HttpError>> {
     near_metrics::inc_counter(&metrics::HTTP_RPC_REQUEST_COUNT);
 
+    let allowed = handler.within_rate_limits(&request, Some(&message.0));
     let response = async move {
-        let message = handler.process(message.0).await?;
-        Ok(HttpResponse::Ok().json(message))
+        match allowed {
+            Ok(()) => {}
+            Err(RateLimitError::TooManyRequests) => {
+                let error = Message::response(message.0.id(), Err(RpcError::too_many_requests()));
+                return Ok(HttpResponse::TooManyRequests().json(error));
+            }
+            // Waiting doesn't help, the message has to be split up.
+            Err(RateLimitError::CostAboveBurst { cost, burst }) => {
+                let error =
+                    Message::response(message.0.id(), Err(RpcError::cost_above_burst(cost, burst)));
+                return Ok(HttpResponse::BadRequest().json(error));
+            }
+        }
+        Ok(match handler.process(message.0, false).await? {
+            Some(message) => HttpResponse::Ok().json(message),
+            None => HttpResponse::NoContent().finish(),
+        })
+    };
+    response.boxed()
+}
+
+fn admin_rpc_handler(
+    request: HttpRequest,
+    message: web::Json<Message>,
+    handler: web::Data<JsonRpcHandler>,
+    admin_token: web::Data<AdminToken>,
+) -> impl Future<Output = Result<HttpResponse, HttpError>> {
+    let is_admin = admin_token.is_authorized(&request);
+    let response = async move {
+        if !is_admin {
+            return Ok(HttpResponse::Unauthorized().finish());
+        }
+        Ok(match handler.process(message.0, true).await? {
+            Some(message) => HttpResponse::Ok().json(message),
+            None => HttpResponse::NoContent().finish(),
+        })
     };
     response.boxed()
 }
 
 fn status_handler(
+    request: HttpRequest,
     handler: web::Data<JsonRpcHandler>,
 ) -> impl Future<Output = Result<HttpResponse, HttpError>> {
     near_metrics::inc_counter(&metrics::HTTP_STATUS_REQUEST_COUNT);
 
+    let allowed = handler.within_rate_limits(&request, None).is_ok();
     let response = async move {
+        if !allowed {
+            return Ok(HttpResponse::TooManyRequests().finish());
+        }
         match handler.status().await {
             Ok(value) => Ok(HttpResponse::Ok().json(value)),
             Err(_) => Ok(HttpResponse::ServiceUnavailable().finish()),
@@ -561,9 +1013,14 @@ fn status_handler(
 }
 
 fn network_info_handler(
+    request: HttpRequest,
     handler: web::Data<JsonRpcHandler>,
 ) -> impl Future<Output = Result<HttpResponse, HttpError>> {
+    let allowed = handler.within_rate_limits(&request, None).is_ok();
     let response = async move {
+        if !allowed {
+            return Ok(HttpResponse::TooManyRequests().finish());
+        }
         match handler.network_info().await {
             Ok(value) => Ok(HttpResponse::Ok().json(value)),
             Err(_) => Ok(HttpResponse::ServiceUnavailable().finish()),
@@ -572,12 +1029,41 @@ fn network_info_handler(
     response.boxed()
 }
 
+fn openrpc_handler(
+    request: HttpRequest,
+    handler: web::Data<JsonRpcHandler>,
+) -> impl Future<Output = Result<HttpResponse, HttpError>> {
+    let response = if handler.within_rate_limits(&request, None).is_ok() {
+        HttpResponse::Ok().json(openrpc::openrpc_document(false))
+    } else {
+        HttpResponse::TooManyRequests().finish()
+    };
+    future::ready(Ok(response))
+}
+
+fn admin_openrpc_handler(
+    request: HttpRequest,
+    admin_token: web::Data<AdminToken>,
+) -> impl Future<Output = Result<HttpResponse, HttpError>> {
+    let response = if admin_token.is_authorized(&request) {
+        HttpResponse::Ok().json(openrpc::openrpc_document(true))
+    } else {
+        HttpResponse::Unauthorized().finish()
+    };
+    future::ready(Ok(response))
+}
+
 fn prometheus_handler(
+    request: HttpRequest,
     handler: web::Data<JsonRpcHandler>,
 ) -> impl Future<Output = Result<HttpResponse, HttpError>> {
     near_metrics::inc_counter(&metrics::PROMETHEUS_REQUEST_COUNT);
 
+    let allowed = handler.within_rate_limits(&request, None).is_ok();
     let response = async move {
+        if !allowed {
+            return Ok(HttpResponse::TooManyRequests().finish());
+        }
         match handler.metrics().await {
             Ok(value) => Ok(HttpResponse::Ok().body(value)),
             Err(_) => Ok(HttpResponse::ServiceUnavailable().finish()),
@@ -596,6 +1082,7 @@ fn get_cors(cors_allowed_origins: &[String]) -> CorsFactory {
     cors.allowed_methods(vec!["GET", "POST"])
         .allowed_headers(vec![http::header::AUTHORIZATION, http::header::ACCEPT])
         .allowed_header(http::header::CONTENT_TYPE)
+        .allowed_header(API_KEY_HEADER)
         .max_age(3600)
         .finish()
 }
@@ -605,15 +1092,53 @@ pub fn start_http(
     client_addr: Addr<ClientActor>,
     view_client_addr: Addr<ViewClientActor>,
 ) {
-    let RpcConfig { addr, polling_config, cors_allowed_origins } = config;
+    let RpcConfig {
+        addr,
+        polling_config,
+        cors_allowed_origins,
+        max_batch_size,
+        rate_limits,
+        admin_addr,
+        admin_token,
+    } = config;
+    let rate_limiter = rate_limits.map(|config| Arc::new(RpcRateLimiter::new(config)));
+    let handler = move || JsonRpcHandler {
+        client_addr: client_addr.clone(),
+        view_client_addr: view_client_addr.clone(),
+        polling_config,
+        max_batch_size,
+        rate_limiter: rate_limiter.clone(),
+    };
+    match (admin_addr, admin_token) {
+        (Some(admin_addr), Some(admin_token)) => {
+            let handler = handler.clone();
+            info!(target: "jsonrpc", "Starting the admin listener on {}", admin_addr);
+            HttpServer::new(move || {
+                App::new()
+                    .data(handler())
+                    .data(AdminToken(admin_token.clone()))
+                    .app_data(web::JsonConfig::default().limit(JSON_PAYLOAD_MAX_SIZE))
+                    .wrap(middleware::Logger::default())
+                    .service(web::resource("/").route(web::post().to(admin_rpc_handler)))
+                    .service(
+                        web::resource("/openrpc.json").route(web::get().to(admin_openrpc_handler)),
+                    )
+            })
+            .bind(admin_addr)
+            .unwrap()
+            .workers(1)
+            .shutdown_timeout(5)
+            .run();
+        }
+        (Some(_), None) => {
+            warn!(target: "jsonrpc", "The admin listener isn't started without the admin token")
+        }
+        (None, _) => {}
+    }
     HttpServer::new(move || {
         App::new()
             .wrap(get_cors(&cors_allowed_origins))
-            .data(JsonRpcHandler {
-                client_addr: client_addr.clone(),
-                view_client_addr: view_client_addr.clone(),
-                polling_config,
-            })
+            .data(handler())
             .app_data(web::JsonConfig::default().limit(JSON_PAYLOAD_MAX_SIZE))
             .wrap(middleware::Logger::default())
             .service(web::resource("/").route(web::post().to(rpc_handler)))
@@ -624,6 +1149,7 @@ pub fn start_http(
             )
             .service(web::resource("/network_info").route(web::get().to(network_info_handler)))
             .service(web::resource("/metrics").route(web::get().to(prometheus_handler)))
+            .service(web::resource("/openrpc.json").route(web::get().to(openrpc_handler)))
     })
     .bind(addr)
     .unwrap()
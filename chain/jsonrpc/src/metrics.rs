@@ -17,4 +17,19 @@ lazy_static! {
             "http_status_requests_total",
             "Total count of HTTP Status requests received"
         );
+    pub static ref RPC_BATCH_REQUEST_COUNT: near_metrics::Result<IntCounter> =
+        near_metrics::try_create_int_counter(
+            "rpc_batch_requests_total",
+            "Total count of requests received in JSON-RPC batches"
+        );
+    pub static ref RPC_RATE_LIMITED_COUNT: near_metrics::Result<IntCounter> =
+        near_metrics::try_create_int_counter(
+            "rpc_rate_limited_requests_total",
+            "Total count of HTTP RPC requests rejected by the rate limits"
+        );
+    pub static ref RPC_REQUEST_COST: near_metrics::Result<IntCounter> =
+        near_metrics::try_create_int_counter(
+            "rpc_request_cost_total",
+            "Total cost of HTTP RPC requests accepted by the rate limits"
+        );
 }
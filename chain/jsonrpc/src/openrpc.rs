@@ -0,0 +1,512 @@
+//! OpenRPC description of the methods served by `JsonRpcHandler`. The schemas of the request
+//! and response types are derived from the Rust types with `RpcSchema` and are committed to
+//! `res/rpc_schema.json`, which is regenerated with `build_rpc_schema.sh`.
+
+use lazy_static::lazy_static;
+use serde_json::{json, Map, Value};
+
+const RPC_SCHEMA: &str = include_str!("../res/rpc_schema.json");
+const OPENRPC_VERSION: &str = "1.2.4";
+
+lazy_static! {
+    static ref OPENRPC_DOCUMENT: Value = build_openrpc_document(false);
+    static ref ADMIN_OPENRPC_DOCUMENT: Value = build_openrpc_document(true);
+}
+
+fn schema_ref(name: &str) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{}", name) })
+}
+
+fn string() -> Value {
+    json!({ "type": "string" })
+}
+
+fn null_type() -> Value {
+    json!({ "type": "null" })
+}
+
+fn array_of(items: Value) -> Value {
+    json!({ "type": "array", "items": items })
+}
+
+fn maybe_block_id() -> Value {
+    json!({ "anyOf": [schema_ref("BlockId"), null_type()] })
+}
+
+/// Parameters of a method.
+enum Params {
+    /// Array of the parameters with their names, schemas and whether they are required.
+    ByPosition(Vec<(&'static str, Value, bool)>),
+    /// Object with the fields of the given type.
+    ByName(&'static str),
+}
+
+struct Method {
+    name: &'static str,
+    summary: &'static str,
+    params: Params,
+    result: Value,
+}
+
+impl Method {
+    fn new(name: &'static str, summary: &'static str, params: Params, result: Value) -> Self {
+        Method { name, summary, params, result }
+    }
+}
+
+/// `ChunkId` of `near-jsonrpc-client`.
+fn chunk_id() -> Value {
+    json!({
+        "anyOf": [
+            {
+                "type": "array",
+                "items": [schema_ref("BlockId"), { "type": "integer" }],
+                "minItems": 2,
+                "maxItems": 2,
+            },
+            string(),
+        ]
+    })
+}
+
+fn public_methods() -> Vec<Method> {
+    use Params::{ByName, ByPosition};
+    vec![
+        Method::new(
+            "broadcast_tx_async",
+            "Sends a transaction and returns its hash without waiting for the execution.",
+            ByPosition(vec![("signed_tx_base64", string(), true)]),
+            string(),
+        ),
+        Method::new(
+            "broadcast_tx_commit",
+            "Sends a transaction and waits until it's executed.",
+            ByPosition(vec![("signed_tx_base64", string(), true)]),
+            schema_ref("FinalExecutionOutcomeView"),
+        ),
+        Method::new(
+            "validators",
+            "Validators of the epoch of the given block.",
+            ByPosition(vec![("block_id", maybe_block_id(), true)]),
+            schema_ref("EpochValidatorInfo"),
+        ),
+        Method::new(
+            "query",
+            "Queries the state of an account or calls a view function.",
+            ByName("RpcQueryRequest"),
+            schema_ref("QueryResponse"),
+        ),
+        Method::new("health", "Succeeds if the node is healthy.", ByPosition(vec![]), null_type()),
+        Method::new(
+            "status",
+            "Version, chain id, validators and sync status of the node.",
+            ByPosition(vec![]),
+            schema_ref("StatusResponse"),
+        ),
+        Method::new(
+            "tx",
+            "Waits until the transaction and its receipts are executed and returns the outcomes.",
+            ByPosition(vec![
+                ("transaction_hash", string(), true),
+                ("sender_account_id", string(), true),
+                ("with_gas_profile", json!({ "type": "boolean" }), false),
+            ]),
+            schema_ref("FinalExecutionOutcomeView"),
+        ),
+        Method::new(
+            "block",
+            "Block by id or by finality.",
+            ByName("BlockQueryInfo"),
+            schema_ref("BlockView"),
+        ),
+        Method::new(
+            "chunk",
+            "Chunk by hash or by the block id and the shard id.",
+            ByPosition(vec![("chunk_id", chunk_id(), true)]),
+            schema_ref("ChunkView"),
+        ),
+        Method::new(
+            "changes",
+            "Changes of the state made in the given block.",
+            ByPosition(vec![
+                ("block_hash", string(), true),
+                ("state_changes_request", schema_ref("StateChangesRequest"), true),
+            ]),
+            schema_ref("StateChangesInBlockView"),
+        ),
+        Method::new(
+            "next_light_client_block",
+            "Next light client block after the given block known to the light client.",
+            ByPosition(vec![("last_block_hash", string(), true)]),
+            json!({ "anyOf": [schema_ref("LightClientBlockView"), null_type()] }),
+        ),
+        Method::new(
+            "network_info",
+            "Active peers and known producers of the node.",
+            ByPosition(vec![]),
+            schema_ref("NetworkInfoView"),
+        ),
+        Method::new(
+            "gas_price",
+            "Gas price of the given block or of the latest block.",
+            ByPosition(vec![("block_id", maybe_block_id(), true)]),
+            schema_ref("GasPriceView"),
+        ),
+        Method::new(
+            "simulate_tx",
+            "Executes a transaction on top of a block without applying it.",
+            ByName("RpcSimulateTransactionRequest"),
+            schema_ref("TransactionSimulationView"),
+        ),
+        Method::new(
+            "account_activity",
+            "Page of the transactions and receipts involving an account.",
+            ByName("RpcAccountActivityRequest"),
+            schema_ref("AccountActivityPageView"),
+        ),
+        Method::new(
+            "receipt",
+            "Receipt by id.",
+            ByName("RpcReceiptRequest"),
+            schema_ref("ReceiptView"),
+        ),
+        Method::new(
+            "outcome",
+            "Execution outcome of the receipt with its proof and the block it was executed in.",
+            ByName("RpcReceiptRequest"),
+            schema_ref("ExecutionOutcomeWithIdView"),
+        ),
+    ]
+}
+
+fn admin_methods() -> Vec<Method> {
+    use Params::{ByName, ByPosition};
+    vec![
+        Method::new(
+            "admin_track",
+            "Starts tracking accounts and shards from the next epoch.",
+            ByName("RpcTrackingRequest"),
+            schema_ref("TrackedShardsView"),
+        ),
+        Method::new(
+            "admin_untrack",
+            "Stops tracking accounts and shards starting from the next epoch.",
+            ByName("RpcTrackingRequest"),
+            schema_ref("TrackedShardsView"),
+        ),
+        Method::new(
+            "admin_peers",
+            "Active peers and bans.",
+            ByPosition(vec![]),
+            schema_ref("PeersView"),
+        ),
+        Method::new(
+            "admin_ban_peer",
+            "Bans a peer id or an IP address / subnet.",
+            ByPosition(vec![("target", string(), true)]),
+            null_type(),
+        ),
+        Method::new(
+            "admin_unban",
+            "Lifts the ban of a peer id or an IP address / subnet.",
+            ByPosition(vec![("target", string(), true)]),
+            null_type(),
+        ),
+        Method::new(
+            "admin_set_log_filter",
+            "Replaces the log filter of the node, using the `RUST_LOG` syntax.",
+            ByPosition(vec![("filter", string(), true)]),
+            null_type(),
+        ),
+        Method::new(
+            "admin_clear_old_data",
+            "Garbage collects the old data.",
+            ByPosition(vec![]),
+            null_type(),
+        ),
+        Method::new(
+            "admin_tx_pool",
+            "Transactions waiting in the pool, by shard.",
+            ByPosition(vec![]),
+            array_of(schema_ref("TransactionPoolView")),
+        ),
+        Method::new(
+            "admin_shutdown",
+            "Flushes the store and stops the node.",
+            ByPosition(vec![]),
+            null_type(),
+        ),
+    ]
+}
+
+/// Property of the by-name parameters: its name, its schema and whether it's required.
+type Property = (String, Value, bool);
+
+/// Adds the property, or merges it into the already collected one of the same name.
+fn merge_property(properties: &mut Vec<Property>, (name, schema, required): Property) {
+    match properties.iter_mut().find(|(existing, _, _)| existing == &name) {
+        Some((_, existing, existing_required)) => {
+            *existing_required = *existing_required && required;
+            // Discriminators of the alternatives are merged into a single enum.
+            if let (Some(Value::Array(values)), Some(Value::Array(more))) =
+                (existing.get_mut("enum"), schema.get("enum"))
+            {
+                values.extend(more.iter().cloned());
+            }
+        }
+        None => properties.push((name, schema, required)),
+    }
+}
+
+/// Collects the properties of an object schema, following the references. The properties of
+/// the alternatives of `oneOf` / `anyOf` are merged, and are only required if every
+/// alternative requires them.
+fn collect_properties(
+    schemas: &Map<String, Value>,
+    schema: &Value,
+    required: bool,
+    properties: &mut Vec<Property>,
+) {
+    if let Some(Value::String(reference)) = schema.get("$ref") {
+        let name = reference.trim_start_matches("#/components/schemas/");
+        if let Some(schema) = schemas.get(name) {
+            collect_properties(schemas, schema, required, properties);
+        }
+        return;
+    }
+    if let Some(Value::Array(all_of)) = schema.get("allOf") {
+        for schema in all_of {
+            collect_properties(schemas, schema, required, properties);
+        }
+    }
+    for alternatives in ["oneOf", "anyOf"].iter().filter_map(|key| schema.get(*key)) {
+        let alternatives: Vec<Vec<Property>> = alternatives
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|schema| {
+                let mut alternative = vec![];
+                collect_properties(schemas, schema, required, &mut alternative);
+                alternative
+            })
+            .collect();
+        for (name, schema, is_required) in alternatives.iter().flatten().cloned() {
+            let in_every_alternative = alternatives.iter().all(|alternative| {
+                alternative.iter().any(|(other, _, required)| other == &name && *required)
+            });
+            merge_property(properties, (name, schema, is_required && in_every_alternative));
+        }
+    }
+    if let Some(Value::Object(object_properties)) = schema.get("properties") {
+        let required_names = schema["required"].as_array().cloned().unwrap_or_default();
+        for (name, property) in object_properties {
+            let is_required = required && required_names.contains(&Value::String(name.clone()));
+            merge_property(properties, (name.clone(), property.clone(), is_required));
+        }
+    }
+}
+
+fn content_descriptor(name: &str, schema: Value, required: bool) -> Value {
+    json!({ "name": name, "schema": schema, "required": required })
+}
+
+fn method_description(schemas: &Map<String, Value>, method: Method) -> Value {
+    let (param_structure, params) = match method.params {
+        Params::ByPosition(params) => (
+            "by-position",
+            params
+                .into_iter()
+                .map(|(name, schema, required)| content_descriptor(name, schema, required))
+                .collect::<Vec<_>>(),
+        ),
+        Params::ByName(type_name) => {
+            let mut properties = vec![];
+            collect_properties(schemas, &schema_ref(type_name), true, &mut properties);
+            (
+                "by-name",
+                properties
+                    .into_iter()
+                    .map(|(name, schema, required)| content_descriptor(&name, schema, required))
+                    .collect(),
+            )
+        }
+    };
+    json!({
+        "name": method.name,
+        "summary": method.summary,
+        "paramStructure": param_structure,
+        "params": params,
+        "result": content_descriptor(&format!("{}_result", method.name), method.result, true),
+    })
+}
+
+/// OpenRPC document describing the public methods, and the admin ones if `is_admin` is set.
+pub fn openrpc_document(is_admin: bool) -> &'static Value {
+    if is_admin {
+        &ADMIN_OPENRPC_DOCUMENT
+    } else {
+        &OPENRPC_DOCUMENT
+    }
+}
+
+fn build_openrpc_document(is_admin: bool) -> Value {
+    let schemas = match serde_json::from_str::<Value>(RPC_SCHEMA) {
+        Ok(Value::Object(mut schema)) => match schema.remove("schemas") {
+            Some(Value::Object(schemas)) => schemas,
+            _ => Map::new(),
+        },
+        _ => Map::new(),
+    };
+    let mut methods = public_methods();
+    if is_admin {
+        methods.extend(admin_methods());
+    }
+    let methods: Vec<Value> =
+        methods.into_iter().map(|method| method_description(&schemas, method)).collect();
+    json!({
+        "openrpc": OPENRPC_VERSION,
+        "info": {
+            "title": "NEAR JSON-RPC",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "methods": methods,
+        "components": { "schemas": schemas },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, BTreeSet};
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use serde_json::{json, Value};
+    use syn::{Data, DataEnum, DataStruct, DeriveInput, Item, Meta, NestedMeta};
+
+    use super::{admin_methods, public_methods, RPC_SCHEMA};
+
+    const JSONRPC_SOURCE: &str = include_str!("lib.rs");
+    /// Directories of the crates defining the RPC types, relative to the repository root.
+    const SOURCE_DIRS: &[&str] = &["core", "runtime", "chain"];
+
+    fn derives_rpc_schema(attrs: &[syn::Attribute]) -> bool {
+        attrs.iter().filter(|attr| attr.path.is_ident("derive")).any(|attr| {
+            match attr.parse_meta() {
+                Ok(Meta::List(list)) => list.nested.iter().any(|nested| match nested {
+                    NestedMeta::Meta(Meta::Path(path)) => path.is_ident("RpcSchema"),
+                    _ => false,
+                }),
+                _ => false,
+            }
+        })
+    }
+
+    fn collect_schemas(items: Vec<Item>, schemas: &mut BTreeMap<String, Value>) {
+        for item in items {
+            let input = match item {
+                Item::Struct(item) if derives_rpc_schema(&item.attrs) => DeriveInput {
+                    attrs: item.attrs,
+                    vis: item.vis,
+                    ident: item.ident,
+                    generics: item.generics,
+                    data: Data::Struct(DataStruct {
+                        struct_token: item.struct_token,
+                        fields: item.fields,
+                        semi_token: item.semi_token,
+                    }),
+                },
+                Item::Enum(item) if derives_rpc_schema(&item.attrs) => DeriveInput {
+                    attrs: item.attrs,
+                    vis: item.vis,
+                    ident: item.ident,
+                    generics: item.generics,
+                    data: Data::Enum(DataEnum {
+                        enum_token: item.enum_token,
+                        brace_token: item.brace_token,
+                        variants: item.variants,
+                    }),
+                },
+                Item::Mod(item) => {
+                    if let Some((_, items)) = item.content {
+                        collect_schemas(items, schemas);
+                    }
+                    continue;
+                }
+                _ => continue,
+            };
+            near_rpc_error_core::parse_json_schema(schemas, &input);
+        }
+    }
+
+    fn rust_files(dir: &Path, files: &mut Vec<PathBuf>) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                if !path.ends_with("target") {
+                    rust_files(&path, files);
+                }
+            } else if path.extension().map_or(false, |extension| extension == "rs") {
+                files.push(path);
+            }
+        }
+    }
+
+    /// `res/rpc_schema.json` matches the schema generated from the current sources, as
+    /// `build_rpc_schema.sh` would dump it.
+    #[test]
+    fn test_rpc_schema_is_up_to_date() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+        let mut files = vec![];
+        for dir in SOURCE_DIRS {
+            rust_files(&root.join(dir), &mut files);
+        }
+        let mut schemas = BTreeMap::new();
+        for file in files {
+            let source = fs::read_to_string(&file).unwrap();
+            if !source.contains("RpcSchema") {
+                continue;
+            }
+            let file = syn::parse_file(&source)
+                .unwrap_or_else(|err| panic!("Failed to parse {}: {}", file.display(), err));
+            collect_schemas(file.items, &mut schemas);
+        }
+        let committed: Value = serde_json::from_str(RPC_SCHEMA).unwrap();
+        assert!(
+            committed == json!({ "schemas": schemas }),
+            "res/rpc_schema.json is outdated, regenerate it with build_rpc_schema.sh"
+        );
+    }
+
+    /// Names of the methods matched by `JsonRpcHandler::process_request`, except the
+    /// adversarial ones.
+    fn served_methods() -> BTreeSet<String> {
+        let start = JSONRPC_SOURCE.find("async fn process_request").unwrap();
+        let body = &JSONRPC_SOURCE[start..];
+        let body = &body[..body.find("\n    }\n").unwrap()];
+        body.lines()
+            .filter_map(|line| {
+                let line = line.trim();
+                if !line.starts_with('"') {
+                    return None;
+                }
+                let name = &line[1..line[1..].find('"')? + 1];
+                if name.starts_with("adv_") {
+                    None
+                } else {
+                    Some(name.to_string())
+                }
+            })
+            .collect()
+    }
+
+    /// Every method served by the handler is described and only those are.
+    #[test]
+    fn test_openrpc_describes_served_methods() {
+        let described: BTreeSet<String> = public_methods()
+            .into_iter()
+            .chain(admin_methods())
+            .map(|method| method.name.to_string())
+            .collect();
+        assert_eq!(described, served_methods());
+    }
+}
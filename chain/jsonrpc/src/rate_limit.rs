@@ -0,0 +1,309 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv6Addr};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use cached::{Cached, SizedCache};
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+
+use near_jsonrpc_client::message::Message;
+
+/// Maximum number of buckets kept. The bucket of the least recently seen client is dropped
+/// first, and that client gets a full bucket if it comes back.
+const MAX_BUCKETS: usize = 10_000;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct RpcRateLimit {
+    /// Cost of requests a client can make per second.
+    pub cost_per_sec: u64,
+    /// Cost of requests a client can make at once after being idle.
+    pub burst: u64,
+}
+
+/// Limits on the cost of requests to the public listener. Clients are told apart by the
+/// `X-Api-Key` header if it carries one of the configured keys, and by IP address otherwise.
+/// IPv6 clients are told apart by their /64 network, as they usually get a whole one. The GET
+/// endpoints, `/metrics` included, cost 1.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct RpcRateLimitConfig {
+    pub per_ip: RpcRateLimit,
+    /// Limits of the clients by their API key.
+    pub api_keys: HashMap<String, RpcRateLimit>,
+    /// Cost of a request by method. Methods not listed cost 1.
+    pub method_costs: HashMap<String, u64>,
+    /// Cost of `query` requests calling a view function, as they execute contract code.
+    pub view_call_cost: u64,
+}
+
+impl Default for RpcRateLimitConfig {
+    fn default() -> Self {
+        let method_costs = [
+            ("account_activity", 10),
+            ("broadcast_tx_commit", 10),
+            ("changes", 20),
+            ("outcome", 5),
+            ("receipt", 5),
+            ("simulate_tx", 100),
+            ("validators", 5),
+        ]
+        .iter()
+        .map(|(method, cost)| (method.to_string(), *cost))
+        .collect();
+        RpcRateLimitConfig {
+            per_ip: RpcRateLimit { cost_per_sec: 100, burst: 500 },
+            api_keys: HashMap::new(),
+            method_costs,
+            view_call_cost: 20,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+enum ClientKey {
+    Ip(IpAddr),
+    ApiKey(String),
+}
+
+impl ClientKey {
+    fn from_ip(ip: IpAddr) -> Self {
+        let ip = match ip {
+            IpAddr::V6(ip) => match ip.segments() {
+                [0, 0, 0, 0, 0, 0xffff, _, _] => IpAddr::V4(ip.to_ipv4().unwrap()),
+                _ => IpAddr::V6(Ipv6Addr::from(u128::from(ip) & (!0u128 << 64))),
+            },
+            ip => ip,
+        };
+        ClientKey::Ip(ip)
+    }
+}
+
+/// Why the rate limits reject a request.
+#[derive(Debug, PartialEq)]
+pub enum RateLimitError {
+    /// The bucket of the client doesn't hold enough tokens yet.
+    TooManyRequests,
+    /// The cost is above the burst of the client, so the request is never allowed.
+    CostAboveBurst { cost: u64, burst: u64 },
+}
+
+/// Token bucket of a client. A request is only allowed if the bucket holds its whole cost.
+struct ClientBucket {
+    limit: RpcRateLimit,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl ClientBucket {
+    fn new(limit: RpcRateLimit, now: Instant) -> Self {
+        ClientBucket { limit, tokens: limit.burst as f64, last_refill: now }
+    }
+
+    fn try_consume_at(&mut self, cost: u64, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        self.last_refill = now;
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * self.limit.cost_per_sec as f64)
+            .min(self.limit.burst as f64);
+        if self.tokens < cost as f64 {
+            return false;
+        }
+        self.tokens -= cost as f64;
+        true
+    }
+}
+
+/// Token buckets of the clients of the public listener, shared by all its workers.
+pub struct RpcRateLimiter {
+    config: RpcRateLimitConfig,
+    buckets: Mutex<SizedCache<ClientKey, ClientBucket>>,
+}
+
+impl RpcRateLimiter {
+    pub fn new(config: RpcRateLimitConfig) -> Self {
+        RpcRateLimiter { config, buckets: Mutex::new(SizedCache::with_size(MAX_BUCKETS)) }
+    }
+
+    /// Cost of the message, which is the sum of the costs of the requests for batches.
+    pub fn message_cost(&self, message: &Message) -> u64 {
+        match message {
+            Message::Request(request) => self.request_cost(&request.method, &request.params),
+            Message::Notification(notification) => {
+                self.request_cost(&notification.method, &notification.params)
+            }
+            Message::Batch(messages) => {
+                messages.iter().map(|message| self.message_cost(message)).sum()
+            }
+            _ => 1,
+        }
+    }
+
+    fn request_cost(&self, method: &str, params: &Option<Value>) -> u64 {
+        if method == "query" && is_view_call(params) {
+            return self.config.view_call_cost;
+        }
+        self.config.method_costs.get(method).cloned().unwrap_or(1)
+    }
+
+    /// Checks whether the client with the given IP address and API key can spend `cost` now, and
+    /// if so, spends it.
+    pub fn allow(
+        &self,
+        ip: IpAddr,
+        api_key: Option<&str>,
+        cost: u64,
+    ) -> Result<(), RateLimitError> {
+        self.allow_at(ip, api_key, cost, Instant::now())
+    }
+
+    fn allow_at(
+        &self,
+        ip: IpAddr,
+        api_key: Option<&str>,
+        cost: u64,
+        now: Instant,
+    ) -> Result<(), RateLimitError> {
+        let (key, limit) = match api_key.and_then(|key| self.config.api_keys.get_key_value(key)) {
+            Some((key, limit)) => (ClientKey::ApiKey(key.clone()), *limit),
+            None => (ClientKey::from_ip(ip), self.config.per_ip),
+        };
+        if cost > limit.burst {
+            return Err(RateLimitError::CostAboveBurst { cost, burst: limit.burst });
+        }
+        let mut buckets = self.buckets.lock().expect("Lock poisoned");
+        let allowed = match buckets.cache_get_mut(&key) {
+            Some(bucket) => bucket.try_consume_at(cost, now),
+            None => {
+                let mut bucket = ClientBucket::new(limit, now);
+                let allowed = bucket.try_consume_at(cost, now);
+                buckets.cache_set(key, bucket);
+                allowed
+            }
+        };
+        if allowed {
+            Ok(())
+        } else {
+            Err(RateLimitError::TooManyRequests)
+        }
+    }
+}
+
+/// Whether the `query` params call a view function, in either the path or the object form.
+fn is_view_call(params: &Option<Value>) -> bool {
+    match params {
+        Some(Value::Array(params)) => {
+            params.first().and_then(Value::as_str).map_or(false, |path| path.starts_with("call/"))
+        }
+        Some(Value::Object(params)) => {
+            params.get("request_type").and_then(Value::as_str) == Some("call_function")
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+    use std::time::{Duration, Instant};
+
+    use cached::Cached;
+    use serde_json::json;
+
+    use near_jsonrpc_client::message::Message;
+
+    use super::{RateLimitError, RpcRateLimit, RpcRateLimitConfig, RpcRateLimiter, MAX_BUCKETS};
+
+    #[test]
+    fn message_cost() {
+        let limiter = RpcRateLimiter::new(RpcRateLimitConfig::default());
+        let request = |method: &str, params| Message::request(method.to_string(), Some(params));
+        assert_eq!(limiter.message_cost(&request("status", json!([]))), 1);
+        assert_eq!(limiter.message_cost(&request("changes", json!([]))), 20);
+        assert_eq!(limiter.message_cost(&request("query", json!(["account/test", ""]))), 1);
+        assert_eq!(limiter.message_cost(&request("query", json!(["call/test/view", ""]))), 20);
+        let view_call = json!({"request_type": "call_function", "finality": "final"});
+        assert_eq!(limiter.message_cost(&request("query", view_call)), 20);
+        let batch =
+            Message::Batch(vec![request("status", json!([])), request("changes", json!([]))]);
+        assert_eq!(limiter.message_cost(&batch), 21);
+        let notification = Message::notification("changes".to_string(), Some(json!([])));
+        assert_eq!(limiter.message_cost(&notification), 20);
+    }
+
+    #[test]
+    fn per_client_limits() {
+        let mut config = RpcRateLimitConfig::default();
+        config.per_ip = RpcRateLimit { cost_per_sec: 1, burst: 2 };
+        config.api_keys.insert("key".to_string(), RpcRateLimit { cost_per_sec: 1, burst: 10 });
+        let limiter = RpcRateLimiter::new(config);
+        let now = Instant::now();
+        let ip1 = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+        let ip2 = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+
+        assert!(limiter.allow_at(ip1, None, 2, now).is_ok());
+        assert!(limiter.allow_at(ip1, None, 1, now).is_err());
+        // Unknown keys are limited by IP.
+        assert!(limiter.allow_at(ip1, Some("other"), 1, now).is_err());
+        assert!(limiter.allow_at(ip2, None, 1, now).is_ok());
+        assert!(limiter.allow_at(ip1, Some("key"), 10, now).is_ok());
+        assert!(limiter.allow_at(ip2, Some("key"), 1, now).is_err());
+
+        let now = now + Duration::from_secs(1);
+        assert!(limiter.allow_at(ip1, None, 1, now).is_ok());
+
+        // Requests costing more than the tokens left are rejected and don't spend them.
+        let ip3 = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 3));
+        assert!(limiter.allow_at(ip3, None, 1, now).is_ok());
+        assert!(limiter.allow_at(ip3, None, 2, now).is_err());
+        assert!(limiter.allow_at(ip3, None, 1, now).is_ok());
+    }
+
+    #[test]
+    fn cost_above_burst() {
+        let mut config = RpcRateLimitConfig::default();
+        config.per_ip = RpcRateLimit { cost_per_sec: 1, burst: 2 };
+        config.api_keys.insert("key".to_string(), RpcRateLimit { cost_per_sec: 1, burst: 10 });
+        let limiter = RpcRateLimiter::new(config);
+        let now = Instant::now();
+        let ip = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+
+        assert_eq!(
+            limiter.allow_at(ip, None, 3, now),
+            Err(RateLimitError::CostAboveBurst { cost: 3, burst: 2 })
+        );
+        // The rejected request doesn't spend the tokens of the client.
+        assert_eq!(limiter.allow_at(ip, None, 2, now), Ok(()));
+        assert_eq!(limiter.allow_at(ip, None, 1, now), Err(RateLimitError::TooManyRequests));
+        assert_eq!(limiter.allow_at(ip, Some("key"), 3, now), Ok(()));
+    }
+
+    #[test]
+    fn ipv6_clients_by_network() {
+        let mut config = RpcRateLimitConfig::default();
+        config.per_ip = RpcRateLimit { cost_per_sec: 1, burst: 1 };
+        let limiter = RpcRateLimiter::new(config);
+        let now = Instant::now();
+        let ip = |segments: [u16; 8]| IpAddr::V6(Ipv6Addr::from(segments));
+
+        assert!(limiter.allow_at(ip([0x2001, 0xdb8, 0, 1, 0, 0, 0, 1]), None, 1, now).is_ok());
+        assert!(limiter
+            .allow_at(ip([0x2001, 0xdb8, 0, 1, 0xa, 0xb, 0xc, 0xd]), None, 1, now)
+            .is_err());
+        assert!(limiter.allow_at(ip([0x2001, 0xdb8, 0, 2, 0, 0, 0, 1]), None, 1, now).is_ok());
+
+        // IPv4 clients connecting over IPv6 are told apart by their IPv4 address.
+        assert!(limiter.allow_at(ip([0, 0, 0, 0, 0, 0xffff, 0x0a00, 1]), None, 1, now).is_ok());
+        assert!(limiter.allow_at(ip([0, 0, 0, 0, 0, 0xffff, 0x0a00, 2]), None, 1, now).is_ok());
+        assert!(limiter.allow_at(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)), None, 1, now).is_err());
+    }
+
+    #[test]
+    fn buckets_are_capped() {
+        let limiter = RpcRateLimiter::new(RpcRateLimitConfig::default());
+        let now = Instant::now();
+        for i in 0..=MAX_BUCKETS as u32 {
+            assert!(limiter.allow_at(IpAddr::V4(Ipv4Addr::from(i)), None, 1, now).is_ok());
+        }
+        assert_eq!(limiter.buckets.lock().unwrap().cache_size(), MAX_BUCKETS);
+    }
+}
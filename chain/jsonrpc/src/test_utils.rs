@@ -28,3 +28,20 @@ pub fn start_all_with_validity_period(
     start_http(RpcConfig::new(&addr), client_addr.clone(), view_client_addr.clone());
     (view_client_addr, addr)
 }
+
+/// Same as `start_all`, but also starts the admin listener that accepts the given token.
+/// Returns the addresses of the public and the admin listeners.
+pub fn start_all_with_admin(admin_token: &str) -> (Addr<ViewClientActor>, String, String) {
+    let (client_addr, view_client_addr) =
+        setup_no_network_with_validity_period(vec!["test1", "test2"], "other", true, 100, false);
+
+    let addr = format!("127.0.0.1:{}", open_port());
+    let admin_addr = format!("127.0.0.1:{}", open_port());
+    let config = RpcConfig {
+        admin_addr: Some(admin_addr.clone()),
+        admin_token: Some(admin_token.to_string()),
+        ..RpcConfig::new(&addr)
+    };
+    start_http(config, client_addr.clone(), view_client_addr.clone());
+    (view_client_addr, addr, admin_addr)
+}
@@ -1,5 +1,6 @@
 use actix::System;
 use futures::{future, FutureExt};
+use serde_json::Value;
 
 use near_jsonrpc::client::new_http_client;
 use near_jsonrpc::test_utils::start_all;
@@ -25,3 +26,53 @@ fn test_status() {
     })
     .unwrap();
 }
+
+/// The OpenRPC document describes the public methods and resolves all its schema references.
+#[test]
+fn test_openrpc() {
+    init_test_logger();
+
+    System::run(|| {
+        let (_view_client_addr, addr) = start_all(false);
+
+        let mut client = new_http_client(&format!("http://{}", addr));
+        actix::spawn(client.openrpc().then(|res| {
+            let document = res.unwrap();
+            let methods: Vec<&str> = document["methods"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|method| method["name"].as_str().unwrap())
+                .collect();
+            for method in &["query", "block", "chunk", "tx", "changes", "receipt", "outcome"] {
+                assert!(methods.contains(method), "{} is not described", method);
+            }
+            assert!(methods.iter().all(|method| !method.starts_with("admin_")));
+            let schemas = document["components"]["schemas"].as_object().unwrap();
+            let mut references = vec![];
+            collect_references(&document, &mut references);
+            for reference in references {
+                let name = reference.trim_start_matches("#/components/schemas/");
+                assert!(schemas.contains_key(name), "{} is not defined", reference);
+            }
+            System::current().stop();
+            future::ready(())
+        }));
+    })
+    .unwrap();
+}
+
+fn collect_references(value: &Value, references: &mut Vec<String>) {
+    match value {
+        Value::Object(object) => {
+            if let Some(Value::String(reference)) = object.get("$ref") {
+                references.push(reference.clone());
+            }
+            object.values().for_each(|value| collect_references(value, references));
+        }
+        Value::Array(values) => {
+            values.iter().for_each(|value| collect_references(value, references))
+        }
+        _ => {}
+    }
+}
@@ -1,18 +1,24 @@
 use std::convert::TryFrom;
+use std::time::Duration;
 
 use actix::{Actor, System};
+use actix_web::client::Client;
+use actix_web::http::StatusCode;
 use futures::{future, FutureExt};
 
 use near_crypto::{KeyType, PublicKey, Signature};
-use near_jsonrpc::client::new_client;
-use near_jsonrpc::test_utils::start_all;
-use near_jsonrpc_client::ChunkId;
-use near_network::test_utils::WaitOrTimeout;
+use near_jsonrpc::client::{new_client, new_client_with_failover, RetryConfig};
+use near_jsonrpc::test_utils::{start_all, start_all_with_admin};
+use near_jsonrpc_client::{ChunkId, JsonRpcError};
+use near_network::test_utils::{open_port, WaitOrTimeout};
 use near_primitives::account::{AccessKey, AccessKeyPermission};
 use near_primitives::hash::CryptoHash;
-use near_primitives::rpc::{BlockQueryInfo, RpcQueryRequest};
+use near_primitives::rpc::{
+    BlockQueryInfo, RpcAccountActivityRequest, RpcQueryRequest, RpcTrackingRequest,
+};
+use near_primitives::serialize::to_base64;
 use near_primitives::test_utils::init_test_logger;
-use near_primitives::types::{BlockId, ShardId};
+use near_primitives::types::{BlockId, ShardId, StateChangesRequest};
 use near_primitives::views::{Finality, QueryRequest, QueryResponseKind};
 
 macro_rules! test_with_client {
@@ -131,10 +137,11 @@ fn test_chunk_invalid_shard_id() {
         let chunk = client.chunk(ChunkId::BlockShardId(BlockId::Height(0), 100)).await;
         match chunk {
             Ok(_) => panic!("should result in an error"),
-            Err(e) => {
+            Err(JsonRpcError::Rpc(e)) => {
                 let s = serde_json::to_string(&e.data.unwrap()).unwrap();
                 assert!(s.starts_with("\"Shard id 100 does not exist"));
             }
+            Err(e) => panic!("unexpected error {}", e),
         }
     });
 }
@@ -385,6 +392,36 @@ fn test_query_call_function() {
     });
 }
 
+/// Connect to json rpc and call function on behalf of another caller with soft-deprecated query API.
+#[test]
+fn test_query_by_path_call_function_with_caller() {
+    test_with_client!(client, async move {
+        let query_response = client
+            .query_by_path(
+                "call/test/method?signer_account_id=alice&predecessor_account_id=bob%2Ecarol\
+                 &attached_deposit=10"
+                    .to_string(),
+                "".to_string(),
+            )
+            .await
+            .unwrap();
+        if let QueryResponseKind::CallResult(_) = query_response.kind {
+        } else {
+            panic!("expected a call function result, but received: {:?}", query_response.kind);
+        }
+        for path in &[
+            "call/test/method?attached_deposit=many",
+            "call/test/method?originator=alice",
+            "call/test/method?signer_account_id=Alice",
+            "call/test/method?predecessor_account_id=bob%20carol",
+            "call/test/method?signer_public_key=invalid",
+        ] {
+            let err = client.query_by_path(path.to_string(), "".to_string()).await.unwrap_err();
+            assert_eq!(err.code(), Some(-32_602));
+        }
+    });
+}
+
 /// Retrieve client status via JSON RPC.
 #[test]
 fn test_status() {
@@ -500,3 +537,234 @@ fn test_gas_price() {
         assert!(gas_price.gas_price > 0);
     });
 }
+
+/// Account activity is only served by nodes with the index enabled.
+#[test]
+fn test_account_activity_disabled() {
+    test_with_client!(client, async move {
+        let result = client
+            .account_activity(RpcAccountActivityRequest {
+                account_id: "test1".to_string(),
+                before_height: None,
+                limit: 10,
+            })
+            .await;
+        assert!(result.is_err());
+    });
+}
+
+/// Retrieve blocks with a single batch request
+#[test]
+fn test_batch_blocks() {
+    test_with_client!(client, async move {
+        let blocks = client
+            .blocks(vec![
+                BlockQueryInfo::BlockId(BlockId::Height(0)),
+                BlockQueryInfo::Finality(Finality::None),
+                BlockQueryInfo::BlockId(BlockId::Height(10)),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[0].as_ref().unwrap().header.height, 0);
+        assert_eq!(blocks[1].as_ref().unwrap().header.height, 0);
+        assert!(blocks[2].is_err());
+    });
+}
+
+/// Send different methods in a single batch request
+#[test]
+fn test_batch_mixed_methods() {
+    test_with_client!(client, async move {
+        let results = client
+            .batch(vec![
+                ("status".to_string(), serde_json::json!([])),
+                ("unknown_method".to_string(), serde_json::json!([])),
+                ("block".to_string(), serde_json::json!([0])),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap()["chain_id"], "unittest");
+        assert_eq!(results[1].as_ref().unwrap_err().code(), Some(-32_601));
+        assert_eq!(results[2].as_ref().unwrap()["header"]["height"], 0);
+    });
+}
+
+/// Notifications are executed without being answered, also within batches.
+#[test]
+fn test_batch_notifications() {
+    init_test_logger();
+
+    System::run(|| {
+        let (_view_client_addr, addr) = start_all(false);
+        let server_addr = format!("http://{}", addr);
+
+        actix::spawn(async move {
+            let client = Client::new();
+            let mut response = client
+                .post(&server_addr)
+                .send_json(&serde_json::json!([
+                    {"jsonrpc": "2.0", "method": "status", "params": []},
+                    {"jsonrpc": "2.0", "method": "status", "params": [], "id": 1},
+                    {"jsonrpc": "2.0", "method": "unknown_method"},
+                ]))
+                .await
+                .unwrap();
+            let responses: serde_json::Value = response.json().await.unwrap();
+            assert_eq!(responses.as_array().unwrap().len(), 1);
+            assert_eq!(responses[0]["id"], 1);
+            assert_eq!(responses[0]["result"]["chain_id"], "unittest");
+
+            let mut response = client
+                .post(&server_addr)
+                .send_json(&serde_json::json!([
+                    {"jsonrpc": "2.0", "method": "status", "params": []},
+                    {"jsonrpc": "2.0", "method": "health"},
+                ]))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::NO_CONTENT);
+            assert!(response.body().await.unwrap().is_empty());
+
+            let mut response = client
+                .post(&server_addr)
+                .send_json(&serde_json::json!({"jsonrpc": "2.0", "method": "health"}))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::NO_CONTENT);
+            assert!(response.body().await.unwrap().is_empty());
+
+            System::current().stop();
+        });
+    })
+    .unwrap();
+}
+
+/// Batches larger than the limit are rejected as a whole
+#[test]
+fn test_batch_too_large() {
+    test_with_client!(client, async move {
+        let calls = (0..101).map(|_| ("status".to_string(), serde_json::json!([]))).collect();
+        let err = client.batch(calls).await.unwrap_err();
+        assert_eq!(err.code(), Some(-32_600));
+        let err = client.batch(vec![]).await.unwrap_err();
+        assert_eq!(err.code(), Some(-32_600));
+    });
+}
+
+/// Admin methods are rejected by the public listener, even with a token.
+#[test]
+fn test_admin_requires_token() {
+    test_with_client!(client, async move {
+        let request = RpcTrackingRequest { accounts: vec!["test2".to_string()], shards: vec![] };
+        let err = client.admin_track("secret", request.clone()).await.unwrap_err();
+        assert_eq!(err.code(), Some(-32_000));
+        let err = client.admin_untrack("secret", request).await.unwrap_err();
+        assert_eq!(err.code(), Some(-32_000));
+    });
+}
+
+/// The admin listener serves the admin methods only with the right token.
+#[test]
+fn test_admin_listener() {
+    init_test_logger();
+
+    System::run(|| {
+        let (_view_client_addr, _addr, admin_addr) = start_all_with_admin("secret");
+        let mut client = new_client(&format!("http://{}", admin_addr));
+
+        actix::spawn(async move {
+            assert!(client.admin_tx_pool("wrong").await.is_err());
+            let pools = client.admin_tx_pool("secret").await.unwrap();
+            assert!(pools.iter().all(|pool| pool.transactions.is_empty()));
+            let err = client.admin_unban("secret", "not a target".to_string()).await.unwrap_err();
+            assert_eq!(err.code(), Some(-32_602));
+            System::current().stop();
+        });
+    })
+    .unwrap();
+}
+
+/// Retrieve the changes of an account made in a block.
+#[test]
+fn test_changes() {
+    test_with_client!(client, async move {
+        let block = client.block_by_id(BlockId::Height(0)).await.unwrap();
+        let block_hash = block.header.hash;
+        let request = StateChangesRequest::AccountChanges { account_id: "test1".to_string() };
+        let changes = client.changes(block_hash, request).await.unwrap();
+        assert_eq!(changes.block_hash, block_hash);
+    });
+}
+
+/// Retrieve the network info of a node that isn't connected to any peer.
+#[test]
+fn test_network_info() {
+    test_with_client!(client, async move {
+        let network_info = client.network_info().await.unwrap();
+        assert_eq!(network_info.num_active_peers, 0);
+        assert!(network_info.active_peers.is_empty());
+    });
+}
+
+/// The client moves on to the next endpoint when the first one can't be reached.
+#[test]
+fn test_failover() {
+    init_test_logger();
+
+    System::run(|| {
+        let (_view_client_addr, addr) = start_all(false);
+        let unreachable_addr = format!("http://127.0.0.1:{}", open_port());
+        let retry_config = RetryConfig {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(100),
+        };
+        let mut client = new_client_with_failover(
+            vec![unreachable_addr, format!("http://{}", addr)],
+            retry_config,
+        );
+
+        actix::spawn(async move {
+            let status = client.status().await.unwrap();
+            assert_eq!(status.chain_id, "unittest");
+            let block = client.block_by_id(BlockId::Height(0)).await.unwrap();
+            assert_eq!(block.header.height, 0);
+            System::current().stop();
+        });
+    })
+    .unwrap();
+}
+
+/// Transactions aren't sent again to the next endpoint, since the first node may have received
+/// them before it couldn't be reached.
+#[test]
+fn test_no_failover_for_transactions() {
+    init_test_logger();
+
+    System::run(|| {
+        let (_view_client_addr, addr) = start_all(false);
+        let unreachable_addr = format!("http://127.0.0.1:{}", open_port());
+        let retry_config = RetryConfig {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(100),
+        };
+        let mut client = new_client_with_failover(
+            vec![unreachable_addr, format!("http://{}", addr)],
+            retry_config,
+        );
+
+        actix::spawn(async move {
+            match client.broadcast_tx_async(to_base64(&[0])).await {
+                Err(JsonRpcError::Transport(_)) => {}
+                res => panic!("Expected a transport error, got {:?}", res),
+            }
+            let status = client.status().await.unwrap();
+            assert_eq!(status.chain_id, "unittest");
+            System::current().stop();
+        });
+    })
+    .unwrap();
+}
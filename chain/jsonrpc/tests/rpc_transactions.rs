@@ -6,11 +6,12 @@ use futures::{future, FutureExt, TryFutureExt};
 
 use near_client::GetBlock;
 use near_crypto::{InMemorySigner, KeyType};
-use near_jsonrpc::client::new_client;
+use near_jsonrpc::client::{new_client, JsonRpcError};
 use near_jsonrpc::test_utils::{start_all, start_all_with_validity_period};
 use near_network::test_utils::{wait_or_panic, WaitOrTimeout};
 use near_primitives::block::BlockHeader;
 use near_primitives::hash::{hash, CryptoHash};
+use near_primitives::rpc::RpcReceiptRequest;
 use near_primitives::serialize::to_base64;
 use near_primitives::test_utils::{init_integration_logger, init_test_logger};
 use near_primitives::transaction::SignedTransaction;
@@ -117,6 +118,47 @@ fn test_send_tx_commit() {
     .unwrap();
 }
 
+/// Test looking up the receipt of a committed transaction and its outcome by receipt id.
+#[test]
+fn test_receipt_and_outcome() {
+    init_test_logger();
+
+    System::run(|| {
+        let (view_client, addr) = start_all(true);
+
+        let mut client = new_client(&format!("http://{}", addr));
+
+        actix::spawn(view_client.send(GetBlock::Finality(Finality::None)).then(move |res| {
+            let header: BlockHeader = res.unwrap().unwrap().header.into();
+            let signer = InMemorySigner::from_seed("test1", KeyType::ED25519, "test1");
+            let tx = SignedTransaction::send_money(
+                1,
+                "test1".to_string(),
+                "test2".to_string(),
+                &signer,
+                100,
+                header.hash,
+            );
+            let bytes = tx.try_to_vec().unwrap();
+            async move {
+                let result = client.broadcast_tx_commit(to_base64(&bytes)).await.unwrap();
+                let receipt_outcome = &result.receipts_outcome[0];
+                let request =
+                    RpcReceiptRequest { receipt_id: receipt_outcome.id, receiver_id: None };
+                let receipt = client.receipt(request.clone()).await.unwrap();
+                assert_eq!(receipt.receipt_id, receipt_outcome.id);
+                assert_eq!(receipt.receiver_id, "test2".to_string());
+                let outcome = client.outcome(request).await.unwrap();
+                assert_eq!(outcome.id, receipt_outcome.id);
+                assert_eq!(outcome.block_hash, receipt_outcome.block_hash);
+                System::current().stop();
+            }
+        }));
+        wait_or_panic(10000);
+    })
+    .unwrap();
+}
+
 /// Test that expired transaction should be rejected
 #[test]
 fn test_expired_tx() {
@@ -224,10 +266,13 @@ fn test_tx_status_invalid_account_id() {
         actix::spawn(
             client
                 .tx(to_base64(&CryptoHash::default()), "".to_string())
-                .map_err(|e| {
-                    let s = serde_json::to_string(&e.data.unwrap()).unwrap();
-                    assert!(s.starts_with("\"Invalid account id"));
-                    System::current().stop();
+                .map_err(|e| match e {
+                    JsonRpcError::Rpc(e) => {
+                        let s = serde_json::to_string(&e.data.unwrap()).unwrap();
+                        assert!(s.starts_with("\"Invalid account id"));
+                        System::current().stop();
+                    }
+                    e => panic!("unexpected error {}", e),
                 })
                 .map_ok(move |_| panic!("transaction should not succeed"))
                 .map(drop),
@@ -1,4 +1,7 @@
-use near_metrics::{try_create_int_counter, try_create_int_gauge, IntCounter, IntGauge};
+use near_metrics::{
+    try_create_int_counter, try_create_int_counter_vec, try_create_int_gauge, IntCounter,
+    IntCounterVec, IntGauge,
+};
 
 macro_rules! type_messages {
     ($name_counter:ident, $name_bytes:ident) => {
@@ -16,6 +19,33 @@ macro_rules! type_messages {
 }
 
 lazy_static! {
+    pub static ref PEER_MESSAGE_THROTTLED_TOTAL: near_metrics::Result<IntCounterVec> =
+        try_create_int_counter_vec(
+            "peer_message_throttled_total",
+            "Total number of messages from peers dropped due to rate limits, by message type",
+            &["type"]
+        );
+    pub static ref PEER_MESSAGE_THROTTLED_BYTES: near_metrics::Result<IntCounterVec> =
+        try_create_int_counter_vec(
+            "peer_message_throttled_bytes",
+            "Total data from peers dropped due to rate limits, by message type",
+            &["type"]
+        );
+    pub static ref PEER_MESSAGE_SEND_THROTTLED_TOTAL: near_metrics::Result<IntCounterVec> =
+        try_create_int_counter_vec(
+            "peer_message_send_throttled_total",
+            "Total number of messages to peers dropped due to rate limits, by message type",
+            &["type"]
+        );
+    pub static ref PEER_BANNED_ABUSIVE_TOTAL: near_metrics::Result<IntCounter> =
+        try_create_int_counter(
+            "peer_banned_abusive_total",
+            "Total number of peers banned for exceeding rate limits"
+        );
+    pub static ref PEER_EVICTED_TOTAL: near_metrics::Result<IntCounter> = try_create_int_counter(
+        "peer_evicted_total",
+        "Total number of peers disconnected to accept peers with better score"
+    );
     pub static ref PEER_CONNECTIONS_TOTAL: near_metrics::Result<IntGauge> =
         try_create_int_gauge("peer_connections_total", "Current number of connected peers");
     pub static ref PEER_DATA_RECEIVED_BYTES: near_metrics::Result<IntCounter> =
@@ -87,6 +117,11 @@ lazy_static! {
             "drop_message_unreachable_peer",
             "Total messages dropped because target peer is not reachable"
         );
+    pub static ref ROUTED_MESSAGE_FALLBACK_ROUTE: near_metrics::Result<IntCounter> =
+        try_create_int_counter(
+            "routed_message_fallback_route",
+            "Total routed messages sent through a fallback route"
+        );
 }
 
 type_messages!(HANDSHAKE_RECEIVED_TOTAL, HANDSHAKE_RECEIVED_BYTES);
@@ -131,5 +166,7 @@ type_messages!(
 type_messages!(ROUTED_PARTIAL_CHUNK_RECEIVED_TOTAL, ROUTED_PARTIAL_CHUNK_RECEIVED_BYTES);
 type_messages!(ROUTED_PING_RECEIVED_TOTAL, ROUTED_PING_RECEIVED_BYTES);
 type_messages!(ROUTED_PONG_RECEIVED_TOTAL, ROUTED_PONG_RECEIVED_BYTES);
+type_messages!(ROUTED_RECEIPT_REQUEST_RECEIVED_TOTAL, ROUTED_RECEIPT_REQUEST_RECEIVED_BYTES);
+type_messages!(ROUTED_RECEIPT_RESPONSE_RECEIVED_TOTAL, ROUTED_RECEIPT_RESPONSE_RECEIVED_BYTES);
 type_messages!(DISCONNECT_RECEIVED_TOTAL, DISCONNECT_RECEIVED_BYTES);
 type_messages!(CHALLENGE_RECEIVED_TOTAL, CHALLENGE_RECEIVED_BYTES);
@@ -1,7 +1,8 @@
 use std::cmp::max;
+use std::collections::HashMap;
 use std::io;
 use std::net::SocketAddr;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use actix::io::{FramedWrite, WriteHandler};
 use actix::{
@@ -20,13 +21,15 @@ use near_primitives::utils::{ser, DisplayOption};
 
 use crate::codec::{bytes_to_peer_message, peer_message_to_bytes, Codec};
 use crate::rate_counter::RateCounter;
+use crate::rate_limiter::PeerRateLimiter;
 use crate::routing::{Edge, EdgeInfo};
 use crate::types::{
     Ban, Consolidate, ConsolidateResponse, Handshake, HandshakeFailureReason,
     NetworkClientMessages, NetworkClientResponses, NetworkRequests, NetworkViewClientMessages,
     NetworkViewClientResponses, PeerChainInfo, PeerInfo, PeerManagerRequest, PeerMessage,
     PeerRequest, PeerResponse, PeerStatsResult, PeerStatus, PeerType, PeersRequest, PeersResponse,
-    QueryPeerStats, ReasonForBan, RoutedMessageBody, RoutedMessageFrom, SendMessage, Unregister,
+    QueryPeerStats, RateLimitConfig, ReasonForBan, RoutedMessageBody, RoutedMessageFrom,
+    SendMessage, Unregister,
 };
 use crate::PeerManagerActor;
 use crate::{metrics, NetworkResponses};
@@ -36,9 +39,8 @@ type WriteHalf = tokio::io::WriteHalf<tokio::net::TcpStream>;
 /// Maximum number of requests and responses to track.
 const MAX_TRACK_SIZE: usize = 30;
 
-/// Maximum number of messages per minute from single peer.
-// TODO: current limit is way to high due to us sending lots of messages during sync.
-const MAX_PEER_MSG_PER_MIN: u64 = std::u64::MAX;
+/// Time after which a request without response is considered failed.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
 
 /// Internal structure to keep a circular queue within a tracker with unique hashes.
 struct CircularUniqueQueue {
@@ -80,10 +82,22 @@ pub struct Tracker {
     sent_bytes: RateCounter,
     /// Bytes we've received.
     received_bytes: RateCounter,
+    /// Received messages dropped due to rate limits.
+    throttled: RateCounter,
     /// Sent requests.
     requested: CircularUniqueQueue,
     /// Received elements.
     received: CircularUniqueQueue,
+    /// Block requests waiting for response with the time they were sent.
+    pending_requests: HashMap<CryptoHash, Instant>,
+    /// Time the headers request waiting for response was sent.
+    pending_headers_request: Option<Instant>,
+    /// Requests delivered since the stats were last taken.
+    delivered_requests: u64,
+    /// Requests failed since the stats were last taken.
+    failed_requests: u64,
+    /// Invalid messages received since the stats were last taken.
+    invalid_messages: u64,
 }
 
 impl Default for Tracker {
@@ -91,8 +105,14 @@ impl Default for Tracker {
         Tracker {
             sent_bytes: RateCounter::new(),
             received_bytes: RateCounter::new(),
+            throttled: RateCounter::new(),
             requested: CircularUniqueQueue::new(MAX_TRACK_SIZE),
             received: CircularUniqueQueue::new(MAX_TRACK_SIZE),
+            pending_requests: HashMap::default(),
+            pending_headers_request: None,
+            delivered_requests: 0,
+            failed_requests: 0,
+            invalid_messages: 0,
         }
     }
 }
@@ -106,6 +126,10 @@ impl Tracker {
         self.sent_bytes.increment(size);
     }
 
+    fn increment_throttled(&mut self, size: u64) {
+        self.throttled.increment(size);
+    }
+
     fn has_received(&self, hash: CryptoHash) -> bool {
         self.received.contains(&hash)
     }
@@ -120,6 +144,48 @@ impl Tracker {
 
     fn push_request(&mut self, hash: CryptoHash) {
         self.requested.push(hash);
+        self.pending_requests.entry(hash).or_insert_with(Instant::now);
+    }
+
+    fn push_headers_request(&mut self) {
+        self.pending_headers_request.get_or_insert_with(Instant::now);
+    }
+
+    fn deliver_request(&mut self, hash: &CryptoHash) {
+        if self.pending_requests.remove(hash).is_some() {
+            self.delivered_requests += 1;
+        }
+    }
+
+    fn deliver_headers_request(&mut self) {
+        if self.pending_headers_request.take().is_some() {
+            self.delivered_requests += 1;
+        }
+    }
+
+    fn increment_invalid(&mut self) {
+        self.invalid_messages += 1;
+    }
+
+    /// Mark requests without response for longer than `timeout` as failed.
+    fn expire_requests(&mut self, timeout: Duration) {
+        let now = Instant::now();
+        let pending = self.pending_requests.len();
+        self.pending_requests.retain(|_, sent| now.duration_since(*sent) < timeout);
+        self.failed_requests += (pending - self.pending_requests.len()) as u64;
+        if self.pending_headers_request.map_or(false, |sent| now.duration_since(sent) >= timeout) {
+            self.pending_headers_request = None;
+            self.failed_requests += 1;
+        }
+    }
+
+    /// Return number of delivered and failed requests and invalid messages since the last call.
+    fn take_request_stats(&mut self) -> (u64, u64, u64) {
+        let stats = (self.delivered_requests, self.failed_requests, self.invalid_messages);
+        self.delivered_requests = 0;
+        self.failed_requests = 0;
+        self.invalid_messages = 0;
+        stats
     }
 }
 
@@ -146,6 +212,10 @@ pub struct Peer {
     view_client_addr: Recipient<NetworkViewClientMessages>,
     /// Tracker for requests and responses.
     tracker: Tracker,
+    /// Limits on messages and traffic exchanged with this peer.
+    rate_limiter: PeerRateLimiter,
+    /// Maximum number of dropped messages per minute before banning the peer.
+    max_throttled_per_min: u64,
     /// This node genesis id.
     genesis_id: GenesisId,
     /// Latest chain info from the peer.
@@ -166,6 +236,7 @@ impl Peer {
         client_addr: Recipient<NetworkClientMessages>,
         view_client_addr: Recipient<NetworkViewClientMessages>,
         edge_info: Option<EdgeInfo>,
+        rate_limits: &RateLimitConfig,
     ) -> Self {
         Peer {
             node_info,
@@ -179,6 +250,8 @@ impl Peer {
             client_addr,
             view_client_addr,
             tracker: Default::default(),
+            rate_limiter: PeerRateLimiter::new(rate_limits),
+            max_throttled_per_min: rate_limits.max_throttled_per_min,
             genesis_id: Default::default(),
             chain_info: Default::default(),
             edge_info,
@@ -187,8 +260,27 @@ impl Peer {
 
     /// Whether the peer is considered abusive due to sending too many messages.
     fn is_abusive(&self) -> bool {
-        self.tracker.received_bytes.count_per_min() > MAX_PEER_MSG_PER_MIN
-            || self.tracker.sent_bytes.count_per_min() > MAX_PEER_MSG_PER_MIN
+        self.tracker.throttled.count_per_min() > self.max_throttled_per_min
+    }
+
+    /// Drop message received from the peer that exceeds rate limits.
+    /// Bans the peer if it keeps exceeding the limits.
+    fn throttle(&mut self, ctx: &mut Context<Peer>, msg_variant: &str, size: usize) {
+        near_metrics::inc_counter_vec(&metrics::PEER_MESSAGE_THROTTLED_TOTAL, &[msg_variant]);
+        near_metrics::inc_counter_vec_by(
+            &metrics::PEER_MESSAGE_THROTTLED_BYTES,
+            &[msg_variant],
+            size as i64,
+        );
+        debug!(target: "network", "Dropping {} message of {} bytes from {}: rate limit exceeded", msg_variant, size, self.peer_info);
+        self.tracker.increment_throttled(size as u64);
+        if let PeerStatus::Banned(_) = self.peer_status {
+            return;
+        }
+        if self.is_abusive() {
+            near_metrics::inc_counter(&metrics::PEER_BANNED_ABUSIVE_TOTAL);
+            self.ban_peer(ctx, ReasonForBan::Abusive);
+        }
     }
 
     fn send_message(&mut self, msg: PeerMessage) {
@@ -197,13 +289,24 @@ impl Peer {
         match &msg {
             PeerMessage::Block(b) if self.tracker.has_received(b.hash()) => return,
             PeerMessage::BlockRequest(h) => self.tracker.push_request(*h),
+            PeerMessage::BlockHeadersRequest(_) => self.tracker.push_headers_request(),
             _ => (),
         };
 
         trace!(target: "diagnostic", key="tx", msg=%ser(&msg));
 
+        let msg_variant = msg.msg_variant();
+        let is_critical = msg.is_critical();
         match peer_message_to_bytes(msg) {
             Ok(bytes) => {
+                if !is_critical && !self.rate_limiter.allow_sent_bytes(bytes.len()) {
+                    near_metrics::inc_counter_vec(
+                        &metrics::PEER_MESSAGE_SEND_THROTTLED_TOTAL,
+                        &[msg_variant],
+                    );
+                    debug!(target: "network", "Dropping {} message of {} bytes to {}: rate limit exceeded", msg_variant, bytes.len(), self.peer_info);
+                    return;
+                }
                 self.tracker.increment_sent(bytes.len() as u64);
                 self.framed.write(bytes);
             }
@@ -304,6 +407,12 @@ impl Peer {
                     RoutedMessageBody::ReceiptOutComeResponse(response) => {
                         NetworkViewClientMessages::ReceiptOutcomeResponse(response)
                     }
+                    RoutedMessageBody::ReceiptRequest(receipt_id, shard_id) => {
+                        NetworkViewClientMessages::ReceiptRequest { receipt_id, shard_id }
+                    }
+                    RoutedMessageBody::ReceiptResponse(receipt_id, response) => {
+                        NetworkViewClientMessages::ReceiptResponse(receipt_id, response)
+                    }
                     RoutedMessageBody::StateRequestHeader(shard_id, sync_hash) => {
                         NetworkViewClientMessages::StateRequestHeader { shard_id, sync_hash }
                     }
@@ -347,6 +456,11 @@ impl Peer {
                         act.peer_manager_addr
                             .do_send(PeerRequest::RouteBack(body, msg_hash.unwrap()));
                     }
+                    Ok(NetworkViewClientResponses::ReceiptResponse(receipt_id, response)) => {
+                        let body = RoutedMessageBody::ReceiptResponse(receipt_id, response);
+                        act.peer_manager_addr
+                            .do_send(PeerRequest::RouteBack(body, msg_hash.unwrap()));
+                    }
                     Ok(NetworkViewClientResponses::StateResponse(state_response)) => {
                         let body = RoutedMessageBody::StateResponse(state_response);
                         act.peer_manager_addr
@@ -384,6 +498,7 @@ impl Peer {
                 near_metrics::inc_counter(&metrics::PEER_BLOCK_RECEIVED_TOTAL);
                 let block_hash = block.hash();
                 self.tracker.push_received(block_hash);
+                self.tracker.deliver_request(&block_hash);
                 self.chain_info.height =
                     max(self.chain_info.height, block.header.inner_lite.height);
                 self.chain_info.score = max(self.chain_info.score, block.header.inner_rest.score);
@@ -394,6 +509,7 @@ impl Peer {
                 NetworkClientMessages::Transaction(transaction)
             }
             PeerMessage::BlockHeaders(headers) => {
+                self.tracker.deliver_headers_request();
                 NetworkClientMessages::BlockHeaders(headers, peer_id)
             }
             // All Routed messages received at this point are for us.
@@ -425,6 +541,8 @@ impl Peer {
                     | RoutedMessageBody::QueryResponse { .. }
                     | RoutedMessageBody::ReceiptOutcomeRequest(_)
                     | RoutedMessageBody::ReceiptOutComeResponse(_)
+                    | RoutedMessageBody::ReceiptRequest(_, _)
+                    | RoutedMessageBody::ReceiptResponse(_, _)
                     | RoutedMessageBody::StateRequestHeader(_, _)
                     | RoutedMessageBody::StateRequestPart(_, _, _) => {
                         error!(target: "network", "Peer receive_client_message received unexpected type: {:?}", routed_message);
@@ -527,9 +645,16 @@ impl StreamHandler<Vec<u8>> for Peer {
             Ok(peer_msg) => peer_msg,
             Err(err) => {
                 error!(target: "network", "Received invalid data {:?} from {}: {}", msg, self.peer_info, err);
+                self.tracker.increment_invalid();
                 return;
             }
         };
+        if (!peer_msg.is_response() && !self.rate_limiter.allow_received_bytes(msg.len()))
+            || !self.rate_limiter.allow_received_message(&peer_msg)
+        {
+            self.throttle(ctx, peer_msg.msg_variant(), msg.len());
+            return;
+        }
 
         trace!(target: "diagnostic", key="rx", length=msg.len(), msg=%ser(&peer_msg));
 
@@ -614,6 +739,7 @@ impl StreamHandler<Vec<u8>> for Peer {
                     .send(Consolidate {
                         actor: ctx.address(),
                         peer_info: peer_info.clone(),
+                        peer_addr: self.peer_addr,
                         peer_type: self.peer_type,
                         chain_info: handshake.chain_info.clone(),
                         this_edge_info: self.edge_info.clone(),
@@ -776,6 +902,9 @@ impl Handler<QueryPeerStats> for Peer {
     type Result = PeerStatsResult;
 
     fn handle(&mut self, _: QueryPeerStats, _: &mut Self::Context) -> Self::Result {
+        self.tracker.expire_requests(REQUEST_TIMEOUT);
+        let (delivered_requests, failed_requests, invalid_messages) =
+            self.tracker.take_request_stats();
         PeerStatsResult {
             chain_info: self.chain_info.clone(),
             received_bytes_per_sec: self.tracker.received_bytes.bytes_per_min() / 60,
@@ -785,6 +914,9 @@ impl Handler<QueryPeerStats> for Peer {
                 self.tracker.sent_bytes.count_per_min(),
                 self.tracker.received_bytes.count_per_min(),
             ),
+            delivered_requests,
+            failed_requests,
+            invalid_messages,
         }
     }
 }
@@ -1,9 +1,9 @@
 use std::cmp;
 use std::collections::{HashMap, HashSet};
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
 use std::pin::Pin;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use actix::actors::resolver::{ConnectAddr, Resolver};
 use actix::io::FramedWrite;
@@ -11,6 +11,7 @@ use actix::{
     Actor, ActorFuture, Addr, AsyncContext, Context, ContextFutureSpawner, Handler, Recipient,
     Running, StreamHandler, SystemService, WrapFuture,
 };
+use cached::{Cached, SizedCache};
 use chrono::offset::TimeZone;
 use chrono::{DateTime, Utc};
 use futures::task::Poll;
@@ -32,12 +33,12 @@ use crate::peer::Peer;
 use crate::peer_store::PeerStore;
 use crate::routing::{Edge, EdgeInfo, EdgeType, ProcessEdgeResult, RoutingTable};
 use crate::types::{
-    AccountOrPeerIdOrHash, Ban, BlockedPorts, Consolidate, ConsolidateResponse, FullPeerInfo,
-    InboundTcpConnect, KnownPeerStatus, KnownProducer, NetworkInfo, NetworkViewClientMessages,
-    NetworkViewClientResponses, OutboundTcpConnect, PeerIdOrHash, PeerList, PeerManagerRequest,
-    PeerMessage, PeerRequest, PeerResponse, PeerType, PeersRequest, PeersResponse, Ping, Pong,
-    QueryPeerStats, RawRoutedMessage, ReasonForBan, RoutedMessage, RoutedMessageBody,
-    RoutedMessageFrom, SendMessage, SyncData, Unregister,
+    AccountOrPeerIdOrHash, Ban, BanTarget, BlockedPorts, Consolidate, ConsolidateResponse,
+    FullPeerInfo, InboundTcpConnect, IpSubnet, KnownPeerStatus, KnownProducer, NetworkInfo,
+    NetworkViewClientMessages, NetworkViewClientResponses, OutboundTcpConnect, PeerIdOrHash,
+    PeerList, PeerManagerRequest, PeerMessage, PeerRequest, PeerResponse, PeerType, PeersRequest,
+    PeersResponse, Ping, Pong, QueryPeerStats, RawRoutedMessage, ReasonForBan, RoutedMessage,
+    RoutedMessageBody, RoutedMessageFrom, SendMessage, SyncData, Unregister,
 };
 use crate::types::{
     NetworkClientMessages, NetworkConfig, NetworkRequests, NetworkResponses, PeerInfo,
@@ -51,6 +52,18 @@ const WAIT_ON_TRY_UPDATE_NONCE: u64 = 6_000;
 /// If we see an edge between us and other peer, but this peer is not a current connection, wait this
 /// timeout and in case it didn't become an active peer, broadcast edge removal update.
 const WAIT_PEER_BEFORE_REMOVE: u64 = 6_000;
+/// Flag set in nonces of pings sent to active peers to measure latency, to tell them apart from
+/// pings requested through `NetworkRequests::PingTo`.
+const LATENCY_PROBE_NONCE_FLAG: u64 = 1 << 63;
+/// Minimum time (in seconds) an inbound peer must have been connected before it can be evicted
+/// in favor of a better scored peer, so that reconnecting peers can't churn our connections.
+const MIN_EVICTION_AGE_SECS: i64 = 600;
+/// How much higher the score of a new peer must be than the score of the evicted peer.
+const EVICTION_SCORE_MARGIN: i64 = 20;
+/// How often to persist peer scores updated in memory (in seconds).
+const SAVE_PEER_SCORES_PERIOD: u64 = 60;
+/// Maximum number of receipt requests waiting for a response.
+const PENDING_RECEIPT_REQUESTS_LIMIT: usize = 1024;
 
 macro_rules! unwrap_or_error(($obj: expr, $error: expr) => (match $obj {
     Ok(result) => result,
@@ -64,12 +77,18 @@ macro_rules! unwrap_or_error(($obj: expr, $error: expr) => (match $obj {
 struct ActivePeer {
     addr: Addr<Peer>,
     full_peer_info: FullPeerInfo,
+    /// Address of the connection with the peer.
+    peer_addr: SocketAddr,
+    /// Whether the connection was started by the peer or by us.
+    peer_type: PeerType,
     /// Number of bytes we've received from the peer.
     received_bytes_per_sec: u64,
     /// Number of bytes we've sent to the peer.
     sent_bytes_per_sec: u64,
     /// Last time requested peers.
     last_time_peer_requested: DateTime<Utc>,
+    /// Time the connection with the peer was established.
+    connection_established_time: DateTime<Utc>,
 }
 
 /// Actor that manages peers connections.
@@ -94,6 +113,10 @@ pub struct PeerManagerActor {
     monitor_peers_attempts: u64,
     /// Active peers we have sent new edge update, but we haven't received response so far.
     pending_update_nonce_request: HashMap<PeerId, u64>,
+    /// Pings sent to measure latency of active peers by nonce, with the time they were sent.
+    pending_latency_probes: HashMap<u64, (PeerId, Instant)>,
+    /// Peers the receipt requests were sent to. Only their responses are accepted.
+    pending_receipt_requests: SizedCache<CryptoHash, PeerId>,
 }
 
 impl PeerManagerActor {
@@ -103,7 +126,9 @@ impl PeerManagerActor {
         client_addr: Recipient<NetworkClientMessages>,
         view_client_addr: Recipient<NetworkViewClientMessages>,
     ) -> Result<Self, Box<dyn std::error::Error>> {
-        let peer_store = PeerStore::new(store.clone(), &config.boot_nodes)?;
+        let known_nodes: Vec<_> =
+            config.boot_nodes.iter().chain(config.sentry_nodes.iter()).cloned().collect();
+        let peer_store = PeerStore::new(store.clone(), &known_nodes)?;
         debug!(target: "network", "Found known peers: {} (boot nodes={})", peer_store.len(), config.boot_nodes.len());
         debug!(target: "network", "Blacklist: {:?}", config.blacklist);
 
@@ -119,6 +144,8 @@ impl PeerManagerActor {
             routing_table: RoutingTable::new(me, store),
             monitor_peers_attempts: 0,
             pending_update_nonce_request: HashMap::new(),
+            pending_latency_probes: HashMap::new(),
+            pending_receipt_requests: SizedCache::with_size(PENDING_RECEIPT_REQUESTS_LIMIT),
         })
     }
 
@@ -126,6 +153,28 @@ impl PeerManagerActor {
         self.active_peers.len()
     }
 
+    /// Whether this node is hidden behind sentry nodes.
+    fn is_behind_sentry_nodes(&self) -> bool {
+        !self.config.sentry_nodes.is_empty()
+    }
+
+    fn is_sentry_node(&self, peer_id: &PeerId) -> bool {
+        self.config.sentry_nodes.iter().any(|peer_info| &peer_info.id == peer_id)
+    }
+
+    fn is_hidden_peer(&self, peer_id: &PeerId) -> bool {
+        self.config.hidden_peers.iter().any(|peer_info| &peer_info.id == peer_id)
+    }
+
+    /// Whether the connection comes from the address of a hidden peer. Only the IP is compared,
+    /// since the port of the inbound connection isn't the one the peer listens on.
+    fn is_hidden_peer_addr(&self, addr: &SocketAddr) -> bool {
+        self.config
+            .hidden_peers
+            .iter()
+            .any(|peer_info| peer_info.addr.map_or(false, |hidden| hidden.ip() == addr.ip()))
+    }
+
     fn is_blacklisted(&self, addr: &SocketAddr) -> bool {
         if let Some(blocked_ports) = self.config.blacklist.get(&addr.ip()) {
             match blocked_ports {
@@ -147,6 +196,7 @@ impl PeerManagerActor {
         &mut self,
         full_peer_info: FullPeerInfo,
         edge_info: EdgeInfo,
+        peer_addr: SocketAddr,
         peer_type: PeerType,
         addr: Addr<Peer>,
         ctx: &mut Context<Self>,
@@ -176,9 +226,12 @@ impl PeerManagerActor {
             ActivePeer {
                 addr: addr.clone(),
                 full_peer_info,
+                peer_addr,
+                peer_type,
                 sent_bytes_per_sec: 0,
                 received_bytes_per_sec: 0,
                 last_time_peer_requested: Utc.timestamp(0, 0),
+                connection_established_time: Utc::now(),
             },
         );
 
@@ -246,11 +299,94 @@ impl PeerManagerActor {
         unwrap_or_error!(self.peer_store.peer_disconnected(&peer_id), "Failed to save peer data");
     }
 
-    /// Add peer to ban list.
+    /// Add peer and the address of the connection with it to ban list, so the peer can't come
+    /// back with a new peer id. Both bans are lifted after `ban_window`.
     fn ban_peer(&mut self, ctx: &mut Context<Self>, peer_id: &PeerId, ban_reason: ReasonForBan) {
         info!(target: "network", "Banning peer {:?} for {:?}", peer_id, ban_reason);
+        let peer_ip = self.active_peers.get(peer_id).map(|active_peer| active_peer.peer_addr.ip());
         self.remove_active_peer(ctx, peer_id);
         unwrap_or_error!(self.peer_store.peer_ban(peer_id, ban_reason), "Failed to save peer data");
+
+        if let Some(ip) = peer_ip {
+            if self.is_ip_ban_exempt(&ip) {
+                debug!(target: "network", "Address {} of banned peer {:?} is not banned", ip, peer_id);
+            } else {
+                unwrap_or_error!(
+                    self.peer_store.ban(&BanTarget::Ip(IpSubnet::from_addr(ip)), ban_reason),
+                    "Failed to save banned address"
+                );
+            }
+        }
+    }
+
+    /// Whether the address is never banned automatically: local addresses and addresses of
+    /// configured nodes can be shared by several honest peers.
+    fn is_ip_ban_exempt(&self, ip: &IpAddr) -> bool {
+        ip.is_loopback()
+            || self
+                .config
+                .boot_nodes
+                .iter()
+                .chain(self.config.sentry_nodes.iter())
+                .chain(self.config.hidden_peers.iter())
+                .any(|peer_info| peer_info.addr.map_or(false, |addr| addr.ip() == *ip))
+    }
+
+    /// Ban a peer or a range of IP addresses at the request of the node operator, and disconnect
+    /// the active peers it covers.
+    fn ban_target(&mut self, target: &BanTarget) -> Result<(), Box<dyn std::error::Error>> {
+        info!(target: "network", "Banning {} at the request of the operator", target);
+        match target {
+            BanTarget::Peer(peer_id) => {
+                if let Some(active_peer) = self.active_peers.get(peer_id) {
+                    active_peer.addr.do_send(PeerManagerRequest::BanPeer(ReasonForBan::Manual));
+                }
+            }
+            BanTarget::Ip(subnet) => {
+                for active_peer in self
+                    .active_peers
+                    .values()
+                    .filter(|active_peer| subnet.contains(&active_peer.peer_addr.ip()))
+                {
+                    active_peer.addr.do_send(PeerManagerRequest::UnregisterPeer);
+                }
+            }
+        }
+        self.peer_store.ban(target, ReasonForBan::Manual)
+    }
+
+    /// Check that a new inbound connection from `addr` is not banned and doesn't exceed
+    /// the limit of connections per IP address and per subnet.
+    fn is_inbound_addr_allowed(&self, addr: &SocketAddr) -> bool {
+        let ip = addr.ip();
+        if self.peer_store.is_ip_banned(&ip) {
+            debug!(target: "network", "Inbound connection from banned address {}", addr);
+            return false;
+        }
+
+        // Local connections are not limited.
+        if ip.is_loopback() {
+            return true;
+        }
+
+        let subnet = IpSubnet::from_addr_network(ip);
+        let (same_ip, same_subnet) = self
+            .active_peers
+            .values()
+            .filter(|active_peer| active_peer.peer_type == PeerType::Inbound)
+            .fold((0, 0), |(same_ip, same_subnet), active_peer| {
+                let peer_ip = active_peer.peer_addr.ip();
+                (same_ip + (peer_ip == ip) as u32, same_subnet + subnet.contains(&peer_ip) as u32)
+            });
+
+        if same_ip >= self.config.max_inbound_per_ip
+            || same_subnet >= self.config.max_inbound_per_subnet
+        {
+            debug!(target: "network", "Too many inbound connections from {} ({} from ip, {} from subnet)", addr, same_ip, same_subnet);
+            return false;
+        }
+
+        true
     }
 
     /// Connects peer with given TcpStream and optional information if it's outbound.
@@ -267,6 +403,7 @@ impl PeerManagerActor {
         let account_id = self.config.account_id.clone();
         let server_addr = self.config.addr;
         let handshake_timeout = self.config.handshake_timeout;
+        let rate_limits = self.config.rate_limits.clone();
         let client_addr = self.client_addr.clone();
         let view_client_addr = self.view_client_addr.clone();
 
@@ -292,7 +429,6 @@ impl PeerManagerActor {
         Peer::create(move |ctx| {
             let (read, write) = tokio::io::split(stream);
 
-            // TODO: check if peer is banned or known based on IP address and port.
             Peer::add_stream(
                 FramedRead::new(read, Codec::new())
                     .take_while(|x| match x {
@@ -316,6 +452,7 @@ impl PeerManagerActor {
                 client_addr,
                 view_client_addr,
                 edge_info,
+                &rate_limits,
             )
         });
     }
@@ -342,7 +479,8 @@ impl PeerManagerActor {
             None => return vec![],
         };
         // Find all peers whose height is within `highest_peer_horizon` from max height peer(s).
-        self.active_peers
+        let mut peers = self
+            .active_peers
             .values()
             .filter_map(|active_peer| {
                 if max_score_and_height.beyond_threshold(
@@ -354,7 +492,9 @@ impl PeerManagerActor {
                     Some(active_peer.full_peer_info.clone())
                 }
             })
-            .collect::<Vec<_>>()
+            .collect::<Vec<_>>();
+        peers.sort_by_key(|peer| cmp::Reverse(self.peer_store.score(&peer.peer_info.id)));
+        peers
     }
 
     /// Returns bytes sent/received across all peers.
@@ -364,16 +504,34 @@ impl PeerManagerActor {
         (sent_bps, received_bps)
     }
 
-    /// Get a random peer we are not connected to from the known list.
-    fn sample_random_peer(&self, ignore_list: &HashSet<PeerId>) -> Option<PeerInfo> {
-        let unconnected_peers = self.peer_store.unconnected_peers(ignore_list);
-        let index = thread_rng().gen_range(0, std::cmp::max(unconnected_peers.len(), 1));
+    /// Get the best scored peer we are not connected to from the known list.
+    /// Peers with the same score are sampled randomly.
+    /// If this node is behind sentry nodes, only sentry nodes are sampled.
+    fn sample_best_peer(&self, ignore_list: &HashSet<PeerId>) -> Option<PeerInfo> {
+        self.peer_store
+            .unconnected_peers(ignore_list)
+            .into_iter()
+            .find(|peer_info| !self.is_behind_sentry_nodes() || self.is_sentry_node(&peer_info.id))
+    }
 
-        unconnected_peers
+    /// Find inbound peer with the lowest score that can be disconnected to accept a new peer
+    /// when at max capacity. Hidden peers, sentry nodes and recently connected peers are never
+    /// evicted.
+    fn lowest_score_evictable_peer(&self) -> Option<(PeerId, i64)> {
+        let now = Utc::now();
+        self.active_peers
             .iter()
-            .enumerate()
-            .filter_map(|(i, v)| if i == index { Some(v.clone()) } else { None })
-            .next()
+            .filter(|(peer_id, active_peer)| {
+                active_peer.peer_type == PeerType::Inbound
+                    && now
+                        .signed_duration_since(active_peer.connection_established_time)
+                        .num_seconds()
+                        >= MIN_EVICTION_AGE_SECS
+                    && !self.is_hidden_peer(peer_id)
+                    && !self.is_sentry_node(peer_id)
+            })
+            .map(|(peer_id, _)| (peer_id.clone(), self.peer_store.score(peer_id)))
+            .min_by_key(|(_, score)| *score)
     }
 
     /// Query current peers for more peers.
@@ -461,8 +619,15 @@ impl PeerManagerActor {
         });
     }
 
-    /// Periodically query peer actors for latest weight and traffic info.
+    /// Periodically query peer actors for latest weight and traffic info, and update peer scores.
     fn monitor_peer_stats(&mut self, ctx: &mut Context<Self>) {
+        let period = self.config.peer_stats_period;
+        self.pending_latency_probes.retain(|_, (_, sent)| sent.elapsed() < period);
+        let peer_ids = self.active_peers.keys().cloned().collect::<Vec<_>>();
+        for peer_id in peer_ids {
+            self.send_latency_probe(ctx, peer_id);
+        }
+
         for (peer_id, active_peer) in self.active_peers.iter() {
             let peer_id1 = peer_id.clone();
             active_peer
@@ -473,17 +638,18 @@ impl PeerManagerActor {
                 .map(move |res, act, _| {
                     let _ignore = res.map(|res| {
                         if res.is_abusive {
-                            trace!(target: "network", "Banning peer {} for abuse ({} sent, {} recv)", peer_id1, res.message_counts.0, res.message_counts.1);
-                            // TODO(MarX, #1586): Ban peer if we found them abusive. Fix issue with heavy
-                            //  network traffic that flags honest peers.
-                            // Send ban signal to peer instance. It should send ban signal back and stop the instance.
-                            // if let Some(active_peer) = act.active_peers.get(&peer_id1) {
-                            //     active_peer.addr.do_send(PeerManagerRequest::BanPeer(ReasonForBan::Abusive));
-                            // }
+                            // The peer instance bans the peer itself once it exceeds the rate limits.
+                            trace!(target: "network", "Peer {} is abusive ({} sent, {} recv)", peer_id1, res.message_counts.0, res.message_counts.1);
                         } else if let Some(active_peer) = act.active_peers.get_mut(&peer_id1) {
                             active_peer.full_peer_info.chain_info = res.chain_info;
                             active_peer.sent_bytes_per_sec = res.sent_bytes_per_sec;
                             active_peer.received_bytes_per_sec = res.received_bytes_per_sec;
+                            act.peer_store.update_score(&peer_id1, |score| {
+                                score.delivered_requests += res.delivered_requests;
+                                score.failed_requests += res.failed_requests;
+                                score.invalid_messages += res.invalid_messages;
+                                score.uptime_secs += period.as_secs();
+                            });
                         }
                     });
                 })
@@ -495,6 +661,15 @@ impl PeerManagerActor {
         });
     }
 
+    /// Periodically persist peer scores updated since the last time they were saved.
+    fn save_peer_scores(&mut self, ctx: &mut Context<Self>) {
+        unwrap_or_error!(self.peer_store.save_scores(), "Failed to save peer scores");
+
+        ctx.run_later(Duration::from_secs(SAVE_PEER_SCORES_PERIOD), move |act, ctx| {
+            act.save_peer_scores(ctx);
+        });
+    }
+
     /// Periodically monitor list of peers and:
     ///  - request new peers from connected peers,
     ///  - bootstrap outbound connections from known peers,
@@ -518,11 +693,26 @@ impl PeerManagerActor {
             unwrap_or_error!(self.peer_store.peer_unban(&peer_id), "Failed to unban a peer");
         }
 
+        let mut to_unban = vec![];
+        for (subnet, ip_ban) in self.peer_store.banned_ips() {
+            let interval = unwrap_or_error!(
+                (Utc::now() - from_timestamp(ip_ban.banned_at)).to_std(),
+                "Failed to convert time"
+            );
+            if interval > self.config.ban_window {
+                info!(target: "network", "Monitor peers: unbanned ip {} after {:?}.", subnet, interval);
+                to_unban.push(*subnet);
+            }
+        }
+        for subnet in to_unban {
+            unwrap_or_error!(self.peer_store.ip_unban(&subnet), "Failed to unban an ip");
+        }
+
         if self.is_outbound_bootstrap_needed() {
-            if let Some(peer_info) = self.sample_random_peer(&self.outgoing_peers) {
+            if let Some(peer_info) = self.sample_best_peer(&self.outgoing_peers) {
                 self.outgoing_peers.insert(peer_info.id.clone());
                 ctx.notify(OutboundTcpConnect { peer_info });
-            } else {
+            } else if !self.is_behind_sentry_nodes() {
                 self.query_active_peers_for_more_peers(ctx);
             }
         }
@@ -616,6 +806,7 @@ impl PeerManagerActor {
     }
 
     /// Route signed message to target peer.
+    /// If the next hop is not available, try next hops from other routes to the same target.
     /// Return whether the message is sent or not.
     fn send_signed_message_to_peer(&mut self, ctx: &mut Context<Self>, msg: RoutedMessage) -> bool {
         match self.routing_table.find_route(&msg.target) {
@@ -625,10 +816,25 @@ impl PeerManagerActor {
                     self.routing_table.add_route_back(msg.hash(), self.peer_id.clone());
                 }
 
-                self.send_message(ctx, &peer_id, PeerMessage::Routed(msg))
+                if self.send_message(ctx, &peer_id, PeerMessage::Routed(msg.clone())) {
+                    return true;
+                }
+
+                if let PeerIdOrHash::PeerId(target) = &msg.target {
+                    let mut excluded = HashSet::new();
+                    excluded.insert(peer_id);
+                    for next_hop in self.routing_table.find_fallback_routes(target, &excluded) {
+                        if self.send_message(ctx, &next_hop, PeerMessage::Routed(msg.clone())) {
+                            near_metrics::inc_counter(&metrics::ROUTED_MESSAGE_FALLBACK_ROUTE);
+                            return true;
+                        }
+                    }
+                }
+
+                near_metrics::inc_counter(&metrics::DROP_MESSAGE_UNREACHABLE_PEER);
+                false
             }
             Err(find_route_error) => {
-                // TODO(MarX, #1369): Message is dropped here. Define policy for this case.
                 near_metrics::inc_counter(&metrics::DROP_MESSAGE_UNREACHABLE_PEER);
                 debug!(target: "network", "{:?} Drop signed message to {:?} Reason {:?}. Known peers: {:?} Message {:?}",
                       self.config.account_id,
@@ -650,6 +856,8 @@ impl PeerManagerActor {
     }
 
     /// Send message to specific account.
+    /// If the account was announced by several peers, try all of them starting from the
+    /// reachable ones until the message is sent.
     /// Return whether the message is sent or not.
     fn send_message_to_account(
         &mut self,
@@ -657,10 +865,20 @@ impl PeerManagerActor {
         account_id: &AccountId,
         msg: RoutedMessageBody,
     ) -> bool {
-        let target = match self.routing_table.account_owner(&account_id) {
-            Ok(peer_id) => peer_id,
+        self.send_message_to_account_owner(ctx, account_id, msg).is_some()
+    }
+
+    /// Same as `send_message_to_account`, returning the peer of the account the message was sent
+    /// to.
+    fn send_message_to_account_owner(
+        &mut self,
+        ctx: &mut Context<Self>,
+        account_id: &AccountId,
+        msg: RoutedMessageBody,
+    ) -> Option<PeerId> {
+        let targets = match self.routing_table.account_owners(&account_id) {
+            Ok(peer_ids) => peer_ids,
             Err(find_route_error) => {
-                // TODO(MarX, #1369): Message is dropped here. Define policy for this case.
                 near_metrics::inc_counter(&metrics::DROP_MESSAGE_UNKNOWN_ACCOUNT);
                 debug!(target: "network", "{:?} Drop message to {} Reason {:?}. Known peers: {:?} Message {:?}",
                        self.config.account_id,
@@ -669,12 +887,17 @@ impl PeerManagerActor {
                        self.routing_table.get_accounts_keys(),
                        msg,
                 );
-                return false;
+                return None;
             }
         };
 
-        let msg = RawRoutedMessage { target: AccountOrPeerIdOrHash::PeerId(target), body: msg };
-        self.send_message_to_peer(ctx, msg)
+        targets.into_iter().find(|target| {
+            let msg = RawRoutedMessage {
+                target: AccountOrPeerIdOrHash::PeerId(target.clone()),
+                body: msg.clone(),
+            };
+            self.send_message_to_peer(ctx, msg)
+        })
     }
 
     fn sign_routed_message(&self, msg: RawRoutedMessage) -> RoutedMessage {
@@ -723,11 +946,33 @@ impl PeerManagerActor {
 
     fn handle_ping(&mut self, ctx: &mut Context<Self>, ping: Ping, hash: CryptoHash) {
         self.send_pong(ctx, ping.nonce as usize, hash);
-        self.routing_table.add_ping(ping);
+        if ping.nonce & LATENCY_PROBE_NONCE_FLAG == 0 {
+            self.routing_table.add_ping(ping);
+        }
     }
 
     fn handle_pong(&mut self, _ctx: &mut Context<Self>, pong: Pong) {
-        self.routing_table.add_pong(pong);
+        if pong.nonce & LATENCY_PROBE_NONCE_FLAG == 0 {
+            self.routing_table.add_pong(pong);
+            return;
+        }
+        if let Some((peer_id, sent)) = self.pending_latency_probes.remove(&pong.nonce) {
+            if peer_id == pong.source {
+                let latency = sent.elapsed();
+                trace!(target: "network", "Latency to {} is {:?}", peer_id, latency);
+                self.peer_store.update_score(&peer_id, |score| score.record_latency(latency));
+            }
+        }
+    }
+
+    /// Send ping to active peer to measure latency.
+    fn send_latency_probe(&mut self, ctx: &mut Context<Self>, target: PeerId) {
+        let nonce = thread_rng().gen::<u64>() | LATENCY_PROBE_NONCE_FLAG;
+        let body = RoutedMessageBody::Ping(Ping { nonce, source: self.peer_id.clone() });
+        let msg = RawRoutedMessage { target: AccountOrPeerIdOrHash::PeerId(target.clone()), body };
+        if self.send_message_to_peer(ctx, msg) {
+            self.pending_latency_probes.insert(nonce, (target, Instant::now()));
+        }
     }
 
     pub(crate) fn get_network_info(&mut self) -> NetworkInfo {
@@ -736,7 +981,13 @@ impl PeerManagerActor {
             active_peers: self
                 .active_peers
                 .values()
-                .map(|a| a.full_peer_info.clone())
+                .map(|a| {
+                    let mut full_peer_info = a.full_peer_info.clone();
+                    if self.is_hidden_peer(&full_peer_info.peer_info.id) {
+                        full_peer_info.peer_info.addr = None;
+                    }
+                    full_peer_info
+                })
                 .collect::<Vec<_>>(),
             num_active_peers: self.num_active_peers(),
             peer_max_count: self.config.max_peer,
@@ -811,10 +1062,14 @@ impl Actor for PeerManagerActor {
 
         // Start active peer stats querying.
         self.monitor_peer_stats(ctx);
+
+        // Start periodically saving peer scores.
+        self.save_peer_scores(ctx);
     }
 
     /// Try to gracefully disconnect from active peers.
     fn stopping(&mut self, _: &mut Self::Context) -> Running {
+        unwrap_or_error!(self.peer_store.save_scores(), "Failed to save peer scores");
         let msg = SendMessage { message: PeerMessage::Disconnect };
 
         for (_, active_peer) in self.active_peers.iter() {
@@ -983,6 +1238,19 @@ impl Handler<NetworkRequests> for PeerManagerActor {
                     NetworkResponses::RouteNotFound
                 }
             }
+            NetworkRequests::ReceiptRequest(account_id, receipt_id, shard_id) => {
+                match self.send_message_to_account_owner(
+                    ctx,
+                    &account_id,
+                    RoutedMessageBody::ReceiptRequest(receipt_id, shard_id),
+                ) {
+                    Some(peer_id) => {
+                        self.pending_receipt_requests.cache_set(receipt_id, peer_id);
+                        NetworkResponses::NoResponse
+                    }
+                    None => NetworkResponses::RouteNotFound,
+                }
+            }
             NetworkRequests::FetchRoutingTable => {
                 NetworkResponses::RoutingTableInfo(self.routing_table.info())
             }
@@ -998,19 +1266,16 @@ impl Handler<NetworkRequests> for PeerManagerActor {
                 let new_accounts = accounts
                     .into_iter()
                     .filter_map(|announce_account| {
-                        if let Some(current_announce_account) =
-                            self.routing_table.get_announce(&announce_account.account_id)
-                        {
-                            if announce_account.epoch_id == current_announce_account.epoch_id {
-                                None
-                            } else {
-                                Some((
-                                    announce_account,
-                                    Some(current_announce_account.epoch_id.clone()),
-                                ))
-                            }
+                        if self.routing_table.contains_account(&announce_account) {
+                            None
                         } else {
-                            Some((announce_account, None))
+                            let known_epochs = self
+                                .routing_table
+                                .get_announces(&announce_account.account_id)
+                                .into_iter()
+                                .map(|current_announce_account| current_announce_account.epoch_id)
+                                .collect();
+                            Some((announce_account, known_epochs))
                         }
                     })
                     .collect();
@@ -1072,9 +1337,17 @@ impl Handler<NetworkRequests> for PeerManagerActor {
 
                             // Add accounts to the routing table.
                             debug!(target: "network", "{:?} Received new accounts: {:?}", act.config.account_id, accounts);
-                            for account in accounts.iter() {
-                                act.routing_table.add_account(account.clone());
-                            }
+                            let accounts: Vec<_> = accounts
+                                .into_iter()
+                                .map(|(account, stale_epochs)| {
+                                    act.routing_table.remove_announces_of_epochs(
+                                        &account.account_id,
+                                        &stale_epochs,
+                                    );
+                                    act.routing_table.add_account(account.clone());
+                                    account
+                                })
+                                .collect();
 
                             let new_data = SyncData { edges: new_edges, accounts };
 
@@ -1152,6 +1425,22 @@ impl Handler<NetworkRequests> for PeerManagerActor {
                 let (pings, pongs) = self.routing_table.fetch_ping_pong();
                 NetworkResponses::PingPongInfo { pings, pongs }
             }
+            NetworkRequests::Ban(target) => {
+                let result = self.ban_target(&target);
+                if let Err(err) = &result {
+                    warn!(target: "network", "Failed to ban {:?}: {}", target, err);
+                }
+                NetworkResponses::BanResult(result.is_ok())
+            }
+            NetworkRequests::FetchBans => NetworkResponses::Bans(self.peer_store.bans()),
+            NetworkRequests::Unban(target) => {
+                info!(target: "network", "Lifting ban of {:?}", target);
+                let result = self.peer_store.unban(&target);
+                if let Err(err) = &result {
+                    warn!(target: "network", "Failed to unban {:?}: {}", target, err);
+                }
+                NetworkResponses::UnbanResult(result.is_ok())
+            }
         }
     }
 }
@@ -1160,7 +1449,21 @@ impl Handler<InboundTcpConnect> for PeerManagerActor {
     type Result = ();
 
     fn handle(&mut self, msg: InboundTcpConnect, ctx: &mut Self::Context) {
-        if self.is_inbound_allowed() {
+        let peer_addr = msg.stream.peer_addr().ok();
+        if let Some(addr) = peer_addr {
+            if !self.is_inbound_addr_allowed(&addr) {
+                debug!(target: "network", "Inbound connection from {} dropped.", addr);
+                return;
+            }
+        }
+
+        // Hidden peers are accepted even at max capacity, and peers with better score than some
+        // active peer can replace it. It is only known who is connecting after the handshake,
+        // so in that case the check is done on consolidation.
+        if self.is_inbound_allowed()
+            || peer_addr.map_or(false, |addr| self.is_hidden_peer_addr(&addr))
+            || self.lowest_score_evictable_peer().is_some()
+        {
             self.try_connect_peer(ctx.address(), msg.stream, PeerType::Inbound, None, None);
         } else {
             // TODO(1896): Gracefully drop inbound connection for other peer.
@@ -1195,6 +1498,9 @@ impl Handler<OutboundTcpConnect> for PeerManagerActor {
                         Err(err) => {
                             info!(target: "network", "Error connecting to {}: {}", addr, err);
                             act.outgoing_peers.remove(&msg.peer_info.id);
+                            act.peer_store.update_score(&msg.peer_info.id, |score| {
+                                score.failed_connections += 1
+                            });
                             actix::fut::ready(())
                         }
                     },
@@ -1221,6 +1527,12 @@ impl Handler<Consolidate> for PeerManagerActor {
             return ConsolidateResponse::Reject;
         }
 
+        // Nodes behind sentry nodes only accept connections from them.
+        if self.is_behind_sentry_nodes() && !self.is_sentry_node(&msg.peer_info.id) {
+            debug!(target: "network", "Dropping connection from peer which is not a sentry node: {:?}", msg.peer_info);
+            return ConsolidateResponse::Reject;
+        }
+
         // We already connected to this peer.
         if self.active_peers.contains_key(&msg.peer_info.id) {
             debug!(target: "network", "Dropping handshake (Active Peer). {:?} {:?}", self.peer_id, msg.peer_info.id);
@@ -1236,12 +1548,36 @@ impl Handler<Consolidate> for PeerManagerActor {
             }
         }
 
-        if msg.peer_type == PeerType::Inbound && !self.is_inbound_allowed() {
-            // TODO(1896): Gracefully drop inbound connection for other peer.
-            debug!(target: "network", "Inbound connection dropped (network at max capacity).");
+        if msg.peer_type == PeerType::Inbound && !self.is_inbound_addr_allowed(&msg.peer_addr) {
             return ConsolidateResponse::Reject;
         }
 
+        if msg.peer_type == PeerType::Inbound
+            && !self.is_inbound_allowed()
+            && !self.is_hidden_peer(&msg.peer_info.id)
+        {
+            // Replace the worst inbound peer if the new one has a clearly better score. Only peers
+            // that earned a positive score can replace others, so new peer ids never do.
+            let score = self.peer_store.score(&msg.peer_info.id);
+            match self.lowest_score_evictable_peer() {
+                Some((evicted_peer_id, evicted_score))
+                    if score > 0 && score >= evicted_score + EVICTION_SCORE_MARGIN =>
+                {
+                    debug!(target: "network", "Evicting peer {} (score {}) in favor of {} (score {}).", evicted_peer_id, evicted_score, msg.peer_info.id, score);
+                    near_metrics::inc_counter(&metrics::PEER_EVICTED_TOTAL);
+                    // The peer unregisters itself once it is stopped.
+                    if let Some(active_peer) = self.active_peers.get(&evicted_peer_id) {
+                        active_peer.addr.do_send(PeerManagerRequest::UnregisterPeer);
+                    }
+                }
+                _ => {
+                    // TODO(1896): Gracefully drop inbound connection for other peer.
+                    debug!(target: "network", "Inbound connection dropped (network at max capacity).");
+                    return ConsolidateResponse::Reject;
+                }
+            }
+        }
+
         if msg.other_edge_info.nonce == 0 {
             debug!(target: "network", "Invalid nonce. It must be greater than 0. nonce={}", msg.other_edge_info.nonce);
             return ConsolidateResponse::Reject;
@@ -1273,6 +1609,7 @@ impl Handler<Consolidate> for PeerManagerActor {
                 edge_info: msg.other_edge_info,
             },
             edge_info,
+            msg.peer_addr,
             msg.peer_type,
             msg.actor,
             ctx,
@@ -1302,7 +1639,14 @@ impl Handler<PeersRequest> for PeerManagerActor {
     type Result = PeerList;
 
     fn handle(&mut self, _msg: PeersRequest, _ctx: &mut Self::Context) -> Self::Result {
-        PeerList { peers: self.peer_store.healthy_peers(self.config.max_send_peers) }
+        // Never share address of hidden peers.
+        let peers = self
+            .peer_store
+            .healthy_peers(self.config.max_send_peers)
+            .into_iter()
+            .filter(|peer_info| !self.is_hidden_peer(&peer_info.id))
+            .collect();
+        PeerList { peers }
     }
 }
 
@@ -1310,6 +1654,11 @@ impl Handler<PeersResponse> for PeerManagerActor {
     type Result = ();
 
     fn handle(&mut self, msg: PeersResponse, _ctx: &mut Self::Context) {
+        // Nodes behind sentry nodes don't learn about other peers.
+        if self.is_behind_sentry_nodes() {
+            return;
+        }
+
         self.peer_store.add_peers(
             msg.peers.into_iter().filter(|peer_info| peer_info.id != self.peer_id).collect(),
         );
@@ -1334,6 +1683,19 @@ impl Handler<RoutedMessageFrom> for PeerManagerActor {
             match &msg.body {
                 RoutedMessageBody::Ping(ping) => self.handle_ping(ctx, ping.clone(), msg.hash()),
                 RoutedMessageBody::Pong(pong) => self.handle_pong(ctx, pong.clone()),
+                RoutedMessageBody::ReceiptResponse(receipt_id, _) => {
+                    match self.pending_receipt_requests.cache_remove(receipt_id) {
+                        Some(peer_id) if peer_id == msg.author => return true,
+                        Some(peer_id) => {
+                            // Keep waiting for the response of the peer the request was sent to.
+                            self.pending_receipt_requests.cache_set(*receipt_id, peer_id);
+                            warn!(target: "network", "Dropping receipt {} from {}, it wasn't requested from it", receipt_id, msg.author);
+                        }
+                        None => {
+                            debug!(target: "network", "Dropping receipt {} from {}, it wasn't requested", receipt_id, msg.author);
+                        }
+                    }
+                }
                 _ => return true,
             }
 
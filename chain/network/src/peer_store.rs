@@ -1,8 +1,10 @@
+use std::cmp::Reverse;
 use std::collections::{hash_map::Iter, HashMap, HashSet};
 use std::convert::TryInto;
+use std::net::IpAddr;
 use std::sync::Arc;
 
-use borsh::BorshSerialize;
+use borsh::{BorshDeserialize, BorshSerialize};
 use chrono::Utc;
 use log::debug;
 use rand::seq::SliceRandom;
@@ -10,16 +12,21 @@ use rand::thread_rng;
 
 use near_primitives::network::PeerId;
 use near_primitives::utils::to_timestamp;
-use near_store::{ColPeers, Store};
+use near_store::{ColBannedIps, ColPeers, Store};
 
 use crate::types::{
-    FullPeerInfo, KnownPeerState, KnownPeerStatus, NetworkConfig, PeerInfo, ReasonForBan,
+    BanInfo, BanTarget, FullPeerInfo, IpSubnet, KnownIpBan, KnownPeerState, KnownPeerStatus,
+    NetworkConfig, PeerInfo, PeerScore, ReasonForBan,
 };
 
 /// Known peers store, maintaining cache of known peers and connection to storage to save/load them.
 pub struct PeerStore {
     store: Arc<Store>,
     peer_states: HashMap<PeerId, KnownPeerState>,
+    /// Banned IP addresses and subnets.
+    banned_ips: HashMap<IpSubnet, KnownIpBan>,
+    /// Peers whose score was updated since it was last saved.
+    updated_scores: HashSet<PeerId>,
 }
 
 impl PeerStore {
@@ -44,7 +51,13 @@ impl PeerStore {
                 peer_states.insert(peer_info.id.clone(), KnownPeerState::new(peer_info.clone()));
             }
         }
-        Ok(PeerStore { store, peer_states })
+        let mut banned_ips = HashMap::default();
+        for (key, value) in store.iter(ColBannedIps) {
+            let subnet: IpSubnet = String::from_utf8(key.into())?.parse()?;
+            let ip_ban = KnownIpBan::try_from_slice(&value)?;
+            banned_ips.insert(subnet, ip_ban);
+        }
+        Ok(PeerStore { store, peer_states, banned_ips, updated_scores: HashSet::default() })
     }
 
     pub fn len(&self) -> usize {
@@ -65,6 +78,7 @@ impl PeerStore {
             .or_insert_with(|| KnownPeerState::new(peer_info.peer_info.clone()));
         entry.last_seen = to_timestamp(Utc::now());
         entry.status = KnownPeerStatus::Connected;
+        entry.score.failed_connections = 0;
         let mut store_update = self.store.store_update();
         store_update.set_ser(ColPeers, &peer_info.peer_info.id.try_to_vec()?, entry)?;
         store_update.commit().map_err(|err| err.into())
@@ -101,6 +115,13 @@ impl PeerStore {
         }
     }
 
+    pub fn is_banned(&self, peer_id: &PeerId) -> bool {
+        self.peer_states.get(peer_id).map_or(false, |peer_state| match peer_state.status {
+            KnownPeerStatus::Banned(_, _) => true,
+            _ => false,
+        })
+    }
+
     pub fn peer_unban(&mut self, peer_id: &PeerId) -> Result<(), Box<dyn std::error::Error>> {
         if let Some(peer_state) = self.peer_states.get_mut(peer_id) {
             peer_state.status = KnownPeerStatus::NotConnected;
@@ -112,43 +133,155 @@ impl PeerStore {
         }
     }
 
+    /// Score of the peer, zero for unknown peers.
+    pub fn score(&self, peer_id: &PeerId) -> i64 {
+        self.peer_states.get(peer_id).map_or(0, |peer_state| peer_state.score.value())
+    }
+
+    /// Update statistics used to compute the score of the peer. Updates are kept in memory
+    /// until `save_scores` is called. Unknown peers are ignored.
+    pub fn update_score<F>(&mut self, peer_id: &PeerId, update: F)
+    where
+        F: FnOnce(&mut PeerScore),
+    {
+        if let Some(peer_state) = self.peer_states.get_mut(peer_id) {
+            update(&mut peer_state.score);
+            self.updated_scores.insert(peer_id.clone());
+        }
+    }
+
+    /// Save all peers with scores updated since the last call in a single store update.
+    pub fn save_scores(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.updated_scores.is_empty() {
+            return Ok(());
+        }
+        let mut store_update = self.store.store_update();
+        for peer_id in self.updated_scores.drain() {
+            if let Some(peer_state) = self.peer_states.get(&peer_id) {
+                store_update.set_ser(ColPeers, &peer_id.try_to_vec()?, peer_state)?;
+            }
+        }
+        store_update.commit().map_err(|err| err.into())
+    }
+
+    /// Ban IP address or subnet. New connections from addresses in this subnet will be dropped.
+    pub fn ip_ban(
+        &mut self,
+        subnet: IpSubnet,
+        ban_reason: ReasonForBan,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let ip_ban = KnownIpBan { reason: ban_reason, banned_at: to_timestamp(Utc::now()) };
+        let mut store_update = self.store.store_update();
+        store_update.set_ser(ColBannedIps, subnet.to_string().as_bytes(), &ip_ban)?;
+        self.banned_ips.insert(subnet, ip_ban);
+        store_update.commit().map_err(|err| err.into())
+    }
+
+    pub fn ip_unban(&mut self, subnet: &IpSubnet) -> Result<(), Box<dyn std::error::Error>> {
+        if self.banned_ips.remove(subnet).is_some() {
+            let mut store_update = self.store.store_update();
+            store_update.delete(ColBannedIps, subnet.to_string().as_bytes());
+            store_update.commit().map_err(|err| err.into())
+        } else {
+            Err(format!("IP subnet {} is not banned", subnet).into())
+        }
+    }
+
+    /// Whether `addr` belongs to some banned subnet.
+    pub fn is_ip_banned(&self, addr: &IpAddr) -> bool {
+        self.banned_ips.keys().any(|subnet| subnet.contains(addr))
+    }
+
+    /// Return iterator over all banned IP addresses and subnets.
+    pub fn banned_ips(&self) -> Iter<IpSubnet, KnownIpBan> {
+        self.banned_ips.iter()
+    }
+
+    /// Ban the peer or IP subnet. Peers that aren't known yet are added to the store, so they are
+    /// rejected once they connect.
+    pub fn ban(
+        &mut self,
+        target: &BanTarget,
+        ban_reason: ReasonForBan,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        match target {
+            BanTarget::Peer(peer_id) => {
+                if !self.peer_states.contains_key(peer_id) {
+                    let peer_info = PeerInfo { id: peer_id.clone(), addr: None, account_id: None };
+                    self.peer_states.insert(peer_id.clone(), KnownPeerState::new(peer_info));
+                }
+                self.peer_ban(peer_id, ban_reason)
+            }
+            BanTarget::Ip(subnet) => self.ip_ban(*subnet, ban_reason),
+        }
+    }
+
+    /// Lift ban of the peer or IP subnet.
+    pub fn unban(&mut self, target: &BanTarget) -> Result<(), Box<dyn std::error::Error>> {
+        match target {
+            BanTarget::Peer(peer_id) if self.is_banned(peer_id) => self.peer_unban(peer_id),
+            BanTarget::Peer(peer_id) => Err(format!("Peer {} is not banned", peer_id).into()),
+            BanTarget::Ip(subnet) => self.ip_unban(subnet),
+        }
+    }
+
+    /// Return all current bans of peers and IP addresses.
+    pub fn bans(&self) -> Vec<BanInfo> {
+        self.peer_states
+            .iter()
+            .filter_map(|(peer_id, peer_state)| match peer_state.status {
+                KnownPeerStatus::Banned(reason, banned_at) => {
+                    Some(BanInfo { target: BanTarget::Peer(peer_id.clone()), reason, banned_at })
+                }
+                _ => None,
+            })
+            .chain(self.banned_ips.iter().map(|(subnet, ip_ban)| BanInfo {
+                target: BanTarget::Ip(*subnet),
+                reason: ip_ban.reason,
+                banned_at: ip_ban.banned_at,
+            }))
+            .collect()
+    }
+
+    fn is_peer_ip_banned(&self, peer_info: &PeerInfo) -> bool {
+        peer_info.addr.map_or(false, |addr| self.is_ip_banned(&addr.ip()))
+    }
+
+    /// Return up to `count` peers matching the filter (all of them if `count` is zero),
+    /// sorted by score from the best. Peers with the same score are returned in random order.
     fn find_peers<F>(&self, mut filter: F, count: u32) -> Vec<PeerInfo>
     where
         F: FnMut(&KnownPeerState) -> bool,
     {
-        let mut peers = self
-            .peer_states
-            .values()
-            .filter_map(|p| if filter(p) { Some(p.peer_info.clone()) } else { None })
-            .collect::<Vec<_>>();
-        if count == 0 {
-            return peers;
-        }
+        let mut peers = self.peer_states.values().filter(|p| filter(p)).collect::<Vec<_>>();
         peers.shuffle(&mut thread_rng());
-        peers.iter().take(count as usize).cloned().collect::<Vec<_>>()
+        peers.sort_by_key(|p| Reverse(p.score.value()));
+        if count > 0 {
+            peers.truncate(count as usize);
+        }
+        peers.into_iter().map(|p| p.peer_info.clone()).collect()
     }
 
-    /// Return unconnected or peers with unknown status that we can try to connect to.
-    /// Peers with unknown addresses are filtered out
+    /// Return unconnected or peers with unknown status that we can try to connect to,
+    /// best scored first. Peers with unknown addresses are filtered out
     pub fn unconnected_peers(&self, ignore_list: &HashSet<PeerId>) -> Vec<PeerInfo> {
         self.find_peers(
             |p| {
                 (p.status == KnownPeerStatus::NotConnected || p.status == KnownPeerStatus::Unknown)
                     && !ignore_list.contains(&p.peer_info.id)
                     && p.peer_info.addr.is_some()
+                    && !self.is_peer_ip_banned(&p.peer_info)
             },
             0,
         )
     }
 
-    /// Return healthy known peers up to given amount.
+    /// Return healthy known peers with the best score up to given amount.
     pub fn healthy_peers(&self, max_count: u32) -> Vec<PeerInfo> {
-        // TODO: better healthy peer definition here.
-        //  Discussion: wdyt about using reachable peers in the current routing table?
         self.find_peers(
             |p| match p.status {
                 KnownPeerStatus::Banned(_, _) => false,
-                _ => true,
+                _ => !self.is_peer_ip_banned(&p.peer_info),
             },
             max_count,
         )
@@ -228,4 +361,80 @@ mod test {
             assert_eq!(peer_store_new.healthy_peers(3).iter().count(), 1);
         }
     }
+
+    #[test]
+    fn ip_ban_store() {
+        let tmp_dir = tempdir::TempDir::new("_test_store_ip_ban").unwrap();
+        let mut peer_info_a = gen_peer_info();
+        peer_info_a.addr = Some("10.0.0.1:24567".parse().unwrap());
+        let mut peer_info_b = gen_peer_info();
+        peer_info_b.addr = Some("10.0.1.1:24567".parse().unwrap());
+        let boot_nodes = vec![peer_info_a.clone(), peer_info_b.clone()];
+        let subnet: IpSubnet = "10.0.0.0/24".parse().unwrap();
+        {
+            let store = create_store(tmp_dir.path().to_str().unwrap());
+            let mut peer_store = PeerStore::new(store, &boot_nodes).unwrap();
+            assert_eq!(peer_store.unconnected_peers(&HashSet::new()).len(), 2);
+            peer_store.ip_ban(subnet, ReasonForBan::Abusive).unwrap();
+            assert!(peer_store.is_ip_banned(&"10.0.0.20".parse().unwrap()));
+            assert_eq!(peer_store.unconnected_peers(&HashSet::new()), vec![peer_info_b.clone()]);
+            assert_eq!(peer_store.healthy_peers(3), vec![peer_info_b.clone()]);
+        }
+        {
+            let store_new = create_store(tmp_dir.path().to_str().unwrap());
+            let mut peer_store_new = PeerStore::new(store_new, &boot_nodes).unwrap();
+            assert!(peer_store_new.is_ip_banned(&"10.0.0.1".parse().unwrap()));
+            assert_eq!(peer_store_new.bans().len(), 1);
+            peer_store_new.ip_unban(&subnet).unwrap();
+            assert!(!peer_store_new.is_ip_banned(&"10.0.0.1".parse().unwrap()));
+            assert_eq!(peer_store_new.healthy_peers(3).len(), 2);
+        }
+    }
+
+    #[test]
+    fn manual_ban_store() {
+        let tmp_dir = tempdir::TempDir::new("_test_store_manual_ban").unwrap();
+        let unknown_peer = gen_peer_info().id;
+        let subnet: IpSubnet = "10.0.0.0/16".parse().unwrap();
+        let store = create_store(tmp_dir.path().to_str().unwrap());
+        let mut peer_store = PeerStore::new(store, &[]).unwrap();
+        peer_store.ban(&BanTarget::Peer(unknown_peer.clone()), ReasonForBan::Manual).unwrap();
+        peer_store.ban(&BanTarget::Ip(subnet), ReasonForBan::Manual).unwrap();
+        assert!(peer_store.is_banned(&unknown_peer));
+        assert!(peer_store.is_ip_banned(&"10.0.200.1".parse().unwrap()));
+        assert_eq!(peer_store.bans().len(), 2);
+        peer_store.unban(&BanTarget::Peer(unknown_peer.clone())).unwrap();
+        assert!(!peer_store.is_banned(&unknown_peer));
+    }
+
+    #[test]
+    fn score_store() {
+        let tmp_dir = tempdir::TempDir::new("_test_store_score").unwrap();
+        let peer_info_a = gen_peer_info();
+        let peer_info_b = gen_peer_info();
+        let peer_info_c = gen_peer_info();
+        let boot_nodes = vec![peer_info_a.clone(), peer_info_b.clone(), peer_info_c.clone()];
+        {
+            let store = create_store(tmp_dir.path().to_str().unwrap());
+            let mut peer_store = PeerStore::new(store, &boot_nodes).unwrap();
+            peer_store.update_score(&peer_info_a.id, |score| {
+                score.delivered_requests = 10;
+                score.uptime_secs = 10 * 3600;
+            });
+            peer_store.update_score(&peer_info_c.id, |score| score.invalid_messages = 1);
+            assert!(peer_store.score(&peer_info_a.id) > 0);
+            assert_eq!(peer_store.score(&peer_info_b.id), 0);
+            assert!(peer_store.score(&peer_info_c.id) < 0);
+            peer_store.save_scores().unwrap();
+        }
+        {
+            let store_new = create_store(tmp_dir.path().to_str().unwrap());
+            let peer_store_new = PeerStore::new(store_new, &boot_nodes).unwrap();
+            assert_eq!(
+                peer_store_new.healthy_peers(3),
+                vec![peer_info_a.clone(), peer_info_b.clone(), peer_info_c.clone()]
+            );
+            assert_eq!(peer_store_new.healthy_peers(1), vec![peer_info_a]);
+        }
+    }
 }
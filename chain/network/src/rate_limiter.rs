@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+use std::time::Instant;
+
+use crate::types::{PeerMessage, RateLimitConfig};
+
+/// Token bucket that is refilled continuously at a fixed rate up to its capacity.
+pub struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(capacity: u64, refill_per_sec: f64) -> Self {
+        TokenBucket {
+            capacity: capacity as f64,
+            tokens: capacity as f64,
+            refill_per_sec,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Try to take `amount` tokens from the bucket.
+    /// Request is allowed while bucket is not empty, and the balance can go below zero, so items
+    /// larger than the capacity of the bucket (i.e. big blocks or state parts) still go through
+    /// but delay following ones.
+    pub fn try_consume(&mut self, amount: u64) -> bool {
+        self.try_consume_at(amount, Instant::now())
+    }
+
+    fn try_consume_at(&mut self, amount: u64, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        self.last_refill = now;
+        self.tokens =
+            (self.tokens + elapsed.as_secs_f64() * self.refill_per_sec).min(self.capacity);
+        if self.tokens > 0.0 {
+            self.tokens -= amount as f64;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Rate limits for a single peer connection: number of messages of each type received per minute,
+/// and number of bytes received and sent per second.
+pub struct PeerRateLimiter {
+    messages: HashMap<String, TokenBucket>,
+    received_bytes: TokenBucket,
+    sent_bytes: TokenBucket,
+}
+
+impl PeerRateLimiter {
+    pub fn new(config: &RateLimitConfig) -> Self {
+        PeerRateLimiter {
+            messages: config
+                .messages_per_min
+                .iter()
+                .map(|(variant, &limit)| {
+                    (variant.clone(), TokenBucket::new(limit, limit as f64 / 60.0))
+                })
+                .collect(),
+            received_bytes: TokenBucket::new(
+                config.max_received_bytes_per_sec,
+                config.max_received_bytes_per_sec as f64,
+            ),
+            sent_bytes: TokenBucket::new(
+                config.max_sent_bytes_per_sec,
+                config.max_sent_bytes_per_sec as f64,
+            ),
+        }
+    }
+
+    /// Whether message of given size can be received from the peer.
+    /// Responses to our own requests are not checked, see `PeerMessage::is_response`.
+    pub fn allow_received_bytes(&mut self, size: usize) -> bool {
+        self.received_bytes.try_consume(size as u64)
+    }
+
+    /// Whether message of given size can be sent to the peer.
+    /// Critical messages are not checked, see `PeerMessage::is_critical`.
+    pub fn allow_sent_bytes(&mut self, size: usize) -> bool {
+        self.sent_bytes.try_consume(size as u64)
+    }
+
+    /// Whether message of given type can be received from the peer.
+    /// Messages without configured limit are always allowed.
+    pub fn allow_received_message(&mut self, msg: &PeerMessage) -> bool {
+        self.messages.get_mut(msg.msg_variant()).map_or(true, |bucket| bucket.try_consume(1))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use super::{PeerRateLimiter, TokenBucket};
+    use crate::types::{PeerMessage, RateLimitConfig};
+
+    #[test]
+    fn token_bucket() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(3, 1.0);
+        bucket.last_refill = now;
+        assert!(bucket.try_consume_at(1, now));
+        assert!(bucket.try_consume_at(1, now));
+        assert!(bucket.try_consume_at(1, now));
+        assert!(!bucket.try_consume_at(1, now));
+
+        // Refilled with one token after a second.
+        let now = now + Duration::from_secs(1);
+        assert!(bucket.try_consume_at(1, now));
+        assert!(!bucket.try_consume_at(1, now));
+
+        // Refill is capped by the capacity, but oversized request is allowed when not empty.
+        let now = now + Duration::from_secs(100);
+        assert!(bucket.try_consume_at(10, now));
+        assert!(!bucket.try_consume_at(1, now + Duration::from_secs(6)));
+        assert!(bucket.try_consume_at(1, now + Duration::from_secs(8)));
+    }
+
+    #[test]
+    fn peer_rate_limiter_bytes() {
+        let config = RateLimitConfig {
+            max_received_bytes_per_sec: 10,
+            max_sent_bytes_per_sec: 20,
+            ..Default::default()
+        };
+        let mut limiter = PeerRateLimiter::new(&config);
+        assert!(limiter.allow_received_bytes(11));
+        assert!(!limiter.allow_received_bytes(1));
+        assert!(limiter.allow_sent_bytes(21));
+        assert!(!limiter.allow_sent_bytes(1));
+
+        // Responses and critical messages are never checked against the quotas.
+        assert!(PeerMessage::PeersResponse(vec![]).is_response());
+        assert!(!PeerMessage::PeersRequest.is_response());
+        assert!(PeerMessage::Disconnect.is_critical());
+        assert!(!PeerMessage::PeersResponse(vec![]).is_critical());
+    }
+}
@@ -1,4 +1,5 @@
 use std::collections::{HashMap, HashSet};
+use std::io;
 use std::ops::Sub;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -16,7 +17,7 @@ use near_crypto::{SecretKey, Signature};
 use near_metrics;
 use near_primitives::hash::{hash, CryptoHash};
 use near_primitives::network::{AnnounceAccount, PeerId};
-use near_primitives::types::AccountId;
+use near_primitives::types::{AccountId, EpochId};
 use near_primitives::utils::index_to_bytes;
 use near_store::{
     ColAccountAnnouncements, ColComponentEdges, ColPeerComponent, LastComponentNonce, Store,
@@ -27,6 +28,11 @@ use crate::metrics;
 use crate::types::{PeerIdOrHash, Ping, Pong};
 
 const ANNOUNCE_ACCOUNT_CACHE_SIZE: usize = 10_000;
+/// Maximum number of peer ids that will be remembered for the same account id.
+const MAX_PEERS_PER_ACCOUNT: usize = 4;
+/// Routes to a peer which are at most this number of hops longer than the shortest route
+/// are considered as fallback when every next hop in the shortest routes fail.
+const FALLBACK_ROUTE_MAX_EXTRA_HOPS: usize = 1;
 const ROUTE_BACK_CACHE_SIZE: usize = 10_000;
 const ROUND_ROBIN_MAX_NONCE_DIFFERENCE_ALLOWED: usize = 10;
 const ROUND_ROBIN_NONCE_CACHE_SIZE: usize = 10_000;
@@ -241,8 +247,9 @@ impl Edge {
 }
 
 pub struct RoutingTable {
-    /// PeerId associated for every known account id.
-    account_peers: SizedCache<AccountId, AnnounceAccount>,
+    /// Announcements for every known account id. There is one announcement per peer id
+    /// owned by the account, most recent first.
+    account_peers: SizedCache<AccountId, Vec<AnnounceAccount>>,
     /// Active PeerId that are part of the shortest path to each PeerId.
     pub peer_forwarding: HashMap<PeerId, HashSet<PeerId>>,
     /// Store last update for known edges.
@@ -360,34 +367,93 @@ impl RoutingTable {
         }
     }
 
+    /// Find fallback next hops to reach `peer_id`, excluding every peer in `excluded`.
+    /// Next hops in the shortest routes come first, followed by next hops in routes which are
+    /// at most `FALLBACK_ROUTE_MAX_EXTRA_HOPS` longer, sorted by length.
+    pub fn find_fallback_routes(
+        &self,
+        peer_id: &PeerId,
+        excluded: &HashSet<PeerId>,
+    ) -> Vec<PeerId> {
+        self.raw_graph
+            .calculate_next_hops(peer_id, FALLBACK_ROUTE_MAX_EXTRA_HOPS)
+            .into_iter()
+            .filter(|next_hop| !excluded.contains(next_hop))
+            .collect()
+    }
+
     /// Find peer that owns this AccountId.
+    /// If the account was announced by several peers, a reachable one is preferred.
     pub fn account_owner(&mut self, account_id: &AccountId) -> Result<PeerId, FindRouteError> {
-        self.get_announce(account_id)
-            .map(|announce_account| announce_account.peer_id.clone())
-            .ok_or_else(|| FindRouteError::AccountNotFound)
+        self.account_owners(account_id).map(|mut owners| owners.remove(0))
+    }
+
+    /// Find all peers that own this AccountId. Reachable peers come first, otherwise peers
+    /// are sorted from the most recent announcement to the oldest one.
+    pub fn account_owners(
+        &mut self,
+        account_id: &AccountId,
+    ) -> Result<Vec<PeerId>, FindRouteError> {
+        let announcements = self.get_announces(account_id);
+        if announcements.is_empty() {
+            return Err(FindRouteError::AccountNotFound);
+        }
+
+        let (mut reachable, unreachable): (Vec<_>, Vec<_>) =
+            announcements.into_iter().map(|announce_account| announce_account.peer_id).partition(
+                |peer_id| peer_id == self.peer_id() || self.peer_forwarding.contains_key(peer_id),
+            );
+        reachable.extend(unreachable);
+        Ok(reachable)
     }
 
     /// Add (account id, peer id) to routing table.
-    /// Note: There is at most on peer id per account id.
+    /// Note: There are at most `MAX_PEERS_PER_ACCOUNT` peer ids per account id. If the account
+    /// was already announced by the same peer id, the previous announcement is replaced.
     pub fn add_account(&mut self, announce_account: AnnounceAccount) {
         let account_id = announce_account.account_id.clone();
-        self.account_peers.cache_set(account_id.clone(), announce_account.clone());
+        let mut announcements = self.get_announces(&account_id);
+        announcements.retain(|current| current.peer_id != announce_account.peer_id);
+        announcements.insert(0, announce_account);
+        announcements.truncate(MAX_PEERS_PER_ACCOUNT);
+        self.set_announces(account_id, announcements);
+    }
+
+    /// Remove the announcements of this account id made for any of the given epochs.
+    /// Used to drop announcements from epochs older than the newest one seen for the account.
+    pub fn remove_announces_of_epochs(&mut self, account_id: &AccountId, epoch_ids: &[EpochId]) {
+        if epoch_ids.is_empty() {
+            return;
+        }
+        let mut announcements = self.get_announces(account_id);
+        let total = announcements.len();
+        announcements.retain(|announce_account| !epoch_ids.contains(&announce_account.epoch_id));
+        if announcements.len() != total {
+            self.set_announces(account_id.clone(), announcements);
+        }
+    }
+
+    fn set_announces(&mut self, account_id: AccountId, announcements: Vec<AnnounceAccount>) {
+        self.account_peers.cache_set(account_id.clone(), announcements.clone());
 
         // Add account to store
         let mut update = self.store.store_update();
         if let Err(e) = update
-            .set_ser(ColAccountAnnouncements, account_id.as_bytes(), &announce_account)
+            .set_ser(ColAccountAnnouncements, account_id.as_bytes(), &announcements)
             .and_then(|_| update.commit())
         {
             warn!(target: "network", "Error saving announce account to store: {:?}", e);
         }
     }
 
-    // TODO(MarX, #1694): Allow one account id to be routed to several peer id.
+    /// Check if this exact announcement (same account id, peer id and epoch id) is known.
     pub fn contains_account(&mut self, announce_account: &AnnounceAccount) -> bool {
-        self.get_announce(&announce_account.account_id).map_or(false, |current_announce_account| {
-            current_announce_account.epoch_id == announce_account.epoch_id
-        })
+        self.get_announce_from_peer(&announce_account.account_id, &announce_account.peer_id).map_or(
+            false,
+            |current_announce_account| {
+                current_announce_account.epoch_id == announce_account.epoch_id
+            },
+        )
     }
 
     /// Get the nonce of the component where the peer was stored
@@ -572,11 +638,13 @@ impl RoutingTable {
     }
 
     pub fn info(&mut self) -> RoutingTableInfo {
-        let account_peers = self
-            .get_announce_accounts()
-            .into_iter()
-            .map(|announce_account| (announce_account.account_id, announce_account.peer_id))
-            .collect();
+        let mut account_peers = HashMap::new();
+        for announce_account in self.get_announce_accounts() {
+            account_peers
+                .entry(announce_account.account_id)
+                .or_insert_with(Vec::new)
+                .push(announce_account.peer_id);
+        }
         RoutingTableInfo { account_peers, peer_forwarding: self.peer_forwarding.clone() }
     }
 
@@ -672,32 +740,59 @@ impl RoutingTable {
 
     /// Get announce accounts on cache.
     pub fn get_announce_accounts(&mut self) -> Vec<AnnounceAccount> {
-        self.account_peers.value_order().cloned().collect()
+        self.account_peers.value_order().flatten().cloned().collect()
     }
 
-    /// Get account announce from
+    /// Get most recent account announce.
     pub fn get_announce(&mut self, account_id: &AccountId) -> Option<AnnounceAccount> {
-        if let Some(announce_account) = self.account_peers.cache_get(&account_id) {
-            Some(announce_account.clone())
+        self.get_announces(account_id).into_iter().next()
+    }
+
+    /// Get account announce done by `peer_id`.
+    pub fn get_announce_from_peer(
+        &mut self,
+        account_id: &AccountId,
+        peer_id: &PeerId,
+    ) -> Option<AnnounceAccount> {
+        self.get_announces(account_id)
+            .into_iter()
+            .find(|announce_account| &announce_account.peer_id == peer_id)
+    }
+
+    /// Get all account announces, most recent first.
+    pub fn get_announces(&mut self, account_id: &AccountId) -> Vec<AnnounceAccount> {
+        if let Some(announcements) = self.account_peers.cache_get(&account_id) {
+            announcements.clone()
         } else {
             self.store
-                .get_ser(ColAccountAnnouncements, account_id.as_bytes())
-                .and_then(|res: Option<AnnounceAccount>| {
-                    if let Some(announce_account) = res {
-                        self.add_account(announce_account.clone());
-                        Ok(Some(announce_account))
+                .get(ColAccountAnnouncements, account_id.as_bytes())
+                .and_then(|res| res.map(|bytes| decode_announcements(&bytes)).transpose())
+                .map(|res| {
+                    if let Some(announcements) = res {
+                        self.account_peers.cache_set(account_id.clone(), announcements.clone());
+                        announcements
                     } else {
-                        Ok(None)
+                        vec![]
                     }
                 })
                 .unwrap_or_else(|e| {
                     warn!(target: "network", "Error loading announce account from store: {:?}", e);
-                    None
+                    vec![]
                 })
         }
     }
 }
 
+/// Decodes the announcements of an account stored in `ColAccountAnnouncements`. Before several
+/// peer ids per account were supported, a single `AnnounceAccount` was stored instead.
+fn decode_announcements(bytes: &[u8]) -> Result<Vec<AnnounceAccount>, io::Error> {
+    Vec::<AnnounceAccount>::try_from_slice(bytes).or_else(|err| {
+        AnnounceAccount::try_from_slice(bytes)
+            .map(|announce_account| vec![announce_account])
+            .map_err(|_| err)
+    })
+}
+
 pub struct ProcessEdgeResult {
     pub new_edge: bool,
     pub schedule_computation: Option<Duration>,
@@ -705,7 +800,7 @@ pub struct ProcessEdgeResult {
 
 #[derive(Debug)]
 pub struct RoutingTableInfo {
-    pub account_peers: HashMap<AccountId, PeerId>,
+    pub account_peers: HashMap<AccountId, Vec<PeerId>>,
     pub peer_forwarding: HashMap<PeerId, HashSet<PeerId>>,
 }
 
@@ -807,13 +902,82 @@ impl Graph {
 
         routes.into_iter().filter(|(_, hops)| !hops.is_empty()).collect()
     }
+
+    /// Compute neighbors of `source` which belong to some path from `source` to `target` that is
+    /// at most `max_extra_hops` longer than the shortest path. Paths going through `source`
+    /// are not considered. Neighbors are sorted by the length of the path.
+    pub fn calculate_next_hops(&self, target: &PeerId, max_extra_hops: usize) -> Vec<PeerId> {
+        let neighbors = match self.adjacency.get(&self.source) {
+            Some(neighbors) => neighbors,
+            None => return vec![],
+        };
+
+        // Distance from `target` to every node, without going through `source`.
+        let mut queue = vec![target];
+        let mut distance = HashMap::new();
+        distance.insert(target, 0usize);
+
+        let mut head = 0;
+
+        while head < queue.len() {
+            let cur_peer = queue[head];
+            let cur_distance = *distance.get(cur_peer).unwrap();
+            head += 1;
+
+            if let Some(adjacent) = self.adjacency.get(&cur_peer) {
+                for neighbor in adjacent {
+                    if neighbor != &self.source && !distance.contains_key(&neighbor) {
+                        queue.push(neighbor);
+                        distance.insert(neighbor, cur_distance + 1);
+                    }
+                }
+            }
+        }
+
+        let mut next_hops: Vec<_> = neighbors
+            .iter()
+            .filter_map(|neighbor| distance.get(neighbor).map(|d| (*d, neighbor.clone())))
+            .collect();
+        next_hops.sort();
+
+        let max_distance = match next_hops.first() {
+            Some((min_distance, _)) => min_distance + max_extra_hops,
+            None => return vec![],
+        };
+
+        next_hops
+            .into_iter()
+            .take_while(|(d, _)| *d <= max_distance)
+            .map(|(_, next_hop)| next_hop)
+            .collect()
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::routing::Graph;
+    use borsh::BorshSerialize;
+
+    use near_crypto::Signature;
+    use near_primitives::network::AnnounceAccount;
+    use near_primitives::types::EpochId;
+
+    use crate::routing::{decode_announcements, Graph};
     use crate::test_utils::{expected_routing_tables, random_peer_id};
 
+    #[test]
+    fn decode_legacy_announcement() {
+        let announce_account = AnnounceAccount {
+            account_id: "test".to_string(),
+            peer_id: random_peer_id(),
+            epoch_id: EpochId::default(),
+            signature: Signature::default(),
+        };
+        let legacy = announce_account.try_to_vec().unwrap();
+        assert_eq!(decode_announcements(&legacy).unwrap(), vec![announce_account.clone()]);
+        let current = vec![announce_account.clone(), announce_account].try_to_vec().unwrap();
+        assert_eq!(decode_announcements(&current).unwrap().len(), 2);
+    }
+
     #[test]
     fn graph_contains_edge() {
         let source = random_peer_id();
@@ -956,4 +1120,35 @@ mod test {
 
         assert!(expected_routing_tables(graph.calculate_distance(), next_hops));
     }
+
+    /// Test the following graph
+    ///     0 - 3
+    ///   /     |
+    /// s - 1 - 4 - 5
+    ///   \
+    ///     2
+    ///
+    /// Shortest path to 5 goes through 1, and path through 0 is one hop longer.
+    #[test]
+    fn graph_next_hops() {
+        let source = random_peer_id();
+        let nodes: Vec<_> = (0..6).map(|_| random_peer_id()).collect();
+
+        let mut graph = Graph::new(source.clone());
+
+        for i in 0..3 {
+            graph.add_edge(source.clone(), nodes[i].clone());
+        }
+        graph.add_edge(nodes[0].clone(), nodes[3].clone());
+        graph.add_edge(nodes[3].clone(), nodes[4].clone());
+        graph.add_edge(nodes[1].clone(), nodes[4].clone());
+        graph.add_edge(nodes[4].clone(), nodes[5].clone());
+
+        assert_eq!(graph.calculate_next_hops(&nodes[5], 0), vec![nodes[1].clone()]);
+        assert_eq!(
+            graph.calculate_next_hops(&nodes[5], 1),
+            vec![nodes[1].clone(), nodes[0].clone()]
+        );
+        assert_eq!(graph.calculate_next_hops(&nodes[2], 1), vec![nodes[2].clone()]);
+    }
 }
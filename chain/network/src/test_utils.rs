@@ -14,7 +14,7 @@ use near_primitives::network::PeerId;
 use near_primitives::types::EpochId;
 use near_primitives::utils::index_to_bytes;
 
-use crate::types::{NetworkConfig, NetworkInfo, PeerInfo, ROUTED_MESSAGE_TTL};
+use crate::types::{NetworkConfig, NetworkInfo, PeerInfo, RateLimitConfig, ROUTED_MESSAGE_TTL};
 use crate::PeerManagerActor;
 
 /// Returns available port.
@@ -52,6 +52,11 @@ impl NetworkConfig {
             push_info_period: Duration::from_millis(100),
             blacklist: HashMap::new(),
             outbound_disabled: false,
+            sentry_nodes: vec![],
+            hidden_peers: vec![],
+            max_inbound_per_ip: u32::max_value(),
+            max_inbound_per_subnet: u32::max_value(),
+            rate_limits: RateLimitConfig::default(),
         }
     }
 }
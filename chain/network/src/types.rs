@@ -14,7 +14,7 @@ use futures::{future::BoxFuture, FutureExt};
 use serde_derive::{Deserialize, Serialize};
 use tokio::net::TcpStream;
 
-use near_chain::types::ShardStateSyncResponse;
+use near_chain::types::{ReceiptProofResponse, ShardStateSyncResponse};
 use near_chain::{Block, BlockHeader};
 use near_chain_configs::PROTOCOL_VERSION;
 use near_crypto::{PublicKey, SecretKey, Signature};
@@ -28,7 +28,10 @@ use near_primitives::sharding::{ChunkHash, PartialEncodedChunk};
 use near_primitives::transaction::{ExecutionOutcomeWithIdAndProof, SignedTransaction};
 use near_primitives::types::{AccountId, BlockHeight, EpochId, MaybeBlockId, ShardId};
 use near_primitives::utils::{from_timestamp, to_timestamp};
-use near_primitives::views::{FinalExecutionOutcomeView, Finality, QueryRequest, QueryResponse};
+use near_primitives::views::{
+    BanInfoView, BanTargetView, FinalExecutionOutcomeView, Finality, KnownProducerView,
+    PeerInfoView, QueryRequest, QueryResponse,
+};
 
 use crate::metrics;
 use crate::peer::Peer;
@@ -112,6 +115,12 @@ impl TryFrom<&str> for PeerInfo {
     }
 }
 
+impl From<PeerInfo> for PeerInfoView {
+    fn from(peer_info: PeerInfo) -> Self {
+        Self { id: peer_info.id, addr: peer_info.addr, account_id: peer_info.account_id }
+    }
+}
+
 /// Peer chain information.
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Clone, Debug, Eq, PartialEq, Default)]
 pub struct PeerChainInfo {
@@ -231,6 +240,10 @@ pub enum RoutedMessageBody {
     /// Ping/Pong used for testing networking and routing.
     Ping(Ping),
     Pong(Pong),
+    /// Request for a receipt received by the shard.
+    ReceiptRequest(CryptoHash, ShardId),
+    /// The receipt with the given id, with the proof of its inclusion in the block it was received in.
+    ReceiptResponse(CryptoHash, ReceiptProofResponse),
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Serialize, PartialEq, Eq, Clone, Debug)]
@@ -335,7 +348,8 @@ impl RoutedMessage {
             | RoutedMessageBody::StateRequestPart(_, _, _)
             | RoutedMessageBody::PartialEncodedChunkRequest(_)
             | RoutedMessageBody::QueryRequest { .. }
-            | RoutedMessageBody::ReceiptOutcomeRequest(_) => true,
+            | RoutedMessageBody::ReceiptOutcomeRequest(_)
+            | RoutedMessageBody::ReceiptRequest(_, _) => true,
             _ => false,
         }
     }
@@ -450,6 +464,8 @@ impl fmt::Display for PeerMessage {
                 RoutedMessageBody::PartialEncodedChunk(_) => f.write_str("PartialEncodedChunk"),
                 RoutedMessageBody::Ping(_) => f.write_str("Ping"),
                 RoutedMessageBody::Pong(_) => f.write_str("Pong"),
+                RoutedMessageBody::ReceiptRequest(_, _) => f.write_str("Receipt request"),
+                RoutedMessageBody::ReceiptResponse(_, _) => f.write_str("Receipt response"),
             },
             PeerMessage::Disconnect => f.write_str("Disconnect"),
             PeerMessage::Challenge(_) => f.write_str("Challenge"),
@@ -458,6 +474,46 @@ impl fmt::Display for PeerMessage {
 }
 
 impl PeerMessage {
+    /// Name of the message variant. For routed messages it is the name of the routed body variant.
+    pub fn msg_variant(&self) -> &'static str {
+        match self {
+            PeerMessage::Handshake(_) => "Handshake",
+            PeerMessage::HandshakeFailure(_, _) => "HandshakeFailure",
+            PeerMessage::LastEdge(_) => "LastEdge",
+            PeerMessage::Sync(_) => "Sync",
+            PeerMessage::RequestUpdateNonce(_) => "RequestUpdateNonce",
+            PeerMessage::ResponseUpdateNonce(_) => "ResponseUpdateNonce",
+            PeerMessage::PeersRequest => "PeersRequest",
+            PeerMessage::PeersResponse(_) => "PeersResponse",
+            PeerMessage::BlockHeadersRequest(_) => "BlockHeadersRequest",
+            PeerMessage::BlockHeaders(_) => "BlockHeaders",
+            PeerMessage::BlockRequest(_) => "BlockRequest",
+            PeerMessage::Block(_) => "Block",
+            PeerMessage::Transaction(_) => "Transaction",
+            PeerMessage::Routed(routed_message) => match routed_message.body {
+                RoutedMessageBody::BlockApproval(_) => "BlockApproval",
+                RoutedMessageBody::ForwardTx(_) => "ForwardTx",
+                RoutedMessageBody::TxStatusRequest(_, _) => "TxStatusRequest",
+                RoutedMessageBody::TxStatusResponse(_) => "TxStatusResponse",
+                RoutedMessageBody::QueryRequest { .. } => "QueryRequest",
+                RoutedMessageBody::QueryResponse { .. } => "QueryResponse",
+                RoutedMessageBody::ReceiptOutcomeRequest(_) => "ReceiptOutcomeRequest",
+                RoutedMessageBody::ReceiptOutComeResponse(_) => "ReceiptOutComeResponse",
+                RoutedMessageBody::StateRequestHeader(_, _) => "StateRequestHeader",
+                RoutedMessageBody::StateRequestPart(_, _, _) => "StateRequestPart",
+                RoutedMessageBody::StateResponse(_) => "StateResponse",
+                RoutedMessageBody::PartialEncodedChunkRequest(_) => "PartialEncodedChunkRequest",
+                RoutedMessageBody::PartialEncodedChunk(_) => "PartialEncodedChunk",
+                RoutedMessageBody::Ping(_) => "Ping",
+                RoutedMessageBody::Pong(_) => "Pong",
+                RoutedMessageBody::ReceiptRequest(_, _) => "ReceiptRequest",
+                RoutedMessageBody::ReceiptResponse(_, _) => "ReceiptResponse",
+            },
+            PeerMessage::Disconnect => "Disconnect",
+            PeerMessage::Challenge(_) => "Challenge",
+        }
+    }
+
     pub fn record(&self, size: usize) {
         match self {
             PeerMessage::Handshake(_) => {
@@ -630,6 +686,20 @@ impl PeerMessage {
                     near_metrics::inc_counter(&metrics::ROUTED_PONG_RECEIVED_TOTAL);
                     near_metrics::inc_counter_by(&metrics::ROUTED_PONG_RECEIVED_BYTES, size as i64);
                 }
+                RoutedMessageBody::ReceiptRequest(_, _) => {
+                    near_metrics::inc_counter(&metrics::ROUTED_RECEIPT_REQUEST_RECEIVED_TOTAL);
+                    near_metrics::inc_counter_by(
+                        &metrics::ROUTED_RECEIPT_REQUEST_RECEIVED_BYTES,
+                        size as i64,
+                    );
+                }
+                RoutedMessageBody::ReceiptResponse(_, _) => {
+                    near_metrics::inc_counter(&metrics::ROUTED_RECEIPT_RESPONSE_RECEIVED_TOTAL);
+                    near_metrics::inc_counter_by(
+                        &metrics::ROUTED_RECEIPT_RESPONSE_RECEIVED_BYTES,
+                        size as i64,
+                    );
+                }
             },
             PeerMessage::Disconnect => {
                 near_metrics::inc_counter(&metrics::DISCONNECT_RECEIVED_TOTAL);
@@ -642,6 +712,54 @@ impl PeerMessage {
         }
     }
 
+    /// Whether the message is needed for the connection or the consensus to make progress
+    /// (i.e. handshakes, blocks, approvals or chunks), so it is sent to the peer even when
+    /// the peer is over its quota of sent bytes.
+    pub fn is_critical(&self) -> bool {
+        match self {
+            PeerMessage::Handshake(_)
+            | PeerMessage::HandshakeFailure(_, _)
+            | PeerMessage::LastEdge(_)
+            | PeerMessage::Sync(_)
+            | PeerMessage::RequestUpdateNonce(_)
+            | PeerMessage::ResponseUpdateNonce(_)
+            | PeerMessage::Block(_)
+            | PeerMessage::Challenge(_)
+            | PeerMessage::Disconnect => true,
+            PeerMessage::Routed(r) => match r.body {
+                RoutedMessageBody::BlockApproval(_)
+                | RoutedMessageBody::PartialEncodedChunk(_)
+                | RoutedMessageBody::Ping(_)
+                | RoutedMessageBody::Pong(_) => true,
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Whether the message answers a request sent by this node (i.e. blocks, headers, chunks or
+    /// state parts). Such messages are not counted against the received bytes quota of the peer,
+    /// so a peer can't be banned for serving large responses we asked for.
+    pub fn is_response(&self) -> bool {
+        match self {
+            PeerMessage::PeersResponse(_)
+            | PeerMessage::BlockHeaders(_)
+            | PeerMessage::Block(_)
+            | PeerMessage::ResponseUpdateNonce(_) => true,
+            PeerMessage::Routed(r) => match r.body {
+                RoutedMessageBody::TxStatusResponse(_)
+                | RoutedMessageBody::QueryResponse { .. }
+                | RoutedMessageBody::ReceiptOutComeResponse(_)
+                | RoutedMessageBody::ReceiptResponse(_, _)
+                | RoutedMessageBody::StateResponse(_)
+                | RoutedMessageBody::PartialEncodedChunk(_)
+                | RoutedMessageBody::Pong(_) => true,
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
     pub fn is_client_message(&self) -> bool {
         match self {
             PeerMessage::Block(_)
@@ -669,6 +787,8 @@ impl PeerMessage {
                 | RoutedMessageBody::TxStatusResponse(_)
                 | RoutedMessageBody::ReceiptOutcomeRequest(_)
                 | RoutedMessageBody::ReceiptOutComeResponse(_)
+                | RoutedMessageBody::ReceiptRequest(_, _)
+                | RoutedMessageBody::ReceiptResponse(_, _)
                 | RoutedMessageBody::StateRequestHeader(_, _)
                 | RoutedMessageBody::StateRequestPart(_, _, _) => true,
                 _ => false,
@@ -728,6 +848,65 @@ pub struct NetworkConfig {
     /// are satisfied.
     /// This flag should be ALWAYS FALSE. Only set to true for testing purposes.
     pub outbound_disabled: bool,
+    /// Sentry nodes this node is hidden behind. If not empty, this node will only establish or
+    /// accept connections to/from these peers, and it will not learn about other peers.
+    pub sentry_nodes: Vec<PeerInfo>,
+    /// Peers hidden behind this node (i.e. validators using this node as a sentry).
+    /// Their address is never shared with other peers, and connections from them are accepted
+    /// even if this node is at max capacity, as long as their address is known.
+    pub hidden_peers: Vec<PeerInfo>,
+    /// Maximum number of inbound connections accepted from the same IP address.
+    pub max_inbound_per_ip: u32,
+    /// Maximum number of inbound connections accepted from the same subnet
+    /// (/24 for IPv4 and /64 for IPv6 addresses).
+    pub max_inbound_per_subnet: u32,
+    /// Limits on messages and traffic exchanged with each peer.
+    pub rate_limits: RateLimitConfig,
+}
+
+/// Per peer limits on received messages and exchanged traffic.
+/// Messages exceeding the limits are dropped, and peers whose messages are dropped too often
+/// are banned as abusive.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    /// Maximum number of messages received per minute by message variant
+    /// (i.e. `BlockHeadersRequest` or `StateRequestPart`). Variants not listed are not limited.
+    pub messages_per_min: HashMap<String, u64>,
+    /// Maximum number of bytes per second received from a peer. Responses to requests sent by
+    /// this node are not counted.
+    pub max_received_bytes_per_sec: u64,
+    /// Maximum number of bytes per second sent to a peer. Messages over this limit are dropped,
+    /// except the ones needed for the connection or the consensus to make progress.
+    pub max_sent_bytes_per_sec: u64,
+    /// Maximum number of messages dropped per minute before the peer is banned.
+    pub max_throttled_per_min: u64,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        let messages_per_min = [
+            ("PeersRequest", 60),
+            ("BlockHeadersRequest", 1_200),
+            ("BlockRequest", 12_000),
+            ("TxStatusRequest", 6_000),
+            ("QueryRequest", 6_000),
+            ("ReceiptOutcomeRequest", 6_000),
+            ("ReceiptRequest", 6_000),
+            ("StateRequestHeader", 600),
+            ("StateRequestPart", 6_000),
+            ("PartialEncodedChunkRequest", 12_000),
+        ]
+        .iter()
+        .map(|(variant, limit)| (variant.to_string(), *limit))
+        .collect();
+        RateLimitConfig {
+            messages_per_min,
+            max_received_bytes_per_sec: 50_000_000,
+            max_sent_bytes_per_sec: 50_000_000,
+            max_throttled_per_min: 600,
+        }
+    }
 }
 
 /// Used to match a socket addr by IP:Port or only by IP
@@ -758,6 +937,164 @@ impl FromStr for PatternAddr {
     }
 }
 
+/// Range of IP addresses sharing the first `prefix_len` bits.
+/// A subnet with full prefix length contains a single address.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct IpSubnet {
+    addr: IpAddr,
+    prefix_len: u8,
+}
+
+impl IpSubnet {
+    /// Build subnet with first `prefix_len` bits of `addr`. Remaining bits are cleared.
+    /// Fails if `prefix_len` is longer than the address (32 bits for IPv4, 128 for IPv6).
+    pub fn new(addr: IpAddr, prefix_len: u8) -> Result<Self, Box<dyn std::error::Error>> {
+        let max_prefix_len = match addr {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        if prefix_len > max_prefix_len {
+            return Err(format!(
+                "Prefix length {} is longer than the {} bits of {}",
+                prefix_len, max_prefix_len, addr
+            )
+            .into());
+        }
+        Ok(Self::masked(addr, prefix_len))
+    }
+
+    /// Build subnet from a prefix length known to be valid for the address.
+    fn masked(addr: IpAddr, prefix_len: u8) -> Self {
+        match addr {
+            IpAddr::V4(addr) => {
+                let mask = u32::max_value().checked_shl(32 - prefix_len as u32).unwrap_or(0);
+                Self { addr: IpAddr::V4((u32::from(addr) & mask).into()), prefix_len }
+            }
+            IpAddr::V6(addr) => {
+                let mask = u128::max_value().checked_shl(128 - prefix_len as u32).unwrap_or(0);
+                Self { addr: IpAddr::V6((u128::from(addr) & mask).into()), prefix_len }
+            }
+        }
+    }
+
+    /// Subnet containing only `addr`.
+    pub fn from_addr(addr: IpAddr) -> Self {
+        match addr {
+            IpAddr::V4(_) => Self::masked(addr, 32),
+            IpAddr::V6(_) => Self::masked(addr, 128),
+        }
+    }
+
+    /// Subnet used to limit inbound connections: /24 for IPv4 and /64 for IPv6.
+    pub fn from_addr_network(addr: IpAddr) -> Self {
+        match addr {
+            IpAddr::V4(_) => Self::masked(addr, 24),
+            IpAddr::V6(_) => Self::masked(addr, 64),
+        }
+    }
+
+    pub fn contains(&self, addr: &IpAddr) -> bool {
+        match (self.addr, addr) {
+            (IpAddr::V4(_), IpAddr::V4(_)) | (IpAddr::V6(_), IpAddr::V6(_)) => {
+                Self::masked(*addr, self.prefix_len) == *self
+            }
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for IpSubnet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix_len)
+    }
+}
+
+impl FromStr for IpSubnet {
+    type Err = Box<dyn std::error::Error>;
+
+    /// Parse subnet as `IP/prefix_len` or single address as `IP`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(2, '/');
+        let addr = parts.next().unwrap_or_default().parse::<IpAddr>()?;
+        match parts.next() {
+            Some(prefix_len) => Self::new(addr, prefix_len.parse()?),
+            None => Ok(Self::from_addr(addr)),
+        }
+    }
+}
+
+impl serde::Serialize for IpSubnet {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for IpSubnet {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = <String as serde::Deserialize>::deserialize(deserializer)?;
+        s.parse().map_err(|err| serde::de::Error::custom(format!("{}", err)))
+    }
+}
+
+/// Ban of an IP address or subnet, stored on disk.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct KnownIpBan {
+    pub reason: ReasonForBan,
+    pub banned_at: u64,
+}
+
+/// Peer or range of IP addresses that can be banned.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub enum BanTarget {
+    Peer(PeerId),
+    Ip(IpSubnet),
+}
+
+impl FromStr for BanTarget {
+    type Err = Box<dyn std::error::Error>;
+
+    /// Parse either an IP address/subnet or a peer id.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(subnet) = s.parse::<IpSubnet>() {
+            return Ok(BanTarget::Ip(subnet));
+        }
+        Ok(BanTarget::Peer(PublicKey::try_from(s)?.into()))
+    }
+}
+
+impl fmt::Display for BanTarget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BanTarget::Peer(peer_id) => write!(f, "{}", peer_id),
+            BanTarget::Ip(subnet) => write!(f, "{}", subnet),
+        }
+    }
+}
+
+/// Information about current ban.
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct BanInfo {
+    pub target: BanTarget,
+    pub reason: ReasonForBan,
+    pub banned_at: u64,
+}
+
+impl From<BanInfo> for BanInfoView {
+    fn from(ban_info: BanInfo) -> Self {
+        let target = match ban_info.target {
+            BanTarget::Peer(peer_id) => BanTargetView::Peer(peer_id),
+            BanTarget::Ip(subnet) => BanTargetView::Ip(subnet.to_string()),
+        };
+        Self { target, reason: format!("{:?}", ban_info.reason), banned_at: ban_info.banned_at }
+    }
+}
+
 /// Status of the known peers.
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Eq, PartialEq, Debug)]
 pub enum KnownPeerStatus {
@@ -767,6 +1104,65 @@ pub enum KnownPeerStatus {
     Banned(ReasonForBan, u64),
 }
 
+/// Maximum penalty for latency in score points, given at one point per `LATENCY_MS_PER_POINT`.
+const MAX_LATENCY_PENALTY: u64 = 100;
+const LATENCY_MS_PER_POINT: u64 = 10;
+/// Maximum bonus for uptime in score points, given at one point per `UPTIME_SECS_PER_POINT`.
+const MAX_UPTIME_BONUS: u64 = 100;
+const UPTIME_SECS_PER_POINT: u64 = 3600;
+/// Penalty in score points for each invalid message received from the peer.
+const INVALID_MESSAGE_PENALTY: i64 = 50;
+/// Penalty in score points for each consecutive failed attempt to connect to the peer.
+const FAILED_CONNECTION_PENALTY: i64 = 10;
+/// Maximum number of events accounted in the score for each penalty.
+const MAX_PENALIZED_EVENTS: u64 = 1000;
+
+/// Statistics about past interactions with the peer, used to rank peers.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Clone, Debug, Default, Eq, PartialEq)]
+pub struct PeerScore {
+    /// Moving average of ping round trip time in milliseconds. Zero if unknown.
+    pub latency_ms: u64,
+    /// Number of our requests the peer has responded to.
+    pub delivered_requests: u64,
+    /// Number of our requests the peer hasn't responded to in time.
+    pub failed_requests: u64,
+    /// Number of messages from the peer that failed to be decoded.
+    pub invalid_messages: u64,
+    /// Number of failed attempts to connect to the peer since the last successful connection.
+    pub failed_connections: u64,
+    /// Total time we have been connected to the peer in seconds.
+    pub uptime_secs: u64,
+}
+
+impl PeerScore {
+    pub fn record_latency(&mut self, latency: Duration) {
+        let latency_ms = latency.as_millis() as u64;
+        self.latency_ms =
+            if self.latency_ms == 0 { latency_ms } else { (self.latency_ms * 7 + latency_ms) / 8 };
+    }
+
+    /// Score of the peer, higher is better. Peers we know nothing about have score zero.
+    pub fn value(&self) -> i64 {
+        let requests = self.delivered_requests + self.failed_requests;
+        // Share of delivered requests, from -100 if all failed to 100 if all delivered.
+        let reliability = if requests == 0 {
+            0
+        } else {
+            (self.delivered_requests as i128 * 200 / requests as i128) as i64 - 100
+        };
+        let latency_penalty =
+            std::cmp::min(self.latency_ms / LATENCY_MS_PER_POINT, MAX_LATENCY_PENALTY) as i64;
+        let uptime_bonus =
+            std::cmp::min(self.uptime_secs / UPTIME_SECS_PER_POINT, MAX_UPTIME_BONUS) as i64;
+        let invalid_penalty = std::cmp::min(self.invalid_messages, MAX_PENALIZED_EVENTS) as i64
+            * INVALID_MESSAGE_PENALTY;
+        let connection_penalty = std::cmp::min(self.failed_connections, MAX_PENALIZED_EVENTS)
+            as i64
+            * FAILED_CONNECTION_PENALTY;
+        reliability + uptime_bonus - latency_penalty - invalid_penalty - connection_penalty
+    }
+}
+
 /// Information node stores about known peers.
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Debug)]
 pub struct KnownPeerState {
@@ -774,6 +1170,16 @@ pub struct KnownPeerState {
     pub status: KnownPeerStatus,
     pub first_seen: u64,
     pub last_seen: u64,
+    pub score: PeerScore,
+}
+
+/// Information about known peers stored before peer scores were introduced.
+#[derive(BorshDeserialize)]
+struct LegacyKnownPeerState {
+    peer_info: PeerInfo,
+    status: KnownPeerStatus,
+    first_seen: u64,
+    last_seen: u64,
 }
 
 impl KnownPeerState {
@@ -783,6 +1189,7 @@ impl KnownPeerState {
             status: KnownPeerStatus::Unknown,
             first_seen: to_timestamp(Utc::now()),
             last_seen: to_timestamp(Utc::now()),
+            score: PeerScore::default(),
         }
     }
 
@@ -799,7 +1206,17 @@ impl TryFrom<Vec<u8>> for KnownPeerState {
     type Error = Box<dyn std::error::Error>;
 
     fn try_from(bytes: Vec<u8>) -> Result<KnownPeerState, Self::Error> {
-        KnownPeerState::try_from_slice(&bytes).map_err(|err| err.into())
+        KnownPeerState::try_from_slice(&bytes).or_else(|err| {
+            let LegacyKnownPeerState { peer_info, status, first_seen, last_seen } =
+                LegacyKnownPeerState::try_from_slice(&bytes).map_err(|_| err)?;
+            Ok(KnownPeerState {
+                peer_info,
+                status,
+                first_seen,
+                last_seen,
+                score: Default::default(),
+            })
+        })
     }
 }
 
@@ -837,6 +1254,8 @@ pub struct SendMessage {
 pub struct Consolidate {
     pub actor: Addr<Peer>,
     pub peer_info: PeerInfo,
+    /// Address of the connection with the peer.
+    pub peer_addr: SocketAddr,
     pub peer_type: PeerType,
     pub chain_info: PeerChainInfo,
     // Edge information from this node.
@@ -925,6 +1344,8 @@ pub enum ReasonForBan {
     InvalidPeerId = 8,
     InvalidHash = 9,
     InvalidEdge = 10,
+    /// Banned by the node operator.
+    Manual = 11,
 }
 
 #[derive(Message)]
@@ -1012,6 +1433,8 @@ pub enum NetworkRequests {
     },
     /// Request for receipt execution outcome
     ReceiptOutComeRequest(AccountId, CryptoHash),
+    /// Request for receipt by receipt id and the shard that received it
+    ReceiptRequest(AccountId, CryptoHash, ShardId),
 
     /// The following types of requests are used to trigger actions in the Peer Manager for testing.
     /// Fetch current routing table.
@@ -1032,6 +1455,13 @@ pub enum NetworkRequests {
 
     /// A challenge to invalidate a block.
     Challenge(Challenge),
+
+    /// Ban a peer or IP address / subnet at the request of the node operator.
+    Ban(BanTarget),
+    /// Fetch all banned peers and IP addresses.
+    FetchBans,
+    /// Lift the ban of a peer or IP address / subnet.
+    Unban(BanTarget),
 }
 
 /// Messages from PeerManager to Peer
@@ -1057,11 +1487,18 @@ pub struct KnownProducer {
     pub peer_id: PeerId,
 }
 
+impl From<KnownProducer> for KnownProducerView {
+    fn from(producer: KnownProducer) -> Self {
+        Self { account_id: producer.account_id, addr: producer.addr, peer_id: producer.peer_id }
+    }
+}
+
 #[derive(Debug)]
 pub struct NetworkInfo {
     pub active_peers: Vec<FullPeerInfo>,
     pub num_active_peers: usize,
     pub peer_max_count: u32,
+    /// Peers at the highest height, sorted by their score from the best.
     pub highest_height_peers: Vec<FullPeerInfo>,
     pub sent_bytes_per_sec: u64,
     pub received_bytes_per_sec: u64,
@@ -1089,6 +1526,9 @@ pub enum NetworkResponses {
     BanPeer(ReasonForBan),
     EdgeUpdate(Edge),
     RouteNotFound,
+    Bans(Vec<BanInfo>),
+    BanResult(bool),
+    UnbanResult(bool),
 }
 
 impl<A, M> MessageResponse<A, M> for NetworkResponses
@@ -1204,6 +1644,10 @@ pub enum NetworkViewClientMessages {
     ReceiptOutcomeRequest(CryptoHash),
     /// Receipt outcome response
     ReceiptOutcomeResponse(ExecutionOutcomeWithIdAndProof),
+    /// Request for receipt
+    ReceiptRequest { receipt_id: CryptoHash, shard_id: ShardId },
+    /// Receipt response, with the proof of its inclusion
+    ReceiptResponse(CryptoHash, ReceiptProofResponse),
     /// Request a block.
     BlockRequest(CryptoHash),
     /// Request headers.
@@ -1215,9 +1659,9 @@ pub enum NetworkViewClientMessages {
     /// Get Chain information from Client.
     GetChainInfo,
     /// Account announcements that needs to be validated before being processed.
-    /// They are paired with last epoch id known to this announcement, in order to accept only
-    /// newer announcements.
-    AnnounceAccount(Vec<(AnnounceAccount, Option<EpochId>)>),
+    /// They are paired with the epoch ids of the announcements already known for the account,
+    /// in order to accept only announcements that are not older than the known ones.
+    AnnounceAccount(Vec<(AnnounceAccount, Vec<EpochId>)>),
 }
 
 pub enum NetworkViewClientResponses {
@@ -1227,6 +1671,8 @@ pub enum NetworkViewClientResponses {
     QueryResponse { query_id: String, response: Result<QueryResponse, String> },
     /// Receipt outcome response
     ReceiptOutcomeResponse(ExecutionOutcomeWithIdAndProof),
+    /// Receipt response, with the proof of its inclusion
+    ReceiptResponse(CryptoHash, ReceiptProofResponse),
     /// Block response.
     Block(Block),
     /// Headers response.
@@ -1240,8 +1686,9 @@ pub enum NetworkViewClientResponses {
     },
     /// Response to state request.
     StateResponse(StateResponseInfo),
-    /// Valid announce accounts.
-    AnnounceAccount(Vec<AnnounceAccount>),
+    /// Valid announce accounts, paired with the known epoch ids of the account that are older
+    /// than the announcement and should be dropped.
+    AnnounceAccount(Vec<(AnnounceAccount, Vec<EpochId>)>),
     /// Ban peer for malicious behavior.
     Ban { ban_reason: ReasonForBan },
     /// Response not needed
@@ -1280,6 +1727,12 @@ pub struct PeerStatsResult {
     pub is_abusive: bool,
     /// Counts of incoming/outgoing messages from given peer.
     pub message_counts: (u64, u64),
+    /// Number of requests delivered by the peer since the last query.
+    pub delivered_requests: u64,
+    /// Number of requests the peer failed to respond to since the last query.
+    pub failed_requests: u64,
+    /// Number of invalid messages received from the peer since the last query.
+    pub invalid_messages: u64,
 }
 
 impl<A, M> MessageResponse<A, M> for PeerStatsResult
@@ -1376,6 +1829,38 @@ mod tests {
         };
     }
 
+    #[test]
+    fn test_ip_subnet() {
+        let subnet: IpSubnet = "10.1.2.3/24".parse().unwrap();
+        assert_eq!(subnet.to_string(), "10.1.2.0/24");
+        assert!(subnet.contains(&"10.1.2.200".parse().unwrap()));
+        assert!(!subnet.contains(&"10.1.3.1".parse().unwrap()));
+        assert!(!subnet.contains(&"::1".parse().unwrap()));
+
+        let single: IpSubnet = "10.1.2.3".parse().unwrap();
+        assert_eq!(single, IpSubnet::from_addr("10.1.2.3".parse().unwrap()));
+        assert!(single.contains(&"10.1.2.3".parse().unwrap()));
+        assert!(!single.contains(&"10.1.2.4".parse().unwrap()));
+
+        let any: IpSubnet = "0.0.0.0/0".parse().unwrap();
+        assert!(any.contains(&"1.2.3.4".parse().unwrap()));
+
+        let network = IpSubnet::from_addr_network("2001:db8::1".parse().unwrap());
+        assert_eq!(network.to_string(), "2001:db8::/64");
+        assert!(network.contains(&"2001:db8::ffff".parse().unwrap()));
+
+        assert_eq!(
+            "10.1.2.0/24".parse::<BanTarget>().unwrap(),
+            BanTarget::Ip(IpSubnet::new("10.1.2.0".parse().unwrap(), 24).unwrap())
+        );
+        assert!("1.2.3.4/99".parse::<IpSubnet>().is_err());
+        assert!("1.2.3.4/33".parse::<IpSubnet>().is_err());
+        assert!("::1/129".parse::<IpSubnet>().is_err());
+        assert!("::1/128".parse::<IpSubnet>().is_ok());
+        assert!("1.2.3.4/99".parse::<BanTarget>().is_err());
+        assert!("not a target".parse::<BanTarget>().is_err());
+    }
+
     #[test]
     fn test_enum_size() {
         assert_size!(PeerType);
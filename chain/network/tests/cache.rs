@@ -31,12 +31,92 @@ fn announcement_same_epoch() {
 
     routing_table.add_account(announce0.clone());
     assert!(routing_table.contains_account(&announce0));
-    assert!(routing_table.contains_account(&announce1));
+    assert!(!routing_table.contains_account(&announce1));
     assert_eq!(routing_table.get_announce_accounts().len(), 1);
     assert_eq!(routing_table.account_owner(&announce0.account_id).unwrap(), peer_id0);
     routing_table.add_account(announce1.clone());
-    assert_eq!(routing_table.get_announce_accounts().len(), 1);
-    assert_eq!(routing_table.account_owner(&announce1.account_id).unwrap(), peer_id1);
+    assert!(routing_table.contains_account(&announce1));
+    assert_eq!(routing_table.get_announce_accounts().len(), 2);
+    // Peer id0 is the local peer, hence it is reachable and preferred.
+    assert_eq!(routing_table.account_owner(&announce1.account_id).unwrap(), peer_id0);
+    assert_eq!(
+        routing_table.account_owners(&announce1.account_id).unwrap(),
+        vec![peer_id0, peer_id1]
+    );
+}
+
+#[test]
+fn announcement_same_peer_replaced() {
+    let store = create_test_store();
+
+    let peer_id0 = random_peer_id();
+    let peer_id1 = random_peer_id();
+
+    let mut routing_table = RoutingTable::new(peer_id0, store);
+
+    let announce0 = AnnounceAccount {
+        account_id: "near0".to_string(),
+        peer_id: peer_id1.clone(),
+        epoch_id: random_epoch_id(),
+        signature: Signature::default(),
+    };
+
+    // Same as announce0 but with different epoch id
+    let announce1 = AnnounceAccount {
+        account_id: "near0".to_string(),
+        peer_id: peer_id1.clone(),
+        epoch_id: random_epoch_id(),
+        signature: Signature::default(),
+    };
+
+    routing_table.add_account(announce0.clone());
+    routing_table.add_account(announce1.clone());
+    assert!(!routing_table.contains_account(&announce0));
+    assert!(routing_table.contains_account(&announce1));
+    assert_eq!(routing_table.get_announce_accounts(), vec![announce1]);
+    assert_eq!(routing_table.account_owners(&announce0.account_id).unwrap(), vec![peer_id1]);
+}
+
+#[test]
+fn announcement_stale_epochs_removed() {
+    let store = create_test_store();
+
+    let peer_id0 = random_peer_id();
+    let peer_id1 = random_peer_id();
+    let peer_id2 = random_peer_id();
+    let old_epoch_id = random_epoch_id();
+
+    let mut routing_table = RoutingTable::new(peer_id0, store.clone());
+
+    let announce0 = AnnounceAccount {
+        account_id: "near0".to_string(),
+        peer_id: peer_id1.clone(),
+        epoch_id: old_epoch_id.clone(),
+        signature: Signature::default(),
+    };
+
+    // Same account announced by another peer in a newer epoch
+    let announce1 = AnnounceAccount {
+        account_id: "near0".to_string(),
+        peer_id: peer_id2.clone(),
+        epoch_id: random_epoch_id(),
+        signature: Signature::default(),
+    };
+
+    routing_table.add_account(announce0.clone());
+    routing_table.remove_announces_of_epochs(&announce1.account_id, &[old_epoch_id]);
+    routing_table.add_account(announce1.clone());
+    assert!(!routing_table.contains_account(&announce0));
+    assert!(routing_table.contains_account(&announce1));
+    assert_eq!(routing_table.get_announce_accounts(), vec![announce1.clone()]);
+    assert_eq!(
+        routing_table.account_owners(&announce1.account_id).unwrap(),
+        vec![peer_id2.clone()]
+    );
+
+    // Stale announcement is removed from disk as well
+    let mut routing_table1 = RoutingTable::new(peer_id1, store);
+    assert_eq!(routing_table1.account_owners(&announce1.account_id).unwrap(), vec![peer_id2]);
 }
 
 #[test]
@@ -47,6 +47,25 @@ fn three_nodes_path() {
     .unwrap();
 }
 
+#[test]
+fn three_nodes_sentry() {
+    System::run(|| {
+        let mut runner = Runner::new(3, 3).use_sentry_nodes(0, vec![1]);
+
+        runner.push(Action::AddEdge(0, 1));
+        runner.push(Action::AddEdge(1, 2));
+        // Node 0 only accepts connections from its sentry.
+        runner.push(Action::AddEdge(2, 0));
+        runner.push(Action::Wait(1000));
+        runner.push(Action::CheckRoutingTable(0, vec![(1, vec![1]), (2, vec![1])]));
+        runner.push(Action::CheckRoutingTable(2, vec![(1, vec![1]), (0, vec![1])]));
+        runner.push(Action::CheckAccountId(2, vec![0, 1]));
+
+        runner.run();
+    })
+    .unwrap();
+}
+
 #[test]
 fn three_nodes_star() {
     System::run(|| {
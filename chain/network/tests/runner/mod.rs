@@ -326,6 +326,7 @@ struct TestConfig {
     boot_nodes: Vec<usize>,
     blacklist: HashSet<Option<usize>>,
     outbound_disabled: bool,
+    sentry_nodes: Vec<usize>,
 }
 
 impl TestConfig {
@@ -336,6 +337,7 @@ impl TestConfig {
             boot_nodes: vec![],
             blacklist: HashSet::new(),
             outbound_disabled: true,
+            sentry_nodes: vec![],
         }
     }
 }
@@ -372,6 +374,13 @@ impl Runner {
         self
     }
 
+    /// Hide node `u` behind `sentry_nodes`. Node `u` will only connect with its sentry nodes,
+    /// and sentry nodes will not share the address of `u`.
+    pub fn use_sentry_nodes(mut self, u: usize, sentry_nodes: Vec<usize>) -> Self {
+        self.test_config[u].sentry_nodes = sentry_nodes;
+        self
+    }
+
     /// Specify boot nodes. By default there are no boot nodes.
     pub fn use_boot_nodes(mut self, boot_nodes: Vec<usize>) -> Self {
         self.apply_all(move |test_config| {
@@ -461,6 +470,22 @@ impl Runner {
                         .collect(),
                 );
 
+                let sentry_nodes = convert_boot_nodes(
+                    test_config
+                        .sentry_nodes
+                        .iter()
+                        .map(|ix| (accounts_id[*ix].as_str(), ports[*ix]))
+                        .collect(),
+                );
+
+                let hidden_peers = self
+                    .test_config
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, other_config)| other_config.sentry_nodes.contains(&ix))
+                    .map(|(other, _)| peers_info[other].clone())
+                    .collect();
+
                 let mut network_config =
                     NetworkConfig::from_seed(accounts_id[ix].as_str(), ports[ix].clone());
 
@@ -471,6 +496,8 @@ impl Runner {
                 network_config.blacklist = blacklist;
                 network_config.outbound_disabled = test_config.outbound_disabled;
                 network_config.boot_nodes = boot_nodes;
+                network_config.sentry_nodes = sentry_nodes;
+                network_config.hidden_peers = hidden_peers;
 
                 setup_network_node(
                     accounts_id[ix].clone(),
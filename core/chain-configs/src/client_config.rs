@@ -72,6 +72,12 @@ pub struct ClientConfig {
     pub tracked_shards: Vec<ShardId>,
     /// Not clear old data, set `true` for archive nodes.
     pub archive: bool,
+    /// Index transactions and receipts by account. Entries are not garbage collected, so this is
+    /// meant for archive nodes.
+    pub account_activity_index: bool,
+    /// Record the gas profiles of the execution outcomes and return them from the RPC. Profiles
+    /// are stored in a separate column, which is meant for debugging nodes.
+    pub gas_profiling: bool,
 }
 
 impl ClientConfig {
@@ -121,6 +127,8 @@ impl ClientConfig {
             tracked_accounts: vec![],
             tracked_shards: vec![],
             archive,
+            account_activity_index: false,
+            gas_profiling: false,
         }
     }
 }
@@ -12,9 +12,10 @@ use serde::{Deserialize, Serialize};
 use smart_default::SmartDefault;
 
 use near_primitives::serialize::u128_dec_format;
+use near_primitives::shard_layout::ShardLayout;
 use near_primitives::state_record::StateRecord;
 use near_primitives::types::{
-    AccountId, AccountInfo, Balance, BlockHeightDelta, Gas, NumBlocks, NumSeats,
+    AccountId, AccountInfo, Balance, BlockHeightDelta, Gas, NumBlocks, NumSeats, NumShards,
 };
 use near_runtime_configs::RuntimeConfig;
 
@@ -45,6 +46,11 @@ pub struct GenesisConfig {
     pub avg_hidden_validator_seats_per_shard: Vec<NumSeats>,
     /// Enable dynamic re-sharding.
     pub dynamic_resharding: bool,
+    /// Accounts splitting the sorted account id space into shards, so that an account and its
+    /// sub-accounts share a shard. Must contain one account less than the number of shards.
+    /// If empty, accounts are assigned to shards by the hash of their id.
+    #[serde(default)]
+    pub boundary_accounts: Vec<AccountId>,
     /// Epoch length counted in block heights.
     pub epoch_length: BlockHeightDelta,
     /// Initial gas limit.
@@ -88,6 +94,26 @@ impl GenesisConfig {
         self.total_supply = get_initial_supply(&self.records);
     }
 
+    /// Assignment of accounts to shards. Fails if the boundary accounts are invalid or don't
+    /// match the number of shards.
+    pub fn shard_layout(&self) -> Result<ShardLayout, String> {
+        let num_shards = self.num_block_producer_seats_per_shard.len() as NumShards;
+        if self.boundary_accounts.is_empty() {
+            return Ok(ShardLayout::v0(num_shards));
+        }
+        let shard_layout = ShardLayout::v1(self.boundary_accounts.clone())
+            .map_err(|err| format!("Invalid boundary_accounts: {}", err))?;
+        if shard_layout.num_shards() != num_shards {
+            return Err(format!(
+                "{} boundary_accounts define {} shards, but the genesis config has {} shards",
+                self.boundary_accounts.len(),
+                shard_layout.num_shards(),
+                num_shards
+            ));
+        }
+        Ok(shard_layout)
+    }
+
     /// Reads GenesisConfig from a file.
     pub fn from_file(path: &PathBuf, records_path: Option<PathBuf>) -> Self {
         let mut file = File::open(path).expect("Could not open genesis config file.");
@@ -9,6 +9,8 @@
 //! - `IncCounter`: used to represent an ideally ever-growing, never-shrinking integer (e.g.,
 //! number of block processing requests).
 //! - `IntGauge`: used to represent an varying integer (e.g., number of attestations per block).
+//! - `IntCounterVec`: `IntCounter`s partitioned by the values of some labels (e.g., number of
+//! messages by message type).
 //!
 //! ## Important
 //!
@@ -55,7 +57,9 @@
 //! }
 //! ```
 
-pub use prometheus::{Encoder, Histogram, IntCounter, IntGauge, Result, TextEncoder};
+pub use prometheus::{
+    Encoder, Histogram, IntCounter, IntCounterVec, IntGauge, Result, TextEncoder,
+};
 use prometheus::{HistogramOpts, HistogramTimer, Opts};
 
 use log::error;
@@ -74,6 +78,19 @@ pub fn try_create_int_counter(name: &str, help: &str) -> Result<IntCounter> {
     Ok(counter)
 }
 
+/// Attempts to crate an `IntCounterVec` with the given label names, returning `Err` if the
+/// registry does not accept the counter (potentially due to naming conflict).
+pub fn try_create_int_counter_vec(
+    name: &str,
+    help: &str,
+    label_names: &[&str],
+) -> Result<IntCounterVec> {
+    let opts = Opts::new(name, help);
+    let counter = IntCounterVec::new(opts, label_names)?;
+    prometheus::register(Box::new(counter.clone()))?;
+    Ok(counter)
+}
+
 /// Attempts to crate an `IntGauge`, returning `Err` if the registry does not accept the counter
 /// (potentially due to naming conflict).
 pub fn try_create_int_gauge(name: &str, help: &str) -> Result<IntGauge> {
@@ -132,6 +149,21 @@ pub fn inc_counter_by(counter: &Result<IntCounter>, value: i64) {
     }
 }
 
+pub fn inc_counter_vec(counter: &Result<IntCounterVec>, label_values: &[&str]) {
+    inc_counter_vec_by(counter, label_values, 1);
+}
+
+pub fn inc_counter_vec_by(counter: &Result<IntCounterVec>, label_values: &[&str], value: i64) {
+    if let Ok(counter) = counter {
+        match counter.get_metric_with_label_values(label_values) {
+            Ok(counter) => counter.inc_by(value),
+            Err(err) => error!(target: "metrics", "Failed to fetch counter: {}", err),
+        }
+    } else {
+        error!(target: "metrics", "Failed to fetch counter");
+    }
+}
+
 pub fn set_gauge(gauge: &Result<IntGauge>, value: i64) {
     if let Ok(gauge) = gauge {
         gauge.set(value);
@@ -1,6 +1,7 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 
 use near_crypto::Signature;
+use near_rpc_error_macro::RpcSchema;
 
 use crate::hash::{hash, CryptoHash};
 use crate::merkle::MerklePath;
@@ -98,7 +99,9 @@ impl Challenge {
 
 pub type Challenges = Vec<Challenge>;
 
-#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Clone, Debug, RpcSchema,
+)]
 pub struct SlashedValidator {
     pub account_id: AccountId,
     pub is_double_sign: bool,
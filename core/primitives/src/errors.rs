@@ -1,16 +1,25 @@
 use crate::serialize::u128_dec_format;
-use crate::types::{AccountId, Balance, Gas, Nonce};
+use crate::types::{AccountId, Balance, BlockHeight, Gas, Nonce};
 use borsh::{BorshDeserialize, BorshSerialize};
 use near_crypto::PublicKey;
 use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 
-use near_rpc_error_macro::RpcError;
+use near_rpc_error_macro::{RpcError, RpcSchema};
 use near_vm_errors::FunctionCallError;
 
 /// Error returned in the ExecutionOutcome in case of failure
 #[derive(
-    BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq, Deserialize, Serialize, RpcError,
+    BorshSerialize,
+    BorshDeserialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    Deserialize,
+    Serialize,
+    RpcError,
+    RpcSchema,
 )]
 pub enum TxExecutionError {
     /// An error happened during Acton execution
@@ -55,6 +64,8 @@ pub enum RuntimeError {
     BalanceMismatchError(BalanceMismatchError),
     /// The incoming receipt didn't pass the validation, it's likely a malicious behaviour.
     ReceiptValidationError(ReceiptValidationError),
+    /// The information about the validators of the current epoch is not available.
+    ValidatorError(EpochInfoProviderError),
 }
 
 /// Internal
@@ -80,9 +91,56 @@ impl std::fmt::Display for StorageError {
 
 impl std::error::Error for StorageError {}
 
+/// Error returned by `EpochInfoProvider` when it can't provide the information about an epoch.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub enum EpochInfoProviderError {
+    /// The requested epoch is not known to the node.
+    EpochOutOfBounds,
+    /// Reading the information about the epoch failed.
+    /// Error message is for debugging purposes only.
+    Other(String),
+}
+
+impl std::fmt::Display for EpochInfoProviderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        f.write_str(&format!("{:?}", self))
+    }
+}
+
+impl std::error::Error for EpochInfoProviderError {}
+
+/// Error of the runtime host functions that is passed through the VM as
+/// `VMLogicError::ExternalError` and is not a contract error.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub enum ExternalError {
+    StorageError(StorageError),
+    ValidatorError(EpochInfoProviderError),
+}
+
+impl From<StorageError> for ExternalError {
+    fn from(e: StorageError) -> Self {
+        ExternalError::StorageError(e)
+    }
+}
+
+impl From<EpochInfoProviderError> for ExternalError {
+    fn from(e: EpochInfoProviderError) -> Self {
+        ExternalError::ValidatorError(e)
+    }
+}
+
 /// An error happened during TX execution
 #[derive(
-    BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq, Deserialize, Serialize, RpcError,
+    BorshSerialize,
+    BorshDeserialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    Deserialize,
+    Serialize,
+    RpcError,
+    RpcSchema,
 )]
 pub enum InvalidTxError {
     /// Happens if a wrong AccessKey used or AccessKey has not enough permissions
@@ -121,10 +179,27 @@ pub enum InvalidTxError {
     Expired,
     /// An error occurred while validating actions of a Transaction.
     ActionsValidation(ActionsValidationError),
+    /// Delegate actions are not enabled by the current protocol version.
+    DelegateActionNotEnabled,
+    /// The signature of a Delegate action doesn't match its public key.
+    DelegateActionInvalidSignature,
+    /// The `sender_id` of a Delegate action is not the receiver of the transaction.
+    DelegateActionSenderDoesNotMatchReceiver { sender_id: AccountId, receiver_id: AccountId },
+    /// A Delegate action is relayed after its `max_block_height`.
+    DelegateActionExpired { max_block_height: BlockHeight, block_height: BlockHeight },
 }
 
 #[derive(
-    BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq, Deserialize, Serialize, RpcError,
+    BorshSerialize,
+    BorshDeserialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    Deserialize,
+    Serialize,
+    RpcError,
+    RpcSchema,
 )]
 pub enum InvalidAccessKeyError {
     /// The access key identified by the `public_key` doesn't exist for the account
@@ -149,7 +224,9 @@ pub enum InvalidAccessKeyError {
 }
 
 /// Describes the error for validating a list of actions.
-#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq, Eq, RpcSchema,
+)]
 pub enum ActionsValidationError {
     /// The total prepaid gas (for all given actions) exceeded the limit.
     TotalPrepaidGasExceeded { total_prepaid_gas: Gas, limit: Gas },
@@ -169,10 +246,14 @@ pub enum ActionsValidationError {
     FunctionCallMethodNameLengthExceeded { length: u64, limit: u64 },
     /// The length of the arguments exceeded the limit in a Function Call action.
     FunctionCallArgumentsLengthExceeded { length: u64, limit: u64 },
+    /// A Delegate action contains another Delegate action.
+    NestedDelegateAction,
 }
 
 /// Describes the error for validating a receipt.
-#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq, Eq, RpcSchema,
+)]
 pub enum ReceiptValidationError {
     /// The `predecessor_id` of a Receipt is not valid.
     InvalidPredecessorId { account_id: AccountId },
@@ -269,13 +350,25 @@ impl Display for ActionsValidationError {
                 "The length of the arguments {} exceeds the maximum allowed length {} in a FunctionCall action",
                 length, limit
             ),
+            ActionsValidationError::NestedDelegateAction => {
+                write!(f, "A Delegate action can't contain other Delegate actions")
+            }
         }
     }
 }
 
 /// An error happened during Acton execution
 #[derive(
-    BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq, Deserialize, Serialize, RpcError,
+    BorshSerialize,
+    BorshDeserialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    Deserialize,
+    Serialize,
+    RpcError,
+    RpcSchema,
 )]
 pub struct ActionError {
     /// Index of the failed action in the transaction.
@@ -286,7 +379,16 @@ pub struct ActionError {
 }
 
 #[derive(
-    BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq, Deserialize, Serialize, RpcError,
+    BorshSerialize,
+    BorshDeserialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    Deserialize,
+    Serialize,
+    RpcError,
+    RpcSchema,
 )]
 pub enum ActionErrorKind {
     /// Happens when CreateAccount action tries to create an account with account_id which is already exists in the storage
@@ -337,6 +439,19 @@ pub enum ActionErrorKind {
     /// Error occurs when a new `ActionReceipt` created by the `FunctionCall` action fails
     /// receipt validation.
     NewReceiptValidationError(ReceiptValidationError),
+    /// The access key used to sign a Delegate action doesn't allow its actions.
+    DelegateActionAccessKeyError(InvalidAccessKeyError),
+    /// The nonce of a Delegate action must be larger than the nonce of the used access key.
+    DelegateActionInvalidNonce { delegate_nonce: Nonce, ak_nonce: Nonce },
+    /// The sender of a Delegate action doesn't have enough balance for the deposits of the
+    /// relayed actions.
+    DelegateActionNotEnoughBalance {
+        sender_id: AccountId,
+        #[serde(with = "u128_dec_format")]
+        balance: Balance,
+        #[serde(with = "u128_dec_format")]
+        deposit: Balance,
+    },
 }
 
 impl From<ActionErrorKind> for ActionError {
@@ -386,6 +501,24 @@ impl Display for InvalidTxError {
             InvalidTxError::ActionsValidation(error) => {
                 write!(f, "Transaction actions validation error: {}", error)
             }
+            InvalidTxError::DelegateActionNotEnabled => {
+                write!(f, "Delegate actions are not enabled by the current protocol version")
+            }
+            InvalidTxError::DelegateActionInvalidSignature => {
+                write!(f, "The signature of the Delegate action is not valid")
+            }
+            InvalidTxError::DelegateActionSenderDoesNotMatchReceiver { sender_id, receiver_id } => {
+                write!(
+                    f,
+                    "The Delegate action sender {:?} doesn't match the transaction receiver {:?}",
+                    sender_id, receiver_id
+                )
+            }
+            InvalidTxError::DelegateActionExpired { max_block_height, block_height } => write!(
+                f,
+                "The Delegate action expired at block height {}, current block height is {}",
+                max_block_height, block_height
+            ),
         }
     }
 }
@@ -439,7 +572,16 @@ impl Display for InvalidAccessKeyError {
 
 /// Happens when the input balance doesn't match the output balance in Runtime apply.
 #[derive(
-    BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq, Deserialize, Serialize, RpcError,
+    BorshSerialize,
+    BorshDeserialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    Deserialize,
+    Serialize,
+    RpcError,
+    RpcSchema,
 )]
 pub struct BalanceMismatchError {
     // Input balances
@@ -548,6 +690,21 @@ impl From<StorageError> for RuntimeError {
     }
 }
 
+impl From<EpochInfoProviderError> for RuntimeError {
+    fn from(e: EpochInfoProviderError) -> Self {
+        RuntimeError::ValidatorError(e)
+    }
+}
+
+impl From<ExternalError> for RuntimeError {
+    fn from(e: ExternalError) -> Self {
+        match e {
+            ExternalError::StorageError(e) => RuntimeError::StorageError(e),
+            ExternalError::ValidatorError(e) => RuntimeError::ValidatorError(e),
+        }
+    }
+}
+
 impl From<BalanceMismatchError> for RuntimeError {
     fn from(e: BalanceMismatchError) -> Self {
         RuntimeError::BalanceMismatchError(e)
@@ -626,6 +783,21 @@ impl Display for ActionErrorKind {
             ActionErrorKind::NewReceiptValidationError(e) => {
                 write!(f, "An new action receipt created during a FunctionCall is not valid: {}", e)
             }
+            ActionErrorKind::DelegateActionAccessKeyError(access_key_error) => {
+                access_key_error.fmt(f)
+            }
+            ActionErrorKind::DelegateActionInvalidNonce { delegate_nonce, ak_nonce } => write!(
+                f,
+                "Delegate action nonce {} must be larger than nonce of the used access key {}",
+                delegate_nonce, ak_nonce
+            ),
+            ActionErrorKind::DelegateActionNotEnoughBalance { sender_id, balance, deposit } => {
+                write!(
+                f,
+                "Delegate action sender {:?} does not have enough balance {} for the deposit {}",
+                sender_id, balance, deposit
+            )
+            }
         }
     }
 }
@@ -25,6 +25,7 @@ pub mod network;
 pub mod receipt;
 pub mod rpc;
 pub mod serialize;
+pub mod shard_layout;
 pub mod sharding;
 pub mod state_record;
 pub mod telemetry;
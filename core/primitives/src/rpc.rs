@@ -1,10 +1,14 @@
 use serde::{Deserialize, Serialize};
 
+use near_rpc_error_macro::RpcSchema;
+
+use crate::hash::CryptoHash;
 use crate::types::BlockId;
 use crate::types::MaybeBlockId;
+use crate::types::{AccountId, BlockHeight, ShardId};
 use crate::views::{Finality, QueryRequest};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, RpcSchema)]
 pub struct RpcQueryRequest {
     pub block_id: MaybeBlockId,
     #[serde(flatten)]
@@ -12,7 +16,53 @@ pub struct RpcQueryRequest {
     pub finality: Finality,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, RpcSchema)]
+pub struct RpcSimulateTransactionRequest {
+    /// Base64 encoded `SignedTransaction`.
+    pub signed_tx_base64: String,
+    #[serde(default)]
+    pub block_id: MaybeBlockId,
+    #[serde(default)]
+    pub finality: Finality,
+    /// Whether the signature of the transaction has to be valid.
+    #[serde(default)]
+    pub verify_signature: bool,
+}
+
+/// Accounts and shards to start or stop tracking through the admin RPC.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, RpcSchema)]
+pub struct RpcTrackingRequest {
+    #[serde(default)]
+    pub accounts: Vec<AccountId>,
+    #[serde(default)]
+    pub shards: Vec<ShardId>,
+}
+
+/// Page of the activity of an account, starting from the newest block.
+#[derive(Serialize, Deserialize, Debug, Clone, RpcSchema)]
+pub struct RpcAccountActivityRequest {
+    pub account_id: AccountId,
+    /// Only the activity in the blocks below this height is returned.
+    #[serde(default)]
+    pub before_height: Option<BlockHeight>,
+    /// Maximum number of blocks in the page, clamped to 1..=100.
+    #[serde(default = "default_account_activity_limit")]
+    pub limit: u64,
+}
+
+fn default_account_activity_limit() -> u64 {
+    20
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, RpcSchema)]
+pub struct RpcReceiptRequest {
+    pub receipt_id: CryptoHash,
+    /// Used to find the shard of the receipt if the node doesn't know it.
+    #[serde(default)]
+    pub receiver_id: Option<AccountId>,
+}
+
+#[derive(Serialize, Deserialize, RpcSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum BlockQueryInfo {
     BlockId(BlockId),
@@ -0,0 +1,162 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::io::Cursor;
+
+use byteorder::{LittleEndian, ReadBytesExt};
+
+use crate::hash::hash;
+use crate::types::{AccountId, NumShards, ShardId};
+use crate::utils::is_valid_account_id;
+
+/// Describes which shard every account belongs to. The layout is defined by the genesis config
+/// and doesn't change during the life of the chain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShardLayout {
+    /// The shard of an account is the hash of its id modulo the number of shards.
+    V0 { num_shards: NumShards },
+    /// Accounts are split into contiguous ranges by the sorted boundary accounts: shard `i`
+    /// holds the accounts between `boundary_accounts[i - 1]` (inclusive) and
+    /// `boundary_accounts[i]` (exclusive). Accounts are ordered by `compare_account_ids`, so an
+    /// account and all of its sub-accounts always land on the same shard unless a boundary
+    /// account is one of those sub-accounts.
+    V1 { boundary_accounts: Vec<AccountId> },
+}
+
+impl ShardLayout {
+    /// Hash-based layout with the given number of shards.
+    pub fn v0(num_shards: NumShards) -> Self {
+        ShardLayout::V0 { num_shards }
+    }
+
+    /// Layout splitting accounts into ranges by the given boundary accounts. The boundary
+    /// accounts must be valid account ids, strictly increasing by `compare_account_ids`.
+    pub fn v1(boundary_accounts: Vec<AccountId>) -> Result<Self, ShardLayoutError> {
+        for account_id in boundary_accounts.iter() {
+            if !is_valid_account_id(account_id) {
+                return Err(ShardLayoutError::InvalidBoundaryAccount(account_id.clone()));
+            }
+        }
+        for pair in boundary_accounts.windows(2) {
+            if compare_account_ids(&pair[0], &pair[1]) != Ordering::Less {
+                return Err(ShardLayoutError::UnsortedBoundaryAccounts(
+                    pair[0].clone(),
+                    pair[1].clone(),
+                ));
+            }
+        }
+        Ok(ShardLayout::V1 { boundary_accounts })
+    }
+
+    pub fn num_shards(&self) -> NumShards {
+        match self {
+            ShardLayout::V0 { num_shards } => *num_shards,
+            ShardLayout::V1 { boundary_accounts } => boundary_accounts.len() as NumShards + 1,
+        }
+    }
+
+    pub fn account_id_to_shard_id(&self, account_id: &AccountId) -> ShardId {
+        match self {
+            ShardLayout::V0 { num_shards } => {
+                let mut cursor = Cursor::new((hash(account_id.as_bytes()).0).0);
+                cursor.read_u64::<LittleEndian>().expect("Must not happened") % num_shards
+            }
+            ShardLayout::V1 { boundary_accounts } => {
+                match boundary_accounts
+                    .binary_search_by(|boundary| compare_account_ids(boundary, account_id))
+                {
+                    Ok(index) => index as ShardId + 1,
+                    Err(index) => index as ShardId,
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShardLayoutError {
+    /// The boundary account is not a valid account id.
+    InvalidBoundaryAccount(AccountId),
+    /// The boundary accounts are not sorted or contain duplicates.
+    UnsortedBoundaryAccounts(AccountId, AccountId),
+}
+
+impl fmt::Display for ShardLayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShardLayoutError::InvalidBoundaryAccount(account_id) => {
+                write!(f, "Boundary account {:?} is not a valid account id", account_id)
+            }
+            ShardLayoutError::UnsortedBoundaryAccounts(first, second) => write!(
+                f,
+                "Boundary accounts must be sorted and unique, but {:?} is not before {:?}",
+                first, second
+            ),
+        }
+    }
+}
+
+/// Orders account ids by their labels starting from the top-level one, e.g. `app.alice.near`
+/// is compared as `near`, `alice`, `app`. This keeps every account next to its sub-accounts.
+pub fn compare_account_ids(a: &AccountId, b: &AccountId) -> Ordering {
+    a.split('.').rev().cmp(b.split('.').rev())
+}
+
+impl Default for ShardLayout {
+    fn default() -> Self {
+        ShardLayout::v0(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_v0_layout() {
+        let layout = ShardLayout::v0(4);
+        assert_eq!(layout.num_shards(), 4);
+        for account_id in &["test1", "test2", "alice.near", "app.alice.near"] {
+            assert!(layout.account_id_to_shard_id(&account_id.to_string()) < 4);
+        }
+        assert_eq!(ShardLayout::v0(1).account_id_to_shard_id(&"test1".to_string()), 0);
+    }
+
+    #[test]
+    fn test_v1_layout() {
+        let layout = ShardLayout::v1(vec!["near".to_string(), "bob.near".to_string()]).unwrap();
+        assert_eq!(layout.num_shards(), 3);
+        let shard_id = |account_id: &str| layout.account_id_to_shard_id(&account_id.to_string());
+        assert_eq!(shard_id("alice.aurora"), 0);
+        assert_eq!(shard_id("near"), 1);
+        assert_eq!(shard_id("alice.near"), 1);
+        assert_eq!(shard_id("app.alice.near"), 1);
+        assert_eq!(shard_id("zed.alice.near"), 1);
+        assert_eq!(shard_id("bob.near"), 2);
+        assert_eq!(shard_id("app.bob.near"), 2);
+        assert_eq!(shard_id("carol.near"), 2);
+        assert_eq!(shard_id("zzz"), 2);
+    }
+
+    #[test]
+    fn test_v1_layout_invalid_boundary_accounts() {
+        let v1 = |accounts: &[&str]| {
+            ShardLayout::v1(accounts.iter().map(|account_id| account_id.to_string()).collect())
+        };
+        assert_eq!(
+            v1(&["bob.near", "near"]),
+            Err(ShardLayoutError::UnsortedBoundaryAccounts(
+                "bob.near".to_string(),
+                "near".to_string()
+            ))
+        );
+        assert_eq!(
+            v1(&["near", "near"]),
+            Err(ShardLayoutError::UnsortedBoundaryAccounts("near".to_string(), "near".to_string()))
+        );
+        assert_eq!(
+            v1(&["near", "Bob.near"]),
+            Err(ShardLayoutError::InvalidBoundaryAccount("Bob.near".to_string()))
+        );
+        assert!(v1(&[]).is_ok());
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::sync::{Mutex, Once};
 
 use log::LevelFilter;
@@ -7,12 +8,13 @@ use near_crypto::{EmptySigner, PublicKey, Signer};
 
 use crate::account::{AccessKey, AccessKeyPermission};
 use crate::block::{Approval, Block};
+use crate::errors::EpochInfoProviderError;
 use crate::hash::CryptoHash;
 use crate::transaction::{
     Action, AddKeyAction, CreateAccountAction, SignedTransaction, StakeAction, Transaction,
     TransferAction,
 };
-use crate::types::{AccountId, Balance, BlockHeight, EpochId, Nonce};
+use crate::types::{AccountId, Balance, BlockHeight, EpochId, EpochInfoProvider, Nonce};
 use crate::validator_signer::ValidatorSigner;
 
 lazy_static! {
@@ -231,3 +233,32 @@ impl Block {
         )
     }
 }
+
+/// Epoch info provider with the same set of validators in every epoch.
+#[derive(Default)]
+pub struct MockEpochInfoProvider {
+    pub validators: HashMap<AccountId, Balance>,
+}
+
+impl MockEpochInfoProvider {
+    pub fn new(validators: impl Iterator<Item = (AccountId, Balance)>) -> Self {
+        MockEpochInfoProvider { validators: validators.collect() }
+    }
+}
+
+impl EpochInfoProvider for MockEpochInfoProvider {
+    fn validator_stake(
+        &self,
+        _epoch_id: &EpochId,
+        account_id: &AccountId,
+    ) -> Result<Option<Balance>, EpochInfoProviderError> {
+        Ok(self.validators.get(account_id).cloned())
+    }
+
+    fn validator_total_stake(
+        &self,
+        _epoch_id: &EpochId,
+    ) -> Result<Balance, EpochInfoProviderError> {
+        Ok(self.validators.values().sum())
+    }
+}
@@ -4,13 +4,14 @@ use std::hash::{Hash, Hasher};
 use borsh::{BorshDeserialize, BorshSerialize};
 
 use near_crypto::{PublicKey, Signature, Signer};
+use near_rpc_error_macro::RpcSchema;
 
 use crate::account::AccessKey;
 use crate::errors::TxExecutionError;
 use crate::hash::{hash, CryptoHash};
 use crate::logging;
 use crate::merkle::MerklePath;
-use crate::types::{AccountId, Balance, Gas, Nonce};
+use crate::types::{AccountId, Balance, BlockHeight, Gas, Nonce};
 use std::borrow::Borrow;
 
 pub type LogEntry = String;
@@ -54,12 +55,20 @@ pub enum Action {
     AddKey(AddKeyAction),
     DeleteKey(DeleteKeyAction),
     DeleteAccount(DeleteAccountAction),
+    /// Actions signed by `sender_id` and relayed by the transaction signer, who pays for the gas.
+    /// The deposits of the actions are paid by `sender_id`. Must be sent to `sender_id`.
+    Delegate(SignedDelegateAction),
 }
 
 impl Action {
     pub fn get_prepaid_gas(&self) -> Gas {
         match self {
             Action::FunctionCall(a) => a.gas,
+            Action::Delegate(a) => a
+                .delegate_action
+                .actions
+                .iter()
+                .fold(0, |gas: Gas, action| gas.saturating_add(action.get_prepaid_gas())),
             _ => 0,
         }
     }
@@ -143,6 +152,57 @@ pub struct DeleteAccountAction {
     pub beneficiary_id: AccountId,
 }
 
+/// Prefix of the signed delegate action message, so that its signature can never be reused as
+/// a signature of a transaction.
+pub const DELEGATE_ACTION_PREFIX: u32 = (1 << 30) + 366;
+
+/// Actions that `sender_id` wants to be executed on `receiver_id` on their behalf, without paying
+/// for the gas. Any account can relay them by sending a transaction with `Action::Delegate` to
+/// `sender_id`. The deposits of the actions are still paid by `sender_id`, so that the refunds of
+/// the deposits go back to the account that paid them.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, PartialEq, Eq, Clone, Debug)]
+pub struct DelegateAction {
+    /// The account on which behalf the actions are executed.
+    pub sender_id: AccountId,
+    /// The account on which the actions are executed.
+    pub receiver_id: AccountId,
+    /// Actions to execute. Can't contain other delegate actions.
+    pub actions: Vec<Action>,
+    /// Nonce of the access key of `sender_id` used to sign the delegate action.
+    pub nonce: Nonce,
+    /// The delegate action is only valid while the block height doesn't exceed this value.
+    pub max_block_height: BlockHeight,
+    /// A public key of the access key of `sender_id` used to sign the delegate action.
+    pub public_key: PublicKey,
+}
+
+impl DelegateAction {
+    /// Computes a hash of the delegate action for signing.
+    pub fn get_hash(&self) -> CryptoHash {
+        let bytes = (DELEGATE_ACTION_PREFIX, self).try_to_vec().expect("Failed to serialize");
+        hash(&bytes)
+    }
+
+    pub fn sign(self, signer: &dyn Signer) -> SignedDelegateAction {
+        let signature = signer.sign(self.get_hash().as_ref());
+        SignedDelegateAction { delegate_action: self, signature }
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, PartialEq, Eq, Clone, Debug)]
+pub struct SignedDelegateAction {
+    pub delegate_action: DelegateAction,
+    pub signature: Signature,
+}
+
+impl SignedDelegateAction {
+    /// Checks that the delegate action is signed with its own public key.
+    pub fn verify(&self) -> bool {
+        self.signature
+            .verify(self.delegate_action.get_hash().as_ref(), &self.delegate_action.public_key)
+    }
+}
+
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Eq, Debug, Clone)]
 #[borsh_init(init)]
 pub struct SignedTransaction {
@@ -289,6 +349,69 @@ impl fmt::Debug for ExecutionOutcome {
     }
 }
 
+/// Gas attributed to a single cost while executing a transaction or a receipt.
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Clone, Debug, RpcSchema,
+)]
+pub struct CostGasUsed {
+    /// Name of the cost, e.g. `storage_write_base` or `function_call`.
+    pub cost: String,
+    pub gas_used: Gas,
+}
+
+/// Breakdown of the gas spent by a transaction or a receipt. It is not a part of the execution
+/// outcome and is only stored by the nodes that have gas profiling enabled.
+#[derive(
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    Clone,
+    Debug,
+    Default,
+    RpcSchema,
+)]
+pub struct GasProfile {
+    /// Gas burnt for executing Wasm instructions.
+    pub wasm_gas: Gas,
+    /// Gas burnt by the host functions, one entry per `ExtCosts` that was paid.
+    pub host_costs: Vec<CostGasUsed>,
+    /// Gas used by the actions, one entry per action fee of `RuntimeFeesConfig` that was paid.
+    /// For the actions created by contracts it includes the prepaid execution fees.
+    pub action_costs: Vec<CostGasUsed>,
+}
+
+impl GasProfile {
+    /// Adds gas to the action cost with the given name.
+    pub fn add_action_cost(&mut self, cost: &str, gas_used: Gas) {
+        match self.action_costs.iter_mut().find(|entry| entry.cost == cost) {
+            Some(entry) => entry.gas_used = entry.gas_used.saturating_add(gas_used),
+            None => self.action_costs.push(CostGasUsed { cost: cost.to_string(), gas_used }),
+        }
+    }
+
+    /// Adds gas to the host function cost with the given name.
+    pub fn add_host_cost(&mut self, cost: &str, gas_used: Gas) {
+        match self.host_costs.iter_mut().find(|entry| entry.cost == cost) {
+            Some(entry) => entry.gas_used = entry.gas_used.saturating_add(gas_used),
+            None => self.host_costs.push(CostGasUsed { cost: cost.to_string(), gas_used }),
+        }
+    }
+
+    /// Merges the other profile into this one.
+    pub fn merge(&mut self, other: GasProfile) {
+        self.wasm_gas = self.wasm_gas.saturating_add(other.wasm_gas);
+        for entry in other.host_costs {
+            self.add_host_cost(&entry.cost, entry.gas_used);
+        }
+        for entry in other.action_costs {
+            self.add_action_cost(&entry.cost, entry.gas_used);
+        }
+    }
+}
+
 /// Execution outcome with the identifier.
 /// For a signed transaction, the ID is the hash of the transaction.
 /// For a receipt, the ID is the receipt ID.
@@ -426,4 +549,17 @@ mod tests {
         let hashes = outcome.to_hashes();
         assert_eq!(hashes.len(), 3);
     }
+
+    #[test]
+    fn test_gas_profile_merge() {
+        let mut profile = GasProfile::default();
+        profile.add_action_cost("transfer", 100);
+        let mut other = GasProfile::default();
+        other.add_action_cost("transfer", 23);
+        other.add_host_cost("storage_write_base", 10);
+        profile.merge(other);
+        assert_eq!(profile.action_costs.len(), 1);
+        assert_eq!(profile.action_costs[0].gas_used, 123);
+        assert_eq!(profile.host_costs[0].gas_used, 10);
+    }
 }
@@ -2,8 +2,10 @@ use borsh::{BorshDeserialize, BorshSerialize};
 use serde_derive::{Deserialize, Serialize};
 
 use near_crypto::PublicKey;
+use near_rpc_error_macro::RpcSchema;
 
 use crate::challenge::ChallengesResult;
+use crate::errors::EpochInfoProviderError;
 use crate::hash::CryptoHash;
 use crate::serialize::u128_dec_format;
 
@@ -38,6 +40,8 @@ pub type NumShards = u64;
 pub type NumSeats = u64;
 /// Block height delta that measures the difference between `BlockHeight`s.
 pub type BlockHeightDelta = u64;
+/// Number of the epoch, starting from the genesis epoch.
+pub type EpochHeight = u64;
 
 pub type ReceiptIndex = usize;
 pub type PromiseId = Vec<ReceiptIndex>;
@@ -85,7 +89,9 @@ impl From<Vec<u8>> for FunctionArgs {
 }
 
 /// A structure used to index state changes due to transaction/receipt processing and other things.
-#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Debug, Clone)]
+#[derive(
+    Serialize, Deserialize, BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq, RpcSchema,
+)]
 pub enum StateChangeCause {
     /// A type of update that does not get finalized. Used for verification and execution of
     /// immutable smart contract methods. Attempt fo finalize a `TrieUpdate` containing such
@@ -120,7 +126,7 @@ pub enum StateChangeCause {
 pub type StateChanges =
     std::collections::BTreeMap<Vec<u8>, Vec<(StateChangeCause, Option<Vec<u8>>)>>;
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, RpcSchema)]
 #[serde(tag = "changes_type", rename_all = "snake_case")]
 pub enum StateChangesRequest {
     AccountChanges { account_id: AccountId },
@@ -235,14 +241,24 @@ impl ChunkExtra {
     }
 }
 
+/// Transactions and receipts of a chunk where an account is the signer, the receiver or the
+/// predecessor. Stored in the account activity index.
+#[derive(Debug, Default, PartialEq, BorshSerialize, BorshDeserialize, Serialize, Clone, Eq)]
+pub struct AccountActivity {
+    pub transactions: Vec<CryptoHash>,
+    pub receipts: Vec<CryptoHash>,
+}
+
 /// Data structure for semver version and github tag or commit.
-#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default, RpcSchema)]
 pub struct Version {
     pub version: String,
     pub build: String,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[derive(
+    Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize, Serialize, Deserialize, RpcSchema,
+)]
 #[serde(untagged)]
 pub enum BlockId {
     Height(BlockHeight),
@@ -262,3 +278,18 @@ pub struct BlockChunkValidatorStats {
     pub block_stats: ValidatorStats,
     pub chunk_stats: ValidatorStats,
 }
+
+/// Provides information about the validators of an epoch to the runtime.
+/// Used to break the dependency between the runtime and the epoch manager.
+pub trait EpochInfoProvider: Send + Sync {
+    /// Returns the stake of the validator in the given epoch or `None` if the account is not a
+    /// validator in that epoch.
+    fn validator_stake(
+        &self,
+        epoch_id: &EpochId,
+        account_id: &AccountId,
+    ) -> Result<Option<Balance>, EpochInfoProviderError>;
+
+    /// Returns the total stake of all validators in the given epoch.
+    fn validator_total_stake(&self, epoch_id: &EpochId) -> Result<Balance, EpochInfoProviderError>;
+}
@@ -1,35 +1,41 @@
 use std::convert::{TryFrom, TryInto};
 use std::fmt;
+use std::net::SocketAddr;
 
 use chrono::{DateTime, Utc};
 
 use borsh::{BorshDeserialize, BorshSerialize};
 use near_crypto::{PublicKey, Signature};
+use near_rpc_error_macro::{RpcError, RpcSchema};
 
 use crate::account::{AccessKey, AccessKeyPermission, Account, FunctionCallPermission};
 use crate::block::{Approval, Block, BlockHeader, BlockHeaderInnerLite, BlockHeaderInnerRest};
 use crate::challenge::{Challenge, ChallengesResult};
-use crate::errors::TxExecutionError;
+use crate::errors::{InvalidTxError, TxExecutionError};
 use crate::hash::{hash, CryptoHash};
 use crate::logging;
 use crate::merkle::MerklePath;
+use crate::network::PeerId;
 use crate::receipt::{ActionReceipt, DataReceipt, DataReceiver, Receipt, ReceiptEnum};
 use crate::serialize::{
     from_base64, option_base64_format, option_u128_dec_format, to_base64, u128_dec_format,
 };
 use crate::sharding::{ChunkHash, ShardChunk, ShardChunkHeader, ShardChunkHeaderInner};
 use crate::transaction::{
-    Action, AddKeyAction, CreateAccountAction, DeleteAccountAction, DeleteKeyAction,
-    DeployContractAction, ExecutionOutcome, ExecutionOutcomeWithIdAndProof, ExecutionStatus,
-    FunctionCallAction, SignedTransaction, StakeAction, TransferAction,
+    Action, AddKeyAction, CreateAccountAction, DelegateAction, DeleteAccountAction,
+    DeleteKeyAction, DeployContractAction, ExecutionOutcome, ExecutionOutcomeWithIdAndProof,
+    ExecutionStatus, FunctionCallAction, GasProfile, SignedDelegateAction, SignedTransaction,
+    StakeAction, TransferAction,
 };
 use crate::types::{
     AccountId, Balance, BlockHeight, EpochId, FunctionArgs, Gas, Nonce, NumBlocks, ShardId,
-    StateChanges, StateRoot, StorageUsage, StoreKey, ValidatorStake, Version,
+    StateChangeCause, StateChanges, StateRoot, StorageUsage, StoreKey, ValidatorStake, Version,
 };
 
 /// A view of the account
-#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Eq, PartialEq, Clone)]
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Eq, PartialEq, Clone, RpcSchema,
+)]
 pub struct AccountView {
     #[serde(with = "u128_dec_format")]
     pub amount: Balance,
@@ -64,7 +70,9 @@ impl From<AccountView> for Account {
     }
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Eq, PartialEq, Clone)]
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Eq, PartialEq, Clone, RpcSchema,
+)]
 pub enum AccessKeyPermissionView {
     FunctionCall {
         #[serde(with = "option_u128_dec_format")]
@@ -103,7 +111,9 @@ impl From<AccessKeyPermissionView> for AccessKeyPermission {
     }
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Eq, PartialEq, Clone)]
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Eq, PartialEq, Clone, RpcSchema,
+)]
 pub struct AccessKeyView {
     pub nonce: Nonce,
     pub permission: AccessKeyPermissionView,
@@ -125,38 +135,50 @@ impl From<AccessKeyView> for AccessKey {
 pub type TrieProofPath = Vec<String>;
 
 /// Item of the state, key and value are serialized in base64 and proof for inclusion of given state item.
-#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone, RpcSchema,
+)]
 pub struct StateItem {
     pub key: String,
     pub value: String,
     pub proof: TrieProofPath,
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone, RpcSchema,
+)]
 pub struct ViewStateResult {
     pub values: Vec<StateItem>,
     pub proof: TrieProofPath,
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone, RpcSchema,
+)]
 pub struct CallResult {
     pub result: Vec<u8>,
     pub logs: Vec<String>,
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone, RpcSchema,
+)]
 pub struct QueryError {
     pub error: String,
     pub logs: Vec<String>,
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone, RpcSchema,
+)]
 pub struct AccessKeyInfoView {
     pub public_key: PublicKey,
     pub access_key: AccessKeyView,
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone, RpcSchema,
+)]
 pub struct AccessKeyList {
     pub keys: Vec<AccessKeyInfoView>,
 }
@@ -167,7 +189,9 @@ impl std::iter::FromIterator<AccessKeyInfoView> for AccessKeyList {
     }
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone, RpcSchema,
+)]
 #[serde(untagged)]
 pub enum QueryResponseKind {
     ViewAccount(AccountView),
@@ -178,17 +202,65 @@ pub enum QueryResponseKind {
     AccessKeyList(AccessKeyList),
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone, RpcSchema,
+)]
 #[serde(tag = "request_type", rename_all = "snake_case")]
 pub enum QueryRequest {
-    ViewAccount { account_id: AccountId },
-    ViewState { account_id: AccountId, prefix: StoreKey },
-    ViewAccessKey { account_id: AccountId, public_key: PublicKey },
-    ViewAccessKeyList { account_id: AccountId },
-    CallFunction { account_id: AccountId, method_name: String, args: FunctionArgs },
+    ViewAccount {
+        account_id: AccountId,
+    },
+    ViewState {
+        account_id: AccountId,
+        prefix: StoreKey,
+    },
+    ViewAccessKey {
+        account_id: AccountId,
+        public_key: PublicKey,
+    },
+    ViewAccessKeyList {
+        account_id: AccountId,
+    },
+    CallFunction {
+        account_id: AccountId,
+        method_name: String,
+        args: FunctionArgs,
+    },
+    // The caller isn't made of optional fields of `CallFunction` for wire compatibility: query
+    // requests are borsh encoded when routed to other nodes, so new fields would break the
+    // decoding of every `CallFunction` by the nodes that don't know about them. As a separate
+    // variant appended last, only the calls with a caller are rejected by these nodes.
+    /// Calls a view function on behalf of the given caller, see `CallerContext`. Unlike
+    /// `CallFunction`, it is not understood by the nodes that don't know about the caller.
+    CallFunctionWithCaller {
+        account_id: AccountId,
+        method_name: String,
+        args: FunctionArgs,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        signer_account_id: Option<AccountId>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        predecessor_account_id: Option<AccountId>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        signer_public_key: Option<PublicKey>,
+        #[serde(default, with = "option_u128_dec_format", skip_serializing_if = "Option::is_none")]
+        attached_deposit: Option<Balance>,
+    },
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+/// Caller of a view function call. The signer defaults to the called contract, the predecessor
+/// defaults to the signer, the public key defaults to an empty ED25519 key and the attached
+/// deposit defaults to zero.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CallerContext {
+    pub signer_account_id: Option<AccountId>,
+    pub predecessor_account_id: Option<AccountId>,
+    pub signer_public_key: Option<PublicKey>,
+    pub attached_deposit: Option<Balance>,
+}
+
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone, RpcSchema,
+)]
 pub struct QueryResponse {
     #[serde(flatten)]
     pub kind: QueryResponseKind,
@@ -196,7 +268,7 @@ pub struct QueryResponse {
     pub block_hash: CryptoHash,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, RpcSchema)]
 pub struct StatusSyncInfo {
     pub latest_block_hash: CryptoHash,
     pub latest_block_height: BlockHeight,
@@ -206,14 +278,14 @@ pub struct StatusSyncInfo {
 }
 
 // TODO: add more information to ValidatorInfo
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, RpcSchema)]
 pub struct ValidatorInfo {
     pub account_id: AccountId,
     pub is_slashed: bool,
 }
 
 // TODO: add more information to status.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, RpcSchema)]
 pub struct StatusResponse {
     /// Binary version.
     pub version: Version,
@@ -227,6 +299,87 @@ pub struct StatusResponse {
     pub sync_info: StatusSyncInfo,
 }
 
+/// A peer the node is connected to.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, RpcSchema)]
+pub struct PeerInfoView {
+    pub id: PeerId,
+    pub addr: Option<SocketAddr>,
+    pub account_id: Option<AccountId>,
+}
+
+/// A block or chunk producer known from the routing table.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, RpcSchema)]
+pub struct KnownProducerView {
+    pub account_id: AccountId,
+    pub addr: Option<SocketAddr>,
+    pub peer_id: PeerId,
+}
+
+/// Peer or range of IP addresses banned by the node.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, RpcSchema)]
+pub enum BanTargetView {
+    Peer(PeerId),
+    /// IP address or subnet, e.g. `10.0.0.0/8`.
+    Ip(String),
+}
+
+/// Information about current ban.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, RpcSchema)]
+pub struct BanInfoView {
+    pub target: BanTargetView,
+    /// Reason for the ban, e.g. `Manual` for the bans of the node operator.
+    pub reason: String,
+    pub banned_at: u64,
+}
+
+/// Active peers and bans of the node.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, RpcSchema)]
+pub struct PeersView {
+    pub active_peers: Vec<PeerInfoView>,
+    pub bans: Vec<BanInfoView>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, RpcSchema)]
+pub struct NetworkInfoView {
+    pub active_peers: Vec<PeerInfoView>,
+    pub num_active_peers: usize,
+    pub peer_max_count: u32,
+    pub sent_bytes_per_sec: u64,
+    pub received_bytes_per_sec: u64,
+    /// Accounts of known block and chunk producers from routing table.
+    pub known_producers: Vec<KnownProducerView>,
+}
+
+/// Accounts and shards tracked by the node once all the requested changes take effect.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, RpcSchema)]
+pub struct TrackedShardsView {
+    pub tracked_accounts: Vec<AccountId>,
+    pub tracked_shards: Vec<ShardId>,
+}
+
+/// Transactions and receipts involving an account in a block.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, RpcSchema)]
+pub struct AccountActivityView {
+    pub block_height: BlockHeight,
+    pub transactions: Vec<CryptoHash>,
+    pub receipts: Vec<CryptoHash>,
+}
+
+/// Activity of an account in consecutive blocks, starting from the newest one.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, RpcSchema)]
+pub struct AccountActivityPageView {
+    pub activity: Vec<AccountActivityView>,
+    /// Height to pass as `before_height` to get the next page, if there is one.
+    pub next_before_height: Option<BlockHeight>,
+}
+
+/// Transactions waiting in the pool of a shard to be included into a chunk.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, RpcSchema)]
+pub struct TransactionPoolView {
+    pub shard_id: ShardId,
+    pub transactions: Vec<SignedTransactionView>,
+}
+
 impl TryFrom<QueryResponse> for AccountView {
     type Error = String;
 
@@ -260,7 +413,7 @@ impl TryFrom<QueryResponse> for AccessKeyView {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, RpcSchema)]
 pub struct ChallengeView {
     // TODO: decide how to represent challenges in json.
 }
@@ -271,7 +424,7 @@ impl From<Challenge> for ChallengeView {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, RpcSchema)]
 pub struct BlockHeaderView {
     pub height: BlockHeight,
     pub epoch_id: CryptoHash,
@@ -428,7 +581,7 @@ impl From<BlockHeaderView> for BlockHeader {
     }
 }
 
-#[derive(Serialize, Debug, Clone, BorshDeserialize, BorshSerialize)]
+#[derive(Serialize, Debug, Clone, BorshDeserialize, BorshSerialize, RpcSchema)]
 pub struct BlockHeaderInnerLiteView {
     pub height: BlockHeight,
     pub epoch_id: CryptoHash,
@@ -439,7 +592,7 @@ pub struct BlockHeaderInnerLiteView {
     pub next_bp_hash: CryptoHash,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, RpcSchema)]
 pub struct ChunkHeaderView {
     pub chunk_hash: CryptoHash,
     pub prev_block_hash: CryptoHash,
@@ -523,7 +676,7 @@ impl From<ChunkHeaderView> for ShardChunkHeader {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, RpcSchema)]
 pub struct BlockView {
     pub author: AccountId,
     pub header: BlockHeaderView,
@@ -540,7 +693,7 @@ impl BlockView {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, RpcSchema)]
 pub struct ChunkView {
     pub author: AccountId,
     pub header: ChunkHeaderView,
@@ -559,7 +712,9 @@ impl ChunkView {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq, Eq)]
+#[derive(
+    Serialize, Deserialize, Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq, Eq, RpcSchema,
+)]
 pub enum ActionView {
     CreateAccount,
     DeployContract {
@@ -591,6 +746,15 @@ pub enum ActionView {
     DeleteAccount {
         beneficiary_id: AccountId,
     },
+    Delegate {
+        sender_id: AccountId,
+        receiver_id: AccountId,
+        actions: Vec<ActionView>,
+        nonce: Nonce,
+        max_block_height: BlockHeight,
+        public_key: PublicKey,
+        signature: Signature,
+    },
 }
 
 impl From<Action> for ActionView {
@@ -618,6 +782,18 @@ impl From<Action> for ActionView {
             Action::DeleteAccount(action) => {
                 ActionView::DeleteAccount { beneficiary_id: action.beneficiary_id }
             }
+            Action::Delegate(action) => {
+                let SignedDelegateAction { delegate_action, signature } = action;
+                ActionView::Delegate {
+                    sender_id: delegate_action.sender_id,
+                    receiver_id: delegate_action.receiver_id,
+                    actions: delegate_action.actions.into_iter().map(Into::into).collect(),
+                    nonce: delegate_action.nonce,
+                    max_block_height: delegate_action.max_block_height,
+                    public_key: delegate_action.public_key,
+                    signature,
+                }
+            }
         }
     }
 }
@@ -652,11 +828,35 @@ impl TryFrom<ActionView> for Action {
             ActionView::DeleteAccount { beneficiary_id } => {
                 Action::DeleteAccount(DeleteAccountAction { beneficiary_id })
             }
+            ActionView::Delegate {
+                sender_id,
+                receiver_id,
+                actions,
+                nonce,
+                max_block_height,
+                public_key,
+                signature,
+            } => Action::Delegate(SignedDelegateAction {
+                delegate_action: DelegateAction {
+                    sender_id,
+                    receiver_id,
+                    actions: actions
+                        .into_iter()
+                        .map(Action::try_from)
+                        .collect::<Result<Vec<_>, _>>()?,
+                    nonce,
+                    max_block_height,
+                    public_key,
+                },
+                signature,
+            }),
         })
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, BorshSerialize, BorshDeserialize, PartialEq, Eq, Clone)]
+#[derive(
+    Serialize, Deserialize, Debug, BorshSerialize, BorshDeserialize, PartialEq, Eq, Clone, RpcSchema,
+)]
 pub struct SignedTransactionView {
     pub signer_id: AccountId,
     pub public_key: PublicKey,
@@ -687,7 +887,9 @@ impl From<SignedTransaction> for SignedTransactionView {
     }
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Clone)]
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Clone, RpcSchema,
+)]
 pub enum FinalExecutionStatus {
     /// The execution has not yet started.
     NotStarted,
@@ -719,14 +921,44 @@ impl Default for FinalExecutionStatus {
     }
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+/// A general Server Error
+#[derive(
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+    Debug,
+    PartialEq,
+    Eq,
+    Clone,
+    RpcError,
+    RpcSchema,
+)]
 pub enum ServerError {
     TxExecutionError(TxExecutionError),
     Timeout,
     Closed,
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Clone)]
+impl fmt::Display for ServerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            ServerError::TxExecutionError(e) => write!(f, "ServerError: {}", e),
+            ServerError::Timeout => write!(f, "ServerError: Timeout"),
+            ServerError::Closed => write!(f, "ServerError: Closed"),
+        }
+    }
+}
+
+impl From<InvalidTxError> for ServerError {
+    fn from(e: InvalidTxError) -> ServerError {
+        ServerError::TxExecutionError(TxExecutionError::InvalidTxError(e))
+    }
+}
+
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Clone, RpcSchema,
+)]
 pub enum ExecutionStatusView {
     /// The execution is pending or unknown.
     Unknown,
@@ -768,7 +1000,9 @@ impl From<ExecutionStatus> for ExecutionStatusView {
     }
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq, Eq, RpcSchema,
+)]
 pub struct ExecutionOutcomeView {
     /// Execution status. Contains the result in case of successful execution.
     pub status: ExecutionStatusView,
@@ -778,6 +1012,11 @@ pub struct ExecutionOutcomeView {
     pub receipt_ids: Vec<CryptoHash>,
     /// The amount of the gas burnt by the given transaction or receipt.
     pub gas_burnt: Gas,
+    /// Breakdown of the gas spent by the given transaction or receipt, if the node that served
+    /// the request has gas profiling enabled. Not sent over the network.
+    #[borsh_skip]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_profile: Option<GasProfile>,
 }
 
 impl From<ExecutionOutcome> for ExecutionOutcomeView {
@@ -787,11 +1026,14 @@ impl From<ExecutionOutcome> for ExecutionOutcomeView {
             logs: outcome.logs,
             receipt_ids: outcome.receipt_ids,
             gas_burnt: outcome.gas_burnt,
+            gas_profile: None,
         }
     }
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone, RpcSchema,
+)]
 pub struct ExecutionOutcomeWithIdView {
     pub id: CryptoHash,
     pub outcome: ExecutionOutcomeView,
@@ -811,7 +1053,9 @@ impl From<ExecutionOutcomeWithIdAndProof> for ExecutionOutcomeWithIdView {
 }
 
 /// Final execution outcome of the transaction and all of subsequent the receipts.
-#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Clone)]
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Clone, RpcSchema,
+)]
 pub struct FinalExecutionOutcomeView {
     /// Execution status. Contains the result in case of successful execution.
     pub status: FinalExecutionStatus,
@@ -823,6 +1067,17 @@ pub struct FinalExecutionOutcomeView {
     pub receipts_outcome: Vec<ExecutionOutcomeWithIdView>,
 }
 
+impl FinalExecutionOutcomeView {
+    /// Drops the gas profiles from the outcomes of the transaction and its receipts.
+    pub fn without_gas_profile(mut self) -> Self {
+        self.transaction_outcome.outcome.gas_profile = None;
+        for receipt_outcome in self.receipts_outcome.iter_mut() {
+            receipt_outcome.outcome.gas_profile = None;
+        }
+        self
+    }
+}
+
 impl fmt::Debug for FinalExecutionOutcomeView {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_struct("FinalExecutionOutcome")
@@ -837,12 +1092,65 @@ impl fmt::Debug for FinalExecutionOutcomeView {
     }
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone)]
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, RpcSchema)]
 pub struct StateChangesView {
     pub changes: StateChanges,
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+/// A single change of the value under some key and the cause of the change.
+#[derive(Serialize, Deserialize, Debug, Clone, RpcSchema)]
+pub struct StateChangeWithCauseView {
+    pub cause: StateChangeCause,
+    /// The new value, `None` if the key was removed.
+    pub value: Option<Vec<u8>>,
+}
+
+/// All the changes of the value under the given key.
+#[derive(Serialize, Deserialize, Debug, Clone, RpcSchema)]
+pub struct StateChangesByKeyView {
+    pub key: Vec<u8>,
+    pub changes: Vec<StateChangeWithCauseView>,
+}
+
+impl StateChangesByKeyView {
+    pub fn from_state_changes(changes: StateChanges) -> Vec<Self> {
+        changes
+            .into_iter()
+            .map(|(key, changes)| StateChangesByKeyView {
+                key,
+                changes: changes
+                    .into_iter()
+                    .map(|(cause, value)| StateChangeWithCauseView { cause, value })
+                    .collect(),
+            })
+            .collect()
+    }
+}
+
+/// Changes of the state made in a block.
+#[derive(Serialize, Deserialize, Debug, Clone, RpcSchema)]
+pub struct StateChangesInBlockView {
+    pub block_hash: CryptoHash,
+    pub changes_by_key: Vec<StateChangesByKeyView>,
+}
+
+/// Result of executing a transaction on top of some block without applying it.
+#[derive(Serialize, Deserialize, Debug, Clone, RpcSchema)]
+pub struct TransactionSimulationView {
+    /// Outcomes of the transaction and of all the executed receipts. The outcomes have empty
+    /// proofs and refer to the block the transaction was executed on top of.
+    pub outcome: FinalExecutionOutcomeView,
+    /// Total gas burnt by the transaction and the executed receipts.
+    pub gas_burnt: Gas,
+    /// Receipts for the accounts in other shards, which were not executed.
+    pub pending_receipts: Vec<ReceiptView>,
+    /// State changes made by the transaction and the executed receipts.
+    pub changes_by_key: Vec<StateChangesByKeyView>,
+}
+
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, Eq, PartialEq, RpcSchema,
+)]
 pub struct ValidatorStakeView {
     pub account_id: AccountId,
     pub public_key: PublicKey,
@@ -862,7 +1170,7 @@ impl From<ValidatorStakeView> for ValidatorStake {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, RpcSchema)]
 pub struct ReceiptView {
     pub predecessor_id: AccountId,
     pub receiver_id: AccountId,
@@ -871,13 +1179,13 @@ pub struct ReceiptView {
     pub receipt: ReceiptEnumView,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, RpcSchema)]
 pub struct DataReceiverView {
     pub data_id: CryptoHash,
     pub receiver_id: AccountId,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, RpcSchema)]
 pub enum ReceiptEnumView {
     Action {
         signer_id: AccountId,
@@ -971,7 +1279,9 @@ impl TryFrom<ReceiptView> for Receipt {
 }
 
 /// Information about this epoch validators and next epoch validators
-#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone, RpcSchema,
+)]
 pub struct EpochValidatorInfo {
     /// Validators for the current epoch
     pub current_validators: Vec<CurrentEpochValidatorInfo>,
@@ -985,7 +1295,9 @@ pub struct EpochValidatorInfo {
     pub current_proposals: Vec<ValidatorStakeView>,
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone, RpcSchema,
+)]
 pub struct CurrentEpochValidatorInfo {
     pub account_id: AccountId,
     pub public_key: PublicKey,
@@ -997,7 +1309,9 @@ pub struct CurrentEpochValidatorInfo {
     pub num_expected_blocks: NumBlocks,
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone, RpcSchema,
+)]
 pub struct NextEpochValidatorInfo {
     pub account_id: AccountId,
     pub public_key: PublicKey,
@@ -1006,14 +1320,14 @@ pub struct NextEpochValidatorInfo {
     pub shards: Vec<ShardId>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, BorshDeserialize, BorshSerialize)]
+#[derive(Serialize, Deserialize, Debug, Clone, BorshDeserialize, BorshSerialize, RpcSchema)]
 pub struct LightClientApprovalView {
     pub parent_hash: CryptoHash,
     pub reference_hash: CryptoHash,
     pub signature: Signature,
 }
 
-#[derive(Serialize, Debug, Clone, BorshDeserialize, BorshSerialize)]
+#[derive(Serialize, Debug, Clone, BorshDeserialize, BorshSerialize, RpcSchema)]
 pub struct LightClientBlockView {
     pub inner_lite: BlockHeaderInnerLiteView,
     pub inner_rest_hash: CryptoHash,
@@ -1025,14 +1339,16 @@ pub struct LightClientBlockView {
     pub prev_hash: CryptoHash,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, RpcSchema)]
 pub struct GasPriceView {
     #[serde(with = "u128_dec_format")]
     pub gas_price: Balance,
 }
 
 /// Different types of finality.
-#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+#[derive(
+    Serialize, Deserialize, BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq, RpcSchema,
+)]
 pub enum Finality {
     #[serde(rename = "optimistic")]
     None,
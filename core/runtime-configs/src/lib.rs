@@ -4,7 +4,14 @@ use serde::{Deserialize, Serialize};
 use near_primitives::serialize::u128_dec_format;
 use near_primitives::types::{Balance, NumBlocks};
 use near_runtime_fees::RuntimeFeesConfig;
-use near_vm_logic::VMConfig;
+use near_vm_logic::{FloatPolicy, VMConfig};
+
+/// Protocol version since which NaNs produced by contracts are canonicalized, unless the genesis
+/// config sets another float policy.
+pub const NAN_CANONICALIZATION_PROTOCOL_VERSION: u32 = 5;
+
+/// Protocol version since which transactions can relay delegate actions.
+pub const DELEGATE_ACTION_PROTOCOL_VERSION: u32 = 5;
 
 /// The structure that holds the parameters of the runtime, mostly economics.
 #[derive(Default, Debug, Serialize, Deserialize, Clone)]
@@ -25,6 +32,9 @@ pub struct RuntimeConfig {
     /// This value represents `1,000` above adjusted to use per block.
     #[serde(with = "u128_dec_format")]
     pub account_length_baseline_cost_per_block: Balance,
+    /// Whether transactions can contain delegate actions. Set by the protocol version.
+    #[serde(skip)]
+    pub delegate_action_enabled: bool,
 }
 
 impl RuntimeConfig {
@@ -35,6 +45,18 @@ impl RuntimeConfig {
             transaction_costs: RuntimeFeesConfig::free(),
             wasm_config: VMConfig::free(),
             account_length_baseline_cost_per_block: 0,
+            delegate_action_enabled: false,
+        }
+    }
+
+    /// Config with the features enabled by the given protocol version.
+    pub fn for_protocol_version(mut self, protocol_version: u32) -> Self {
+        if protocol_version >= NAN_CANONICALIZATION_PROTOCOL_VERSION
+            && self.wasm_config.float_policy == FloatPolicy::Allow
+        {
+            self.wasm_config.float_policy = FloatPolicy::Canonicalize;
         }
+        self.delegate_action_enabled = protocol_version >= DELEGATE_ACTION_PROTOCOL_VERSION;
+        self
     }
 }
@@ -3,7 +3,7 @@ use rocksdb::{
     ReadOptions, WriteBatch, DB,
 };
 use std::cmp;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::io;
 use std::sync::RwLock;
 
@@ -84,6 +84,14 @@ pub enum DBCol {
     ColChunkPerHeightShard = 35,
     /// Changes to key-values that we have recorded.
     ColKeyValueChanges = 36,
+    /// Banned IP addresses and subnets.
+    ColBannedIps = 37,
+    /// Compiled contracts by code hash and VM config hash.
+    ColCachedContractCode = 38,
+    /// Transactions and receipts by account, block height and shard id.
+    ColAccountActivity = 39,
+    /// Gas profiles of the execution outcomes, only recorded if gas profiling is enabled.
+    ColGasProfiles = 40,
 }
 
 impl std::fmt::Display for DBCol {
@@ -126,12 +134,16 @@ impl std::fmt::Display for DBCol {
             Self::ColTransactions => "transactions",
             Self::ColChunkPerHeightShard => "hash of chunk per height and shard_id",
             Self::ColKeyValueChanges => "key value changes",
+            Self::ColBannedIps => "banned ip addresses",
+            Self::ColCachedContractCode => "cached compiled contracts",
+            Self::ColAccountActivity => "account activity",
+            Self::ColGasProfiles => "gas profiles",
         };
         write!(formatter, "{}", desc)
     }
 }
 
-const NUM_COLS: usize = 37;
+const NUM_COLS: usize = 41;
 
 pub struct DBTransaction {
     pub ops: Vec<DBOp>,
@@ -167,8 +179,10 @@ pub struct RocksDB {
 unsafe impl Send for RocksDB {}
 unsafe impl Sync for RocksDB {}
 
+/// In-memory database. Keys of the columns are ordered, so they are iterated in the same order
+/// as in RocksDB.
 pub struct TestDB {
-    db: RwLock<Vec<HashMap<Vec<u8>, Vec<u8>>>>,
+    db: RwLock<Vec<BTreeMap<Vec<u8>, Vec<u8>>>>,
 }
 
 pub trait Database: Sync + Send {
@@ -182,7 +196,17 @@ pub trait Database: Sync + Send {
         col: DBCol,
         key_prefix: &'a [u8],
     ) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a>;
+    /// Same as `iter_prefix`, starting from the first key not less than `start`, which has to
+    /// begin with the prefix.
+    fn iter_prefix_from<'a>(
+        &'a self,
+        col: DBCol,
+        key_prefix: &'a [u8],
+        start: &'a [u8],
+    ) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a>;
     fn write(&self, batch: DBTransaction) -> Result<(), DBError>;
+    /// Persists the data kept in memory, so nothing has to be replayed from the log on restart.
+    fn flush(&self) -> Result<(), DBError>;
 }
 
 impl Database for RocksDB {
@@ -205,6 +229,15 @@ impl Database for RocksDB {
         &'a self,
         col: DBCol,
         key_prefix: &'a [u8],
+    ) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> {
+        self.iter_prefix_from(col, key_prefix, key_prefix)
+    }
+
+    fn iter_prefix_from<'a>(
+        &'a self,
+        col: DBCol,
+        key_prefix: &'a [u8],
+        start: &'a [u8],
     ) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> {
         // NOTE: There is no Clone implementation for ReadOptions, so we cannot really reuse
         // `self.read_options` here.
@@ -219,7 +252,7 @@ impl Database for RocksDB {
                 .iterator_cf_opt(
                     cf_handle,
                     &read_options,
-                    IteratorMode::From(key_prefix, Direction::Forward),
+                    IteratorMode::From(start, Direction::Forward),
                 )
                 .unwrap()
                 .take_while(move |(key, _value)| key.starts_with(key_prefix));
@@ -241,6 +274,15 @@ impl Database for RocksDB {
         }
         Ok(self.db.write(batch)?)
     }
+
+    fn flush(&self) -> Result<(), DBError> {
+        for cf in self.cfs.iter() {
+            unsafe {
+                self.db.flush_cf(&**cf)?;
+            }
+        }
+        Ok(())
+    }
 }
 
 impl Database for TestDB {
@@ -264,6 +306,15 @@ impl Database for TestDB {
         Box::new(self.iter(col).filter(move |(key, _value)| key.starts_with(key_prefix)))
     }
 
+    fn iter_prefix_from<'a>(
+        &'a self,
+        col: DBCol,
+        key_prefix: &'a [u8],
+        start: &'a [u8],
+    ) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> {
+        Box::new(self.iter_prefix(col, key_prefix).filter(move |(key, _value)| &**key >= start))
+    }
+
     fn write(&self, transaction: DBTransaction) -> Result<(), DBError> {
         let mut db = self.db.write().unwrap();
         for op in transaction.ops {
@@ -274,6 +325,10 @@ impl Database for TestDB {
         }
         Ok(())
     }
+
+    fn flush(&self) -> Result<(), DBError> {
+        Ok(())
+    }
 }
 
 fn rocksdb_read_options() -> ReadOptions {
@@ -341,7 +396,7 @@ impl RocksDB {
 
 impl TestDB {
     pub fn new() -> Self {
-        let db: Vec<_> = (0..NUM_COLS).map(|_| HashMap::new()).collect();
+        let db: Vec<_> = (0..NUM_COLS).map(|_| BTreeMap::new()).collect();
         Self { db: RwLock::new(db) }
     }
 }
@@ -24,9 +24,9 @@ use near_primitives::utils::{
 
 use crate::db::{DBOp, DBTransaction, Database, RocksDB};
 pub use crate::trie::{
-    iterator::TrieIterator, update::PrefixKeyValueChanges, update::TrieUpdate,
-    update::TrieUpdateIterator, update::TrieUpdateValuePtr, PartialStorage, Trie, TrieChanges,
-    WrappedTrieChanges,
+    iterator::TrieIterator, update::ForkedChanges, update::PrefixKeyValueChanges,
+    update::TrieUpdate, update::TrieUpdateIterator, update::TrieUpdateValuePtr, PartialStorage,
+    Trie, TrieChanges, WrappedTrieChanges,
 };
 
 mod db;
@@ -69,6 +69,10 @@ impl Store {
         StoreUpdate::new(self.storage.clone())
     }
 
+    pub fn flush(&self) -> Result<(), io::Error> {
+        self.storage.flush().map_err(|e| e.into())
+    }
+
     pub fn iter<'a>(
         &'a self,
         column: DBCol,
@@ -88,6 +92,20 @@ impl Store {
         )
     }
 
+    /// Same as `iter_prefix_ser`, starting from the first key not less than `start`.
+    pub fn iter_prefix_from_ser<'a, T: BorshDeserialize>(
+        &'a self,
+        column: DBCol,
+        key_prefix: &'a [u8],
+        start: &'a [u8],
+    ) -> Box<dyn Iterator<Item = Result<(Vec<u8>, T), io::Error>> + 'a> {
+        Box::new(
+            self.storage
+                .iter_prefix_from(column, key_prefix, start)
+                .map(|(key, value)| Ok((key.to_vec(), T::try_from_slice(value.as_ref())?))),
+        )
+    }
+
     pub fn save_to_file(&self, column: DBCol, filename: &Path) -> Result<(), std::io::Error> {
         let mut file = File::create(filename)?;
         for (key, value) in self.storage.iter(column) {
@@ -215,6 +233,27 @@ pub fn read_with_cache<'a, T: BorshDeserialize + 'a>(
     Ok(None)
 }
 
+/// Version of the format of the data in the store.
+pub type DbVersion = u32;
+
+/// Current version of the store format. Increase it and add a migration whenever the encoding of
+/// stored values changes.
+pub const DB_VERSION: DbVersion = 1;
+
+const DB_VERSION_KEY: &[u8] = b"DB_VERSION";
+
+/// Returns the version of the store format or `None` if the store doesn't record it, which is the
+/// case for new stores and for stores written before the version was introduced.
+pub fn get_store_version(store: &Store) -> Result<Option<DbVersion>, io::Error> {
+    store.get_ser(ColBlockMisc, DB_VERSION_KEY)
+}
+
+pub fn set_store_version(store: &Store, version: DbVersion) -> Result<(), io::Error> {
+    let mut store_update = store.store_update();
+    store_update.set_ser(ColBlockMisc, DB_VERSION_KEY, &version)?;
+    store_update.commit()
+}
+
 pub fn create_store(path: &str) -> Arc<Store> {
     let db = Arc::new(RocksDB::new(path).expect("Failed to open the database"));
     Arc::new(Store::new(db))
@@ -418,7 +418,7 @@ impl RcTrieNode {
 }
 
 pub struct Trie {
-    storage: Box<dyn TrieStorage>,
+    storage: Arc<dyn TrieStorage>,
     pub counter: TouchedNodesCounter,
 }
 
@@ -579,7 +579,7 @@ impl WrappedTrieChanges {
 impl Trie {
     pub fn new(store: Arc<Store>) -> Self {
         Trie {
-            storage: Box::new(TrieCachingStorage::new(store)),
+            storage: Arc::new(TrieCachingStorage::new(store)),
             counter: TouchedNodesCounter::default(),
         }
     }
@@ -594,7 +594,13 @@ impl Trie {
             },
             recorded: Arc::new(Mutex::new(Default::default())),
         };
-        Trie { storage: Box::new(storage), counter: TouchedNodesCounter::default() }
+        Trie { storage: Arc::new(storage), counter: TouchedNodesCounter::default() }
+    }
+
+    /// Returns a trie that reads from the same storage, but counts the touched nodes separately.
+    /// Used to execute receipts concurrently without mixing up the touched nodes of each other.
+    pub fn with_separate_counter(&self) -> Self {
+        Trie { storage: Arc::clone(&self.storage), counter: TouchedNodesCounter::default() }
     }
 
     pub fn empty_root() -> StateRoot {
@@ -613,7 +619,7 @@ impl Trie {
         let recorded_storage =
             partial_storage.nodes.0.into_iter().map(|value| (hash(&value), value)).collect();
         Trie {
-            storage: Box::new(TrieMemoryPartialStorage {
+            storage: Arc::new(TrieMemoryPartialStorage {
                 recorded_storage,
                 visited_nodes: Default::default(),
             }),
@@ -75,7 +75,7 @@ where
     print!("Test touches {} nodes, expected result {:?}...", size, expected);
     for i in 0..(size + 1) {
         let storage = IncompletePartialStorage::new(storage.clone(), i);
-        let trie = Arc::new(Trie { storage: Box::new(storage), counter: Default::default() });
+        let trie = Arc::new(Trie { storage: Arc::new(storage), counter: Default::default() });
         let expected_result =
             if i < size { Err(&StorageError::TrieNodeMissing) } else { Ok(&expected) };
         assert_eq!(test(Arc::clone(&trie)).as_ref(), expected_result);
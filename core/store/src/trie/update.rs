@@ -13,12 +13,22 @@ use super::{Trie, TrieIterator};
 /// key that was updated -> the update.
 pub type TrieUpdates = BTreeMap<Vec<u8>, Option<Vec<u8>>>;
 
+/// Commits made to a fork of a `TrieUpdate`, in the order they were made.
+pub type ForkedChanges = Vec<(StateChangeCause, TrieUpdates)>;
+
 /// Provides a way to access Storage and record changes with future commit.
 pub struct TrieUpdate {
     pub trie: Arc<Trie>,
     root: CryptoHash,
-    committed: StateChanges,
+    /// Committed changes of the `TrieUpdate` this one was forked from. They are shared between
+    /// all the forks and are only read.
+    base: Option<Arc<StateChanges>>,
+    /// Shared with the forks while they are alive, so it is only cloned if it is changed while
+    /// there is a fork.
+    committed: Arc<StateChanges>,
     prospective: TrieUpdates,
+    /// Commits that were not taken yet. Only recorded for forks.
+    forked_changes: Option<ForkedChanges>,
 }
 
 pub enum TrieUpdateValuePtr<'a> {
@@ -48,7 +58,48 @@ pub type PrefixKeyValueChanges = HashMap<Vec<u8>, HashMap<Vec<u8>, Option<Vec<u8
 
 impl TrieUpdate {
     pub fn new(trie: Arc<Trie>, root: CryptoHash) -> Self {
-        TrieUpdate { trie, root, committed: Default::default(), prospective: Default::default() }
+        TrieUpdate {
+            trie,
+            root,
+            base: None,
+            committed: Default::default(),
+            prospective: Default::default(),
+            forked_changes: None,
+        }
+    }
+
+    /// Creates a view of this `TrieUpdate` that records its commits, so they can be replayed on
+    /// top of the original one with `apply_forked_changes`. The committed changes are shared with
+    /// the fork instead of being copied. The fork counts touched trie nodes separately, so it can
+    /// be used from another thread.
+    pub fn fork(&self) -> TrieUpdate {
+        assert!(self.prospective.is_empty(), "Uncommitted changes exist");
+        assert!(self.base.is_none(), "Forks can't be forked");
+        TrieUpdate {
+            trie: Arc::new(self.trie.with_separate_counter()),
+            root: self.root,
+            base: Some(self.committed.clone()),
+            committed: Default::default(),
+            prospective: Default::default(),
+            forked_changes: Some(vec![]),
+        }
+    }
+
+    /// Returns the commits made to this fork since the last call.
+    pub fn take_forked_changes(&mut self) -> ForkedChanges {
+        std::mem::replace(
+            self.forked_changes.as_mut().expect("Forked changes are only recorded for forks"),
+            vec![],
+        )
+    }
+
+    /// Replays the commits taken from a fork. Uncommitted changes of this `TrieUpdate` end up in
+    /// the first replayed commit, the same way as if the commits were made here.
+    pub fn apply_forked_changes(&mut self, changes: ForkedChanges) {
+        for (event, updates) in changes {
+            self.prospective.extend(updates);
+            self.commit(event);
+        }
     }
     pub fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, StorageError> {
         if let Some(value) = self.prospective.get(key) {
@@ -57,6 +108,10 @@ impl TrieUpdate {
             if let Some((_, last_change)) = changes.last() {
                 return Ok(last_change.as_ref().map(<Vec<u8>>::clone));
             }
+        } else if let Some(changes) = self.base.as_ref().and_then(|base| base.get(key)) {
+            if let Some((_, last_change)) = changes.last() {
+                return Ok(last_change.as_ref().map(<Vec<u8>>::clone));
+            }
         }
 
         self.trie.get(&self.root, key)
@@ -69,6 +124,10 @@ impl TrieUpdate {
             if let Some((_, last_change)) = changes.last() {
                 return Ok(last_change.as_ref().map(TrieUpdateValuePtr::MemoryRef));
             }
+        } else if let Some(changes) = self.base.as_ref().and_then(|base| base.get(key)) {
+            if let Some((_, last_change)) = changes.last() {
+                return Ok(last_change.as_ref().map(TrieUpdateValuePtr::MemoryRef));
+            }
         }
         self.trie.get_ref(&self.root, key).map(|option| {
             option.map(|(length, hash)| TrieUpdateValuePtr::HashAndSize(&self.trie, length, hash))
@@ -138,8 +197,12 @@ impl TrieUpdate {
     }
 
     pub fn commit(&mut self, event: StateChangeCause) {
+        if let Some(forked_changes) = self.forked_changes.as_mut() {
+            forked_changes.push((event.clone(), self.prospective.clone()));
+        }
+        let committed = Arc::make_mut(&mut self.committed);
         for (key, val) in std::mem::replace(&mut self.prospective, BTreeMap::new()).into_iter() {
-            self.committed.entry(key).or_default().push((event.clone(), val));
+            committed.entry(key).or_default().push((event.clone(), val));
         }
     }
 
@@ -149,6 +212,7 @@ impl TrieUpdate {
 
     pub fn finalize(self) -> Result<TrieChanges, StorageError> {
         assert!(self.prospective.is_empty(), "Finalize cannot be called with uncommitted changes.");
+        assert!(self.base.is_none(), "Forks can't be finalized");
         let TrieUpdate { trie, root, committed, .. } = self;
         trie.update(
             &root,
@@ -181,9 +245,11 @@ impl TrieUpdate {
     }
 }
 
+type OverlayIter<'a> = Box<dyn Iterator<Item = (&'a Vec<u8>, &'a Option<Vec<u8>>)> + 'a>;
+
 struct MergeIter<'a> {
-    left: Peekable<Box<dyn Iterator<Item = (&'a Vec<u8>, &'a Option<Vec<u8>>)> + 'a>>,
-    right: Peekable<Box<dyn Iterator<Item = (&'a Vec<u8>, &'a Option<Vec<u8>>)> + 'a>>,
+    left: Peekable<OverlayIter<'a>>,
+    right: Peekable<OverlayIter<'a>>,
 }
 
 impl<'a> Iterator for MergeIter<'a> {
@@ -237,8 +303,8 @@ impl<'a> TrieUpdateIterator<'a> {
             None => None,
         };
         trie_iter.seek(&start_offset)?;
-        let committed_iter =
-            state_update.committed.range(start_offset.clone()..).map(|(k, changes)| {
+        let last_changes = |changes: &'a StateChanges| -> OverlayIter<'a> {
+            Box::new(changes.range(start_offset.clone()..).map(|(k, changes)| {
                 (
                     k,
                     &changes
@@ -247,13 +313,20 @@ impl<'a> TrieUpdateIterator<'a> {
                         .expect("Committed entry should have at least one change.")
                         .1,
                 )
-            });
-        let prospective_iter = state_update.prospective.range(start_offset..);
-        let overlay_iter = MergeIter {
-            left: (Box::new(committed_iter) as Box<dyn Iterator<Item = _>>).peekable(),
-            right: (Box::new(prospective_iter) as Box<dyn Iterator<Item = _>>).peekable(),
-        }
-        .peekable();
+            }))
+        };
+        let committed_iter: OverlayIter<'a> = match state_update.base.as_ref() {
+            Some(base) => Box::new(MergeIter {
+                left: last_changes(&**base).peekable(),
+                right: last_changes(&*state_update.committed).peekable(),
+            }),
+            None => last_changes(&*state_update.committed),
+        };
+        let prospective_iter: OverlayIter<'a> =
+            Box::new(state_update.prospective.range(start_offset..));
+        let overlay_iter =
+            MergeIter { left: committed_iter.peekable(), right: prospective_iter.peekable() }
+                .peekable();
         Ok(TrieUpdateIterator {
             prefix: prefix.to_vec(),
             end_offset,
@@ -411,6 +484,39 @@ mod tests {
         assert_eq!(new_root, CryptoHash::default());
     }
 
+    #[test]
+    fn trie_fork() {
+        let trie = create_trie();
+        let cause1 = StateChangeCause::TransactionProcessing { tx_hash: CryptoHash::default() };
+        let cause2 = StateChangeCause::ReceiptProcessing { receipt_hash: CryptoHash::default() };
+        let mut trie_update = TrieUpdate::new(trie.clone(), CryptoHash::default());
+        trie_update.set(b"dog".to_vec(), b"puppy".to_vec());
+        trie_update.commit(cause1.clone());
+
+        let mut fork = trie_update.fork();
+        assert_eq!(fork.get(b"dog"), Ok(Some(b"puppy".to_vec())));
+        fork.set(b"cat".to_vec(), b"kitten".to_vec());
+        fork.commit(cause2.clone());
+        let keys: Result<Vec<_>, _> = fork.iter(b"").unwrap().collect();
+        assert_eq!(keys.unwrap(), vec![b"cat".to_vec(), b"dog".to_vec()]);
+        fork.set(b"cow".to_vec(), b"calf".to_vec());
+        fork.rollback();
+        let changes = fork.take_forked_changes();
+        assert_eq!(changes.len(), 1);
+        assert!(fork.take_forked_changes().is_empty());
+        assert_eq!(trie_update.get(b"cat"), Ok(None));
+
+        trie_update.remove(b"dog");
+        trie_update.apply_forked_changes(changes);
+        let committed = trie_update.committed_updates_per_cause();
+        assert_eq!(
+            committed[&b"dog".to_vec()],
+            vec![(cause1, Some(b"puppy".to_vec())), (cause2.clone(), None)]
+        );
+        assert_eq!(committed[&b"cat".to_vec()], vec![(cause2, Some(b"kitten".to_vec()))]);
+        assert!(!committed.contains_key(&b"cow".to_vec()));
+    }
+
     #[test]
     fn trie_iter() {
         let trie = create_trie();
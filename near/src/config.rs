@@ -16,7 +16,7 @@ use near_chain_configs::{GenesisConfig, GENESIS_CONFIG_VERSION, PROTOCOL_VERSION
 use near_crypto::{InMemorySigner, KeyFile, KeyType, PublicKey, Signer};
 use near_jsonrpc::RpcConfig;
 use near_network::test_utils::open_port;
-use near_network::types::ROUTED_MESSAGE_TTL;
+use near_network::types::{PeerInfo, RateLimitConfig, ROUTED_MESSAGE_TTL};
 use near_network::utils::blacklist_from_vec;
 use near_network::NetworkConfig;
 use near_primitives::account::AccessKey;
@@ -30,6 +30,7 @@ use near_primitives::validator_signer::{InMemoryValidatorSigner, ValidatorSigner
 use near_primitives::views::AccountView;
 use near_runtime_configs::RuntimeConfig;
 use near_telemetry::TelemetryConfig;
+use node_runtime::ReceiptsExecutionConfig;
 
 /// Initial balance used in tests.
 pub const TESTING_INIT_BALANCE: Balance = 1_000_000_000 * NEAR_BASE;
@@ -131,6 +132,12 @@ pub const NUM_BLOCK_PRODUCER_SEATS: NumSeats = 50;
 /// How much height horizon to give to consider peer up to date.
 pub const HIGHEST_PEER_HORIZON: u64 = 5;
 
+/// Maximum number of inbound connections from the same IP address.
+pub const MAX_INBOUND_PER_IP: u32 = 4;
+
+/// Maximum number of inbound connections from the same subnet.
+pub const MAX_INBOUND_PER_SUBNET: u32 = 16;
+
 pub const CONFIG_FILENAME: &str = "config.json";
 pub const GENESIS_CONFIG_FILENAME: &str = "genesis.json";
 pub const NODE_KEY_FILE: &str = "node_key.json";
@@ -162,6 +169,24 @@ pub struct Network {
     /// It can be IP:Port or IP (to blacklist all connections coming from this address).
     #[serde(default)]
     pub blacklist: Vec<String>,
+    /// List of sentry nodes this node is hidden behind, as `peer_id@ip:port`.
+    /// If not empty, this node will only connect to these nodes.
+    #[serde(default)]
+    pub sentry_nodes: Vec<String>,
+    /// List of the nodes hidden behind this node (i.e. this node is their sentry), as `peer_id`
+    /// or `peer_id@ip:port`. Their addresses will never be shared with other peers. If the
+    /// address is given, connections from them are accepted even at max capacity.
+    #[serde(default)]
+    pub hidden_peers: Vec<String>,
+    /// Maximum number of inbound connections from the same IP address.
+    #[serde(default = "default_max_inbound_per_ip")]
+    pub max_inbound_per_ip: u32,
+    /// Maximum number of inbound connections from the same /24 (IPv4) or /64 (IPv6) subnet.
+    #[serde(default = "default_max_inbound_per_subnet")]
+    pub max_inbound_per_subnet: u32,
+    /// Limits on messages and traffic exchanged with each peer.
+    #[serde(default)]
+    pub rate_limits: RateLimitConfig,
 }
 
 impl Default for Network {
@@ -176,10 +201,23 @@ impl Default for Network {
             skip_sync_wait: false,
             ban_window: Duration::from_secs(3 * 60 * 60),
             blacklist: vec![],
+            sentry_nodes: vec![],
+            hidden_peers: vec![],
+            max_inbound_per_ip: default_max_inbound_per_ip(),
+            max_inbound_per_subnet: default_max_inbound_per_subnet(),
+            rate_limits: RateLimitConfig::default(),
         }
     }
 }
 
+fn default_max_inbound_per_ip() -> u32 {
+    MAX_INBOUND_PER_IP
+}
+
+fn default_max_inbound_per_subnet() -> u32 {
+    MAX_INBOUND_PER_SUBNET
+}
+
 /// Serde default only supports functions without parameters.
 fn default_reduce_wait_for_missing_block() -> Duration {
     Duration::from_millis(REDUCE_DELAY_FOR_MISSING_BLOCKS)
@@ -247,6 +285,9 @@ pub struct Config {
     pub tracked_accounts: Vec<AccountId>,
     pub tracked_shards: Vec<ShardId>,
     pub archive: bool,
+    pub account_activity_index: bool,
+    pub gas_profiling: bool,
+    pub receipts_execution: ReceiptsExecutionConfig,
 }
 
 impl Default for Config {
@@ -263,6 +304,9 @@ impl Default for Config {
             tracked_accounts: vec![],
             tracked_shards: vec![],
             archive: false,
+            account_activity_index: false,
+            gas_profiling: false,
+            receipts_execution: ReceiptsExecutionConfig::default(),
         }
     }
 }
@@ -381,6 +425,7 @@ pub struct NearConfig {
     pub telemetry_config: TelemetryConfig,
     pub validator_signer: Option<Arc<dyn ValidatorSigner>>,
     pub genesis_config: GenesisConfig,
+    pub receipts_execution_config: ReceiptsExecutionConfig,
 }
 
 impl NearConfig {
@@ -389,8 +434,22 @@ impl NearConfig {
         genesis_config: &GenesisConfig,
         network_key_pair: KeyFile,
         validator_signer: Option<Arc<dyn ValidatorSigner>>,
-    ) -> Self {
-        NearConfig {
+    ) -> Result<Self, String> {
+        validate_genesis_config(genesis_config)?;
+        let boot_nodes = if config.network.boot_nodes.is_empty() {
+            vec![]
+        } else {
+            parse_peer_infos("boot_nodes", config.network.boot_nodes.split(','))?
+        };
+        let sentry_nodes = parse_peer_infos(
+            "sentry_nodes",
+            config.network.sentry_nodes.iter().map(String::as_str),
+        )?;
+        let hidden_peers = parse_peer_infos(
+            "hidden_peers",
+            config.network.hidden_peers.iter().map(String::as_str),
+        )?;
+        Ok(NearConfig {
             config: config.clone(),
             client_config: ClientConfig {
                 version: Default::default(),
@@ -426,6 +485,8 @@ impl NearConfig {
                 tracked_accounts: config.tracked_accounts,
                 tracked_shards: config.tracked_shards,
                 archive: config.archive,
+                account_activity_index: config.account_activity_index,
+                gas_profiling: config.gas_profiling,
             },
             network_config: NetworkConfig {
                 public_key: network_key_pair.public_key,
@@ -436,16 +497,7 @@ impl NearConfig {
                 } else {
                     Some(config.network.addr.parse().unwrap())
                 },
-                boot_nodes: if config.network.boot_nodes.is_empty() {
-                    vec![]
-                } else {
-                    config
-                        .network
-                        .boot_nodes
-                        .split(',')
-                        .map(|chunk| chunk.try_into().expect("Failed to parse PeerInfo"))
-                        .collect()
-                },
+                boot_nodes,
                 handshake_timeout: config.network.handshake_timeout,
                 reconnect_delay: config.network.reconnect_delay,
                 bootstrap_peers_period: Duration::from_secs(60),
@@ -461,15 +513,41 @@ impl NearConfig {
                 push_info_period: Duration::from_millis(100),
                 blacklist: blacklist_from_vec(&config.network.blacklist),
                 outbound_disabled: false,
+                sentry_nodes,
+                hidden_peers,
+                max_inbound_per_ip: config.network.max_inbound_per_ip,
+                max_inbound_per_subnet: config.network.max_inbound_per_subnet,
+                rate_limits: config.network.rate_limits.clone(),
             },
             telemetry_config: config.telemetry,
             rpc_config: config.rpc,
             genesis_config: genesis_config.clone(),
             validator_signer,
-        }
+            receipts_execution_config: config.receipts_execution,
+        })
     }
 }
 
+/// Checks the parts of the genesis config that the node can't run with.
+fn validate_genesis_config(genesis_config: &GenesisConfig) -> Result<(), String> {
+    genesis_config.shard_layout()?;
+    Ok(())
+}
+
+/// Parses the peers of the `field` of the network config, given as `peer_id[@ip:port]`.
+fn parse_peer_infos<'a>(
+    field: &str,
+    peer_infos: impl Iterator<Item = &'a str>,
+) -> Result<Vec<PeerInfo>, String> {
+    peer_infos
+        .map(|peer_info| {
+            peer_info.try_into().map_err(|err| {
+                format!("Failed to parse {:?} of network.{}: {}", peer_info, field, err)
+            })
+        })
+        .collect()
+}
+
 impl NearConfig {
     /// Test tool to save configs back to the folder.
     /// Useful for dynamic creating testnet configs and then saving them in different folders.
@@ -665,6 +743,7 @@ pub fn init_configs(
                 ),
                 avg_hidden_validator_seats_per_shard: (0..num_shards).map(|_| 0).collect(),
                 dynamic_resharding: false,
+                boundary_accounts: vec![],
                 epoch_length: if fast { FAST_EPOCH_LENGTH } else { EXPECTED_EPOCH_LENGTH },
                 gas_limit: INITIAL_GAS_LIMIT,
                 gas_price_adjustment_rate: GAS_PRICE_ADJUSTMENT_RATE,
@@ -790,7 +869,7 @@ pub fn init_testnet_configs(
     }
 }
 
-pub fn load_config(dir: &Path) -> NearConfig {
+pub fn load_config(dir: &Path) -> Result<NearConfig, String> {
     let config = Config::from_file(&dir.join(CONFIG_FILENAME));
     let genesis_config = GenesisConfig::from_file(
         &dir.join(config.genesis_file.clone()),
@@ -827,6 +906,7 @@ pub fn load_test_config(seed: &str, port: u16, genesis_config: &GenesisConfig) -
         (signer, Some(validator_signer))
     };
     NearConfig::new(config, &genesis_config, signer.into(), validator_signer)
+        .expect("Invalid test config")
 }
 
 #[cfg(test)]
@@ -845,4 +925,23 @@ mod test {
         assert_eq!(genesis_config.protocol_version, PROTOCOL_VERSION);
         assert_eq!(genesis_config.config_version, GENESIS_CONFIG_VERSION);
     }
+
+    #[test]
+    fn test_invalid_peer_infos() {
+        let peer_id = "ed25519:7rNEmDbkn8grQREdTt3PWhR1phNtsqJdgfV26XdR35QL";
+        let peers = parse_peer_infos("hidden_peers", vec![peer_id].into_iter()).unwrap();
+        assert_eq!(peers[0].addr, None);
+        assert!(parse_peer_infos("hidden_peers", vec!["not a peer id"].into_iter()).is_err());
+    }
+
+    #[test]
+    fn test_invalid_boundary_accounts() {
+        let mut genesis_config = GenesisConfig::test_sharded(vec!["test1", "test2"], 2, vec![1, 1]);
+        genesis_config.boundary_accounts = vec!["test2".to_string()];
+        assert!(validate_genesis_config(&genesis_config).is_ok());
+        genesis_config.boundary_accounts = vec!["test1".to_string(), "test2".to_string()];
+        assert!(validate_genesis_config(&genesis_config).is_err());
+        genesis_config.boundary_accounts = vec!["Test2".to_string()];
+        assert!(validate_genesis_config(&genesis_config).is_err());
+    }
 }
@@ -9,9 +9,12 @@ use log::info;
 
 use near_chain::ChainGenesis;
 use near_client::{ClientActor, ViewClientActor};
+use near_epoch_manager::migrate_block_info_add_epoch_height;
 use near_jsonrpc::start_http;
 use near_network::{NetworkRecipient, PeerManagerActor};
-use near_store::create_store;
+use near_store::{
+    create_store, get_store_version, set_store_version, ColBlockInfo, Store, DB_VERSION,
+};
 use near_telemetry::TelemetryActor;
 use tracing::trace;
 
@@ -62,18 +65,44 @@ fn get_expected_genesis_hash(home_dir: &Path) -> Option<String> {
     None
 }
 
+/// Brings a store written by an older version of the node to the current format.
+pub fn apply_store_migrations(store: &Store) {
+    let db_version = match get_store_version(store).expect("Failed to read the store version") {
+        Some(db_version) => db_version,
+        // A store without any blocks is new, otherwise it was written before the version was
+        // recorded.
+        None if store.iter(ColBlockInfo).next().is_none() => DB_VERSION,
+        None => 0,
+    };
+    if db_version > DB_VERSION {
+        panic!(
+            "The store version {} is newer than the version {} supported by this node",
+            db_version, DB_VERSION
+        );
+    }
+    if db_version < 1 {
+        info!(target: "near", "Migrating the store to version 1: adding epoch heights to block infos");
+        migrate_block_info_add_epoch_height(store).expect("Failed to migrate block infos");
+    }
+    if db_version != DB_VERSION {
+        set_store_version(store, DB_VERSION).expect("Failed to write the store version");
+    }
+}
+
 pub fn start_with_config(
     home_dir: &Path,
     config: NearConfig,
 ) -> (Addr<ClientActor>, Addr<ViewClientActor>) {
     let store = create_store(&get_store_path(home_dir));
+    apply_store_migrations(&store);
     near_primitives::test_utils::init_stop_on_panic();
-    let runtime = Arc::new(NightshadeRuntime::new(
+    let runtime = Arc::new(NightshadeRuntime::new_with_execution_config(
         home_dir,
         store.clone(),
         config.genesis_config.clone(),
         config.client_config.tracked_accounts.clone(),
         config.client_config.tracked_shards.clone(),
+        &config.receipts_execution_config,
     ));
 
     let telemetry = TelemetryActor::new(config.telemetry_config.clone()).start();
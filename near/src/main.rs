@@ -13,9 +13,15 @@ use tracing_subscriber::filter::LevelFilter;
 use tracing_subscriber::EnvFilter;
 
 use git_version::git_version;
-use near::config::init_testnet_configs;
+use near::config::{init_testnet_configs, Config, CONFIG_FILENAME};
 use near::{get_default_home, get_store_path, init_configs, load_config, start_with_config};
+use near_jsonrpc::client::new_client;
+use near_jsonrpc::set_log_filter_reloader;
+use near_network::peer_store::PeerStore;
+use near_network::types::{BanTarget, ReasonForBan};
+use near_primitives::rpc::RpcTrackingRequest;
 use near_primitives::types::Version;
+use near_store::create_store;
 
 fn init_logging(verbose: Option<&str>) {
     let mut env_filter = EnvFilter::new("tokio_reactor=info,near=info,stats=info");
@@ -43,10 +49,16 @@ fn init_logging(verbose: Option<&str>) {
         }
     }
 
-    tracing_subscriber::fmt::Subscriber::builder()
+    let builder = tracing_subscriber::fmt::Subscriber::builder()
         .with_env_filter(env_filter)
         .with_writer(io::stderr)
-        .init();
+        .with_filter_reloading();
+    let reload_handle = builder.reload_handle();
+    builder.init();
+    set_log_filter_reloader(move |filter| {
+        let env_filter = EnvFilter::try_new(filter).map_err(|err| err.to_string())?;
+        reload_handle.reload(env_filter).map_err(|err| err.to_string())
+    });
 }
 
 fn main() {
@@ -89,6 +101,21 @@ fn main() {
             .arg(Arg::with_name("telemetry-url").long("telemetry-url").help("Customize telemetry url").takes_value(true))
             .arg(Arg::with_name("archive").long("archive").help("Keep old blocks in the storage (default false)").takes_value(false))
         )
+        .subcommand(SubCommand::with_name("track").about("Starts tracking accounts and shards on a running node, they are tracked once their state is caught up, and updates config.json")
+            .arg(Arg::with_name("accounts").long("accounts").takes_value(true).help("Comma separated list of accounts to track"))
+            .arg(Arg::with_name("shards").long("shards").takes_value(true).help("Comma separated list of shards to track"))
+        )
+        .subcommand(SubCommand::with_name("untrack").about("Stops tracking accounts and shards on a running node from the next epoch, and updates config.json")
+            .arg(Arg::with_name("accounts").long("accounts").takes_value(true).help("Comma separated list of accounts to stop tracking"))
+            .arg(Arg::with_name("shards").long("shards").takes_value(true).help("Comma separated list of shards to stop tracking"))
+        )
+        .subcommand(SubCommand::with_name("bans").about("Lists banned peers and IP addresses (node must be stopped)"))
+        .subcommand(SubCommand::with_name("ban").about("Bans a peer, IP address or subnet (node must be stopped)")
+            .arg(Arg::with_name("target").required(true).takes_value(true).help("Peer id, IP address or subnet in CIDR notation"))
+        )
+        .subcommand(SubCommand::with_name("unban").about("Lifts ban of a peer or IP address (node must be stopped)")
+            .arg(Arg::with_name("target").required(true).takes_value(true).help("Peer id, IP address or subnet in CIDR notation"))
+        )
         .subcommand(SubCommand::with_name("unsafe_reset_data").about("(unsafe) Remove all the data, effectively resetting node to genesis state (keeps genesis and config)"))
         .subcommand(SubCommand::with_name("unsafe_reset_all").about("(unsafe) Remove all the config, keys, data and effectively removing all information about the network"))
         .get_matches();
@@ -159,7 +186,10 @@ fn main() {
         }
         ("run", Some(args)) => {
             // Load configs from home.
-            let mut near_config = load_config(home_dir);
+            let mut near_config = load_config(home_dir).unwrap_or_else(|err| {
+                error!("Invalid config: {}", err);
+                std::process::exit(1);
+            });
             // Set current version in client config.
             near_config.client_config.version = version;
             // Override some parameters from command line.
@@ -204,6 +234,78 @@ fn main() {
             start_with_config(home_dir, near_config);
             system.run().unwrap();
         }
+        (command @ "track", Some(args)) | (command @ "untrack", Some(args)) => {
+            let request = RpcTrackingRequest {
+                accounts: args
+                    .value_of("accounts")
+                    .map(|accounts| accounts.split(',').map(|a| a.trim().to_string()).collect())
+                    .unwrap_or_default(),
+                shards: args
+                    .value_of("shards")
+                    .map(|shards| {
+                        shards
+                            .split(',')
+                            .map(|s| s.trim().parse().expect("Failed to parse shard id"))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            };
+            let config_path = home_dir.join(CONFIG_FILENAME);
+            let mut config = Config::from_file(&config_path);
+            let admin_token =
+                config.rpc.admin_token.clone().expect("Admin token is not set in the rpc config");
+            let admin_addr = config
+                .rpc
+                .admin_addr
+                .clone()
+                .expect("Admin listener address is not set in the rpc config")
+                .replace("0.0.0.0", "127.0.0.1");
+            let mut client = new_client(&format!("http://{}", admin_addr));
+            let mut system = System::new("NEAR");
+            let result = system.block_on(if command == "track" {
+                client.admin_track(&admin_token, request)
+            } else {
+                client.admin_untrack(&admin_token, request)
+            });
+            match result {
+                Ok(tracked) => {
+                    println!("Tracked accounts: {:?}", tracked.tracked_accounts);
+                    println!("Tracked shards: {:?}", tracked.tracked_shards);
+                    // Keep the config in line with the node, so the tracking is the same if the
+                    // node is started again with a fresh database.
+                    config.tracked_accounts = tracked.tracked_accounts;
+                    config.tracked_shards = tracked.tracked_shards;
+                    config.write_to_file(&config_path);
+                }
+                Err(err) => {
+                    eprintln!("Failed to change tracking: {:?}", err);
+                    std::process::exit(1);
+                }
+            }
+        }
+        ("bans", Some(_args)) => {
+            let store = create_store(&get_store_path(home_dir));
+            let peer_store = PeerStore::new(store, &[]).expect("Failed to open peer store");
+            for ban in peer_store.bans() {
+                println!("{} {:?} {}", ban.target, ban.reason, ban.banned_at);
+            }
+        }
+        ("ban", Some(args)) => {
+            let target: BanTarget =
+                args.value_of("target").unwrap().parse().expect("Failed to parse ban target");
+            let store = create_store(&get_store_path(home_dir));
+            let mut peer_store = PeerStore::new(store, &[]).expect("Failed to open peer store");
+            peer_store.ban(&target, ReasonForBan::Manual).expect("Failed to ban");
+            info!(target: "near", "Banned {}", target);
+        }
+        ("unban", Some(args)) => {
+            let target: BanTarget =
+                args.value_of("target").unwrap().parse().expect("Failed to parse ban target");
+            let store = create_store(&get_store_path(home_dir));
+            let mut peer_store = PeerStore::new(store, &[]).expect("Failed to open peer store");
+            peer_store.unban(&target).expect("Failed to lift ban");
+            info!(target: "near", "Lifted ban of {}", target);
+        }
         ("unsafe_reset_data", Some(_args)) => {
             let store_path = get_store_path(home_dir);
             info!(target: "near", "Removing all data from {}", store_path);
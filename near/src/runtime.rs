@@ -19,32 +19,39 @@ use near_pool::types::PoolIterator;
 use near_primitives::account::{AccessKey, Account};
 use near_primitives::block::Approval;
 use near_primitives::challenge::{ChallengesResult, SlashedValidator};
-use near_primitives::errors::{InvalidTxError, RuntimeError};
+use near_primitives::errors::{EpochInfoProviderError, InvalidTxError, RuntimeError};
 use near_primitives::hash::{hash, CryptoHash};
 use near_primitives::receipt::Receipt;
 use near_primitives::serialize::from_base64;
+use near_primitives::shard_layout::ShardLayout;
 use near_primitives::sharding::ShardChunkHeader;
 use near_primitives::state_record::StateRecord;
-use near_primitives::transaction::SignedTransaction;
+use near_primitives::transaction::{ExecutionOutcomeWithIdAndProof, SignedTransaction};
 use near_primitives::types::{
-    AccountId, Balance, BlockHeight, EpochId, Gas, MerkleHash, NumShards, ShardId,
-    StateChangeCause, StateChanges, StateChangesRequest, StateRoot, StateRootNode, ValidatorStake,
-    ValidatorStats,
+    AccountId, Balance, BlockHeight, EpochHeight, EpochId, EpochInfoProvider, Gas, MerkleHash,
+    NumShards, ShardId, StateChangeCause, StateChanges, StateChangesRequest, StateRoot,
+    StateRootNode, ValidatorStake, ValidatorStats,
 };
 use near_primitives::utils::{prefix_for_access_key, ACCOUNT_DATA_SEPARATOR};
 use near_primitives::views::{
-    AccessKeyInfoView, CallResult, EpochValidatorInfo, QueryError, QueryRequest, QueryResponse,
-    QueryResponseKind, ViewStateResult,
+    AccessKeyInfoView, CallResult, CallerContext, EpochValidatorInfo, ExecutionOutcomeWithIdView,
+    ExecutionStatusView, FinalExecutionOutcomeView, FinalExecutionStatus, QueryError, QueryRequest,
+    QueryResponse, QueryResponseKind, StateChangesByKeyView, TrackedShardsView,
+    TransactionSimulationView, ViewStateResult,
 };
 use near_store::{
     get_access_key_raw, ColState, PartialStorage, Store, StoreUpdate, Trie, TrieUpdate,
     WrappedTrieChanges,
 };
 use node_runtime::adapter::ViewRuntimeAdapter;
+use node_runtime::cache::StoreCompiledContractCache;
 use node_runtime::state_viewer::TrieViewer;
-use node_runtime::{verify_and_charge_transaction, ApplyState, Runtime, ValidatorAccountsUpdate};
+use node_runtime::{
+    verify_and_charge_transaction, ApplyState, ReceiptsExecutionConfig, Runtime,
+    ValidatorAccountsUpdate,
+};
 
-use crate::shard_tracker::{account_id_to_shard_id, ShardTracker};
+use crate::shard_tracker::ShardTracker;
 
 const POISONED_LOCK_ERR: &str = "The lock was poisoned.";
 const STATE_DUMP_FILE: &str = "state_dump";
@@ -61,7 +68,8 @@ pub struct NightshadeRuntime {
     trie_viewer: TrieViewer,
     pub runtime: Runtime,
     epoch_manager: Arc<RwLock<EpochManager>>,
-    shard_tracker: ShardTracker,
+    shard_tracker: RwLock<ShardTracker>,
+    shard_layout: ShardLayout,
 }
 
 impl NightshadeRuntime {
@@ -71,14 +79,40 @@ impl NightshadeRuntime {
         genesis_config: GenesisConfig,
         initial_tracking_accounts: Vec<AccountId>,
         initial_tracking_shards: Vec<ShardId>,
+    ) -> Self {
+        Self::new_with_execution_config(
+            home_dir,
+            store,
+            genesis_config,
+            initial_tracking_accounts,
+            initial_tracking_shards,
+            &ReceiptsExecutionConfig::default(),
+        )
+    }
+
+    pub fn new_with_execution_config(
+        home_dir: &Path,
+        store: Arc<Store>,
+        genesis_config: GenesisConfig,
+        initial_tracking_accounts: Vec<AccountId>,
+        initial_tracking_shards: Vec<ShardId>,
+        receipts_execution_config: &ReceiptsExecutionConfig,
     ) -> Self {
         let trie = Arc::new(Trie::new(store.clone()));
-        let runtime = Runtime::new(genesis_config.runtime_config.clone());
+        let runtime = Runtime::new_with_execution_config(
+            genesis_config
+                .runtime_config
+                .clone()
+                .for_protocol_version(genesis_config.protocol_version),
+            receipts_execution_config,
+        );
         let trie_viewer = TrieViewer::new();
-        let num_shards = genesis_config.num_block_producer_seats_per_shard.len() as NumShards;
+        let shard_layout = genesis_config
+            .shard_layout()
+            .expect("Genesis config is validated when the node config is loaded");
         let initial_epoch_config = EpochConfig {
             epoch_length: genesis_config.epoch_length,
-            num_shards,
+            shard_layout: shard_layout.clone(),
             num_block_producer_seats: genesis_config.num_block_producer_seats,
             num_block_producer_seats_per_shard: genesis_config
                 .num_block_producer_seats_per_shard
@@ -119,13 +153,13 @@ impl NightshadeRuntime {
             )
             .expect("Failed to start Epoch Manager"),
         ));
-        let shard_tracker = ShardTracker::new(
+        let mut shard_tracker = ShardTracker::new(
             initial_tracking_accounts,
             initial_tracking_shards,
-            EpochId::default(),
             epoch_manager.clone(),
-            num_shards,
+            shard_layout.clone(),
         );
+        shard_tracker.load(&store).expect("Failed to read the tracked shards");
         NightshadeRuntime {
             genesis_config,
             home_dir: home_dir.to_path_buf(),
@@ -134,7 +168,8 @@ impl NightshadeRuntime {
             runtime,
             trie_viewer,
             epoch_manager,
-            shard_tracker,
+            shard_tracker: RwLock::new(shard_tracker),
+            shard_layout,
         }
     }
 
@@ -162,7 +197,7 @@ impl NightshadeRuntime {
         let mut shard_records: Vec<Vec<StateRecord>> = (0..num_shards).map(|_| vec![]).collect();
         let mut has_protocol_account = false;
         for record in self.genesis_config.records.iter() {
-            shard_records[state_record_to_shard_id(record, num_shards) as usize]
+            shard_records[state_record_to_shard_id(record, &self.shard_layout) as usize]
                 .push(record.clone());
             if let StateRecord::Account { account_id, .. } = record {
                 if account_id == &self.genesis_config.protocol_treasury_account {
@@ -217,6 +252,13 @@ impl NightshadeRuntime {
         gas_limit: Gas,
         challenges_result: &ChallengesResult,
     ) -> Result<ApplyTransactionResult, Error> {
+        let (epoch_id, epoch_height) = {
+            let mut epoch_manager = self.epoch_manager.write().expect(POISONED_LOCK_ERR);
+            (
+                epoch_manager.get_epoch_id_from_prev_block(prev_block_hash)?,
+                epoch_manager.get_epoch_height_from_prev_block(prev_block_hash)?,
+            )
+        };
         let validator_accounts_update = {
             let mut epoch_manager = self.epoch_manager.write().expect(POISONED_LOCK_ERR);
             debug!(target: "runtime",
@@ -288,7 +330,10 @@ impl NightshadeRuntime {
             epoch_length: self.genesis_config.epoch_length,
             gas_price,
             block_timestamp,
+            epoch_id,
+            epoch_height,
             gas_limit: Some(gas_limit),
+            cache: Some(Arc::new(StoreCompiledContractCache { store: self.store.clone() })),
         };
 
         let apply_result = self
@@ -300,6 +345,7 @@ impl NightshadeRuntime {
                 &apply_state,
                 &receipts,
                 &transactions,
+                self,
             )
             .map_err(|e| match e {
                 RuntimeError::InvalidTxError(_) => ErrorKind::InvalidTransactions,
@@ -312,6 +358,10 @@ impl NightshadeRuntime {
                 RuntimeError::StorageError(_) => ErrorKind::StorageError,
                 // TODO(#2152): process gracefully
                 RuntimeError::ReceiptValidationError(e) => panic!("{}", e),
+                RuntimeError::ValidatorError(EpochInfoProviderError::EpochOutOfBounds) => {
+                    ErrorKind::EpochOutOfBounds
+                }
+                RuntimeError::ValidatorError(e) => ErrorKind::ValidatorError(e.to_string()),
             })?;
 
         // Sort the receipts into appropriate outgoing shards.
@@ -334,6 +384,7 @@ impl NightshadeRuntime {
             ),
             new_root: apply_result.state_root,
             outcomes: apply_result.outcomes,
+            gas_profiles: apply_result.gas_profiles,
             receipt_result,
             validator_proposals: apply_result.validator_proposals,
             total_gas_burnt,
@@ -348,26 +399,25 @@ impl NightshadeRuntime {
     }
 }
 
-pub fn state_record_to_shard_id(state_record: &StateRecord, num_shards: NumShards) -> ShardId {
+pub fn state_record_to_shard_id(state_record: &StateRecord, shard_layout: &ShardLayout) -> ShardId {
     match &state_record {
         StateRecord::Account { account_id, .. }
         | StateRecord::AccessKey { account_id, .. }
         | StateRecord::Contract { account_id, .. }
         | StateRecord::ReceivedData { account_id, .. } => {
-            account_id_to_shard_id(account_id, num_shards)
+            shard_layout.account_id_to_shard_id(account_id)
         }
         StateRecord::Data { key, .. } => {
             let key = from_base64(key).unwrap();
             let separator = (1..key.len())
                 .find(|&x| key[x] == ACCOUNT_DATA_SEPARATOR[0])
                 .expect("Invalid data record");
-            account_id_to_shard_id(
+            shard_layout.account_id_to_shard_id(
                 &String::from_utf8(key[1..separator].to_vec()).expect("Must be account id"),
-                num_shards,
             )
         }
         StateRecord::PostponedReceipt(receipt) => {
-            account_id_to_shard_id(&receipt.receiver_id, num_shards)
+            shard_layout.account_id_to_shard_id(&receipt.receiver_id)
         }
     }
 }
@@ -437,8 +487,12 @@ impl RuntimeAdapter for NightshadeRuntime {
             epoch_length: self.genesis_config.epoch_length,
             gas_price,
             block_timestamp,
+            // NOTE: verify transaction doesn't execute contracts, so the epoch is not used
+            epoch_id: EpochId::default(),
+            epoch_height: 0,
             // NOTE: verify transaction doesn't use gas limit
             gas_limit: None,
+            cache: None,
         };
 
         match verify_and_charge_transaction(
@@ -474,7 +528,11 @@ impl RuntimeAdapter for NightshadeRuntime {
             epoch_length: self.genesis_config.epoch_length,
             gas_price,
             block_timestamp,
+            // NOTE: converting transactions into receipts doesn't execute contracts
+            epoch_id: EpochId::default(),
+            epoch_height: 0,
             gas_limit: Some(gas_limit),
+            cache: None,
         };
 
         // Total amount of gas burnt for converting transactions towards receipts.
@@ -736,7 +794,7 @@ impl RuntimeAdapter for NightshadeRuntime {
     }
 
     fn account_id_to_shard_id(&self, account_id: &AccountId) -> ShardId {
-        account_id_to_shard_id(account_id, self.num_shards())
+        self.shard_layout.account_id_to_shard_id(account_id)
     }
 
     fn get_part_owner(&self, parent_hash: &CryptoHash, part_id: u64) -> Result<String, Error> {
@@ -754,7 +812,8 @@ impl RuntimeAdapter for NightshadeRuntime {
         shard_id: ShardId,
         is_me: bool,
     ) -> bool {
-        self.shard_tracker.care_about_shard(account_id, parent_hash, shard_id, is_me)
+        let shard_tracker = self.shard_tracker.read().expect(POISONED_LOCK_ERR);
+        shard_tracker.care_about_shard(account_id, parent_hash, shard_id, is_me)
     }
 
     fn will_care_about_shard(
@@ -764,7 +823,36 @@ impl RuntimeAdapter for NightshadeRuntime {
         shard_id: ShardId,
         is_me: bool,
     ) -> bool {
-        self.shard_tracker.will_care_about_shard(account_id, parent_hash, shard_id, is_me)
+        let shard_tracker = self.shard_tracker.read().expect(POISONED_LOCK_ERR);
+        shard_tracker.will_care_about_shard(account_id, parent_hash, shard_id, is_me)
+    }
+
+    fn track_accounts_and_shards(
+        &self,
+        block_hash: &CryptoHash,
+        accounts: Vec<AccountId>,
+        shards: Vec<ShardId>,
+    ) -> Result<TrackedShardsView, Error> {
+        let mut shard_tracker = self.shard_tracker.write().expect(POISONED_LOCK_ERR);
+        shard_tracker.track_accounts_after_catchup(block_hash, accounts)?;
+        shard_tracker.track_shards_after_catchup(block_hash, shards)?;
+        shard_tracker.save(&self.store)?;
+        let (tracked_accounts, tracked_shards) = shard_tracker.tracked_after_pending();
+        Ok(TrackedShardsView { tracked_accounts, tracked_shards })
+    }
+
+    fn untrack_accounts_and_shards(
+        &self,
+        block_hash: &CryptoHash,
+        accounts: Vec<AccountId>,
+        shards: Vec<ShardId>,
+    ) -> Result<TrackedShardsView, Error> {
+        let mut shard_tracker = self.shard_tracker.write().expect(POISONED_LOCK_ERR);
+        shard_tracker.untrack_accounts(block_hash, accounts)?;
+        shard_tracker.untrack_shards(block_hash, shards)?;
+        shard_tracker.save(&self.store)?;
+        let (tracked_accounts, tracked_shards) = shard_tracker.tracked_after_pending();
+        Ok(TrackedShardsView { tracked_accounts, tracked_shards })
     }
 
     fn is_next_block_epoch_start(&self, parent_hash: &CryptoHash) -> Result<bool, Error> {
@@ -941,15 +1029,41 @@ impl RuntimeAdapter for NightshadeRuntime {
                     Err(e) => Err(e),
                 }
             }
-            QueryRequest::CallFunction { account_id, method_name, args } => {
+            QueryRequest::CallFunction { account_id, method_name, args }
+            | QueryRequest::CallFunctionWithCaller { account_id, method_name, args, .. } => {
+                let (epoch_id, epoch_height) = {
+                    let mut epoch_manager = self.epoch_manager.write().expect(POISONED_LOCK_ERR);
+                    (
+                        epoch_manager.get_epoch_id(block_hash)?,
+                        epoch_manager.get_block_info(block_hash)?.epoch_height,
+                    )
+                };
+                let caller = match request {
+                    QueryRequest::CallFunctionWithCaller {
+                        signer_account_id,
+                        predecessor_account_id,
+                        signer_public_key,
+                        attached_deposit,
+                        ..
+                    } => CallerContext {
+                        signer_account_id: signer_account_id.clone(),
+                        predecessor_account_id: predecessor_account_id.clone(),
+                        signer_public_key: signer_public_key.clone(),
+                        attached_deposit: *attached_deposit,
+                    },
+                    _ => CallerContext::default(),
+                };
                 let mut logs = vec![];
                 match self.call_function(
                     *state_root,
                     block_height,
                     block_timestamp,
+                    epoch_height,
+                    &epoch_id,
                     account_id,
                     method_name,
                     args.as_ref(),
+                    &caller,
                     &mut logs,
                 ) {
                     Ok(result) => Ok(QueryResponse {
@@ -1026,6 +1140,101 @@ impl RuntimeAdapter for NightshadeRuntime {
         }
     }
 
+    fn simulate_transaction(
+        &self,
+        shard_id: ShardId,
+        state_root: &StateRoot,
+        block_height: BlockHeight,
+        block_timestamp: u64,
+        block_hash: &CryptoHash,
+        gas_price: Balance,
+        gas_limit: Gas,
+        transaction: &SignedTransaction,
+        verify_signature: bool,
+    ) -> Result<TransactionSimulationView, Box<dyn std::error::Error>> {
+        let (epoch_id, epoch_height) = {
+            let mut epoch_manager = self.epoch_manager.write().expect(POISONED_LOCK_ERR);
+            (
+                epoch_manager.get_epoch_id(block_hash)?,
+                epoch_manager.get_block_info(block_hash)?.epoch_height,
+            )
+        };
+        let apply_state = ApplyState {
+            block_index: block_height,
+            epoch_length: self.genesis_config.epoch_length,
+            gas_price,
+            block_timestamp,
+            epoch_id,
+            epoch_height,
+            gas_limit: Some(gas_limit),
+            // Simulations come from the public RPC, so the compiled contracts are not cached.
+            cache: None,
+        };
+        let result = self
+            .runtime
+            .simulate_transaction(
+                self.trie.clone(),
+                *state_root,
+                &apply_state,
+                transaction,
+                verify_signature,
+                &|account_id: &AccountId| self.account_id_to_shard_id(account_id) == shard_id,
+                self,
+            )
+            .map_err(|err| match err {
+                RuntimeError::InvalidTxError(err) => format!("Invalid transaction: {}", err),
+                err => format!("Transaction simulation failed: {:?}", err),
+            })?;
+
+        let gas_burnt =
+            result.outcomes.iter().map(|outcome| outcome.outcome.gas_burnt).sum::<Gas>();
+        let mut gas_profiles: HashMap<_, _> = result.gas_profiles.into_iter().collect();
+        let mut outcomes = result
+            .outcomes
+            .into_iter()
+            .map(|outcome_with_id| {
+                let id = outcome_with_id.id;
+                let mut outcome: ExecutionOutcomeWithIdView = ExecutionOutcomeWithIdAndProof {
+                    outcome_with_id,
+                    proof: vec![],
+                    block_hash: *block_hash,
+                }
+                .into();
+                outcome.outcome.gas_profile = gas_profiles.remove(&id);
+                outcome
+            })
+            .collect::<Vec<_>>();
+        let mut looking_for_id = transaction.get_hash();
+        let status = loop {
+            match outcomes.iter().find(|outcome| outcome.id == looking_for_id) {
+                Some(outcome) => match &outcome.outcome.status {
+                    ExecutionStatusView::Unknown => break FinalExecutionStatus::Started,
+                    ExecutionStatusView::Failure(err) => {
+                        break FinalExecutionStatus::Failure(err.clone())
+                    }
+                    ExecutionStatusView::SuccessValue(value) => {
+                        break FinalExecutionStatus::SuccessValue(value.clone())
+                    }
+                    ExecutionStatusView::SuccessReceiptId(id) => looking_for_id = *id,
+                },
+                // The receipt is for another shard, so it was not executed.
+                None => break FinalExecutionStatus::Started,
+            }
+        };
+        let receipts_outcome = outcomes.split_off(1);
+        Ok(TransactionSimulationView {
+            outcome: FinalExecutionOutcomeView {
+                status,
+                transaction: transaction.clone().into(),
+                transaction_outcome: outcomes.pop().unwrap(),
+                receipts_outcome,
+            },
+            gas_burnt,
+            pending_receipts: result.pending_receipts.into_iter().map(Into::into).collect(),
+            changes_by_key: StateChangesByKeyView::from_state_changes(result.key_value_changes),
+        })
+    }
+
     fn get_validator_info(&self, block_hash: &CryptoHash) -> Result<EpochValidatorInfo, Error> {
         let mut epoch_manager = self.epoch_manager.write().expect(POISONED_LOCK_ERR);
         epoch_manager.get_validator_info(block_hash).map_err(|e| e.into())
@@ -1128,6 +1337,23 @@ impl RuntimeAdapter for NightshadeRuntime {
     }
 }
 
+impl EpochInfoProvider for NightshadeRuntime {
+    fn validator_stake(
+        &self,
+        epoch_id: &EpochId,
+        account_id: &AccountId,
+    ) -> Result<Option<Balance>, EpochInfoProviderError> {
+        let mut epoch_manager = self.epoch_manager.write().expect(POISONED_LOCK_ERR);
+        let validator = epoch_manager.get_validator_by_account_id(epoch_id, account_id)?;
+        Ok(validator.map(|validator| validator.stake))
+    }
+
+    fn validator_total_stake(&self, epoch_id: &EpochId) -> Result<Balance, EpochInfoProviderError> {
+        let mut epoch_manager = self.epoch_manager.write().expect(POISONED_LOCK_ERR);
+        Ok(epoch_manager.get_validator_total_stake(epoch_id)?)
+    }
+}
+
 impl node_runtime::adapter::ViewRuntimeAdapter for NightshadeRuntime {
     fn view_account(
         &self,
@@ -1143,9 +1369,12 @@ impl node_runtime::adapter::ViewRuntimeAdapter for NightshadeRuntime {
         state_root: MerkleHash,
         height: BlockHeight,
         block_timestamp: u64,
+        epoch_height: EpochHeight,
+        epoch_id: &EpochId,
         contract_id: &AccountId,
         method_name: &str,
         args: &[u8],
+        caller: &CallerContext,
         logs: &mut Vec<String>,
     ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
         let state_update = TrieUpdate::new(self.trie.clone(), state_root);
@@ -1153,9 +1382,13 @@ impl node_runtime::adapter::ViewRuntimeAdapter for NightshadeRuntime {
             state_update,
             height,
             block_timestamp,
+            epoch_height,
+            epoch_id,
+            self,
             contract_id,
             method_name,
             args,
+            caller,
             logs,
         )
     }
@@ -2368,4 +2601,24 @@ mod test {
             TESTING_INIT_BALANCE + protocol_treasury_reward
         );
     }
+
+    /// Genesis records are split between shards by the boundary accounts from the genesis config.
+    #[test]
+    fn test_genesis_boundary_accounts() {
+        let dir = TempDir::new("test_genesis_boundary_accounts").unwrap();
+        let store = create_store(&get_store_path(dir.path()));
+        let mut genesis_config =
+            GenesisConfig::test_sharded(vec!["test1", "test2", "test3"], 3, vec![2, 1]);
+        genesis_config.boundary_accounts = vec!["test2".to_string()];
+        let runtime = NightshadeRuntime::new(dir.path(), store, genesis_config, vec![], vec![]);
+        assert_eq!(runtime.account_id_to_shard_id(&"test1".to_string()), 0);
+        assert_eq!(runtime.account_id_to_shard_id(&"test2".to_string()), 1);
+        assert_eq!(runtime.account_id_to_shard_id(&"app.test2".to_string()), 1);
+        assert_eq!(runtime.account_id_to_shard_id(&"test3".to_string()), 1);
+        let (store_update, state_roots) = runtime.genesis_state();
+        store_update.commit().unwrap();
+        assert!(runtime.view_account(state_roots[0], &"test1".to_string()).is_ok());
+        assert!(runtime.view_account(state_roots[0], &"test3".to_string()).is_err());
+        assert!(runtime.view_account(state_roots[1], &"test3".to_string()).is_ok());
+    }
 }
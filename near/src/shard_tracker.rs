@@ -1,26 +1,42 @@
-use std::collections::{HashMap, HashSet};
-use std::io::Cursor;
-use std::sync::{Arc, RwLock};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io;
+use std::sync::{Arc, Mutex, RwLock};
 
-use byteorder::{LittleEndian, ReadBytesExt};
-use log::info;
+use borsh::{BorshDeserialize, BorshSerialize};
+use log::{info, warn};
 
 use near_epoch_manager::{EpochError, EpochManager};
-use near_primitives::hash::{hash, CryptoHash};
-use near_primitives::types::{AccountId, EpochId, NumShards, ShardId};
+use near_primitives::hash::CryptoHash;
+use near_primitives::shard_layout::ShardLayout;
+use near_primitives::types::{AccountId, EpochHeight, ShardId};
+use near_store::{ColBlockMisc, Store};
 
 const POISONED_LOCK_ERR: &str = "The lock was poisoned.";
 
-pub fn account_id_to_shard_id(account_id: &AccountId, num_shards: NumShards) -> ShardId {
-    let mut cursor = Cursor::new((hash(&account_id.clone().into_bytes()).0).0);
-    cursor.read_u64::<LittleEndian>().expect("Must not happened") % (num_shards)
+/// Key of the tracked accounts and shards changed on a running node.
+const TRACKING_KEY: &[u8] = b"TRACKING";
+
+/// Changes of the tracked accounts and shards that take effect together at some epoch.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Default)]
+struct PendingTracking {
+    track_accounts: Vec<AccountId>,
+    track_shards: Vec<ShardId>,
+    untrack_accounts: Vec<AccountId>,
+    untrack_shards: Vec<ShardId>,
 }
 
-/// Tracker that tracks shard ids and accounts. It maintains two items: `tracked_accounts` and
-/// `tracked_shards`. The shards that are actually tracked are the union of shards that `tracked_accounts`
-/// are in and `tracked_shards`.
-#[derive(Clone)]
-pub struct ShardTracker {
+/// Tracked accounts and shards with their changes scheduled on a running node, as they are
+/// stored. They take priority over the ones from the config.
+#[derive(BorshSerialize, BorshDeserialize)]
+struct StoredTracking {
+    tracked_accounts: Vec<AccountId>,
+    tracked_shards: Vec<ShardId>,
+    pending: Vec<(EpochHeight, PendingTracking)>,
+}
+
+/// Tracked accounts and shards at some epoch.
+#[derive(Clone, Default)]
+struct TrackedShards {
     /// Tracked accounts by shard id. For each shard id, the corresponding set of accounts should be
     /// non empty (otherwise the entry should not exist).
     tracked_accounts: HashMap<ShardId, HashSet<AccountId>>,
@@ -28,51 +44,11 @@ pub struct ShardTracker {
     tracked_shards: HashSet<ShardId>,
     /// Combination of shards that correspond to tracked accounts and tracked shards.
     actual_tracked_shards: HashSet<ShardId>,
-    /// Accounts that we stop tracking in the next epoch.
-    pending_untracked_accounts: HashSet<AccountId>,
-    /// Shards that we stop tracking in the next epoch.
-    pending_untracked_shards: HashSet<ShardId>,
-    /// Current epoch id. Used to determine whether we need to flush pending requests.
-    current_epoch_id: EpochId,
-    /// Epoch manager that for given block hash computes the epoch id.
-    epoch_manager: Arc<RwLock<EpochManager>>,
-    /// Number of shards in the system.
-    num_shards: NumShards,
 }
 
-impl ShardTracker {
-    pub fn new(
-        accounts: Vec<AccountId>,
-        shards: Vec<ShardId>,
-        epoch_id: EpochId,
-        epoch_manager: Arc<RwLock<EpochManager>>,
-        num_shards: NumShards,
-    ) -> Self {
-        let tracked_accounts = accounts.into_iter().fold(HashMap::new(), |mut acc, x| {
-            let shard_id = account_id_to_shard_id(&x, num_shards);
-            acc.entry(shard_id).or_insert_with(HashSet::new).insert(x);
-            acc
-        });
-        let tracked_shards: HashSet<_> = shards.into_iter().collect();
-        let mut actual_tracked_shards = tracked_shards.clone();
-        for (shard_id, _) in tracked_accounts.iter() {
-            actual_tracked_shards.insert(*shard_id);
-        }
-        info!(target: "runtime", "Tracking shards: {:?}", actual_tracked_shards);
-        ShardTracker {
-            tracked_accounts,
-            tracked_shards,
-            actual_tracked_shards,
-            pending_untracked_accounts: HashSet::default(),
-            pending_untracked_shards: HashSet::default(),
-            current_epoch_id: epoch_id,
-            epoch_manager,
-            num_shards,
-        }
-    }
-
-    fn track_account(&mut self, account_id: &AccountId) {
-        let shard_id = account_id_to_shard_id(account_id, self.num_shards);
+impl TrackedShards {
+    fn track_account(&mut self, account_id: &AccountId, shard_layout: &ShardLayout) {
+        let shard_id = shard_layout.account_id_to_shard_id(account_id);
         self.tracked_accounts
             .entry(shard_id)
             .or_insert_with(HashSet::new)
@@ -80,35 +56,17 @@ impl ShardTracker {
         self.actual_tracked_shards.insert(shard_id);
     }
 
-    /// Track a list of accounts. The tracking will take effect immediately because
-    /// even if we want to start tracking the accounts in the next epoch, it cannot harm
-    /// us to start tracking them earlier.
-    #[allow(unused)]
-    pub fn track_accounts(&mut self, account_ids: &[AccountId]) {
-        for account_id in account_ids.iter() {
-            self.track_account(account_id);
-        }
-    }
-
     fn track_shard(&mut self, shard_id: ShardId) {
         self.tracked_shards.insert(shard_id);
         self.actual_tracked_shards.insert(shard_id);
     }
 
-    /// Track a list of shards. Similar to tracking accounts, the tracking starts immediately.
-    #[allow(unused)]
-    pub fn track_shards(&mut self, shard_ids: &[ShardId]) {
-        for shard_id in shard_ids.iter() {
-            self.track_shard(*shard_id);
-        }
-    }
-
-    fn flush_pending(&mut self) {
+    fn apply(&mut self, pending: &PendingTracking, shard_layout: &ShardLayout) {
         let mut shards_to_remove = HashSet::new();
-        for account_id in self.pending_untracked_accounts.drain() {
-            let shard_id = account_id_to_shard_id(&account_id, self.num_shards);
+        for account_id in pending.untrack_accounts.iter() {
+            let shard_id = shard_layout.account_id_to_shard_id(account_id);
             self.tracked_accounts.entry(shard_id).and_modify(|e| {
-                e.remove(&account_id);
+                e.remove(account_id);
             });
             let to_remove = if let Some(accounts) = self.tracked_accounts.get(&shard_id) {
                 accounts.is_empty()
@@ -120,9 +78,9 @@ impl ShardTracker {
                 shards_to_remove.insert(shard_id);
             }
         }
-        for shard_id in self.pending_untracked_shards.drain() {
-            self.tracked_shards.remove(&shard_id);
-            shards_to_remove.insert(shard_id);
+        for shard_id in pending.untrack_shards.iter() {
+            self.tracked_shards.remove(shard_id);
+            shards_to_remove.insert(*shard_id);
         }
         for shard_id in shards_to_remove.drain() {
             if !self.tracked_accounts.contains_key(&shard_id)
@@ -131,50 +89,304 @@ impl ShardTracker {
                 self.actual_tracked_shards.remove(&shard_id);
             }
         }
+        for account_id in pending.track_accounts.iter() {
+            self.track_account(account_id, shard_layout);
+        }
+        for shard_id in pending.track_shards.iter() {
+            self.track_shard(*shard_id);
+        }
     }
 
-    fn update_epoch(&mut self, block_hash: &CryptoHash) -> Result<(), EpochError> {
-        let epoch_id = {
-            let mut epoch_manager = self.epoch_manager.write().expect(POISONED_LOCK_ERR);
-            epoch_manager.get_epoch_id(block_hash)?
+    /// Sorted tracked accounts and shards.
+    fn to_vecs(&self) -> (Vec<AccountId>, Vec<ShardId>) {
+        let mut accounts: Vec<_> = self.tracked_accounts.values().flatten().cloned().collect();
+        accounts.sort();
+        let mut shards: Vec<_> = self.tracked_shards.iter().cloned().collect();
+        shards.sort();
+        (accounts, shards)
+    }
+}
+
+/// Tracker that tracks shard ids and accounts. It maintains two items: `tracked_accounts` and
+/// `tracked_shards`. The shards that are actually tracked are the union of shards that `tracked_accounts`
+/// are in and `tracked_shards`.
+pub struct ShardTracker {
+    /// Accounts and shards tracked in the epochs before the first pending change.
+    tracked: TrackedShards,
+    /// Changes requested on a running node by the epoch height they take effect at.
+    pending: BTreeMap<EpochHeight, PendingTracking>,
+    /// Shards actually tracked from each epoch height of `pending` on. Recomputed when the
+    /// changes are requested, so checking a shard doesn't need to replay them.
+    scheduled_shards: BTreeMap<EpochHeight, HashSet<ShardId>>,
+    /// Accounts and shards tracked once all the pending changes take effect.
+    tracked_after_pending: TrackedShards,
+    /// Epoch height of the block following the last queried block, so that the epoch manager is
+    /// only consulted once per block while changes are pending.
+    last_epoch_height: Mutex<Option<(CryptoHash, EpochHeight)>>,
+    /// Epoch manager that for given block hash computes the epoch id.
+    epoch_manager: Arc<RwLock<EpochManager>>,
+    /// Assignment of accounts to shards.
+    shard_layout: ShardLayout,
+}
+
+impl ShardTracker {
+    pub fn new(
+        accounts: Vec<AccountId>,
+        shards: Vec<ShardId>,
+        epoch_manager: Arc<RwLock<EpochManager>>,
+        shard_layout: ShardLayout,
+    ) -> Self {
+        let mut tracked = TrackedShards::default();
+        for account_id in accounts.iter() {
+            tracked.track_account(account_id, &shard_layout);
+        }
+        for shard_id in shards {
+            tracked.track_shard(shard_id);
+        }
+        info!(target: "runtime", "Tracking shards: {:?}", tracked.actual_tracked_shards);
+        ShardTracker {
+            tracked_after_pending: tracked.clone(),
+            tracked,
+            pending: BTreeMap::default(),
+            scheduled_shards: BTreeMap::default(),
+            last_epoch_height: Mutex::new(None),
+            epoch_manager,
+            shard_layout,
+        }
+    }
+
+    /// Restores the tracked accounts and shards changed on a running node. The store is
+    /// authoritative: they replace the ones from the config, which the `track` and `untrack`
+    /// commands keep in line with the store.
+    pub fn load(&mut self, store: &Store) -> Result<(), io::Error> {
+        let stored: StoredTracking = match store.get_ser(ColBlockMisc, TRACKING_KEY)? {
+            Some(stored) => stored,
+            None => return Ok(()),
         };
-        if self.current_epoch_id != epoch_id {
-            // if epoch id has changed, we need to flush the pending removals
-            // and update the shards to track
-            self.flush_pending();
-            self.current_epoch_id = epoch_id;
+        let from_config = self.tracked.to_vecs();
+        let mut tracked = TrackedShards::default();
+        for account_id in stored.tracked_accounts.iter() {
+            tracked.track_account(account_id, &self.shard_layout);
+        }
+        for shard_id in stored.tracked_shards {
+            tracked.track_shard(shard_id);
         }
+        self.tracked = tracked;
+        self.pending = stored.pending.into_iter().collect();
+        self.update_schedule();
+        let restored = self.tracked_after_pending.to_vecs();
+        if restored != from_config {
+            warn!(
+                target: "runtime",
+                "Tracked accounts {:?} and shards {:?} changed on a running node take precedence over the ones of the config: {:?} and {:?}",
+                restored.0, restored.1, from_config.0, from_config.1
+            );
+        }
+        info!(
+            target: "runtime",
+            "Restored tracked shards {:?}, scheduled changes at epoch heights {:?}",
+            self.tracked.actual_tracked_shards,
+            self.pending.keys().collect::<Vec<_>>()
+        );
         Ok(())
     }
 
-    /// Stop tracking a list of accounts in the next epoch.
+    /// Persists the tracked accounts and shards with the scheduled changes, so they survive a
+    /// restart.
+    pub fn save(&self, store: &Store) -> Result<(), io::Error> {
+        let (tracked_accounts, tracked_shards) = self.tracked.to_vecs();
+        let stored = StoredTracking {
+            tracked_accounts,
+            tracked_shards,
+            pending: self
+                .pending
+                .iter()
+                .map(|(height, pending)| (*height, pending.clone()))
+                .collect(),
+        };
+        let mut store_update = store.store_update();
+        store_update.set_ser(ColBlockMisc, TRACKING_KEY, &stored)?;
+        store_update.commit()
+    }
+
+    /// Track a list of accounts. The tracking will take effect immediately because
+    /// even if we want to start tracking the accounts in the next epoch, it cannot harm
+    /// us to start tracking them earlier.
+    #[allow(unused)]
+    pub fn track_accounts(&mut self, account_ids: &[AccountId]) {
+        for account_id in account_ids.iter() {
+            self.tracked.track_account(account_id, &self.shard_layout);
+        }
+        self.update_schedule();
+    }
+
+    /// Track a list of shards. Similar to tracking accounts, the tracking starts immediately.
     #[allow(unused)]
+    pub fn track_shards(&mut self, shard_ids: &[ShardId]) {
+        for shard_id in shard_ids.iter() {
+            self.tracked.track_shard(*shard_id);
+        }
+        self.update_schedule();
+    }
+
+    /// Recomputes the shards tracked at every epoch with pending changes.
+    fn update_schedule(&mut self) {
+        let mut tracked = self.tracked.clone();
+        self.scheduled_shards.clear();
+        for (epoch_height, pending) in self.pending.iter() {
+            tracked.apply(pending, &self.shard_layout);
+            self.scheduled_shards.insert(*epoch_height, tracked.actual_tracked_shards.clone());
+        }
+        self.tracked_after_pending = tracked;
+    }
+
+    /// Applies the pending changes that take effect at or before the given epoch height.
+    fn flush_pending(&mut self, epoch_height: EpochHeight) {
+        let later = match epoch_height.checked_add(1) {
+            Some(next_epoch_height) => self.pending.split_off(&next_epoch_height),
+            None => BTreeMap::default(),
+        };
+        let pending = std::mem::replace(&mut self.pending, later);
+        for (_, pending) in pending {
+            self.tracked.apply(&pending, &self.shard_layout);
+        }
+        self.update_schedule();
+    }
+
+    fn get_epoch_height(&self, block_hash: &CryptoHash) -> Result<EpochHeight, EpochError> {
+        let mut epoch_manager = self.epoch_manager.write().expect(POISONED_LOCK_ERR);
+        Ok(epoch_manager.get_block_info(block_hash)?.epoch_height)
+    }
+
+    /// Applies the pending changes that are already in effect in the epoch of the given block.
+    fn update_epoch(&mut self, block_hash: &CryptoHash) -> Result<EpochHeight, EpochError> {
+        let epoch_height = self.get_epoch_height(block_hash)?;
+        self.flush_pending(epoch_height);
+        Ok(epoch_height)
+    }
+
+    /// Stop tracking a list of accounts in the next epoch.
     pub fn untrack_accounts(
         &mut self,
         block_hash: &CryptoHash,
         account_ids: Vec<AccountId>,
     ) -> Result<(), EpochError> {
-        self.update_epoch(block_hash)?;
+        let epoch_height = self.update_epoch(block_hash)?;
         for account_id in account_ids {
-            self.pending_untracked_accounts.insert(account_id);
+            for pending in self.pending.values_mut() {
+                pending.track_accounts.retain(|x| x != &account_id);
+            }
+            let pending = self.pending.entry(epoch_height + 1).or_default();
+            if !pending.untrack_accounts.contains(&account_id) {
+                pending.untrack_accounts.push(account_id);
+            }
         }
+        self.update_schedule();
         Ok(())
     }
 
     /// Stop tracking a list of shards in the next epoch.
-    #[allow(unused)]
     pub fn untrack_shards(
         &mut self,
         block_hash: &CryptoHash,
         shard_ids: Vec<ShardId>,
     ) -> Result<(), EpochError> {
-        self.update_epoch(block_hash)?;
+        let epoch_height = self.update_epoch(block_hash)?;
         for shard_id in shard_ids {
-            self.pending_untracked_shards.insert(shard_id);
+            for pending in self.pending.values_mut() {
+                pending.track_shards.retain(|x| *x != shard_id);
+            }
+            let pending = self.pending.entry(epoch_height + 1).or_default();
+            if !pending.untrack_shards.contains(&shard_id) {
+                pending.untrack_shards.push(shard_id);
+            }
+        }
+        self.update_schedule();
+        Ok(())
+    }
+
+    /// Start tracking a list of accounts on a running node. The state of their shards is
+    /// downloaded at the start of the next epoch and caught up during it, so the accounts are
+    /// tracked from the epoch after the next one.
+    pub fn track_accounts_after_catchup(
+        &mut self,
+        block_hash: &CryptoHash,
+        account_ids: Vec<AccountId>,
+    ) -> Result<(), EpochError> {
+        let epoch_height = self.update_epoch(block_hash)?;
+        for account_id in account_ids {
+            for pending in self.pending.values_mut() {
+                pending.untrack_accounts.retain(|x| x != &account_id);
+            }
+            let pending = self.pending.entry(epoch_height + 2).or_default();
+            if !pending.track_accounts.contains(&account_id) {
+                pending.track_accounts.push(account_id);
+            }
+        }
+        self.update_schedule();
+        Ok(())
+    }
+
+    /// Start tracking a list of shards on a running node, see `track_accounts_after_catchup`.
+    pub fn track_shards_after_catchup(
+        &mut self,
+        block_hash: &CryptoHash,
+        shard_ids: Vec<ShardId>,
+    ) -> Result<(), EpochError> {
+        let epoch_height = self.update_epoch(block_hash)?;
+        for shard_id in shard_ids {
+            for pending in self.pending.values_mut() {
+                pending.untrack_shards.retain(|x| *x != shard_id);
+            }
+            let pending = self.pending.entry(epoch_height + 2).or_default();
+            if !pending.track_shards.contains(&shard_id) {
+                pending.track_shards.push(shard_id);
+            }
         }
+        self.update_schedule();
         Ok(())
     }
 
+    /// Accounts and shards that are tracked once all the pending changes take effect.
+    pub fn tracked_after_pending(&self) -> (Vec<AccountId>, Vec<ShardId>) {
+        self.tracked_after_pending.to_vecs()
+    }
+
+    /// Height of the epoch of the block following `parent_hash`.
+    fn get_epoch_height_from_prev_block(&self, parent_hash: &CryptoHash) -> Option<EpochHeight> {
+        let mut last_epoch_height = self.last_epoch_height.lock().expect(POISONED_LOCK_ERR);
+        if let Some((hash, epoch_height)) = *last_epoch_height {
+            if &hash == parent_hash {
+                return Some(epoch_height);
+            }
+        }
+        let mut epoch_manager = self.epoch_manager.write().expect(POISONED_LOCK_ERR);
+        let epoch_height = epoch_manager.get_epoch_height_from_prev_block(parent_hash).ok()?;
+        *last_epoch_height = Some((*parent_hash, epoch_height));
+        Some(epoch_height)
+    }
+
+    /// Whether the shard is tracked in the epoch of the block following `parent_hash`, or in the
+    /// epoch after it if `next_epoch` is set.
+    fn tracks_shard(&self, parent_hash: &CryptoHash, shard_id: ShardId, next_epoch: bool) -> bool {
+        if self.pending.is_empty() {
+            return self.tracked.actual_tracked_shards.contains(&shard_id);
+        }
+        match self.get_epoch_height_from_prev_block(parent_hash) {
+            Some(epoch_height) => {
+                self.tracks_shard_at(epoch_height + if next_epoch { 1 } else { 0 }, shard_id)
+            }
+            None => self.tracked.actual_tracked_shards.contains(&shard_id),
+        }
+    }
+
+    fn tracks_shard_at(&self, epoch_height: EpochHeight, shard_id: ShardId) -> bool {
+        match self.scheduled_shards.range(..=epoch_height).next_back() {
+            Some((_, shards)) => shards.contains(&shard_id),
+            None => self.tracked.actual_tracked_shards.contains(&shard_id),
+        }
+    }
+
     pub fn care_about_shard(
         &self,
         account_id: Option<&AccountId>,
@@ -192,9 +404,9 @@ impl ShardTracker {
             if !is_me {
                 return account_cares_about_shard;
             }
-            account_cares_about_shard || self.actual_tracked_shards.contains(&shard_id)
+            account_cares_about_shard || self.tracks_shard(parent_hash, shard_id, false)
         } else {
-            self.actual_tracked_shards.contains(&shard_id)
+            self.tracks_shard(parent_hash, shard_id, false)
         }
     }
 
@@ -218,9 +430,7 @@ impl ShardTracker {
                 return true;
             }
         }
-        let mut tracker = self.clone();
-        tracker.flush_pending();
-        tracker.actual_tracked_shards.contains(&shard_id)
+        self.tracks_shard(parent_hash, shard_id, true)
     }
 }
 
@@ -232,10 +442,11 @@ mod tests {
     use near_crypto::{KeyType, PublicKey};
     use near_epoch_manager::{BlockInfo, EpochConfig, EpochManager, RewardCalculator};
     use near_primitives::hash::{hash, CryptoHash};
-    use near_primitives::types::{BlockHeight, EpochId, NumShards, ValidatorStake};
+    use near_primitives::shard_layout::ShardLayout;
+    use near_primitives::types::{BlockHeight, NumShards, ValidatorStake};
     use near_store::test_utils::create_test_store;
 
-    use super::{account_id_to_shard_id, ShardTracker, POISONED_LOCK_ERR};
+    use super::{ShardTracker, POISONED_LOCK_ERR};
 
     const DEFAULT_TOTAL_SUPPLY: u128 = 1_000_000_000_000;
 
@@ -243,7 +454,7 @@ mod tests {
         let store = create_test_store();
         let initial_epoch_config = EpochConfig {
             epoch_length: 1,
-            num_shards,
+            shard_layout: ShardLayout::v0(num_shards),
             num_block_producer_seats: 1,
             num_block_producer_seats_per_shard: vec![1],
             avg_hidden_validator_seats_per_shard: vec![],
@@ -306,29 +517,25 @@ mod tests {
     fn test_track_new_accounts_and_shards() {
         let num_shards = 4;
         let epoch_manager = get_epoch_manager(num_shards);
-        let mut tracker =
-            ShardTracker::new(vec![], vec![], EpochId::default(), epoch_manager, num_shards);
+        let shard_layout = ShardLayout::v0(num_shards);
+        let mut tracker = ShardTracker::new(vec![], vec![], epoch_manager, shard_layout.clone());
         tracker.track_accounts(&["test1".to_string(), "test2".to_string()]);
         tracker.track_shards(&[2, 3]);
         let mut total_tracked_shards = HashSet::new();
-        total_tracked_shards.insert(account_id_to_shard_id(&"test1".to_string(), num_shards));
-        total_tracked_shards.insert(account_id_to_shard_id(&"test2".to_string(), num_shards));
+        total_tracked_shards.insert(shard_layout.account_id_to_shard_id(&"test1".to_string()));
+        total_tracked_shards.insert(shard_layout.account_id_to_shard_id(&"test2".to_string()));
         total_tracked_shards.insert(2);
         total_tracked_shards.insert(3);
-        assert_eq!(tracker.actual_tracked_shards, total_tracked_shards);
+        assert_eq!(tracker.tracked.actual_tracked_shards, total_tracked_shards);
     }
 
     #[test]
     fn test_untrack_accounts() {
         let num_shards = 4;
         let epoch_manager = get_epoch_manager(num_shards);
-        let mut tracker = ShardTracker::new(
-            vec![],
-            vec![],
-            EpochId::default(),
-            epoch_manager.clone(),
-            num_shards,
-        );
+        let shard_layout = ShardLayout::v0(num_shards);
+        let mut tracker =
+            ShardTracker::new(vec![], vec![], epoch_manager.clone(), ShardLayout::v0(num_shards));
         tracker.track_accounts(&["test1".to_string(), "test2".to_string(), "test3".to_string()]);
         tracker.track_shards(&[2, 3]);
         {
@@ -343,24 +550,20 @@ mod tests {
         tracker.update_epoch(&hash(&[2])).unwrap();
 
         let mut total_tracked_shards = HashSet::new();
-        total_tracked_shards.insert(account_id_to_shard_id(&"test1".to_string(), num_shards));
+        total_tracked_shards.insert(shard_layout.account_id_to_shard_id(&"test1".to_string()));
         total_tracked_shards.insert(2);
         total_tracked_shards.insert(3);
 
-        assert_eq!(tracker.actual_tracked_shards, total_tracked_shards);
+        assert_eq!(tracker.tracked.actual_tracked_shards, total_tracked_shards);
     }
 
     #[test]
     fn test_untrack_shards() {
         let num_shards = 4;
         let epoch_manager = get_epoch_manager(num_shards);
-        let mut tracker = ShardTracker::new(
-            vec![],
-            vec![],
-            EpochId::default(),
-            epoch_manager.clone(),
-            num_shards,
-        );
+        let shard_layout = ShardLayout::v0(num_shards);
+        let mut tracker =
+            ShardTracker::new(vec![], vec![], epoch_manager.clone(), ShardLayout::v0(num_shards));
         tracker.track_accounts(&["test1".to_string(), "test2".to_string(), "test3".to_string()]);
         tracker.track_shards(&[2, 3]);
         {
@@ -375,9 +578,63 @@ mod tests {
         let mut total_tracked_shards = HashSet::new();
         for account_id in vec!["test1", "test2", "test3"] {
             total_tracked_shards
-                .insert(account_id_to_shard_id(&account_id.to_string(), num_shards));
+                .insert(shard_layout.account_id_to_shard_id(&account_id.to_string()));
         }
 
-        assert_eq!(tracker.actual_tracked_shards, total_tracked_shards);
+        assert_eq!(tracker.tracked.actual_tracked_shards, total_tracked_shards);
+    }
+
+    #[test]
+    fn test_track_shards_after_catchup() {
+        let num_shards = 4;
+        let epoch_manager = get_epoch_manager(num_shards);
+        let mut tracker =
+            ShardTracker::new(vec![], vec![0], epoch_manager.clone(), ShardLayout::v0(num_shards));
+        let epoch_height = {
+            let mut epoch_manager = epoch_manager.write().expect(POISONED_LOCK_ERR);
+            record_block(&mut epoch_manager, CryptoHash::default(), hash(&[0]), 0, vec![]);
+            record_block(&mut epoch_manager, hash(&[0]), hash(&[1]), 1, vec![]);
+            epoch_manager.get_block_info(&hash(&[1])).unwrap().epoch_height
+        };
+        tracker.track_shards_after_catchup(&hash(&[1]), vec![1, 2]).unwrap();
+        tracker.untrack_shards(&hash(&[1]), vec![0, 2]).unwrap();
+
+        // Newly tracked shards are tracked only once their state is caught up.
+        assert!(!tracker.tracks_shard_at(epoch_height + 1, 1));
+        assert!(tracker.tracks_shard_at(epoch_height + 2, 1));
+        // Untracking cancels the pending tracking.
+        assert!(!tracker.tracks_shard_at(epoch_height + 2, 2));
+        // Untracked shards are still tracked till the end of the epoch.
+        assert!(tracker.tracks_shard_at(epoch_height, 0));
+        assert!(!tracker.tracks_shard_at(epoch_height + 1, 0));
+        assert_eq!(tracker.tracked_after_pending(), (vec![], vec![1]));
+    }
+
+    #[test]
+    fn test_save_and_load_tracking() {
+        let num_shards = 4;
+        let epoch_manager = get_epoch_manager(num_shards);
+        let store = create_test_store();
+        let mut tracker =
+            ShardTracker::new(vec![], vec![0], epoch_manager.clone(), ShardLayout::v0(num_shards));
+        let epoch_height = {
+            let mut epoch_manager = epoch_manager.write().expect(POISONED_LOCK_ERR);
+            record_block(&mut epoch_manager, CryptoHash::default(), hash(&[0]), 0, vec![]);
+            record_block(&mut epoch_manager, hash(&[0]), hash(&[1]), 1, vec![]);
+            epoch_manager.get_block_info(&hash(&[1])).unwrap().epoch_height
+        };
+        tracker.track_shards_after_catchup(&hash(&[1]), vec![1]).unwrap();
+        tracker.untrack_shards(&hash(&[1]), vec![0]).unwrap();
+        tracker.save(&store).unwrap();
+
+        // The changes from the store replace the tracked shards from the config.
+        let mut restored =
+            ShardTracker::new(vec![], vec![2], epoch_manager, ShardLayout::v0(num_shards));
+        restored.load(&store).unwrap();
+        assert!(restored.tracks_shard_at(epoch_height, 0));
+        assert!(!restored.tracks_shard_at(epoch_height, 2));
+        assert!(!restored.tracks_shard_at(epoch_height + 1, 0));
+        assert!(restored.tracks_shard_at(epoch_height + 2, 1));
+        assert_eq!(restored.tracked_after_pending(), tracker.tracked_after_pending());
     }
 }
@@ -94,6 +94,9 @@ pub struct ActionCreationConfig {
 
     /// Base cost of deleting an account.
     pub delete_account_cost: Fee,
+
+    /// Base cost of relaying a delegate action, on top of the fees of its inner actions.
+    pub delegate_cost: Fee,
 }
 
 /// Describes the cost of creating an access key.
@@ -179,6 +182,13 @@ impl Default for RuntimeFeesConfig {
                     send_not_sir: 454830000000,
                     execution: 454830000000,
                 },
+                // TODO: Provisional until the estimator measures `ActionDelegate`. The sending
+                // part now covers the signature verification of the delegate action.
+                delegate_cost: Fee {
+                    send_sir: 200000000000,
+                    send_not_sir: 200000000000,
+                    execution: 200000000000,
+                },
             },
             storage_usage_config: StorageUsageConfig {
                 num_bytes_account: 100,
@@ -213,6 +223,7 @@ impl RuntimeFeesConfig {
                 },
                 delete_key_cost: free.clone(),
                 delete_account_cost: free.clone(),
+                delegate_cost: free.clone(),
             },
             storage_usage_config: StorageUsageConfig {
                 num_bytes_account: 0,
@@ -1,5 +1,5 @@
 use borsh::{BorshDeserialize, BorshSerialize};
-use near_rpc_error_macro::RpcError;
+use near_rpc_error_macro::{RpcError, RpcSchema};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
@@ -14,7 +14,16 @@ pub enum VMError {
 }
 
 #[derive(
-    Debug, Clone, PartialEq, Eq, BorshDeserialize, BorshSerialize, Deserialize, Serialize, RpcError,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshDeserialize,
+    BorshSerialize,
+    Deserialize,
+    Serialize,
+    RpcError,
+    RpcSchema,
 )]
 pub enum FunctionCallError {
     CompilationError(CompilationError),
@@ -25,7 +34,16 @@ pub enum FunctionCallError {
 }
 
 #[derive(
-    Debug, Clone, PartialEq, Eq, BorshDeserialize, BorshSerialize, Deserialize, Serialize, RpcError,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshDeserialize,
+    BorshSerialize,
+    Deserialize,
+    Serialize,
+    RpcError,
+    RpcSchema,
 )]
 pub enum MethodResolveError {
     MethodEmptyName,
@@ -35,7 +53,16 @@ pub enum MethodResolveError {
 }
 
 #[derive(
-    Debug, Clone, PartialEq, Eq, BorshDeserialize, BorshSerialize, Deserialize, Serialize, RpcError,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshDeserialize,
+    BorshSerialize,
+    Deserialize,
+    Serialize,
+    RpcError,
+    RpcSchema,
 )]
 pub enum CompilationError {
     CodeDoesNotExist { account_id: String },
@@ -44,7 +71,16 @@ pub enum CompilationError {
 }
 
 #[derive(
-    Debug, Clone, PartialEq, Eq, BorshDeserialize, BorshSerialize, Deserialize, Serialize, RpcError,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshDeserialize,
+    BorshSerialize,
+    Deserialize,
+    Serialize,
+    RpcError,
+    RpcSchema,
 )]
 /// Error that can occur while preparing or executing Wasm smart-contract.
 pub enum PrepareError {
@@ -69,10 +105,21 @@ pub enum PrepareError {
     Instantiate,
     /// Error creating memory.
     Memory,
+    /// Floating point operations are disallowed by the config.
+    FloatingPoint,
 }
 
 #[derive(
-    Debug, Clone, PartialEq, Eq, BorshDeserialize, BorshSerialize, Deserialize, Serialize, RpcError,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshDeserialize,
+    BorshSerialize,
+    Deserialize,
+    Serialize,
+    RpcError,
+    RpcSchema,
 )]
 pub enum HostError {
     /// String encoding is bad UTF-16 sequence
@@ -185,6 +232,7 @@ impl fmt::Display for PrepareError {
             StackHeightInstrumentation => write!(f, "Stack instrumentation failed."),
             Instantiate => write!(f, "Error happened during instantiation."),
             Memory => write!(f, "Error creating memory"),
+            FloatingPoint => write!(f, "Floating point operations are not allowed."),
         }
     }
 }
@@ -15,6 +15,49 @@ pub struct VMConfig {
 
     /// Describes limits for VM and Runtime.
     pub limit_config: VMLimitConfig,
+
+    /// Engine used to execute contracts.
+    #[serde(default)]
+    pub vm_kind: VMKind,
+
+    /// How contracts using floating point operations are handled.
+    #[serde(default)]
+    pub float_policy: FloatPolicy,
+}
+
+/// Engine that executes contracts.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum VMKind {
+    /// Wasmer with the singlepass compiler.
+    Wasmer,
+    /// Wasmi interpreter. Only available with the `wasmi_vm` feature of `near-vm-runner` and used
+    /// to test wasmer against. Nodes must not use it until it is guaranteed to report the same
+    /// errors as wasmer.
+    Wasmi,
+}
+
+impl Default for VMKind {
+    fn default() -> Self {
+        VMKind::Wasmer
+    }
+}
+
+/// Floating point operations can produce NaNs with different bit patterns on different CPUs,
+/// which would make the results of contracts depend on the hardware of the node.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum FloatPolicy {
+    /// Floating point operations are executed as is by the host.
+    Allow,
+    /// Every NaN produced by an arithmetic operation is replaced with the canonical NaN.
+    Canonicalize,
+    /// Contracts with floating point operations are rejected.
+    Reject,
+}
+
+impl Default for FloatPolicy {
+    fn default() -> Self {
+        FloatPolicy::Allow
+    }
 }
 
 /// Describes limits for VM and Runtime.
@@ -83,6 +126,8 @@ impl Default for VMConfig {
             grow_mem_cost: 1,
             regular_op_cost: 3856371,
             limit_config: VMLimitConfig::default(),
+            vm_kind: VMKind::default(),
+            float_policy: FloatPolicy::default(),
         }
     }
 }
@@ -107,6 +152,8 @@ impl VMConfig {
                 max_gas_burnt_view: std::u64::MAX,
                 ..Default::default()
             },
+            vm_kind: VMKind::default(),
+            float_policy: FloatPolicy::default(),
         }
     }
 }
@@ -206,6 +253,29 @@ pub struct ExtCostsConfig {
     /// Cost of getting sha256 per byte
     pub keccak512_byte: Gas,
 
+    /// Cost of getting ripemd160 base
+    pub ripemd160_base: Gas,
+    /// Cost of getting ripemd160 per byte
+    pub ripemd160_byte: Gas,
+
+    /// Cost of getting blake2b base
+    pub blake2b_base: Gas,
+    /// Cost of getting blake2b per byte
+    pub blake2b_byte: Gas,
+
+    /// Cost of verifying ed25519 signature base
+    pub ed25519_verify_base: Gas,
+    /// Cost of verifying ed25519 signature per byte of the message
+    pub ed25519_verify_byte: Gas,
+
+    /// Cost of recovering secp256k1 public key from the signature
+    pub ecrecover_base: Gas,
+
+    /// Cost of getting the stake of a validator
+    pub validator_stake_base: Gas,
+    /// Cost of getting the total stake of all validators
+    pub validator_total_stake_base: Gas,
+
     /// Cost for calling logging.
     pub log_base: Gas,
     /// Cost for logging per byte
@@ -297,6 +367,18 @@ impl Default for ExtCostsConfig {
             keccak256_byte: 5536829,
             keccak512_base: 710092630 * 2,
             keccak512_byte: 5536829 * 2,
+            // TODO: replace the costs of the cryptographic functions with the estimator output.
+            ripemd160_base: 853675086,
+            ripemd160_byte: 10626681,
+            blake2b_base: 710092630,
+            blake2b_byte: 5536829,
+            ed25519_verify_base: 210000000000,
+            ed25519_verify_byte: 9000000,
+            ecrecover_base: 278821988457,
+            // TODO: provisional values that are not measured yet. Replace them with the estimator
+            // output of `validator_stake_10k` and `validator_total_stake_10k`.
+            validator_stake_base: 911834726400,
+            validator_total_stake_base: 911834726400,
             log_base: 0,
             log_byte: 0,
             storage_write_base: 21058769282,
@@ -349,6 +431,15 @@ impl ExtCostsConfig {
             keccak256_byte: 0,
             keccak512_base: 0,
             keccak512_byte: 0,
+            ripemd160_base: 0,
+            ripemd160_byte: 0,
+            blake2b_base: 0,
+            blake2b_byte: 0,
+            ed25519_verify_base: 0,
+            ed25519_verify_byte: 0,
+            ecrecover_base: 0,
+            validator_stake_base: 0,
+            validator_total_stake_base: 0,
             log_base: 0,
             log_byte: 0,
             storage_write_base: 0,
@@ -380,7 +471,7 @@ impl ExtCostsConfig {
 }
 
 /// Strongly-typed representation of the fees for counting.
-#[derive(Copy, Clone, Hash, PartialEq, Eq, Debug, PartialOrd, Ord)]
+#[derive(Copy, Clone, Hash, PartialEq, Eq, Debug, PartialOrd, Ord, Serialize, Deserialize)]
 #[allow(non_camel_case_types)]
 pub enum ExtCosts {
     base,
@@ -402,6 +493,15 @@ pub enum ExtCosts {
     keccak256_byte,
     keccak512_base,
     keccak512_byte,
+    ripemd160_base,
+    ripemd160_byte,
+    blake2b_base,
+    blake2b_byte,
+    ed25519_verify_base,
+    ed25519_verify_byte,
+    ecrecover_base,
+    validator_stake_base,
+    validator_total_stake_base,
     log_base,
     log_byte,
     storage_write_base,
@@ -453,6 +553,15 @@ impl ExtCosts {
             keccak256_byte => config.keccak256_byte,
             keccak512_base => config.keccak512_base,
             keccak512_byte => config.keccak512_byte,
+            ripemd160_base => config.ripemd160_base,
+            ripemd160_byte => config.ripemd160_byte,
+            blake2b_base => config.blake2b_base,
+            blake2b_byte => config.blake2b_byte,
+            ed25519_verify_base => config.ed25519_verify_base,
+            ed25519_verify_byte => config.ed25519_verify_byte,
+            ecrecover_base => config.ecrecover_base,
+            validator_stake_base => config.validator_stake_base,
+            validator_total_stake_base => config.validator_total_stake_base,
             log_base => config.log_base,
             log_byte => config.log_byte,
             storage_write_base => config.storage_write_base,
@@ -482,3 +591,19 @@ impl ExtCosts {
         }
     }
 }
+
+/// Strongly-typed representation of the action fees paid by a contract, used for profiling.
+#[derive(Copy, Clone, Hash, PartialEq, Eq, Debug, PartialOrd, Ord, Serialize, Deserialize)]
+#[allow(non_camel_case_types)]
+pub enum ActionCosts {
+    create_account,
+    deploy_contract,
+    function_call,
+    transfer,
+    stake,
+    add_key,
+    delete_key,
+    delete_account,
+    new_receipt,
+    value_return,
+}
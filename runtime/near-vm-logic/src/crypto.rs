@@ -0,0 +1,43 @@
+//! Cryptographic primitives exposed to the contracts. Shared by all implementations of
+//! `External`, so that contracts get the same results wherever they run.
+use blake2::Blake2b;
+use ripemd160::Ripemd160;
+use sha2::Digest;
+
+lazy_static::lazy_static! {
+    static ref SECP256K1: secp256k1::Secp256k1 = secp256k1::Secp256k1::new();
+}
+
+/// Computes ripemd160 hash of the data.
+pub fn ripemd160(data: &[u8]) -> Vec<u8> {
+    Ripemd160::digest(data).as_slice().to_vec()
+}
+
+/// Computes blake2b hash of the data with 64 bytes output.
+pub fn blake2b(data: &[u8]) -> Vec<u8> {
+    Blake2b::digest(data).as_slice().to_vec()
+}
+
+/// Verifies ed25519 signature. Malformed signature or public key are reported as invalid.
+pub fn ed25519_verify(signature: &[u8], message: &[u8], public_key: &[u8]) -> bool {
+    let signature = match ed25519_dalek::Signature::from_bytes(signature) {
+        Ok(signature) => signature,
+        Err(_) => return false,
+    };
+    let public_key = match ed25519_dalek::PublicKey::from_bytes(public_key) {
+        Ok(public_key) => public_key,
+        Err(_) => return false,
+    };
+    public_key.verify(message, &signature).is_ok()
+}
+
+/// Recovers secp256k1 public key that produced the signature of the hash. Returns 64 bytes of the
+/// uncompressed public key without the prefix, or `None` if the inputs are malformed.
+pub fn ecrecover(hash: &[u8], signature: &[u8], v: u8) -> Option<Vec<u8>> {
+    let message = secp256k1::Message::from_slice(hash).ok()?;
+    let recovery_id = secp256k1::RecoveryId::from_i32(i32::from(v)).ok()?;
+    let signature =
+        secp256k1::RecoverableSignature::from_compact(&SECP256K1, signature, recovery_id).ok()?;
+    let public_key = SECP256K1.recover(&message, &signature).ok()?;
+    Some(public_key.serialize_vec(&SECP256K1, false)[1..].to_vec())
+}
@@ -590,6 +590,51 @@ pub trait External {
     /// ```
     fn keccak512(&self, data: &[u8]) -> Result<Vec<u8>>;
 
+    /// Computes ripemd160 hash
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - data to hash
+    fn ripemd160(&self, data: &[u8]) -> Result<Vec<u8>>;
+
+    /// Computes blake2b hash with 64 bytes output
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - data to hash
+    fn blake2b(&self, data: &[u8]) -> Result<Vec<u8>>;
+
+    /// Verifies ed25519 signature. Malformed signature or public key are reported as invalid.
+    ///
+    /// # Arguments
+    ///
+    /// * `signature` - 64 bytes of the signature
+    /// * `message` - signed data
+    /// * `public_key` - 32 bytes of the public key
+    fn ed25519_verify(&self, signature: &[u8], message: &[u8], public_key: &[u8]) -> Result<bool>;
+
+    /// Recovers secp256k1 public key that produced the signature. Returns `None` if the key can't
+    /// be recovered, i.e. the inputs are malformed.
+    ///
+    /// # Arguments
+    ///
+    /// * `hash` - 32 bytes of the signed hash
+    /// * `signature` - 64 bytes of `r` and `s`
+    /// * `v` - recovery id, `0..4`
+    ///
+    /// Returns 64 bytes of the uncompressed public key without the prefix.
+    fn ecrecover(&self, hash: &[u8], signature: &[u8], v: u8) -> Result<Option<Vec<u8>>>;
+
+    /// Returns the stake of the given account if it is a validator in the current epoch.
+    ///
+    /// # Arguments
+    ///
+    /// * `account_id` - account id of the validator
+    fn validator_stake(&self, account_id: &AccountId) -> Result<Option<Balance>>;
+
+    /// Returns the total stake of all validators in the current epoch.
+    fn validator_total_stake(&self) -> Result<Balance>;
+
     /// Returns amount of touched trie nodes by storage operations
     fn get_touched_nodes_count(&self) -> u64;
 
@@ -1,4 +1,5 @@
-use crate::config::{ExtCosts, ExtCostsConfig};
+use crate::config::{ActionCosts, ExtCosts, ExtCostsConfig};
+use crate::profile::ProfileData;
 use crate::types::Gas;
 use crate::{HostError, VMLogicError};
 use near_runtime_fees::Fee;
@@ -22,6 +23,8 @@ pub struct GasCounter {
     prepaid_gas: Gas,
     is_view: bool,
     ext_costs_config: ExtCostsConfig,
+    /// Breakdown of the gas spent so far.
+    profile: ProfileData,
 }
 
 impl GasCounter {
@@ -31,7 +34,15 @@ impl GasCounter {
         prepaid_gas: Gas,
         is_view: bool,
     ) -> Self {
-        Self { ext_costs_config, burnt_gas: 0, used_gas: 0, max_gas_burnt, prepaid_gas, is_view }
+        Self {
+            ext_costs_config,
+            burnt_gas: 0,
+            used_gas: 0,
+            max_gas_burnt,
+            prepaid_gas,
+            is_view,
+            profile: Default::default(),
+        }
     }
 
     pub fn deduct_gas(&mut self, burn_gas: Gas, use_gas: Gas) -> Result<()> {
@@ -74,20 +85,50 @@ impl GasCounter {
     #[inline]
     fn inc_ext_costs_counter(&self, _cost: ExtCosts, _value: u64) {}
 
+    /// Deducts the given gas and attributes the gas that was actually burnt to `cost`.
+    fn deduct_ext_cost(&mut self, cost: ExtCosts, gas: Gas) -> Result<()> {
+        let burnt_gas = self.burnt_gas;
+        let res = self.deduct_gas(gas, gas);
+        self.profile.add_ext_cost(cost, self.burnt_gas.saturating_sub(burnt_gas));
+        res
+    }
+
     /// A helper function to pay per byte gas
     pub fn pay_per_byte(&mut self, cost: ExtCosts, num_bytes: u64) -> Result<()> {
         self.inc_ext_costs_counter(cost, num_bytes);
         let use_gas = num_bytes
             .checked_mul(cost.value(&self.ext_costs_config))
             .ok_or(HostError::IntegerOverflow)?;
-        self.deduct_gas(use_gas, use_gas)
+        self.deduct_ext_cost(cost, use_gas)
     }
 
     /// A helper function to pay base cost gas
     pub fn pay_base(&mut self, cost: ExtCosts) -> Result<()> {
         self.inc_ext_costs_counter(cost, 1);
         let base_fee = cost.value(&self.ext_costs_config);
-        self.deduct_gas(base_fee, base_fee)
+        self.deduct_ext_cost(cost, base_fee)
+    }
+
+    /// A helper function to pay gas for the Wasm instructions executed by the contract.
+    pub fn pay_wasm_gas(&mut self, gas: Gas) -> Result<()> {
+        let burnt_gas = self.burnt_gas;
+        let res = self.deduct_gas(gas, gas);
+        self.profile.add_wasm_gas(self.burnt_gas.saturating_sub(burnt_gas));
+        res
+    }
+
+    /// A helper function to pay gas for an action or a receipt created by the contract.
+    /// The gas that was actually used is attributed to `action` in the profile.
+    pub fn pay_action_accumulated(
+        &mut self,
+        burn_gas: Gas,
+        use_gas: Gas,
+        action: ActionCosts,
+    ) -> Result<()> {
+        let used_gas = self.used_gas;
+        let res = self.deduct_gas(burn_gas, use_gas);
+        self.profile.add_action_cost(action, self.used_gas.saturating_sub(used_gas));
+        res
     }
 
     /// A helper function to pay per byte gas fee for batching an action.
@@ -95,11 +136,13 @@ impl GasCounter {
     /// * `per_byte_fee`: the fee per byte;
     /// * `num_bytes`: the number of bytes;
     /// * `sir`: whether the receiver_id is same as the current account ID;
+    /// * `action`: the action the fee is attributed to in the profile;
     pub fn pay_action_per_byte(
         &mut self,
         per_byte_fee: &Fee,
         num_bytes: u64,
         sir: bool,
+        action: ActionCosts,
     ) -> Result<()> {
         let burn_gas =
             num_bytes.checked_mul(per_byte_fee.send_fee(sir)).ok_or(HostError::IntegerOverflow)?;
@@ -109,18 +152,24 @@ impl GasCounter {
             )
             .ok_or(HostError::IntegerOverflow)?;
 
-        self.deduct_gas(burn_gas, use_gas)
+        self.pay_action_accumulated(burn_gas, use_gas, action)
     }
 
     /// A helper function to pay base cost gas fee for batching an action.
     /// # Args:
     /// * `base_fee`: base fee for the action;
     /// * `sir`: whether the receiver_id is same as the current account ID;
-    pub fn pay_action_base(&mut self, base_fee: &Fee, sir: bool) -> Result<()> {
+    /// * `action`: the action the fee is attributed to in the profile;
+    pub fn pay_action_base(
+        &mut self,
+        base_fee: &Fee,
+        sir: bool,
+        action: ActionCosts,
+    ) -> Result<()> {
         let burn_gas = base_fee.send_fee(sir);
         let use_gas =
             burn_gas.checked_add(base_fee.exec_fee()).ok_or(HostError::IntegerOverflow)?;
-        self.deduct_gas(burn_gas, use_gas)
+        self.pay_action_accumulated(burn_gas, use_gas, action)
     }
 
     pub fn burnt_gas(&self) -> Gas {
@@ -129,6 +178,9 @@ impl GasCounter {
     pub fn used_gas(&self) -> Gas {
         self.used_gas
     }
+    pub fn profile_data(&self) -> ProfileData {
+        self.profile.clone()
+    }
 }
 
 #[cfg(test)]
@@ -142,6 +194,32 @@ mod tests {
         assert_eq!(counter.used_gas(), 10);
     }
 
+    #[test]
+    fn test_profile() {
+        let mut counter =
+            GasCounter::new(ExtCostsConfig::default(), 1_000_000_000_000, 1_000_000_000_000, false);
+        counter.pay_wasm_gas(10).unwrap();
+        counter.pay_base(ExtCosts::base).unwrap();
+        counter.pay_base(ExtCosts::base).unwrap();
+        counter.pay_per_byte(ExtCosts::log_byte, 3).unwrap();
+        counter.pay_action_accumulated(5, 20, ActionCosts::transfer).unwrap();
+        let config = ExtCostsConfig::default();
+        let profile = counter.profile_data();
+        assert_eq!(profile.wasm_gas, 10);
+        assert_eq!(profile.ext_costs[&ExtCosts::base], 2 * config.base);
+        assert_eq!(profile.ext_costs[&ExtCosts::log_byte], 3 * config.log_byte);
+        assert_eq!(profile.action_costs[&ActionCosts::transfer], 20);
+        assert_eq!(counter.burnt_gas(), 10 + 2 * config.base + 3 * config.log_byte + 5);
+    }
+
+    #[test]
+    fn test_profile_gas_exceeded() {
+        let mut counter = GasCounter::new(ExtCostsConfig::default(), 100, 100, false);
+        counter.pay_wasm_gas(60).unwrap();
+        assert!(counter.pay_wasm_gas(60).is_err());
+        assert_eq!(counter.profile_data().wasm_gas, 100);
+    }
+
     #[test]
     #[should_panic]
     fn test_prepaid_gas_min() {
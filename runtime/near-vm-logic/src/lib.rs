@@ -1,19 +1,25 @@
 mod config;
 mod context;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod crypto;
 mod dependencies;
 mod gas_counter;
 mod logic;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod mocks;
+mod profile;
 pub mod serde_with;
 pub mod types;
 mod utils;
 
-pub use config::{ExtCosts, ExtCostsConfig, VMConfig, VMLimitConfig};
+pub use config::{
+    ActionCosts, ExtCosts, ExtCostsConfig, FloatPolicy, VMConfig, VMKind, VMLimitConfig,
+};
 pub use context::VMContext;
 pub use dependencies::{External, MemoryLike, ValuePtr};
 pub use logic::{VMLogic, VMOutcome};
 pub use near_vm_errors::{HostError, VMLogicError};
+pub use profile::ProfileData;
 pub use types::ReturnData;
 
 #[cfg(feature = "costs_counting")]
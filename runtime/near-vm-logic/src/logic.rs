@@ -1,11 +1,12 @@
 use crate::config::ExtCosts::*;
-use crate::config::VMConfig;
+use crate::config::{ActionCosts, VMConfig};
 use crate::context::VMContext;
 use crate::dependencies::{External, MemoryLike};
 use crate::gas_counter::GasCounter;
+use crate::profile::ProfileData;
 use crate::types::{
-    AccountId, Balance, Gas, IteratorIndex, PromiseIndex, PromiseResult, ReceiptIndex, ReturnData,
-    StorageUsage,
+    AccountId, Balance, EpochHeight, Gas, IteratorIndex, PromiseIndex, PromiseResult, ReceiptIndex,
+    ReturnData, StorageUsage,
 };
 use crate::utils::split_method_names;
 use crate::{ExtCosts, HostError, VMLogicError, ValuePtr};
@@ -606,6 +607,16 @@ impl<'a> VMLogic<'a> {
         Ok(self.context.block_timestamp)
     }
 
+    /// Returns the current epoch height.
+    ///
+    /// # Cost
+    ///
+    /// `base`
+    pub fn epoch_height(&mut self) -> Result<EpochHeight> {
+        self.gas_counter.pay_base(base)?;
+        Ok(self.context.epoch_height)
+    }
+
     /// Returns the number of bytes used by the contract if it was saved to the trie as of the
     /// invocation. This includes:
     /// * The data written with storage_* functions during current and previous execution;
@@ -704,6 +715,45 @@ impl<'a> VMLogic<'a> {
         Ok(self.gas_counter.used_gas())
     }
 
+    /// Writes the stake of the given account in the current epoch into `stake_ptr`. If the account
+    /// is not a validator, writes 0.
+    ///
+    /// # Errors
+    ///
+    /// * If `account_id_len + account_id_ptr` points outside the memory of the guest or host
+    /// returns `MemoryAccessViolation`;
+    /// * If the account id is not UTF-8 returns `BadUTF8`.
+    ///
+    /// # Cost
+    ///
+    /// `base + memory_write_base + memory_write_size * 16 + utf8_decoding_base +
+    /// utf8_decoding_byte * account_id_len + validator_stake_base` and the cost of reading the
+    /// account id.
+    pub fn validator_stake(
+        &mut self,
+        account_id_len: u64,
+        account_id_ptr: u64,
+        stake_ptr: u64,
+    ) -> Result<()> {
+        self.gas_counter.pay_base(base)?;
+        let account_id = self.read_and_parse_account_id(account_id_ptr, account_id_len)?;
+        self.gas_counter.pay_base(validator_stake_base)?;
+        let balance = self.ext.validator_stake(&account_id)?.unwrap_or_default();
+        self.memory_set_u128(stake_ptr, balance)
+    }
+
+    /// Writes the total stake of all validators in the current epoch into `stake_ptr`.
+    ///
+    /// # Cost
+    ///
+    /// `base + memory_write_base + memory_write_size * 16 + validator_total_stake_base`
+    pub fn validator_total_stake(&mut self, stake_ptr: u64) -> Result<()> {
+        self.gas_counter.pay_base(base)?;
+        self.gas_counter.pay_base(validator_total_stake_base)?;
+        let total_stake = self.ext.validator_total_stake()?;
+        self.memory_set_u128(stake_ptr, total_stake)
+    }
+
     // ############
     // # Math API #
     // ############
@@ -776,6 +826,111 @@ impl<'a> VMLogic<'a> {
         self.internal_write_register(register_id, value_hash)
     }
 
+    /// Hashes the random sequence of bytes using ripemd160 and returns it into `register_id`.
+    ///
+    /// # Errors
+    ///
+    /// If `value_len + value_ptr` points outside the memory or the registers use more memory than
+    /// the limit with `MemoryAccessViolation`.
+    ///
+    /// # Cost
+    ///
+    /// `ripemd160_base + ripemd160_byte * num_bytes + write_register_base + write_register_byte * 20`
+    /// and the cost of reading the value.
+    pub fn ripemd160(&mut self, value_len: u64, value_ptr: u64, register_id: u64) -> Result<()> {
+        self.gas_counter.pay_base(ripemd160_base)?;
+        let value = self.get_vec_from_memory_or_register(value_ptr, value_len)?;
+        self.gas_counter.pay_per_byte(ripemd160_byte, value.len() as u64)?;
+        let value_hash = self.ext.ripemd160(&value)?;
+        self.internal_write_register(register_id, value_hash)
+    }
+
+    /// Hashes the random sequence of bytes using blake2b and returns 64 bytes of the hash into
+    /// `register_id`.
+    ///
+    /// # Errors
+    ///
+    /// If `value_len + value_ptr` points outside the memory or the registers use more memory than
+    /// the limit with `MemoryAccessViolation`.
+    ///
+    /// # Cost
+    ///
+    /// `blake2b_base + blake2b_byte * num_bytes + write_register_base + write_register_byte * 64`
+    /// and the cost of reading the value.
+    pub fn blake2b(&mut self, value_len: u64, value_ptr: u64, register_id: u64) -> Result<()> {
+        self.gas_counter.pay_base(blake2b_base)?;
+        let value = self.get_vec_from_memory_or_register(value_ptr, value_len)?;
+        self.gas_counter.pay_per_byte(blake2b_byte, value.len() as u64)?;
+        let value_hash = self.ext.blake2b(&value)?;
+        self.internal_write_register(register_id, value_hash)
+    }
+
+    /// Verifies that `signature` of the message is produced by the ed25519 `public_key`.
+    /// Returns 1 if the signature is valid and 0 otherwise, including malformed signature or key.
+    ///
+    /// # Errors
+    ///
+    /// If any of the inputs points outside the memory returns `MemoryAccessViolation`.
+    ///
+    /// # Cost
+    ///
+    /// `ed25519_verify_base + ed25519_verify_byte * message_len` and the cost of reading the inputs.
+    pub fn ed25519_verify(
+        &mut self,
+        signature_len: u64,
+        signature_ptr: u64,
+        message_len: u64,
+        message_ptr: u64,
+        public_key_len: u64,
+        public_key_ptr: u64,
+    ) -> Result<u64> {
+        self.gas_counter.pay_base(ed25519_verify_base)?;
+        let signature = self.get_vec_from_memory_or_register(signature_ptr, signature_len)?;
+        let message = self.get_vec_from_memory_or_register(message_ptr, message_len)?;
+        let public_key = self.get_vec_from_memory_or_register(public_key_ptr, public_key_len)?;
+        self.gas_counter.pay_per_byte(ed25519_verify_byte, message.len() as u64)?;
+        let is_valid = self.ext.ed25519_verify(&signature, &message, &public_key)?;
+        Ok(is_valid as u64)
+    }
+
+    /// Recovers secp256k1 public key from the `signature` of the 32 bytes `hash`. The signature is
+    /// 64 bytes of `r` and `s`, and `v` is the recovery id in `0..4`. Writes 64 bytes of the
+    /// uncompressed public key without the prefix into `register_id` and returns 1, or returns 0
+    /// if the key can't be recovered.
+    ///
+    /// # Errors
+    ///
+    /// If any of the inputs points outside the memory or the registers use more memory than the
+    /// limit with `MemoryAccessViolation`.
+    ///
+    /// # Cost
+    ///
+    /// `ecrecover_base + write_register_base + write_register_byte * 64` and the cost of reading
+    /// the inputs.
+    pub fn ecrecover(
+        &mut self,
+        hash_len: u64,
+        hash_ptr: u64,
+        signature_len: u64,
+        signature_ptr: u64,
+        v: u64,
+        register_id: u64,
+    ) -> Result<u64> {
+        self.gas_counter.pay_base(ecrecover_base)?;
+        let hash = self.get_vec_from_memory_or_register(hash_ptr, hash_len)?;
+        let signature = self.get_vec_from_memory_or_register(signature_ptr, signature_len)?;
+        if v > 3 {
+            return Ok(0);
+        }
+        match self.ext.ecrecover(&hash, &signature, v as u8)? {
+            Some(public_key) => {
+                self.internal_write_register(register_id, public_key)?;
+                Ok(1)
+            }
+            None => Ok(0),
+        }
+    }
+
     /// Called by gas metering injected into Wasm. Counts both towards `burnt_gas` and `used_gas`.
     ///
     /// # Errors
@@ -785,7 +940,7 @@ impl<'a> VMLogic<'a> {
     /// * If we exceed the `prepaid_gas` then returns `GasExceeded`.
     pub fn gas(&mut self, gas_amount: u32) -> Result<()> {
         let value = Gas::from(gas_amount) * Gas::from(self.config.regular_op_cost);
-        self.gas_counter.deduct_gas(value, value)
+        self.gas_counter.pay_wasm_gas(value)
     }
 
     // ################
@@ -819,7 +974,7 @@ impl<'a> VMLogic<'a> {
                 .ok_or(HostError::IntegerOverflow)?;
         }
         use_gas = use_gas.checked_add(burn_gas).ok_or(HostError::IntegerOverflow)?;
-        self.gas_counter.deduct_gas(burn_gas, use_gas)
+        self.gas_counter.pay_action_accumulated(burn_gas, use_gas, ActionCosts::new_receipt)
     }
 
     /// A helper function to subtract balance on transfer or attached deposit for promises.
@@ -1144,8 +1299,11 @@ impl<'a> VMLogic<'a> {
         }
         let (receipt_idx, sir) = self.promise_idx_to_receipt_idx_with_sir(promise_idx)?;
 
-        self.gas_counter
-            .pay_action_base(&self.fees_config.action_creation_config.create_account_cost, sir)?;
+        self.gas_counter.pay_action_base(
+            &self.fees_config.action_creation_config.create_account_cost,
+            sir,
+            ActionCosts::create_account,
+        )?;
 
         self.ext.append_action_create_account(receipt_idx)?;
         Ok(())
@@ -1193,12 +1351,16 @@ impl<'a> VMLogic<'a> {
         let (receipt_idx, sir) = self.promise_idx_to_receipt_idx_with_sir(promise_idx)?;
 
         let num_bytes = code.len() as u64;
-        self.gas_counter
-            .pay_action_base(&self.fees_config.action_creation_config.deploy_contract_cost, sir)?;
+        self.gas_counter.pay_action_base(
+            &self.fees_config.action_creation_config.deploy_contract_cost,
+            sir,
+            ActionCosts::deploy_contract,
+        )?;
         self.gas_counter.pay_action_per_byte(
             &self.fees_config.action_creation_config.deploy_contract_cost_per_byte,
             num_bytes,
             sir,
+            ActionCosts::deploy_contract,
         )?;
 
         self.ext.append_action_deploy_contract(receipt_idx, code)?;
@@ -1251,12 +1413,16 @@ impl<'a> VMLogic<'a> {
 
         // Input can't be large enough to overflow
         let num_bytes = method_name.len() as u64 + arguments.len() as u64;
-        self.gas_counter
-            .pay_action_base(&self.fees_config.action_creation_config.function_call_cost, sir)?;
+        self.gas_counter.pay_action_base(
+            &self.fees_config.action_creation_config.function_call_cost,
+            sir,
+            ActionCosts::function_call,
+        )?;
         self.gas_counter.pay_action_per_byte(
             &self.fees_config.action_creation_config.function_call_cost_per_byte,
             num_bytes,
             sir,
+            ActionCosts::function_call,
         )?;
         // Prepaid gas
         self.gas_counter.deduct_gas(0, gas)?;
@@ -1299,8 +1465,11 @@ impl<'a> VMLogic<'a> {
 
         let (receipt_idx, sir) = self.promise_idx_to_receipt_idx_with_sir(promise_idx)?;
 
-        self.gas_counter
-            .pay_action_base(&self.fees_config.action_creation_config.transfer_cost, sir)?;
+        self.gas_counter.pay_action_base(
+            &self.fees_config.action_creation_config.transfer_cost,
+            sir,
+            ActionCosts::transfer,
+        )?;
 
         self.deduct_balance(amount)?;
 
@@ -1344,8 +1513,11 @@ impl<'a> VMLogic<'a> {
 
         let (receipt_idx, sir) = self.promise_idx_to_receipt_idx_with_sir(promise_idx)?;
 
-        self.gas_counter
-            .pay_action_base(&self.fees_config.action_creation_config.stake_cost, sir)?;
+        self.gas_counter.pay_action_base(
+            &self.fees_config.action_creation_config.stake_cost,
+            sir,
+            ActionCosts::stake,
+        )?;
 
         self.ext.append_action_stake(receipt_idx, amount, public_key)?;
         Ok(())
@@ -1389,6 +1561,7 @@ impl<'a> VMLogic<'a> {
         self.gas_counter.pay_action_base(
             &self.fees_config.action_creation_config.add_key_cost.full_access_cost,
             sir,
+            ActionCosts::add_key,
         )?;
 
         self.ext.append_action_add_key_with_full_access(receipt_idx, public_key, nonce)?;
@@ -1448,11 +1621,13 @@ impl<'a> VMLogic<'a> {
         self.gas_counter.pay_action_base(
             &self.fees_config.action_creation_config.add_key_cost.function_call_cost,
             sir,
+            ActionCosts::add_key,
         )?;
         self.gas_counter.pay_action_per_byte(
             &self.fees_config.action_creation_config.add_key_cost.function_call_cost_per_byte,
             num_bytes,
             sir,
+            ActionCosts::add_key,
         )?;
 
         self.ext.append_action_add_key_with_function_call(
@@ -1500,8 +1675,11 @@ impl<'a> VMLogic<'a> {
 
         let (receipt_idx, sir) = self.promise_idx_to_receipt_idx_with_sir(promise_idx)?;
 
-        self.gas_counter
-            .pay_action_base(&self.fees_config.action_creation_config.delete_key_cost, sir)?;
+        self.gas_counter.pay_action_base(
+            &self.fees_config.action_creation_config.delete_key_cost,
+            sir,
+            ActionCosts::delete_key,
+        )?;
 
         self.ext.append_action_delete_key(receipt_idx, public_key)?;
         Ok(())
@@ -1541,8 +1719,11 @@ impl<'a> VMLogic<'a> {
 
         let (receipt_idx, sir) = self.promise_idx_to_receipt_idx_with_sir(promise_idx)?;
 
-        self.gas_counter
-            .pay_action_base(&self.fees_config.action_creation_config.delete_account_cost, sir)?;
+        self.gas_counter.pay_action_base(
+            &self.fees_config.action_creation_config.delete_account_cost,
+            sir,
+            ActionCosts::delete_account,
+        )?;
 
         self.ext.append_action_delete_account(receipt_idx, beneficiary_id)?;
         Ok(())
@@ -1697,7 +1878,7 @@ impl<'a> VMLogic<'a> {
                 )
                 .ok_or(HostError::IntegerOverflow)?;
         }
-        self.gas_counter.deduct_gas(burn_gas, burn_gas)?;
+        self.gas_counter.pay_action_accumulated(burn_gas, burn_gas, ActionCosts::value_return)?;
         self.return_data = ReturnData::Value(return_val);
         Ok(())
     }
@@ -2243,6 +2424,7 @@ impl<'a> VMLogic<'a> {
             burnt_gas: self.gas_counter.burnt_gas(),
             used_gas: self.gas_counter.used_gas(),
             logs: self.logs,
+            profile: self.gas_counter.profile_data(),
         }
     }
 }
@@ -2255,4 +2437,6 @@ pub struct VMOutcome {
     pub burnt_gas: Gas,
     pub used_gas: Gas,
     pub logs: Vec<String>,
+    /// Breakdown of the gas spent by the execution.
+    pub profile: ProfileData,
 }
@@ -1,3 +1,4 @@
+use crate::crypto;
 use crate::types::{AccountId, Balance, Gas, PublicKey};
 use crate::{External, ValuePtr};
 use near_vm_errors::HostError;
@@ -20,6 +21,7 @@ pub struct MockedExternal {
     iterators: HashMap<u64, FakeIterator>,
     next_iterator_index: u64,
     receipts: Vec<Receipt>,
+    pub validators: HashMap<AccountId, Balance>,
 }
 
 pub struct MockedValuePtr {
@@ -285,6 +287,22 @@ impl External for MockedExternal {
         Ok(res.to_vec())
     }
 
+    fn ripemd160(&self, data: &[u8]) -> Result<Vec<u8>> {
+        Ok(crypto::ripemd160(data))
+    }
+
+    fn blake2b(&self, data: &[u8]) -> Result<Vec<u8>> {
+        Ok(crypto::blake2b(data))
+    }
+
+    fn ed25519_verify(&self, signature: &[u8], message: &[u8], public_key: &[u8]) -> Result<bool> {
+        Ok(crypto::ed25519_verify(signature, message, public_key))
+    }
+
+    fn ecrecover(&self, hash: &[u8], signature: &[u8], v: u8) -> Result<Option<Vec<u8>>> {
+        Ok(crypto::ecrecover(hash, signature, v))
+    }
+
     fn get_touched_nodes_count(&self) -> u64 {
         0
     }
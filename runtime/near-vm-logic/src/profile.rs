@@ -0,0 +1,32 @@
+use crate::config::{ActionCosts, ExtCosts};
+use crate::types::Gas;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Breakdown of the gas spent by a single contract execution.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProfileData {
+    /// Gas burnt for executing Wasm instructions.
+    pub wasm_gas: Gas,
+    /// Gas burnt by the host functions, per cost that was paid.
+    pub ext_costs: BTreeMap<ExtCosts, Gas>,
+    /// Gas used by the actions and receipts created by the contract. Includes the execution
+    /// fees that are prepaid for the new receipts.
+    pub action_costs: BTreeMap<ActionCosts, Gas>,
+}
+
+impl ProfileData {
+    pub fn add_wasm_gas(&mut self, gas: Gas) {
+        self.wasm_gas = self.wasm_gas.saturating_add(gas);
+    }
+
+    pub fn add_ext_cost(&mut self, cost: ExtCosts, gas: Gas) {
+        let entry = self.ext_costs.entry(cost).or_default();
+        *entry = entry.saturating_add(gas);
+    }
+
+    pub fn add_action_cost(&mut self, action: ActionCosts, gas: Gas) {
+        let entry = self.action_costs.entry(action).or_default();
+        *entry = entry.saturating_add(gas);
+    }
+}
@@ -13,6 +13,7 @@ pub fn create_context() -> VMContext {
         input: vec![0, 1, 2, 3, 5],
         block_index: 10,
         block_timestamp: 42,
+        epoch_height: 1,
         account_balance: 2u128,
         account_locked_balance: 1u128,
         storage_usage: 12,
@@ -92,6 +93,7 @@ decl_test_bytes!(test_input, input, create_context().input.as_slice());
 
 decl_test_u64!(test_block_index, block_index, create_context().block_index);
 decl_test_u64!(test_block_timestamp, block_timestamp, create_context().block_timestamp);
+decl_test_u64!(test_epoch_height, epoch_height, create_context().epoch_height);
 decl_test_u64!(test_storage_usage, storage_usage, create_context().storage_usage);
 decl_test_u64!(test_prepaid_gas, prepaid_gas, create_context().prepaid_gas);
 
@@ -106,3 +108,27 @@ decl_test_u128!(
     create_context().account_locked_balance
 );
 decl_test_u128!(test_attached_deposit, attached_deposit, create_context().attached_deposit);
+
+#[test]
+fn test_validator_stake() {
+    let mut logic_builder = VMLogicBuilder::default();
+    logic_builder.ext.validators =
+        vec![("alice".to_string(), 100), ("bob".to_string(), 1)].into_iter().collect();
+    let mut logic = logic_builder.build(create_context());
+    let buf = [0u8; std::mem::size_of::<u128>()];
+
+    let account_id = b"alice";
+    logic
+        .validator_stake(account_id.len() as _, account_id.as_ptr() as _, buf.as_ptr() as _)
+        .expect("read validator stake should be ok");
+    assert_eq!(u128::from_le_bytes(buf), 100);
+
+    let account_id = b"carol";
+    logic
+        .validator_stake(account_id.len() as _, account_id.as_ptr() as _, buf.as_ptr() as _)
+        .expect("read validator stake should be ok");
+    assert_eq!(u128::from_le_bytes(buf), 0);
+
+    logic.validator_total_stake(buf.as_ptr() as _).expect("read total stake should be ok");
+    assert_eq!(u128::from_le_bytes(buf), 101);
+}
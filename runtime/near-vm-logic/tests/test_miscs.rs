@@ -736,3 +736,145 @@ fn test_contract_size_limit() {
         .into())
     );
 }
+
+fn read_register_vec(logic: &mut near_vm_logic::VMLogic, register_id: u64) -> Vec<u8> {
+    let len = logic.register_len(register_id).unwrap();
+    let res = vec![0u8; len as usize];
+    logic.read_register(register_id, res.as_ptr() as _).unwrap();
+    res
+}
+
+#[test]
+fn test_ripemd160() {
+    let mut logic_builder = VMLogicBuilder::default();
+    let mut logic = logic_builder.build(get_context(vec![], false));
+    let data = b"abc";
+
+    logic.ripemd160(data.len() as _, data.as_ptr() as _, 0).unwrap();
+    assert_eq!(
+        read_register_vec(&mut logic, 0),
+        vec![
+            142, 178, 8, 247, 224, 93, 152, 122, 155, 4, 74, 142, 152, 198, 176, 135, 241, 90, 11,
+            252
+        ]
+    );
+    let len = data.len() as u64;
+    assert_costs(map! {
+        ExtCosts::base: 2,
+        ExtCosts::read_memory_base: 1,
+        ExtCosts::read_memory_byte: len,
+        ExtCosts::write_memory_base: 1,
+        ExtCosts::write_memory_byte: 20,
+        ExtCosts::read_register_base: 1,
+        ExtCosts::read_register_byte: 20,
+        ExtCosts::write_register_base: 1,
+        ExtCosts::write_register_byte: 20,
+        ExtCosts::ripemd160_base: 1,
+        ExtCosts::ripemd160_byte: len,
+    });
+}
+
+#[test]
+fn test_blake2b() {
+    let mut logic_builder = VMLogicBuilder::default();
+    let mut logic = logic_builder.build(get_context(vec![], false));
+    let data = b"abc";
+
+    logic.blake2b(data.len() as _, data.as_ptr() as _, 0).unwrap();
+    assert_eq!(
+        read_register_vec(&mut logic, 0),
+        vec![
+            186, 128, 165, 63, 152, 28, 77, 13, 106, 39, 151, 182, 159, 18, 246, 233, 76, 33, 47,
+            20, 104, 90, 196, 183, 75, 18, 187, 111, 219, 255, 162, 209, 125, 135, 197, 57, 42,
+            171, 121, 45, 194, 82, 213, 222, 69, 51, 204, 149, 24, 211, 138, 168, 219, 241, 146,
+            90, 185, 35, 134, 237, 212, 0, 153, 35
+        ]
+    );
+    let len = data.len() as u64;
+    assert_costs(map! {
+        ExtCosts::base: 2,
+        ExtCosts::read_memory_base: 1,
+        ExtCosts::read_memory_byte: len,
+        ExtCosts::write_memory_base: 1,
+        ExtCosts::write_memory_byte: 64,
+        ExtCosts::read_register_base: 1,
+        ExtCosts::read_register_byte: 64,
+        ExtCosts::write_register_base: 1,
+        ExtCosts::write_register_byte: 64,
+        ExtCosts::blake2b_base: 1,
+        ExtCosts::blake2b_byte: len,
+    });
+}
+
+#[test]
+fn test_ed25519_verify() {
+    let mut logic_builder = VMLogicBuilder::default();
+    let mut logic = logic_builder.build(get_context(vec![], false));
+    let secret_key = ed25519_dalek::SecretKey::from_bytes(&[7u8; 32]).unwrap();
+    let public_key = ed25519_dalek::PublicKey::from(&secret_key);
+    let message = b"multisig request";
+    let signature =
+        ed25519_dalek::ExpandedSecretKey::from(&secret_key).sign(message, &public_key).to_bytes();
+    let public_key = public_key.to_bytes();
+
+    let verify = |logic: &mut near_vm_logic::VMLogic,
+                  signature: &[u8],
+                  message: &[u8],
+                  public_key: &[u8]| {
+        logic
+            .ed25519_verify(
+                signature.len() as _,
+                signature.as_ptr() as _,
+                message.len() as _,
+                message.as_ptr() as _,
+                public_key.len() as _,
+                public_key.as_ptr() as _,
+            )
+            .unwrap()
+    };
+    assert_eq!(verify(&mut logic, &signature, message, &public_key), 1);
+    assert_costs(map! {
+        ExtCosts::read_memory_base: 3,
+        ExtCosts::read_memory_byte: 64 + message.len() as u64 + 32,
+        ExtCosts::ed25519_verify_base: 1,
+        ExtCosts::ed25519_verify_byte: message.len() as u64,
+    });
+    assert_eq!(verify(&mut logic, &signature, b"other request", &public_key), 0);
+    assert_eq!(verify(&mut logic, &signature[..63], message, &public_key), 0);
+    assert_eq!(verify(&mut logic, &signature, message, &public_key[..31]), 0);
+}
+
+#[test]
+fn test_ecrecover() {
+    let mut logic_builder = VMLogicBuilder::default();
+    let mut logic = logic_builder.build(get_context(vec![], false));
+    let secp = secp256k1::Secp256k1::new();
+    let secret_key = secp256k1::key::SecretKey::from_slice(&secp, &[1u8; 32]).unwrap();
+    let public_key = secp256k1::key::PublicKey::from_secret_key(&secp, &secret_key).unwrap();
+    let hash = [3u8; 32];
+    let signature = secp
+        .sign_recoverable(&secp256k1::Message::from_slice(&hash).unwrap(), &secret_key)
+        .unwrap();
+    let (recovery_id, signature) = signature.serialize_compact(&secp);
+    let v = recovery_id.to_i32() as u64;
+
+    let recover = |logic: &mut near_vm_logic::VMLogic, hash: &[u8], v: u64| {
+        logic
+            .ecrecover(hash.len() as _, hash.as_ptr() as _, 64, signature.as_ptr() as _, v, 0)
+            .unwrap()
+    };
+    assert_eq!(recover(&mut logic, &hash, v), 1);
+    assert_costs(map! {
+        ExtCosts::read_memory_base: 2,
+        ExtCosts::read_memory_byte: 32 + 64,
+        ExtCosts::write_register_base: 1,
+        ExtCosts::write_register_byte: 64,
+        ExtCosts::ecrecover_base: 1,
+    });
+    assert_eq!(
+        read_register_vec(&mut logic, 0),
+        public_key.serialize_vec(&secp, false)[1..].to_vec()
+    );
+    assert_eq!(recover(&mut logic, &hash, 4), 0);
+    assert_eq!(recover(&mut logic, &hash[..31], v), 0);
+}
@@ -130,6 +130,7 @@ fn main() {
         &config,
         &fees,
         &promise_results,
+        None,
     );
 
     if let Some(outcome) = outcome {
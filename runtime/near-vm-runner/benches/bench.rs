@@ -24,6 +24,7 @@ fn setup(
         input,
         block_index: 0,
         block_timestamp: 0,
+        epoch_height: 0,
         account_balance: 0,
         account_locked_balance: 0,
         storage_usage: 0,
@@ -70,6 +71,7 @@ fn pass_through(bench: &mut Bencher) {
             &config,
             &fees_config,
             &promise_results,
+            None,
         );
         assert_run_result(result, 42);
     });
@@ -87,6 +89,7 @@ fn benchmark_fake_storage_8b_1000(bench: &mut Bencher) {
             &config,
             &fees_config,
             &promise_results,
+            None,
         );
         assert_run_result(result, 999 * 1000 / 2);
     });
@@ -104,6 +107,7 @@ fn benchmark_fake_storage_10kib_1000(bench: &mut Bencher) {
             &config,
             &fees_config,
             &promise_results,
+            None,
         );
         assert_run_result(result, 999 * 1000 / 2);
     });
@@ -121,6 +125,7 @@ fn sum_n_1000000(bench: &mut Bencher) {
             &config,
             &fees_config,
             &promise_results,
+            None,
         );
         assert_run_result(result, (1000000 - 1) * 1000000 / 2);
     });
@@ -1,29 +1,173 @@
+use std::convert::TryInto;
+use std::fmt;
+
 #[cfg(not(feature = "no_cache"))]
 use cached::{cached_key, SizedCache};
+use sha2::{Digest, Sha256};
 use wasmer_runtime;
+use wasmer_runtime::{compiler_for_backend, Backend};
+use wasmer_runtime_core::cache::Artifact;
+use wasmer_runtime_core::load_cache_with;
 
 use crate::errors::IntoVMError;
 use crate::prepare;
 use near_vm_errors::VMError;
-use near_vm_logic::VMConfig;
+use near_vm_logic::{VMConfig, VMKind};
 
 /// Cache size in number of cached modules to hold.
 #[cfg(not(feature = "no_cache"))]
 const CACHE_SIZE: usize = 1024;
-// TODO: store a larger on-disk cache
+
+/// Version of the format of compiled modules stored in `CompiledContractCache`.
+/// Must be bumped on every change of the format, so modules stored by older versions are
+/// recompiled instead of being loaded. The version of wasmer is part of the key as well.
+const CACHE_RECORD_VERSION: u32 = 2;
+
+/// Length of the checksum of the serialized module.
+const CHECKSUM_LEN: usize = 32;
+
+/// Header of the stored record: version and checksum of the serialized module.
+const CACHE_RECORD_HEADER_LEN: usize = 4 + CHECKSUM_LEN;
+
+/// Persistent cache of compiled contracts, i.e. backed by the node storage.
+/// The cache is an optimization, so failures to read or write it only cause recompilation.
+pub trait CompiledContractCache: Send + Sync {
+    fn put(&self, key: &[u8], value: &[u8]) -> Result<(), std::io::Error>;
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, std::io::Error>;
+}
+
+impl fmt::Debug for dyn CompiledContractCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CompiledContractCache")
+    }
+}
+
+fn checksum(data: &[u8]) -> [u8; CHECKSUM_LEN] {
+    let mut checksum = [0u8; CHECKSUM_LEN];
+    checksum.copy_from_slice(&Sha256::digest(data));
+    checksum
+}
+
+/// Key of the compiled module in the persistent cache. Includes the versions of the record
+/// format and of wasmer, since artifacts are only compatible with the compiler that produced them.
+fn cache_key(code_hash: &[u8], config: &VMConfig) -> Vec<u8> {
+    let mut key = code_hash.to_vec();
+    key.extend_from_slice(&config.non_crypto_hash().to_le_bytes());
+    key.extend_from_slice(&CACHE_RECORD_VERSION.to_le_bytes());
+    key.extend_from_slice(wasmer_runtime_core::VERSION.as_bytes());
+    key
+}
+
+fn compile_module_from_code(
+    code: &[u8],
+    config: &VMConfig,
+) -> Result<wasmer_runtime::Module, VMError> {
+    let prepared_code = prepare::prepare_contract(code, config)?;
+    wasmer_runtime::compile(&prepared_code).map_err(|err| err.into_vm_error())
+}
+
+/// Serialize compiled module into a record with version and checksum.
+fn serialize_module(module: &wasmer_runtime::Module) -> Option<Vec<u8>> {
+    let artifact = module.cache().ok()?.serialize().ok()?;
+    let mut record = Vec::with_capacity(CACHE_RECORD_HEADER_LEN + artifact.len());
+    record.extend_from_slice(&CACHE_RECORD_VERSION.to_le_bytes());
+    record.extend_from_slice(&checksum(&artifact));
+    record.extend_from_slice(&artifact);
+    Some(record)
+}
+
+/// Return serialized module from the record, if it has the current version and is not corrupted.
+fn verify_record(record: &[u8]) -> Option<&[u8]> {
+    if record.len() < CACHE_RECORD_HEADER_LEN {
+        return None;
+    }
+    let (header, artifact) = record.split_at(CACHE_RECORD_HEADER_LEN);
+    let version = u32::from_le_bytes(header[..4].try_into().ok()?);
+    if version != CACHE_RECORD_VERSION || checksum(artifact)[..] != header[4..] {
+        return None;
+    }
+    Some(artifact)
+}
+
+/// Load compiled module from the record.
+fn deserialize_module(record: &[u8]) -> Option<wasmer_runtime::Module> {
+    let artifact = Artifact::deserialize(verify_record(record)?).ok()?;
+    let compiler = compiler_for_backend(Backend::Singlepass)?;
+    // Safe, because artifact was produced by the same version of the compiler (which is part of
+    // the key) and its integrity is checked above.
+    unsafe { load_cache_with(artifact, compiler.as_ref()).ok() }
+}
+
+/// Compile the module and store it in the persistent cache.
+fn compile_and_store_module(
+    key: &[u8],
+    code: &[u8],
+    config: &VMConfig,
+    cache: &dyn CompiledContractCache,
+) -> Result<wasmer_runtime::Module, VMError> {
+    let module = compile_module_from_code(code, config)?;
+    if let Some(record) = serialize_module(&module) {
+        // Failing to store the module only means it will be compiled again.
+        let _ = cache.put(key, &record);
+    }
+    Ok(module)
+}
+
+fn compile_module_cached(
+    code_hash: &[u8],
+    code: &[u8],
+    config: &VMConfig,
+    cache: Option<&dyn CompiledContractCache>,
+) -> Result<wasmer_runtime::Module, VMError> {
+    match cache {
+        None => compile_module_from_code(code, config),
+        Some(cache) => {
+            let key = cache_key(code_hash, config);
+            let module = match cache.get(&key) {
+                Ok(Some(record)) => deserialize_module(&record),
+                _ => None,
+            };
+            match module {
+                Some(module) => Ok(module),
+                None => compile_and_store_module(&key, code, config, cache),
+            }
+        }
+    }
+}
+
+/// Compile the contract and store it in the persistent cache unless it is already there.
+/// Used to compile contracts ahead of their first call, i.e. on deployment.
+/// Does nothing for engines that don't support storing compiled modules.
+pub fn precompile_contract(
+    code_hash: &[u8],
+    code: &[u8],
+    config: &VMConfig,
+    cache: &dyn CompiledContractCache,
+) -> Result<(), VMError> {
+    if config.vm_kind != VMKind::Wasmer {
+        return Ok(());
+    }
+    let key = cache_key(code_hash, config);
+    if let Ok(Some(record)) = cache.get(&key) {
+        if verify_record(&record).is_some() {
+            return Ok(());
+        }
+    }
+    compile_and_store_module(&key, code, config, cache).map(|_| ())
+}
 
 #[cfg(not(feature = "no_cache"))]
 cached_key! {
     MODULES: SizedCache<(Vec<u8>, u64), Result<wasmer_runtime::Module, VMError>>
         = SizedCache::with_size(CACHE_SIZE);
     Key = {
-        (code_hash, config.non_crypto_hash())
+        (code_hash.clone(), config.non_crypto_hash())
     };
 
-    fn compile_module(code_hash: Vec<u8>, code: &[u8], config: &VMConfig
+    fn compile_module(code_hash: Vec<u8>, code: &[u8], config: &VMConfig,
+        cache: Option<&dyn CompiledContractCache>
         ) -> Result<wasmer_runtime::Module, VMError> = {
-        let prepared_code = prepare::prepare_contract(code, config)?;
-        wasmer_runtime::compile(&prepared_code).map_err(|err| err.into_vm_error())
+        compile_module_cached(&code_hash, code, config, cache)
     }
 }
 
@@ -32,7 +176,7 @@ pub(crate) fn compile_module(
     _code_hash: Vec<u8>,
     code: &[u8],
     config: &VMConfig,
+    _cache: Option<&dyn CompiledContractCache>,
 ) -> Result<wasmer_runtime::Module, VMError> {
-    let prepared_code = prepare::prepare_contract(code, config)?;
-    wasmer_runtime::compile(&prepared_code).map_err(|err| err.into_vm_error())
+    compile_module_from_code(code, config)
 }
@@ -1,6 +1,9 @@
 use near_vm_errors::{CompilationError, FunctionCallError, MethodResolveError, VMError};
 use near_vm_logic::VMLogicError;
 
+#[cfg(feature = "wasmi_vm")]
+use crate::imports::WasmiHostError;
+
 pub trait IntoVMError {
     fn into_vm_error(self) -> VMError;
 }
@@ -65,15 +68,7 @@ impl IntoVMError for wasmer_runtime::error::RuntimeError {
             }
             RuntimeError::Error { data } => {
                 if let Some(err) = data.downcast_ref::<VMLogicError>() {
-                    match err {
-                        VMLogicError::HostError(h) => {
-                            VMError::FunctionCallError(FunctionCallError::HostError(h.clone()))
-                        }
-                        VMLogicError::ExternalError(s) => VMError::ExternalError(s.clone()),
-                        VMLogicError::InconsistentStateError(e) => {
-                            VMError::InconsistentStateError(e.clone())
-                        }
-                    }
+                    err.clone().into_vm_error()
                 } else {
                     eprintln!(
                         "Bad error case! Output is non-deterministic {:?} {:?}",
@@ -88,3 +83,62 @@ impl IntoVMError for wasmer_runtime::error::RuntimeError {
         }
     }
 }
+
+impl IntoVMError for VMLogicError {
+    fn into_vm_error(self) -> VMError {
+        match self {
+            VMLogicError::HostError(h) => {
+                VMError::FunctionCallError(FunctionCallError::HostError(h))
+            }
+            VMLogicError::ExternalError(s) => VMError::ExternalError(s),
+            VMLogicError::InconsistentStateError(e) => VMError::InconsistentStateError(e),
+        }
+    }
+}
+
+#[cfg(feature = "wasmi_vm")]
+impl IntoVMError for wasmi::Error {
+    fn into_vm_error(self) -> VMError {
+        use wasmi::Error;
+        match self {
+            Error::Validation(msg) => VMError::FunctionCallError(
+                FunctionCallError::CompilationError(CompilationError::WasmerCompileError { msg }),
+            ),
+            Error::Trap(trap) => trap.into_vm_error(),
+            Error::Host(err) => match err.downcast_ref::<WasmiHostError>() {
+                Some(WasmiHostError(err)) => err.clone().into_vm_error(),
+                None => VMError::FunctionCallError(FunctionCallError::WasmTrap {
+                    msg: "unknown".to_string(),
+                }),
+            },
+            Error::Function(_) => VMError::FunctionCallError(
+                FunctionCallError::MethodResolveError(MethodResolveError::MethodInvalidSignature),
+            ),
+            // Imports are checked before instantiation, see `wasmi_link_error`.
+            Error::Instantiation(msg) => VMError::FunctionCallError(FunctionCallError::LinkError {
+                msg: format!("{:.500}", format!("link error: {}", msg)),
+            }),
+            err => VMError::FunctionCallError(FunctionCallError::LinkError {
+                msg: format!("{:.500}", err.to_string()),
+            }),
+        }
+    }
+}
+
+#[cfg(feature = "wasmi_vm")]
+impl IntoVMError for wasmi::Trap {
+    fn into_vm_error(self) -> VMError {
+        match self.kind() {
+            wasmi::TrapKind::Host(err) => match err.downcast_ref::<WasmiHostError>() {
+                Some(WasmiHostError(err)) => err.clone().into_vm_error(),
+                None => VMError::FunctionCallError(FunctionCallError::WasmTrap {
+                    msg: "unknown".to_string(),
+                }),
+            },
+            // Wasmer doesn't distinguish traps either, so engines report the same error.
+            _ => VMError::FunctionCallError(FunctionCallError::WasmTrap {
+                msg: "unknown".to_string(),
+            }),
+        }
+    }
+}
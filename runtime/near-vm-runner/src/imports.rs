@@ -1,14 +1,77 @@
 use std::ffi::c_void;
+#[cfg(feature = "wasmi_vm")]
+use std::fmt;
 
 use near_vm_logic::{VMLogic, VMLogicError};
+#[cfg(feature = "wasmi_vm")]
+use parity_wasm::elements::{self, External, Type};
 use wasmer_runtime::memory::Memory;
 use wasmer_runtime::{func, imports, Ctx, ImportObject};
+#[cfg(feature = "wasmi_vm")]
+use wasmi::{
+    FuncInstance, FuncRef, MemoryDescriptor, MemoryRef, ModuleImportResolver, RuntimeArgs,
+    RuntimeValue, Signature, Trap, TrapKind, ValueType,
+};
 
 type Result<T> = ::std::result::Result<T, VMLogicError>;
 struct ImportReference(*mut c_void);
 unsafe impl Send for ImportReference {}
 unsafe impl Sync for ImportReference {}
 
+#[cfg(feature = "wasmi_vm")]
+/// Error of the host function raised through the wasmi interpreter.
+#[derive(Debug)]
+pub(crate) struct WasmiHostError(pub VMLogicError);
+
+#[cfg(feature = "wasmi_vm")]
+impl fmt::Display for WasmiHostError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.0)
+    }
+}
+
+#[cfg(feature = "wasmi_vm")]
+impl wasmi::HostError for WasmiHostError {}
+
+#[cfg(feature = "wasmi_vm")]
+/// Type of the argument of the host function in wasmi.
+trait WasmiArg {
+    const VALUE_TYPE: ValueType;
+}
+
+#[cfg(feature = "wasmi_vm")]
+impl WasmiArg for u32 {
+    const VALUE_TYPE: ValueType = ValueType::I32;
+}
+
+#[cfg(feature = "wasmi_vm")]
+impl WasmiArg for u64 {
+    const VALUE_TYPE: ValueType = ValueType::I64;
+}
+
+#[cfg(feature = "wasmi_vm")]
+/// Type of the value returned by the host function in wasmi.
+trait WasmiReturn {
+    const VALUE_TYPE: Option<ValueType>;
+    fn into_runtime_value(self) -> Option<RuntimeValue>;
+}
+
+#[cfg(feature = "wasmi_vm")]
+impl WasmiReturn for () {
+    const VALUE_TYPE: Option<ValueType> = None;
+    fn into_runtime_value(self) -> Option<RuntimeValue> {
+        None
+    }
+}
+
+#[cfg(feature = "wasmi_vm")]
+impl WasmiReturn for u64 {
+    const VALUE_TYPE: Option<ValueType> = Some(ValueType::I64);
+    fn into_runtime_value(self) -> Option<RuntimeValue> {
+        Some(RuntimeValue::I64(self as i64))
+    }
+}
+
 macro_rules! wrapped_imports {
         ( $( $func:ident < [ $( $arg_name:ident : $arg_type:ident ),* ] -> [ $( $returns:ident ),* ] >, )* ) => {
             $(
@@ -34,9 +97,195 @@ macro_rules! wrapped_imports {
                     },
                 }
             }
+
+            #[cfg(feature = "wasmi_vm")]
+            #[allow(non_camel_case_types)]
+            #[derive(Clone, Copy)]
+            enum WasmiHostFunc {
+                $( $func, )*
+            }
+
+            #[cfg(feature = "wasmi_vm")]
+            /// Host functions available to wasmi, the position in the list is the function index.
+            const WASMI_HOST_FUNCS: &[(WasmiHostFunc, &str)] = &[
+                $( (WasmiHostFunc::$func, stringify!($func)), )*
+            ];
+
+            #[cfg(feature = "wasmi_vm")]
+            fn wasmi_signature(func: WasmiHostFunc) -> Signature {
+                match func {
+                    $(
+                        WasmiHostFunc::$func => {
+                            let params: Vec<ValueType> =
+                                vec![ $( <$arg_type as WasmiArg>::VALUE_TYPE ),* ];
+                            Signature::new(params, <($( $returns ),*) as WasmiReturn>::VALUE_TYPE)
+                        }
+                    )*
+                }
+            }
+
+            #[cfg(feature = "wasmi_vm")]
+            /// Links imports of the contract with the host functions and the memory for wasmi.
+            pub(crate) struct WasmiImports {
+                pub memory: MemoryRef,
+            }
+
+            #[cfg(feature = "wasmi_vm")]
+            impl ModuleImportResolver for WasmiImports {
+                fn resolve_func(
+                    &self,
+                    field_name: &str,
+                    signature: &Signature,
+                ) -> ::std::result::Result<FuncRef, wasmi::Error> {
+                    let index = WASMI_HOST_FUNCS
+                        .iter()
+                        .position(|(_, name)| *name == field_name)
+                        .ok_or_else(|| {
+                            wasmi::Error::Instantiation(format!(
+                                "Import not found, namespace: env, name: {}",
+                                field_name
+                            ))
+                        })?;
+                    let expected = wasmi_signature(WASMI_HOST_FUNCS[index].0);
+                    if &expected != signature {
+                        return Err(wasmi::Error::Instantiation(format!(
+                            "Incorrect import signature, namespace: env, name: {}",
+                            field_name
+                        )));
+                    }
+                    Ok(FuncInstance::alloc_host(expected, index))
+                }
+
+                fn resolve_memory(
+                    &self,
+                    field_name: &str,
+                    _memory_type: &MemoryDescriptor,
+                ) -> ::std::result::Result<MemoryRef, wasmi::Error> {
+                    if field_name == "memory" {
+                        Ok(self.memory.clone())
+                    } else {
+                        Err(wasmi::Error::Instantiation(format!(
+                            "Import not found, namespace: env, name: {}",
+                            field_name
+                        )))
+                    }
+                }
+            }
+
+            #[cfg(feature = "wasmi_vm")]
+            /// Dispatches calls of the host functions from wasmi to `VMLogic`.
+            pub(crate) struct WasmiExternals<'a, 'b> {
+                pub logic: &'a mut VMLogic<'b>,
+            }
+
+            #[cfg(feature = "wasmi_vm")]
+            impl wasmi::Externals for WasmiExternals<'_, '_> {
+                fn invoke_index(
+                    &mut self,
+                    index: usize,
+                    args: RuntimeArgs,
+                ) -> ::std::result::Result<Option<RuntimeValue>, Trap> {
+                    let func = WASMI_HOST_FUNCS
+                        .get(index)
+                        .ok_or_else(|| Trap::new(TrapKind::UnexpectedSignature))?
+                        .0;
+                    #[allow(unused_mut, unused_variables)]
+                    let mut args = args.as_ref().iter();
+                    match func {
+                        $(
+                            WasmiHostFunc::$func => {
+                                $(
+                                    let $arg_name: $arg_type = args
+                                        .next()
+                                        .and_then(|value| value.try_into())
+                                        .ok_or_else(|| Trap::new(TrapKind::UnexpectedSignature))?;
+                                )*
+                                self.logic
+                                    .$func( $( $arg_name, )* )
+                                    .map(WasmiReturn::into_runtime_value)
+                                    .map_err(|err| Trap::new(TrapKind::Host(Box::new(WasmiHostError(err)))))
+                            }
+                        )*
+                    }
+                }
+            }
         }
     }
 
+/// Formats the signature the same way as wasmer, i.e. `[I32, I64] -> [I64]`.
+#[cfg(feature = "wasmi_vm")]
+fn describe_signature<T: fmt::Debug>(params: &[T], return_type: Option<T>) -> String {
+    let params = params.iter().map(|t| format!("{:?}", t)).collect::<Vec<_>>().join(", ");
+    let returns = return_type.map(|t| format!("{:?}", t)).unwrap_or_default();
+    format!("[{}] -> [{}]", params, returns)
+}
+
+/// Checks all the imports of the module before wasmi instantiates it, since wasmi stops at the
+/// first import it fails to resolve, while wasmer reports all of them. Returns the errors
+/// formatted the same way as wasmer does.
+#[cfg(feature = "wasmi_vm")]
+pub(crate) fn wasmi_link_error(module: &elements::Module) -> Option<String> {
+    let types = module.type_section().map(elements::TypeSection::types).unwrap_or(&[]);
+    let imports = module.import_section().map(elements::ImportSection::entries).unwrap_or(&[]);
+    let mut errors = vec![];
+    // Wasmer links functions, memories, tables and globals in this order.
+    for kind in &["function", "memory", "table", "global"] {
+        for import in imports {
+            let (namespace, name) = (import.module(), import.field());
+            let expected_kind = match import.external() {
+                External::Function(_) => "function",
+                External::Memory(_) => "memory",
+                External::Table(_) => "table",
+                External::Global(_) => "global",
+            };
+            if expected_kind != *kind {
+                continue;
+            }
+            let host_func = WASMI_HOST_FUNCS.iter().find(|(_, func_name)| *func_name == name);
+            let found_kind = match (namespace, name, host_func) {
+                ("env", "memory", _) => "memory",
+                ("env", _, Some(_)) => "function",
+                _ => {
+                    errors.push(format!(
+                        "Import not found, namespace: {}, name: {}",
+                        namespace, name
+                    ));
+                    continue;
+                }
+            };
+            if found_kind != expected_kind {
+                errors.push(format!(
+                    "Incorrect import type, namespace: {}, name: {}, expected type: {}, found type: {}",
+                    namespace, name, expected_kind, found_kind
+                ));
+                continue;
+            }
+            if let (External::Function(type_idx), Some((func, _))) = (import.external(), host_func)
+            {
+                let expected = match types.get(*type_idx as usize) {
+                    Some(Type::Function(func_type)) => {
+                        describe_signature(func_type.params(), func_type.return_type())
+                    }
+                    None => continue,
+                };
+                let signature = wasmi_signature(*func);
+                let found = describe_signature(signature.params(), signature.return_type());
+                if expected != found {
+                    errors.push(format!(
+                        "Incorrect import signature, namespace: {}, name: {}, expected signature: {}, found signature: {}",
+                        namespace, name, expected, found
+                    ));
+                }
+            }
+        }
+    }
+    match errors.len() {
+        0 => None,
+        1 => Some(format!("link error: {}", errors[0])),
+        count => Some(format!("{} link errors: ({})", count, errors.join(", "))),
+    }
+}
+
 wrapped_imports! {
     // #############
     // # Registers #
@@ -55,6 +304,7 @@ wrapped_imports! {
     // TODO #1903 rename to `block_height`
     block_index<[] -> [u64]>,
     block_timestamp<[] -> [u64]>,
+    epoch_height<[] -> [u64]>,
     storage_usage<[] -> [u64]>,
     // #################
     // # Economics API #
@@ -64,6 +314,8 @@ wrapped_imports! {
     attached_deposit<[balance_ptr: u64] -> []>,
     prepaid_gas<[] -> [u64]>,
     used_gas<[] -> [u64]>,
+    validator_stake<[account_id_len: u64, account_id_ptr: u64, stake_ptr: u64] -> []>,
+    validator_total_stake<[stake_ptr: u64] -> []>,
     // ############
     // # Math API #
     // ############
@@ -71,6 +323,24 @@ wrapped_imports! {
     sha256<[value_len: u64, value_ptr: u64, register_id: u64] -> []>,
     keccak256<[value_len: u64, value_ptr: u64, register_id: u64] -> []>,
     keccak512<[value_len: u64, value_ptr: u64, register_id: u64] -> []>,
+    ripemd160<[value_len: u64, value_ptr: u64, register_id: u64] -> []>,
+    blake2b<[value_len: u64, value_ptr: u64, register_id: u64] -> []>,
+    ed25519_verify<[
+        signature_len: u64,
+        signature_ptr: u64,
+        message_len: u64,
+        message_ptr: u64,
+        public_key_len: u64,
+        public_key_ptr: u64
+    ] -> [u64]>,
+    ecrecover<[
+        hash_len: u64,
+        hash_ptr: u64,
+        signature_len: u64,
+        signature_ptr: u64,
+        v: u64,
+        register_id: u64
+    ] -> [u64]>,
     // #####################
     // # Miscellaneous API #
     // #####################
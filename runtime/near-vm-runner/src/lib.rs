@@ -4,6 +4,10 @@ mod imports;
 mod memory;
 mod prepare;
 mod runner;
+mod wasmer_runner;
+#[cfg(feature = "wasmi_vm")]
+mod wasmi_runner;
+pub use cache::{precompile_contract, CompiledContractCache};
 pub use near_vm_errors::VMError;
 pub use runner::run;
 
@@ -4,6 +4,7 @@ use wasmer_runtime::units::{Bytes, Pages};
 use wasmer_runtime::wasm::MemoryDescriptor;
 use wasmer_runtime::Memory;
 
+#[derive(Clone)]
 pub struct WasmerMemory(Memory);
 
 impl WasmerMemory {
@@ -21,8 +22,8 @@ impl WasmerMemory {
         ))
     }
 
-    pub fn clone(&self) -> Memory {
-        self.0.clone()
+    pub fn into_inner(self) -> Memory {
+        self.0
     }
 }
 
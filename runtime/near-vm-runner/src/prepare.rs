@@ -2,11 +2,16 @@
 //! wasm module before execution.
 
 use parity_wasm::builder;
-use parity_wasm::elements::{self, External, MemorySection, Type};
+use parity_wasm::elements::{self, External, Instruction, Local, MemorySection, Type, ValueType};
 use pwasm_utils::{self, rules};
 
 use near_vm_errors::PrepareError;
-use near_vm_logic::VMConfig;
+use near_vm_logic::{FloatPolicy, VMConfig};
+
+/// Bit pattern of the canonical NaN of `f32`: positive, quiet, with zero payload.
+const CANONICAL_NAN_F32: u32 = 0x7fc0_0000;
+/// Bit pattern of the canonical NaN of `f64`: positive, quiet, with zero payload.
+const CANONICAL_NAN_F64: u64 = 0x7ff8_0000_0000_0000;
 
 struct ContractModule<'a> {
     module: elements::Module,
@@ -56,6 +61,52 @@ impl<'a> ContractModule<'a> {
         }
     }
 
+    /// Makes results of floating point operations independent of the hardware, see `FloatPolicy`.
+    fn handle_floats(self) -> Result<Self, PrepareError> {
+        match self.config.float_policy {
+            FloatPolicy::Allow => Ok(self),
+            FloatPolicy::Canonicalize => Ok(self.canonicalize_nans()),
+            FloatPolicy::Reject => self.reject_floats(),
+        }
+    }
+
+    fn reject_floats(self) -> Result<Self, PrepareError> {
+        let in_code = self.module.code_section().map_or(false, |code| {
+            code.bodies().iter().any(|body| body.code().elements().iter().any(is_float_instruction))
+        });
+        let in_globals = self.module.global_section().map_or(false, |globals| {
+            globals
+                .entries()
+                .iter()
+                .any(|global| global.init_expr().code().iter().any(is_float_instruction))
+        });
+        if in_code || in_globals {
+            Err(PrepareError::FloatingPoint)
+        } else {
+            Ok(self)
+        }
+    }
+
+    /// Replaces the result of every operation that can produce NaN with the canonical NaN, if it
+    /// is NaN. Must be done before gas metering, so the injected code is paid for.
+    fn canonicalize_nans(self) -> Self {
+        let Self { mut module, config } = self;
+        let types = module.type_section().map_or(vec![], |section| section.types().to_vec());
+        let func_types: Vec<u32> = module.function_section().map_or(vec![], |section| {
+            section.entries().iter().map(elements::Func::type_ref).collect()
+        });
+        if let Some(code) = module.code_section_mut() {
+            for (body, type_ref) in code.bodies_mut().iter_mut().zip(func_types) {
+                let params_count = match types.get(type_ref as usize) {
+                    Some(Type::Function(func_type)) => func_type.params().len() as u32,
+                    None => 0,
+                };
+                canonicalize_nans_in_body(body, params_count);
+            }
+        }
+        Self { module, config }
+    }
+
     fn inject_gas_metering(self) -> Result<Self, PrepareError> {
         let Self { module, config } = self;
         let gas_rules = rules::Set::new(1, Default::default()).with_grow_cost(config.grow_mem_cost);
@@ -138,6 +189,78 @@ impl<'a> ContractModule<'a> {
     }
 }
 
+fn is_float_instruction(instruction: &Instruction) -> bool {
+    use Instruction::*;
+    match instruction {
+        F32Load(..) | F64Load(..) | F32Store(..) | F64Store(..) | F32Const(_) | F64Const(_)
+        | F32Eq | F32Ne | F32Lt | F32Gt | F32Le | F32Ge | F64Eq | F64Ne | F64Lt | F64Gt | F64Le
+        | F64Ge | F32Abs | F32Neg | F32Ceil | F32Floor | F32Trunc | F32Nearest | F32Sqrt
+        | F32Add | F32Sub | F32Mul | F32Div | F32Min | F32Max | F32Copysign | F64Abs | F64Neg
+        | F64Ceil | F64Floor | F64Trunc | F64Nearest | F64Sqrt | F64Add | F64Sub | F64Mul
+        | F64Div | F64Min | F64Max | F64Copysign | I32TruncSF32 | I32TruncUF32 | I32TruncSF64
+        | I32TruncUF64 | I64TruncSF32 | I64TruncUF32 | I64TruncSF64 | I64TruncUF64
+        | F32ConvertSI32 | F32ConvertUI32 | F32ConvertSI64 | F32ConvertUI64 | F32DemoteF64
+        | F64ConvertSI32 | F64ConvertUI32 | F64ConvertSI64 | F64ConvertUI64 | F64PromoteF32
+        | I32ReinterpretF32 | I64ReinterpretF64 | F32ReinterpretI32 | F64ReinterpretI64 => true,
+        _ => false,
+    }
+}
+
+/// Type of the result of the instruction, if it can be NaN with a hardware dependent bit pattern.
+/// Sign operations (`abs`, `neg`, `copysign`), loads and reinterpretations only move bits, so
+/// they are deterministic.
+fn nan_result_type(instruction: &Instruction) -> Option<ValueType> {
+    use Instruction::*;
+    match instruction {
+        F32Ceil | F32Floor | F32Trunc | F32Nearest | F32Sqrt | F32Add | F32Sub | F32Mul
+        | F32Div | F32Min | F32Max | F32DemoteF64 => Some(ValueType::F32),
+        F64Ceil | F64Floor | F64Trunc | F64Nearest | F64Sqrt | F64Add | F64Sub | F64Mul
+        | F64Div | F64Min | F64Max | F64PromoteF32 => Some(ValueType::F64),
+        _ => None,
+    }
+}
+
+fn canonicalize_nans_in_body(body: &mut elements::FuncBody, params_count: u32) {
+    if !body.code().elements().iter().any(|instruction| nan_result_type(instruction).is_some()) {
+        return;
+    }
+    // Temporary locals that hold the result while it is checked.
+    let locals_count: u32 = params_count + body.locals().iter().map(Local::count).sum::<u32>();
+    let (f32_tmp, f64_tmp) = (locals_count, locals_count + 1);
+    body.locals_mut().push(Local::new(1, ValueType::F32));
+    body.locals_mut().push(Local::new(1, ValueType::F64));
+
+    let code = std::mem::replace(body.code_mut().elements_mut(), vec![]);
+    let mut result = Vec::with_capacity(code.len());
+    for instruction in code {
+        let value_type = nan_result_type(&instruction);
+        result.push(instruction);
+        // `select` picks the canonical NaN if the value is not equal to itself, i.e. is NaN.
+        match value_type {
+            Some(ValueType::F32) => result.extend_from_slice(&[
+                Instruction::SetLocal(f32_tmp),
+                Instruction::F32Const(CANONICAL_NAN_F32),
+                Instruction::GetLocal(f32_tmp),
+                Instruction::GetLocal(f32_tmp),
+                Instruction::GetLocal(f32_tmp),
+                Instruction::F32Ne,
+                Instruction::Select,
+            ]),
+            Some(ValueType::F64) => result.extend_from_slice(&[
+                Instruction::SetLocal(f64_tmp),
+                Instruction::F64Const(CANONICAL_NAN_F64),
+                Instruction::GetLocal(f64_tmp),
+                Instruction::GetLocal(f64_tmp),
+                Instruction::GetLocal(f64_tmp),
+                Instruction::F64Ne,
+                Instruction::Select,
+            ]),
+            _ => {}
+        }
+    }
+    *body.code_mut().elements_mut() = result;
+}
+
 /// Loads the given module given in `original_code`, performs some checks on it and
 /// does some preprocessing.
 ///
@@ -147,11 +270,14 @@ impl<'a> ContractModule<'a> {
 /// - imported memory (if any) doesn't reserve more memory than permitted by the `config`,
 /// - all imported functions from the external environment matches defined by `env` module,
 ///
-/// The preprocessing includes injecting code for gas metering and metering the height of stack.
+/// The preprocessing includes canonicalization of NaNs (or rejection of floating point
+/// operations, depending on `config.float_policy`), injecting code for gas metering and metering
+/// the height of stack.
 pub fn prepare_contract(original_code: &[u8], config: &VMConfig) -> Result<Vec<u8>, PrepareError> {
     ContractModule::init(original_code, config)?
         .standardize_mem()
         .ensure_no_internal_memory()?
+        .handle_floats()?
         .inject_gas_metering()?
         .inject_stack_height_metering()?
         .scan_imports()?
@@ -223,4 +349,52 @@ mod tests {
         assert_matches!(r, Err(Error::Instantiate));
         */
     }
+
+    #[test]
+    fn floats_rejected() {
+        let config = VMConfig { float_policy: FloatPolicy::Reject, ..VMConfig::default() };
+        let prepare = |wat: &str| {
+            let wasm = wabt::wat2wasm(wat).unwrap();
+            prepare_contract(&wasm, &config)
+        };
+        let r = prepare(r#"(module (func (result i32) (i32.add (i32.const 1) (i32.const 2))))"#);
+        assert_matches!(r, Ok(_));
+        let r = prepare(r#"(module (func (result f32) (f32.const 1)))"#);
+        assert_matches!(r, Err(PrepareError::FloatingPoint));
+        let r = prepare(r#"(module (global f64 (f64.const 1)))"#);
+        assert_matches!(r, Err(PrepareError::FloatingPoint));
+        let r = prepare(
+            r#"(module (func (param i32) (result i32)
+                (i32.trunc_s/f32 (f32.convert_s/i32 (get_local 0)))))"#,
+        );
+        assert_matches!(r, Err(PrepareError::FloatingPoint));
+    }
+
+    #[test]
+    fn nans_canonicalized() {
+        let wasm = wabt::wat2wasm(
+            r#"(module (func (param f32 f32) (result f32) (f32.div (get_local 0) (get_local 1))))"#,
+        )
+        .unwrap();
+        let config = VMConfig { float_policy: FloatPolicy::Canonicalize, ..VMConfig::default() };
+        let prepared = prepare_contract(&wasm, &config).unwrap();
+        let module: elements::Module = elements::deserialize_buffer(&prepared).unwrap();
+        let body = &module.code_section().unwrap().bodies()[0];
+        // Temporary locals are declared after the parameters.
+        assert_eq!(body.locals()[0].value_type(), ValueType::F32);
+        let code = body.code().elements();
+        let div = code.iter().position(|i| *i == Instruction::F32Div).unwrap();
+        assert_eq!(
+            &code[div + 1..div + 8],
+            &[
+                Instruction::SetLocal(2),
+                Instruction::F32Const(CANONICAL_NAN_F32),
+                Instruction::GetLocal(2),
+                Instruction::GetLocal(2),
+                Instruction::GetLocal(2),
+                Instruction::F32Ne,
+                Instruction::Select,
+            ]
+        );
+    }
 }
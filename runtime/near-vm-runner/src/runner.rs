@@ -1,30 +1,42 @@
-use crate::errors::IntoVMError;
-use crate::memory::WasmerMemory;
-use crate::{cache, imports};
+use crate::cache::CompiledContractCache;
+use crate::wasmer_runner::WasmerVM;
+#[cfg(feature = "wasmi_vm")]
+use crate::wasmi_runner::WasmiVM;
 use near_runtime_fees::RuntimeFeesConfig;
 use near_vm_errors::{FunctionCallError, MethodResolveError, VMError};
 use near_vm_logic::types::PromiseResult;
-use near_vm_logic::{External, VMConfig, VMContext, VMLogic, VMOutcome};
-use wasmer_runtime::Module;
+use near_vm_logic::{
+    External, MemoryLike, VMConfig, VMContext, VMKind, VMLimitConfig, VMLogic, VMOutcome,
+};
 
-fn check_method(module: &Module, method_name: &str) -> Result<(), VMError> {
-    let info = module.info();
-    use wasmer_runtime_core::module::ExportIndex::Func;
-    if let Some(Func(index)) = info.exports.get(method_name) {
-        let func = info.func_assoc.get(index.clone()).unwrap();
-        let sig = info.signatures.get(func.clone()).unwrap();
-        if sig.params().is_empty() && sig.returns().is_empty() {
-            Ok(())
-        } else {
-            Err(VMError::FunctionCallError(FunctionCallError::MethodResolveError(
-                MethodResolveError::MethodInvalidSignature,
-            )))
-        }
-    } else {
-        Err(VMError::FunctionCallError(FunctionCallError::MethodResolveError(
-            MethodResolveError::MethodNotFound,
-        )))
-    }
+/// Engine that executes contracts.
+pub(crate) trait VM {
+    type Module;
+    /// Memory of the contract. Clones must share the underlying memory, because it is accessed
+    /// both by the instance and by `VMLogic`.
+    type Memory: MemoryLike + Clone;
+
+    /// Validates, instruments and compiles the `code`.
+    fn compile(
+        code_hash: Vec<u8>,
+        code: &[u8],
+        config: &VMConfig,
+        cache: Option<&dyn CompiledContractCache>,
+    ) -> Result<Self::Module, VMError>;
+
+    /// Checks that the module exports the method without arguments and returns.
+    fn check_method(module: &Self::Module, method_name: &str) -> Result<(), VMError>;
+
+    fn create_memory(limit_config: &VMLimitConfig) -> Result<Self::Memory, VMError>;
+
+    /// Links `VMLogic` and the memory with the imports of the module, instantiates it and calls
+    /// the method.
+    fn call(
+        module: &Self::Module,
+        memory: Self::Memory,
+        logic: &mut VMLogic,
+        method_name: &str,
+    ) -> Result<(), VMError>;
 }
 
 /// `run` does the following:
@@ -38,6 +50,8 @@ fn check_method(module: &Module, method_name: &str) -> Result<(), VMError> {
 ///   - collects logs
 ///   - sets the return data
 ///  returns result as `VMOutcome`
+///
+/// The engine is selected by `wasm_config.vm_kind`.
 pub fn run<'a>(
     code_hash: Vec<u8>,
     code: &[u8],
@@ -47,6 +61,7 @@ pub fn run<'a>(
     wasm_config: &'a VMConfig,
     fees_config: &'a RuntimeFeesConfig,
     promise_results: &'a [PromiseResult],
+    cache: Option<&'a dyn CompiledContractCache>,
 ) -> (Option<VMOutcome>, Option<VMError>) {
     if !cfg!(target_arch = "x86") && !cfg!(target_arch = "x86_64") {
         // TODO(#1940): Remove once NaN is standardized by the VM.
@@ -54,6 +69,46 @@ pub fn run<'a>(
             "Execution of smart contracts is only supported for x86 and x86_64 CPU architectures."
         );
     }
+    match wasm_config.vm_kind {
+        VMKind::Wasmer => run_vm::<WasmerVM>(
+            code_hash,
+            code,
+            method_name,
+            ext,
+            context,
+            wasm_config,
+            fees_config,
+            promise_results,
+            cache,
+        ),
+        #[cfg(feature = "wasmi_vm")]
+        VMKind::Wasmi => run_vm::<WasmiVM>(
+            code_hash,
+            code,
+            method_name,
+            ext,
+            context,
+            wasm_config,
+            fees_config,
+            promise_results,
+            cache,
+        ),
+        #[cfg(not(feature = "wasmi_vm"))]
+        VMKind::Wasmi => panic!("Wasmi is only available with the `wasmi_vm` feature"),
+    }
+}
+
+fn run_vm<'a, V: VM>(
+    code_hash: Vec<u8>,
+    code: &[u8],
+    method_name: &[u8],
+    ext: &mut dyn External,
+    context: VMContext,
+    wasm_config: &'a VMConfig,
+    fees_config: &'a RuntimeFeesConfig,
+    promise_results: &'a [PromiseResult],
+    cache: Option<&'a dyn CompiledContractCache>,
+) -> (Option<VMOutcome>, Option<VMError>) {
     if method_name.is_empty() {
         return (
             None,
@@ -63,14 +118,11 @@ pub fn run<'a>(
         );
     }
 
-    let module = match cache::compile_module(code_hash, code, wasm_config) {
+    let module = match V::compile(code_hash, code, wasm_config, cache) {
         Ok(x) => x,
         Err(err) => return (None, Some(err)),
     };
-    let mut memory = match WasmerMemory::new(
-        wasm_config.limit_config.initial_memory_pages,
-        wasm_config.limit_config.max_memory_pages,
-    ) {
+    let mut memory = match V::create_memory(&wasm_config.limit_config) {
         Ok(x) => x,
         Err(_err) => panic!("Cannot create memory for a contract call"),
     };
@@ -79,8 +131,6 @@ pub fn run<'a>(
     let mut logic =
         VMLogic::new(ext, context, wasm_config, fees_config, promise_results, &mut memory);
 
-    let import_object = imports::build(memory_copy, &mut logic);
-
     let method_name = match std::str::from_utf8(method_name) {
         Ok(x) => x,
         Err(_) => {
@@ -92,15 +142,12 @@ pub fn run<'a>(
             )
         }
     };
-    if let Err(e) = check_method(&module, method_name) {
+    if let Err(e) = V::check_method(&module, method_name) {
         return (None, Some(e));
     }
 
-    match module.instantiate(&import_object) {
-        Ok(instance) => match instance.call(&method_name, &[]) {
-            Ok(_) => (Some(logic.outcome()), None),
-            Err(err) => (Some(logic.outcome()), Some(err.into_vm_error())),
-        },
-        Err(err) => (Some(logic.outcome()), Some(err.into_vm_error())),
+    match V::call(&module, memory_copy, &mut logic, method_name) {
+        Ok(()) => (Some(logic.outcome()), None),
+        Err(err) => (Some(logic.outcome()), Some(err)),
     }
 }
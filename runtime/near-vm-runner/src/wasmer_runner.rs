@@ -0,0 +1,61 @@
+use crate::cache::{self, CompiledContractCache};
+use crate::errors::IntoVMError;
+use crate::imports;
+use crate::memory::WasmerMemory;
+use crate::runner::VM;
+use near_vm_errors::{FunctionCallError, MethodResolveError, VMError};
+use near_vm_logic::{VMConfig, VMLimitConfig, VMLogic};
+use wasmer_runtime::Module;
+
+/// Wasmer with the singlepass compiler. Compiled modules are cached in memory and in the
+/// `CompiledContractCache`.
+pub(crate) struct WasmerVM;
+
+impl VM for WasmerVM {
+    type Module = Module;
+    type Memory = WasmerMemory;
+
+    fn compile(
+        code_hash: Vec<u8>,
+        code: &[u8],
+        config: &VMConfig,
+        cache: Option<&dyn CompiledContractCache>,
+    ) -> Result<Module, VMError> {
+        cache::compile_module(code_hash, code, config, cache)
+    }
+
+    fn check_method(module: &Module, method_name: &str) -> Result<(), VMError> {
+        let info = module.info();
+        use wasmer_runtime_core::module::ExportIndex::Func;
+        if let Some(Func(index)) = info.exports.get(method_name) {
+            let func = info.func_assoc.get(index.clone()).unwrap();
+            let sig = info.signatures.get(func.clone()).unwrap();
+            if sig.params().is_empty() && sig.returns().is_empty() {
+                Ok(())
+            } else {
+                Err(VMError::FunctionCallError(FunctionCallError::MethodResolveError(
+                    MethodResolveError::MethodInvalidSignature,
+                )))
+            }
+        } else {
+            Err(VMError::FunctionCallError(FunctionCallError::MethodResolveError(
+                MethodResolveError::MethodNotFound,
+            )))
+        }
+    }
+
+    fn create_memory(limit_config: &VMLimitConfig) -> Result<WasmerMemory, VMError> {
+        WasmerMemory::new(limit_config.initial_memory_pages, limit_config.max_memory_pages)
+    }
+
+    fn call(
+        module: &Module,
+        memory: WasmerMemory,
+        logic: &mut VMLogic,
+        method_name: &str,
+    ) -> Result<(), VMError> {
+        let import_object = imports::build(memory.into_inner(), logic);
+        let instance = module.instantiate(&import_object).map_err(|err| err.into_vm_error())?;
+        instance.call(method_name, &[]).map(|_| ()).map_err(|err| err.into_vm_error())
+    }
+}
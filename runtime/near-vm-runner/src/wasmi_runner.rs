@@ -0,0 +1,153 @@
+use crate::cache::CompiledContractCache;
+use crate::errors::IntoVMError;
+use crate::imports::{wasmi_link_error, WasmiExternals, WasmiImports};
+use crate::prepare;
+use crate::runner::VM;
+use near_vm_errors::{FunctionCallError, MethodResolveError, PrepareError, VMError};
+use near_vm_logic::{MemoryLike, VMConfig, VMLimitConfig, VMLogic};
+use parity_wasm::elements::{self, External, Internal, Type};
+use wasmer_runtime_core::backend::Features;
+use wasmer_runtime_core::codegen::validate_with_features;
+use wasmi::memory_units::{Bytes, Pages};
+use wasmi::{ImportsBuilder, MemoryInstance, MemoryRef, ModuleInstance};
+
+/// Wasmi interpreter. Slower than wasmer, but doesn't depend on the native code generation, so
+/// it serves as a reference to test wasmer against. Modules are not cached.
+/// Deep recursion is stopped by the stack height instrumentation before it reaches the value and
+/// call stack limits of wasmi, as long as `max_stack_height` stays below them.
+pub(crate) struct WasmiVM;
+
+pub(crate) struct WasmiModule {
+    module: wasmi::Module,
+    /// Instrumented module, used to inspect exports.
+    elements: elements::Module,
+}
+
+#[derive(Clone)]
+pub(crate) struct WasmiMemory(MemoryRef);
+
+impl MemoryLike for WasmiMemory {
+    fn fits_memory(&self, offset: u64, len: u64) -> bool {
+        match offset.checked_add(len) {
+            None => false,
+            Some(end) => Bytes::from(self.0.current_size()).0 as u64 >= end,
+        }
+    }
+
+    fn read_memory(&self, offset: u64, buffer: &mut [u8]) {
+        self.0.get_into(offset as u32, buffer).expect("Memory access is checked by `fits_memory`")
+    }
+
+    fn read_memory_u8(&self, offset: u64) -> u8 {
+        let mut buffer = [0u8];
+        self.read_memory(offset, &mut buffer);
+        buffer[0]
+    }
+
+    fn write_memory(&mut self, offset: u64, buffer: &[u8]) {
+        self.0.set(offset as u32, buffer).expect("Memory access is checked by `fits_memory`")
+    }
+}
+
+/// Type of the function with the given index, counting imported functions first.
+fn function_type(module: &elements::Module, index: u32) -> Option<&elements::FunctionType> {
+    let imported_types = module
+        .import_section()
+        .map(elements::ImportSection::entries)
+        .unwrap_or(&[])
+        .iter()
+        .filter_map(|entry| match entry.external() {
+            External::Function(type_idx) => Some(*type_idx),
+            _ => None,
+        });
+    let defined_types = module
+        .function_section()
+        .map(elements::FunctionSection::entries)
+        .unwrap_or(&[])
+        .iter()
+        .map(elements::Func::type_ref);
+    let type_idx = imported_types.chain(defined_types).nth(index as usize)?;
+    match module.type_section()?.types().get(type_idx as usize)? {
+        Type::Function(func_type) => Some(func_type),
+    }
+}
+
+impl VM for WasmiVM {
+    type Module = WasmiModule;
+    type Memory = WasmiMemory;
+
+    fn compile(
+        _code_hash: Vec<u8>,
+        code: &[u8],
+        config: &VMConfig,
+        _cache: Option<&dyn CompiledContractCache>,
+    ) -> Result<WasmiModule, VMError> {
+        let prepared_code = prepare::prepare_contract(code, config)?;
+        // Modules are validated the same way as wasmer does before compilation, so invalid
+        // modules are rejected with the same errors.
+        validate_with_features(&prepared_code, &Features::default())
+            .map_err(|err| err.into_vm_error())?;
+        let elements = elements::deserialize_buffer(&prepared_code)
+            .map_err(|_| PrepareError::Deserialization)?;
+        let module = wasmi::Module::from_parity_wasm_module(elements.clone())
+            .map_err(|err| err.into_vm_error())?;
+        Ok(WasmiModule { module, elements })
+    }
+
+    fn check_method(module: &WasmiModule, method_name: &str) -> Result<(), VMError> {
+        let export = module
+            .elements
+            .export_section()
+            .and_then(|section| section.entries().iter().find(|e| e.field() == method_name));
+        let func_type = match export.map(elements::ExportEntry::internal) {
+            Some(Internal::Function(index)) => function_type(&module.elements, *index),
+            _ => None,
+        };
+        match func_type {
+            Some(func_type)
+                if func_type.params().is_empty() && func_type.return_type().is_none() =>
+            {
+                Ok(())
+            }
+            Some(_) => Err(VMError::FunctionCallError(FunctionCallError::MethodResolveError(
+                MethodResolveError::MethodInvalidSignature,
+            ))),
+            None => Err(VMError::FunctionCallError(FunctionCallError::MethodResolveError(
+                MethodResolveError::MethodNotFound,
+            ))),
+        }
+    }
+
+    fn create_memory(limit_config: &VMLimitConfig) -> Result<WasmiMemory, VMError> {
+        MemoryInstance::alloc(
+            Pages(limit_config.initial_memory_pages as usize),
+            Some(Pages(limit_config.max_memory_pages as usize)),
+        )
+        .map(WasmiMemory)
+        .map_err(|err| err.into_vm_error())
+    }
+
+    fn call(
+        module: &WasmiModule,
+        memory: WasmiMemory,
+        logic: &mut VMLogic,
+        method_name: &str,
+    ) -> Result<(), VMError> {
+        if let Some(msg) = wasmi_link_error(&module.elements) {
+            return Err(VMError::FunctionCallError(FunctionCallError::LinkError {
+                msg: format!("{:.500}", msg),
+            }));
+        }
+        let resolver = WasmiImports { memory: memory.0 };
+        let imports = ImportsBuilder::new().with_resolver("env", &resolver);
+        let mut externals = WasmiExternals { logic };
+        let instance = ModuleInstance::new(&module.module, &imports)
+            .map_err(|err| err.into_vm_error())?
+            .run_start(&mut externals)
+            .map_err(|err| err.into_vm_error())?;
+        instance
+            .invoke_export(method_name, &[], &mut externals)
+            .map(|_| ())
+            .map_err(|err| err.into_vm_error())
+    }
+}
@@ -0,0 +1,104 @@
+use near_runtime_fees::RuntimeFeesConfig;
+use near_vm_logic::mocks::mock_external::MockedExternal;
+use near_vm_logic::{VMConfig, VMOutcome};
+use near_vm_runner::{precompile_contract, run, CompiledContractCache, VMError};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use wabt::wat2wasm;
+
+mod utils;
+
+use crate::utils::create_context;
+
+#[derive(Default)]
+struct MockCompiledContractCache {
+    store: Mutex<HashMap<Vec<u8>, Vec<u8>>>,
+}
+
+impl MockCompiledContractCache {
+    fn len(&self) -> usize {
+        self.store.lock().unwrap().len()
+    }
+
+    fn values(&self) -> Vec<Vec<u8>> {
+        self.store.lock().unwrap().values().cloned().collect()
+    }
+
+    fn corrupt(&self) {
+        for value in self.store.lock().unwrap().values_mut() {
+            let last = value.len() - 1;
+            value[last] ^= 1;
+        }
+    }
+}
+
+impl CompiledContractCache for MockCompiledContractCache {
+    fn put(&self, key: &[u8], value: &[u8]) -> Result<(), std::io::Error> {
+        self.store.lock().unwrap().insert(key.to_vec(), value.to_vec());
+        Ok(())
+    }
+
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, std::io::Error> {
+        Ok(self.store.lock().unwrap().get(key).cloned())
+    }
+}
+
+fn simple_contract() -> Vec<u8> {
+    wat2wasm(r#"(module (func (export "main")))"#).unwrap()
+}
+
+fn call_main(
+    code_hash: &[u8],
+    code: &[u8],
+    cache: &MockCompiledContractCache,
+) -> (Option<VMOutcome>, Option<VMError>) {
+    let mut fake_external = MockedExternal::new();
+    run(
+        code_hash.to_vec(),
+        code,
+        b"main",
+        &mut fake_external,
+        create_context(vec![]),
+        &VMConfig::default(),
+        &RuntimeFeesConfig::default(),
+        &[],
+        Some(cache),
+    )
+}
+
+#[test]
+fn test_compiled_module_is_stored() {
+    let code = simple_contract();
+    let cache = MockCompiledContractCache::default();
+    let (outcome, err) = call_main(b"cache_stored", &code, &cache);
+    assert!(outcome.is_some());
+    assert_eq!(err, None);
+    assert_eq!(cache.len(), 1);
+}
+
+#[test]
+fn test_precompiled_module_is_used() {
+    let code = simple_contract();
+    let cache = MockCompiledContractCache::default();
+    precompile_contract(b"cache_precompiled", &code, &VMConfig::default(), &cache).unwrap();
+    assert_eq!(cache.len(), 1);
+    let stored = cache.values();
+    let (outcome, err) = call_main(b"cache_precompiled", &code, &cache);
+    assert!(outcome.is_some());
+    assert_eq!(err, None);
+    assert_eq!(cache.values(), stored);
+}
+
+#[test]
+fn test_corrupted_module_is_recompiled() {
+    let code = simple_contract();
+    let cache = MockCompiledContractCache::default();
+    precompile_contract(b"cache_corrupted", &code, &VMConfig::default(), &cache).unwrap();
+    cache.corrupt();
+    let corrupted = cache.values();
+    let (outcome, err) = call_main(b"cache_corrupted", &code, &cache);
+    assert!(outcome.is_some());
+    assert_eq!(err, None);
+    assert_eq!(cache.len(), 1);
+    assert_ne!(cache.values(), corrupted);
+}
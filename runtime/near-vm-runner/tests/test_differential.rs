@@ -0,0 +1,172 @@
+//! Runs the same contracts on every engine and checks that the results, including errors, are
+//! identical.
+use near_runtime_fees::RuntimeFeesConfig;
+use near_vm_logic::mocks::mock_external::MockedExternal;
+use near_vm_logic::{VMConfig, VMKind, VMOutcome};
+use near_vm_runner::{run, VMError};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+mod utils;
+
+use crate::utils::create_context;
+
+const TEST_CONTRACT_RS: &'static [u8] = include_bytes!("../tests/res/test_contract_rs.wasm");
+const TEST_CONTRACT_TS: &'static [u8] = include_bytes!("../tests/res/test_contract_ts.wasm");
+
+fn arr_u64_to_u8(value: &[u64]) -> Vec<u8> {
+    let mut res = vec![];
+    for el in value {
+        res.extend_from_slice(&el.to_le_bytes());
+    }
+    res
+}
+
+fn run_with_vm_kind(
+    vm_kind: VMKind,
+    code: &[u8],
+    method_name: &[u8],
+    input: &[u8],
+    config: &VMConfig,
+) -> (MockedExternal, Option<VMOutcome>, Option<VMError>) {
+    let mut fake_external = MockedExternal::new();
+    let config = VMConfig { vm_kind, ..config.clone() };
+    let fees = RuntimeFeesConfig::default();
+    let mut hash = DefaultHasher::new();
+    code.hash(&mut hash);
+    let code_hash = hash.finish().to_le_bytes().to_vec();
+    let (outcome, err) = run(
+        code_hash,
+        code,
+        method_name,
+        &mut fake_external,
+        create_context(input.to_vec()),
+        &config,
+        &fees,
+        &[],
+        None,
+    );
+    (fake_external, outcome, err)
+}
+
+fn assert_same_with_config(code: &[u8], method_name: &[u8], input: &[u8], config: &VMConfig) {
+    let (wasmer_ext, wasmer_outcome, wasmer_err) =
+        run_with_vm_kind(VMKind::Wasmer, code, method_name, input, config);
+    let (wasmi_ext, wasmi_outcome, wasmi_err) =
+        run_with_vm_kind(VMKind::Wasmi, code, method_name, input, config);
+    let method_name = String::from_utf8_lossy(method_name);
+    assert_eq!(wasmer_outcome, wasmi_outcome, "outcomes differ for {}", method_name);
+    assert_eq!(wasmer_err, wasmi_err, "errors differ for {}", method_name);
+    assert_eq!(wasmer_ext.fake_trie, wasmi_ext.fake_trie, "storage differs for {}", method_name);
+}
+
+fn assert_same(code: &[u8], method_name: &[u8], input: &[u8]) {
+    assert_same_with_config(code, method_name, input, &VMConfig::default());
+}
+
+#[test]
+fn test_differential_rs_contract() {
+    let methods: &[&[u8]] = &[
+        b"ext_storage_usage",
+        b"ext_block_index",
+        b"ext_block_timestamp",
+        b"ext_used_gas",
+        b"ext_prepaid_gas",
+        b"ext_random_seed",
+        b"ext_predecessor_account_id",
+        b"ext_signer_pk",
+        b"ext_signer_id",
+        b"ext_account_id",
+        b"ext_account_balance",
+        b"ext_attached_deposit",
+        b"log_something",
+        b"abort_with_zero",
+        b"panic_with_message",
+        b"panic_after_logging",
+        b"run_test",
+        b"not_existing_method",
+    ];
+    for method in methods {
+        assert_same(TEST_CONTRACT_RS, method, &[]);
+    }
+    assert_same(TEST_CONTRACT_RS, b"ext_sha256", b"tesdsst");
+    assert_same(TEST_CONTRACT_RS, b"write_key_value", &arr_u64_to_u8(&[10, 20]));
+    assert_same(TEST_CONTRACT_RS, b"sum_with_input", &arr_u64_to_u8(&[10, 20]));
+    assert_same(TEST_CONTRACT_RS, b"sum_with_input", &arr_u64_to_u8(&[10]));
+    assert_same(TEST_CONTRACT_RS, b"pass_through", &arr_u64_to_u8(&[42]));
+    assert_same(TEST_CONTRACT_RS, b"sum_n", &arr_u64_to_u8(&[1000]));
+    assert_same(TEST_CONTRACT_RS, b"insert_strings", &arr_u64_to_u8(&[0, 100]));
+    assert_same(TEST_CONTRACT_RS, b"recurse", &arr_u64_to_u8(&[100]));
+    // Hits the stack height limit.
+    assert_same(TEST_CONTRACT_RS, b"recurse", &arr_u64_to_u8(&[1_000_000]));
+    // Runs out of gas.
+    assert_same(TEST_CONTRACT_RS, b"sum_n", &arr_u64_to_u8(&[1_000_000_000_000]));
+}
+
+#[test]
+fn test_differential_out_of_memory() {
+    let config = VMConfig::free();
+    assert_same_with_config(TEST_CONTRACT_RS, b"out_of_memory", &[], &config);
+}
+
+#[test]
+fn test_differential_ts_contract() {
+    assert_same(TEST_CONTRACT_TS, b"try_panic", &[]);
+    assert_same(TEST_CONTRACT_TS, b"try_storage_write", b"foo bar");
+    assert_same(TEST_CONTRACT_TS, b"try_storage_read", b"foo");
+}
+
+#[test]
+fn test_differential_error_cases() {
+    let contracts = [
+        // Infinite loop in the start function.
+        r#"(module
+              (func (loop (br 0)))
+              (func (export "hello"))
+              (start 0))"#,
+        // Trap in the method.
+        r#"(module (func (export "hello") unreachable))"#,
+        // Trap in the start function.
+        r#"(module
+              (func unreachable)
+              (func (export "hello"))
+              (start 0))"#,
+        // Method with arguments.
+        r#"(module (func (export "hello") (param i32)))"#,
+        // Exported global instead of function.
+        r#"(module (global (export "hello") i32 (i32.const 0)))"#,
+        // Unbounded recursion.
+        r#"(module (func $f (export "hello") call $f))"#,
+        // Memory grows above the limit.
+        r#"(module
+              (import "env" "memory" (memory 1))
+              (func (export "hello")
+                (drop (memory.grow (i32.const 1000000)))))"#,
+        // Unknown import.
+        r#"(module
+              (import "env" "wtf" (func))
+              (func (export "hello")))"#,
+        // Import with the wrong signature.
+        r#"(module
+              (import "env" "input" (func (param i32)))
+              (func (export "hello")))"#,
+        // Import of a function as a global.
+        r#"(module
+              (import "env" "input" (global i32))
+              (func (export "hello")))"#,
+        // Division by zero.
+        r#"(module
+              (func (export "hello")
+                (drop (i32.div_u (i32.const 1) (i32.const 0)))))"#,
+        // Out of bounds memory access.
+        r#"(module
+              (import "env" "memory" (memory 1))
+              (func (export "hello")
+                (drop (i32.load (i32.const -1)))))"#,
+    ];
+    for contract in contracts.iter() {
+        let code = wabt::wat2wasm(contract).unwrap();
+        assert_same(&code, b"hello", &[]);
+        assert_same(&code, b"hello2", &[]);
+    }
+}
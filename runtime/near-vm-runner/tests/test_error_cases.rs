@@ -1,6 +1,6 @@
 use crate::utils::{make_simple_contract_call, make_simple_contract_call_with_gas};
 use near_vm_errors::{CompilationError, FunctionCallError, MethodResolveError, PrepareError};
-use near_vm_logic::{HostError, ReturnData, VMOutcome};
+use near_vm_logic::{ExtCosts, HostError, ProfileData, ReturnData, VMOutcome};
 use near_vm_runner::VMError;
 
 mod utils;
@@ -13,6 +13,7 @@ fn vm_outcome_with_gas(gas: u64) -> VMOutcome {
         burnt_gas: gas,
         used_gas: gas,
         logs: vec![],
+        profile: ProfileData { wasm_gas: gas, ..Default::default() },
     }
 }
 
@@ -228,10 +229,13 @@ fn guest_panic() -> Vec<u8> {
 
 #[test]
 fn test_guest_panic() {
+    let mut outcome = vm_outcome_with_gas(130080593);
+    outcome.profile.wasm_gas = 3856371;
+    outcome.profile.ext_costs.insert(ExtCosts::base, 126224222);
     assert_eq!(
         make_simple_contract_call(&guest_panic(), b"hello"),
         (
-            Some(vm_outcome_with_gas(130080593)),
+            Some(outcome),
             Some(VMError::FunctionCallError(FunctionCallError::HostError(HostError::GuestPanic {
                 panic_msg: "explicit guest panic".to_string()
             })))
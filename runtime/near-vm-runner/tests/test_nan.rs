@@ -0,0 +1,101 @@
+use near_runtime_fees::RuntimeFeesConfig;
+use near_vm_logic::mocks::mock_external::MockedExternal;
+use near_vm_logic::types::ReturnData;
+use near_vm_logic::{FloatPolicy, VMConfig, VMKind};
+use near_vm_runner::run;
+
+mod utils;
+
+use crate::utils::create_context;
+
+const CANONICAL_NAN_F32: u32 = 0x7fc0_0000;
+const CANONICAL_NAN_F64: u64 = 0x7ff8_0000_0000_0000;
+
+/// Each method computes NaN in a different way and returns its bits.
+fn nan_contract() -> Vec<u8> {
+    wabt::wat2wasm(
+        r#"
+            (module
+              (import "env" "value_return" (func $value_return (param i64 i64)))
+              (memory 1)
+              (func $return_f32 (param f32)
+                (f32.store (i32.const 0) (get_local 0))
+                (call $value_return (i64.const 4) (i64.const 0)))
+              (func $return_f64 (param f64)
+                (f64.store (i32.const 0) (get_local 0))
+                (call $value_return (i64.const 8) (i64.const 0)))
+              (func (export "f32_div")
+                (call $return_f32 (f32.div (f32.const 0) (f32.const 0))))
+              (func (export "f32_sqrt")
+                (call $return_f32 (f32.sqrt (f32.const -1))))
+              (func (export "f32_payload")
+                (call $return_f32 (f32.add (f32.const 1) (f32.reinterpret/i32 (i32.const 0xffc01234)))))
+              (func (export "f32_demote")
+                (call $return_f32 (f32.demote/f64 (f64.reinterpret/i64 (i64.const 0xfff8000000001234)))))
+              (func (export "f64_div")
+                (call $return_f64 (f64.div (f64.const 0) (f64.const 0))))
+              (func (export "f64_mul")
+                (call $return_f64 (f64.mul (f64.const inf) (f64.const 0))))
+              (func (export "f64_min")
+                (call $return_f64 (f64.min (f64.const 1) (f64.reinterpret/i64 (i64.const 0xfff8000000001234)))))
+              (func (export "f64_promote")
+                (call $return_f64 (f64.promote/f32 (f32.reinterpret/i32 (i32.const 0xffc01234)))))
+              (func (export "not_nan")
+                (call $return_f64 (f64.add (f64.const 1) (f64.const 2))))
+            )"#,
+    )
+    .unwrap()
+}
+
+fn run_method(vm_kind: VMKind, method_name: &[u8]) -> Vec<u8> {
+    let code = nan_contract();
+    let mut fake_external = MockedExternal::new();
+    let config =
+        VMConfig { vm_kind, float_policy: FloatPolicy::Canonicalize, ..VMConfig::default() };
+    let fees = RuntimeFeesConfig::default();
+    let (outcome, err) = run(
+        b"nan_contract".to_vec(),
+        &code,
+        method_name,
+        &mut fake_external,
+        create_context(vec![]),
+        &config,
+        &fees,
+        &[],
+        None,
+    );
+    assert_eq!(err, None);
+    match outcome.unwrap().return_data {
+        ReturnData::Value(value) => value,
+        _ => panic!("Value was not returned"),
+    }
+}
+
+#[test]
+fn test_nan_canonicalized() {
+    let mut vm_kinds = vec![VMKind::Wasmer];
+    if cfg!(feature = "wasmi_vm") {
+        vm_kinds.push(VMKind::Wasmi);
+    }
+    for vm_kind in vm_kinds {
+        for method in &[&b"f32_div"[..], b"f32_sqrt", b"f32_payload", b"f32_demote"] {
+            assert_eq!(
+                run_method(vm_kind, method),
+                CANONICAL_NAN_F32.to_le_bytes().to_vec(),
+                "{:?} {}",
+                vm_kind,
+                String::from_utf8_lossy(method)
+            );
+        }
+        for method in &[&b"f64_div"[..], b"f64_mul", b"f64_min", b"f64_promote"] {
+            assert_eq!(
+                run_method(vm_kind, method),
+                CANONICAL_NAN_F64.to_le_bytes().to_vec(),
+                "{:?} {}",
+                vm_kind,
+                String::from_utf8_lossy(method)
+            );
+        }
+        assert_eq!(run_method(vm_kind, b"not_nan"), 3f64.to_bits().to_le_bytes().to_vec());
+    }
+}
@@ -64,6 +64,7 @@ pub fn test_read_write() {
         &config,
         &fees,
         &promise_results,
+        None,
     );
     assert_run_result(result, 0);
 
@@ -77,6 +78,7 @@ pub fn test_read_write() {
         &config,
         &fees,
         &promise_results,
+        None,
     );
     assert_run_result(result, 20);
 }
@@ -103,8 +105,17 @@ fn run_test_ext(method: &[u8], expected: &[u8], input: &[u8]) {
     let fees = RuntimeFeesConfig::default();
     let context = create_context(&input);
 
-    let (outcome, err) =
-        run(input.to_owned(), &code, &method, &mut fake_external, context, &config, &fees, &[]);
+    let (outcome, err) = run(
+        input.to_owned(),
+        &code,
+        &method,
+        &mut fake_external,
+        context,
+        &config,
+        &fees,
+        &[],
+        None,
+    );
 
     if let Some(_) = err {
         panic!("Failed execution: {:?}", err);
@@ -171,6 +182,7 @@ pub fn test_out_of_memory() {
         &config,
         &fees,
         &promise_results,
+        None,
     );
     assert_eq!(
         result.1,
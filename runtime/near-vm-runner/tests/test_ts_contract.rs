@@ -34,6 +34,7 @@ pub fn test_ts_contract() {
         &config,
         &fees,
         &promise_results,
+        None,
     );
     assert_eq!(
         result.1,
@@ -53,6 +54,7 @@ pub fn test_ts_contract() {
         &config,
         &fees,
         &promise_results,
+        None,
     )
     .0
     .unwrap();
@@ -76,6 +78,7 @@ pub fn test_ts_contract() {
         &config,
         &fees,
         &promise_results,
+        None,
     );
 
     if let ReturnData::Value(value) = result.0.unwrap().return_data {
@@ -20,6 +20,7 @@ pub fn create_context(input: Vec<u8>) -> VMContext {
         input,
         block_index: 10,
         block_timestamp: 42,
+        epoch_height: 1,
         account_balance: 2u128,
         account_locked_balance: 0,
         storage_usage: 12,
@@ -47,7 +48,17 @@ pub fn make_simple_contract_call_with_gas(
     let mut hash = DefaultHasher::new();
     code.hash(&mut hash);
     let code_hash = hash.finish().to_le_bytes().to_vec();
-    run(code_hash, code, method_name, &mut fake_external, context, &config, &fees, &promise_results)
+    run(
+        code_hash,
+        code,
+        method_name,
+        &mut fake_external,
+        context,
+        &config,
+        &fees,
+        &promise_results,
+        None,
+    )
 }
 
 #[allow(dead_code)]
@@ -8,8 +8,9 @@ use near_crypto::{InMemorySigner, KeyType, PublicKey};
 use near_primitives::account::{AccessKey, AccessKeyPermission, FunctionCallPermission};
 use near_primitives::hash::CryptoHash;
 use near_primitives::transaction::{
-    Action, AddKeyAction, CreateAccountAction, DeleteAccountAction, DeleteKeyAction,
-    DeployContractAction, FunctionCallAction, SignedTransaction, StakeAction, TransferAction,
+    Action, AddKeyAction, CreateAccountAction, DelegateAction, DeleteAccountAction,
+    DeleteKeyAction, DeployContractAction, FunctionCallAction, SignedTransaction, StakeAction,
+    TransferAction,
 };
 
 use crate::ext_costs_generator::ExtCostsGenerator;
@@ -22,7 +23,7 @@ use near_runtime_fees::{
     AccessKeyCreationConfig, ActionCreationConfig, DataReceiptCreationConfig, Fee,
     RuntimeFeesConfig,
 };
-use near_vm_logic::{ExtCosts, ExtCostsConfig, VMConfig, VMLimitConfig};
+use near_vm_logic::{ExtCosts, ExtCostsConfig, FloatPolicy, VMConfig, VMKind, VMLimitConfig};
 use node_runtime::config::RuntimeConfig;
 
 /// How much gas there is in a nanosecond worth of computation.
@@ -83,6 +84,7 @@ pub enum Metric {
     ActionTransfer,
     ActionCreateAccount,
     ActionDeleteAccount,
+    ActionDelegate,
     ActionAddFullAccessKey,
     ActionAddFunctionAccessKey1Method,
     ActionAddFunctionAccessKey1000Methods,
@@ -118,6 +120,15 @@ pub enum Metric {
     keccak256_10kib_10k,
     keccak512_10b_10k,
     keccak512_10kib_10k,
+    ripemd160_10b_10k,
+    ripemd160_10kib_10k,
+    blake2b_10b_10k,
+    blake2b_10kib_10k,
+    ed25519_verify_32b_1k,
+    ed25519_verify_10kib_1k,
+    ecrecover_10k,
+    validator_stake_10k,
+    validator_total_stake_10k,
     storage_write_10b_key_10b_value_1k,
     storage_write_10kib_key_10b_value_1k,
     storage_write_10b_key_10kib_value_1k,
@@ -229,6 +240,43 @@ pub fn run(mut config: Config) -> RuntimeConfig {
     };
     measure_transactions(Metric::ActionDeleteAccount, &mut m, &config, None, &mut f, false);
 
+    // Measure the speed of relaying an empty delegate action.
+    let mut nonces: HashMap<usize, u64> = HashMap::new();
+    let mut delegate_nonces: HashMap<usize, u64> = HashMap::new();
+    let mut f = || {
+        let relayer_idx = rand::thread_rng().gen::<usize>() % config.active_accounts;
+        let sender_idx = loop {
+            let x = rand::thread_rng().gen::<usize>() % config.active_accounts;
+            if x != relayer_idx {
+                break x;
+            }
+        };
+        let relayer_id = get_account_id(relayer_idx);
+        let sender_id = get_account_id(sender_idx);
+        let relayer_signer = InMemorySigner::from_seed(&relayer_id, KeyType::ED25519, &relayer_id);
+        let sender_signer = InMemorySigner::from_seed(&sender_id, KeyType::ED25519, &sender_id);
+        let nonce = *nonces.entry(relayer_idx).and_modify(|x| *x += 1).or_insert(1);
+        let delegate_nonce =
+            *delegate_nonces.entry(sender_idx).and_modify(|x| *x += 1).or_insert(1);
+        let delegate_action = DelegateAction {
+            sender_id: sender_id.clone(),
+            receiver_id: sender_id.clone(),
+            actions: vec![],
+            nonce: delegate_nonce,
+            max_block_height: u64::max_value(),
+            public_key: sender_signer.public_key.clone(),
+        };
+        SignedTransaction::from_actions(
+            nonce as u64,
+            relayer_id,
+            sender_id,
+            &relayer_signer,
+            vec![Action::Delegate(delegate_action.sign(&sender_signer))],
+            CryptoHash::default(),
+        )
+    };
+    measure_transactions(Metric::ActionDelegate, &mut m, &config, None, &mut f, false);
+
     // Measure the speed of adding a full access key.
     measure_actions(
         Metric::ActionAddFullAccessKey,
@@ -443,6 +491,15 @@ pub fn run(mut config: Config) -> RuntimeConfig {
     keccak256_10kib_10k => keccak256_10kib_10k,
     keccak512_10b_10k => keccak512_10b_10k,
     keccak512_10kib_10k => keccak512_10kib_10k,
+    ripemd160_10b_10k => ripemd160_10b_10k,
+    ripemd160_10kib_10k => ripemd160_10kib_10k,
+    blake2b_10b_10k => blake2b_10b_10k,
+    blake2b_10kib_10k => blake2b_10kib_10k,
+    ed25519_verify_32b_1k => ed25519_verify_32b_1k,
+    ed25519_verify_10kib_1k => ed25519_verify_10kib_1k,
+    ecrecover_10k => ecrecover_10k,
+    validator_stake_10k => validator_stake_10k,
+    validator_total_stake_10k => validator_total_stake_10k,
     storage_write_10b_key_10b_value_1k => storage_write_10b_key_10b_value_1k,
     storage_read_10b_key_10b_value_1k => storage_read_10b_key_10b_value_1k,
     storage_has_key_10b_key_10b_value_1k => storage_has_key_10b_key_10b_value_1k,
@@ -540,6 +597,7 @@ fn get_runtime_fees_config(measurement: &Measurements) -> RuntimeFeesConfig {
             },
             delete_key_cost: f64_to_fee(pure[&ActionDeleteKey]),
             delete_account_cost: f64_to_fee(pure[&ActionDeleteAccount]),
+            delegate_cost: f64_to_fee(pure[&ActionDelegate]),
         },
         ..Default::default()
     }
@@ -569,6 +627,15 @@ fn get_ext_costs_config(measurement: &Measurements) -> ExtCostsConfig {
         keccak256_byte: f64_to_gas(pure[&keccak256_byte]),
         keccak512_base: f64_to_gas(pure[&keccak512_base]),
         keccak512_byte: f64_to_gas(pure[&keccak512_byte]),
+        ripemd160_base: f64_to_gas(pure[&ripemd160_base]),
+        ripemd160_byte: f64_to_gas(pure[&ripemd160_byte]),
+        blake2b_base: f64_to_gas(pure[&blake2b_base]),
+        blake2b_byte: f64_to_gas(pure[&blake2b_byte]),
+        ed25519_verify_base: f64_to_gas(pure[&ed25519_verify_base]),
+        ed25519_verify_byte: f64_to_gas(pure[&ed25519_verify_byte]),
+        ecrecover_base: f64_to_gas(pure[&ecrecover_base]),
+        validator_stake_base: f64_to_gas(pure[&validator_stake_base]),
+        validator_total_stake_base: f64_to_gas(pure[&validator_total_stake_base]),
         log_base: f64_to_gas(pure[&log_base]),
         log_byte: f64_to_gas(pure[&log_byte]),
         storage_write_base: f64_to_gas(pure[&storage_write_base]),
@@ -607,6 +674,8 @@ fn get_vm_config(measurement: &Measurements) -> VMConfig {
         grow_mem_cost: 1,
         regular_op_cost: f64_to_gas(nanosec_per_op()) as u32,
         limit_config: VMLimitConfig::default(),
+        vm_kind: VMKind::default(),
+        float_policy: FloatPolicy::default(),
     }
 }
 
@@ -618,5 +687,6 @@ fn get_runtime_config(measurement: &Measurements) -> RuntimeConfig {
         storage_cost_byte_per_block: 5000000,
         poke_threshold: 86400,
         account_length_baseline_cost_per_block: 207909813343189798558,
+        delegate_action_enabled: false,
     }
 }
@@ -84,6 +84,15 @@ impl ExtCostsGenerator {
 
         self.extract(sha256_10b_10k, sha256_base, &[sha256_byte]);
         self.extract(sha256_10kib_10k, sha256_byte, &[]);
+        self.extract(ripemd160_10b_10k, ripemd160_base, &[ripemd160_byte]);
+        self.extract(ripemd160_10kib_10k, ripemd160_byte, &[]);
+        self.extract(blake2b_10b_10k, blake2b_base, &[blake2b_byte]);
+        self.extract(blake2b_10kib_10k, blake2b_byte, &[]);
+        self.extract(ed25519_verify_32b_1k, ed25519_verify_base, &[ed25519_verify_byte]);
+        self.extract(ed25519_verify_10kib_1k, ed25519_verify_byte, &[]);
+        self.extract(ecrecover_10k, ecrecover_base, &[]);
+        self.extract(validator_stake_10k, validator_stake_base, &[]);
+        self.extract(validator_total_stake_10k, validator_total_stake_base, &[]);
 
         // TODO: Redo storage costs once we have counting of nodes and we have size peek.
         self.extract(
@@ -24,6 +24,7 @@ pub enum ReceiptFeesFloat {
     ActionAddFunctionAccessKeyPerByte,
     ActionDeleteKey,
     ActionDeleteAccount,
+    ActionDelegate,
 }
 
 impl RuntimeFeesGenerator {
@@ -114,6 +115,12 @@ impl RuntimeFeesGenerator {
             self.aggregated[&Metric::ActionDeleteAccount].upper() as f64
                 - self.aggregated[&Metric::Receipt].upper() as f64,
         );
+        res.insert(
+            ReceiptFeesFloat::ActionDelegate,
+            // Relaying creates another empty receipt for the inner actions.
+            self.aggregated[&Metric::ActionDelegate].upper() as f64
+                - 2f64 * self.aggregated[&Metric::Receipt].upper() as f64,
+        );
         res
     }
 }
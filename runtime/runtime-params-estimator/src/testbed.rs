@@ -1,6 +1,7 @@
 use borsh::BorshDeserialize;
 use near::get_store_path;
 use near_primitives::receipt::Receipt;
+use near_primitives::test_utils::MockEpochInfoProvider;
 use near_primitives::transaction::{ExecutionStatus, SignedTransaction};
 use near_primitives::types::{Gas, MerkleHash, StateRoot};
 use near_store::{create_store, ColState, Trie};
@@ -15,6 +16,9 @@ use tempdir::TempDir;
 
 const STATE_DUMP_FILE: &str = "state_dump";
 const GENESIS_ROOTS_FILE: &str = "genesis_roots";
+/// Number of validators known to the runtime, so that validator lookups are not measured against
+/// an empty validator set.
+const NUM_VALIDATORS: u64 = 100;
 
 pub struct RuntimeTestbed {
     /// Directory where we temporarily keep the storage.
@@ -25,6 +29,7 @@ pub struct RuntimeTestbed {
     runtime: Runtime,
     prev_receipts: Vec<Receipt>,
     apply_state: ApplyState,
+    epoch_info_provider: MockEpochInfoProvider,
 }
 
 impl RuntimeTestbed {
@@ -67,6 +72,8 @@ impl RuntimeTestbed {
 
             ..Default::default()
         };
+        // The delegate actions are measured too.
+        runtime_config.delegate_action_enabled = true;
 
         let runtime = Runtime::new(runtime_config);
         let prev_receipts = vec![];
@@ -78,9 +85,17 @@ impl RuntimeTestbed {
             epoch_length: 4,
             gas_price: 1,
             block_timestamp: 0,
+            epoch_id: Default::default(),
+            epoch_height: 0,
             gas_limit: None,
+            cache: None,
         };
-        Self { workdir, trie, root, runtime, prev_receipts, apply_state }
+        // `validator_stake_10k` queries the stake of `alice.near`.
+        let epoch_info_provider = MockEpochInfoProvider::new(
+            std::iter::once(("alice.near".to_string(), 1_000_000))
+                .chain((1..NUM_VALIDATORS).map(|i| (format!("validator{}.near", i), 1_000_000))),
+        );
+        Self { workdir, trie, root, runtime, prev_receipts, apply_state, epoch_info_provider }
     }
 
     pub fn process_block(
@@ -97,6 +112,7 @@ impl RuntimeTestbed {
                 &self.apply_state,
                 &self.prev_receipts,
                 transactions,
+                &self.epoch_info_provider,
             )
             .unwrap();
 
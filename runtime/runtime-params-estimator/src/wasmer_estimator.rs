@@ -20,6 +20,7 @@ fn create_context(input: Vec<u8>) -> VMContext {
         input,
         block_index: 10,
         block_timestamp: 42,
+        epoch_height: 1,
         account_balance: 2u128,
         account_locked_balance: 1u128,
         storage_usage: 12,
@@ -52,6 +53,7 @@ fn call() -> (Option<VMOutcome>, Option<VMError>) {
         &config,
         &fees,
         &promise_results,
+        None,
     )
 }
 
@@ -35,6 +35,8 @@ extern "C" {
     fn attached_deposit(balance_ptr: u64);
     fn prepaid_gas() -> u64;
     fn used_gas() -> u64;
+    fn validator_stake(account_id_len: u64, account_id_ptr: u64, stake_ptr: u64);
+    fn validator_total_stake(stake_ptr: u64);
     // ############
     // # Math API #
     // ############
@@ -42,6 +44,24 @@ extern "C" {
     fn sha256(value_len: u64, value_ptr: u64, register_id: u64);
     fn keccak256(value_len: u64, value_ptr: u64, register_id: u64);
     fn keccak512(value_len: u64, value_ptr: u64, register_id: u64);
+    fn ripemd160(value_len: u64, value_ptr: u64, register_id: u64);
+    fn blake2b(value_len: u64, value_ptr: u64, register_id: u64);
+    fn ed25519_verify(
+        signature_len: u64,
+        signature_ptr: u64,
+        message_len: u64,
+        message_ptr: u64,
+        public_key_len: u64,
+        public_key_ptr: u64,
+    ) -> u64;
+    fn ecrecover(
+        hash_len: u64,
+        hash_ptr: u64,
+        signature_len: u64,
+        signature_ptr: u64,
+        v: u64,
+        register_id: u64,
+    ) -> u64;
     // #####################
     // # Miscellaneous API #
     // #####################
@@ -440,6 +460,155 @@ pub unsafe fn keccak512_10kib_10k() {
     }
 }
 
+// Function to measure `ripemd160_base` and `ripemd160_byte`. Also measures `base`, `write_register_base`,
+// and `write_register_byte`. However `ripemd160` computation is more expensive than register writing
+// so we are okay overcharging it.
+// Compute ripemd160 on 10b 10k times.
+#[no_mangle]
+pub unsafe fn ripemd160_10b_10k() {
+    let buffer = [65u8; 10];
+    for _ in 0..10_000 {
+        ripemd160(buffer.len() as u64, buffer.as_ptr() as *const u64 as u64, 0);
+    }
+}
+// Function to measure `ripemd160_base` and `ripemd160_byte`. Also measures `base`, `write_register_base`,
+// and `write_register_byte`. However `ripemd160` computation is more expensive than register writing
+// so we are okay overcharging it.
+// Compute ripemd160 on 10kib 10k times.
+#[no_mangle]
+pub unsafe fn ripemd160_10kib_10k() {
+    let buffer = [65u8; 10240];
+    for _ in 0..10_000 {
+        ripemd160(buffer.len() as u64, buffer.as_ptr() as *const u64 as u64, 0);
+    }
+}
+// Function to measure `blake2b_base` and `blake2b_byte`. Also measures `base`, `write_register_base`,
+// and `write_register_byte`. However `blake2b` computation is more expensive than register writing
+// so we are okay overcharging it.
+// Compute blake2b on 10b 10k times.
+#[no_mangle]
+pub unsafe fn blake2b_10b_10k() {
+    let buffer = [65u8; 10];
+    for _ in 0..10_000 {
+        blake2b(buffer.len() as u64, buffer.as_ptr() as *const u64 as u64, 0);
+    }
+}
+// Function to measure `blake2b_base` and `blake2b_byte`. Also measures `base`, `write_register_base`,
+// and `write_register_byte`. However `blake2b` computation is more expensive than register writing
+// so we are okay overcharging it.
+// Compute blake2b on 10kib 10k times.
+#[no_mangle]
+pub unsafe fn blake2b_10kib_10k() {
+    let buffer = [65u8; 10240];
+    for _ in 0..10_000 {
+        blake2b(buffer.len() as u64, buffer.as_ptr() as *const u64 as u64, 0);
+    }
+}
+// Public key of the ed25519 secret key `[7u8; 32]` used to sign the messages below.
+const ED25519_PUBLIC_KEY: [u8; 32] = [
+    234, 74, 108, 99, 226, 156, 82, 10, 190, 245, 80, 123, 19, 46, 197, 249, 149, 71, 118, 174,
+    190, 190, 123, 146, 66, 30, 234, 105, 20, 70, 210, 44,
+];
+
+// Function to measure `ed25519_verify_base` and `ed25519_verify_byte`. Also measures `read_memory_base`
+// and `read_memory_byte`. The signature is valid so that the verification is not cut short.
+// Verify ed25519 signature of 32b message 1k times.
+#[no_mangle]
+pub unsafe fn ed25519_verify_32b_1k() {
+    let signature: [u8; 64] = [
+        134, 31, 123, 182, 114, 207, 124, 119, 253, 253, 139, 114, 65, 31, 142, 104, 38, 198, 79,
+        72, 193, 149, 15, 158, 57, 226, 98, 235, 15, 92, 222, 120, 12, 184, 173, 21, 80, 177, 158,
+        241, 35, 59, 14, 131, 56, 138, 30, 0, 254, 229, 33, 183, 225, 191, 110, 125, 189, 48, 66,
+        22, 20, 179, 132, 5,
+    ];
+    let message = [65u8; 32];
+    for _ in 0..1_000 {
+        ed25519_verify(
+            signature.len() as u64,
+            signature.as_ptr() as *const u64 as u64,
+            message.len() as u64,
+            message.as_ptr() as *const u64 as u64,
+            ED25519_PUBLIC_KEY.len() as u64,
+            ED25519_PUBLIC_KEY.as_ptr() as *const u64 as u64,
+        );
+    }
+}
+
+// Function to measure `ed25519_verify_base` and `ed25519_verify_byte`. Also measures `read_memory_base`
+// and `read_memory_byte`. The signature is valid so that the verification is not cut short.
+// Verify ed25519 signature of 10kib message 1k times.
+#[no_mangle]
+pub unsafe fn ed25519_verify_10kib_1k() {
+    let signature: [u8; 64] = [
+        139, 159, 58, 133, 17, 236, 159, 122, 208, 205, 45, 78, 50, 78, 98, 160, 58, 240, 106, 131,
+        136, 253, 167, 6, 169, 244, 225, 224, 131, 20, 238, 183, 29, 34, 136, 194, 172, 18, 132,
+        153, 90, 225, 148, 174, 26, 240, 45, 142, 79, 87, 103, 226, 12, 98, 231, 50, 151, 110, 170,
+        180, 34, 217, 184, 11,
+    ];
+    let message = [65u8; 10240];
+    for _ in 0..1_000 {
+        ed25519_verify(
+            signature.len() as u64,
+            signature.as_ptr() as *const u64 as u64,
+            message.len() as u64,
+            message.as_ptr() as *const u64 as u64,
+            ED25519_PUBLIC_KEY.len() as u64,
+            ED25519_PUBLIC_KEY.as_ptr() as *const u64 as u64,
+        );
+    }
+}
+
+// Function to measure `ecrecover_base`. Also measures `read_memory_base`, `read_memory_byte`,
+// `write_register_base` and `write_register_byte`. The signature is produced by the secp256k1
+// secret key `[1u8; 32]` so that the key is actually recovered.
+// Recover secp256k1 public key 10k times.
+#[no_mangle]
+pub unsafe fn ecrecover_10k() {
+    let hash = [65u8; 32];
+    let signature: [u8; 64] = [
+        91, 229, 233, 71, 130, 9, 103, 74, 150, 230, 15, 31, 3, 127, 97, 118, 84, 15, 208, 1, 250,
+        29, 100, 105, 71, 112, 197, 106, 119, 9, 196, 44, 117, 255, 96, 251, 155, 122, 32, 239,
+        125, 127, 97, 226, 67, 33, 130, 81, 123, 6, 86, 153, 42, 149, 93, 41, 186, 0, 84, 11, 97,
+        6, 125, 160,
+    ];
+    for _ in 0..10_000 {
+        ecrecover(
+            hash.len() as u64,
+            hash.as_ptr() as *const u64 as u64,
+            signature.len() as u64,
+            signature.as_ptr() as *const u64 as u64,
+            1,
+            0,
+        );
+    }
+}
+
+// Function to measure `validator_stake_base`. Also measures `read_memory_base`,
+// `read_memory_byte` and `write_memory_base`.
+// Query the stake of a validator 10k times.
+#[no_mangle]
+pub unsafe fn validator_stake_10k() {
+    let account_id = b"alice.near";
+    let mut stake = [0u8; 16];
+    for _ in 0..10_000 {
+        validator_stake(
+            account_id.len() as u64,
+            account_id.as_ptr() as *const u64 as u64,
+            stake.as_mut_ptr() as *mut u64 as u64,
+        );
+    }
+}
+
+// Function to measure `validator_total_stake_base`. Also measures `write_memory_base`.
+// Query the total stake of the validators 10k times.
+#[no_mangle]
+pub unsafe fn validator_total_stake_10k() {
+    let mut stake = [0u8; 16];
+    for _ in 0..10_000 {
+        validator_total_stake(stake.as_mut_ptr() as *mut u64 as u64);
+    }
+}
+
 // ###############
 // # Storage API #
 // ###############
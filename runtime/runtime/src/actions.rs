@@ -1,17 +1,20 @@
-use std::sync::Arc;
+use std::sync::mpsc::{sync_channel, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
 
 use borsh::BorshSerialize;
 use log::debug;
 
-use near_primitives::account::Account;
+use near_primitives::account::{AccessKeyPermission, Account};
 use near_primitives::contract::ContractCode;
 use near_primitives::hash::CryptoHash;
-use near_primitives::receipt::{ActionReceipt, Receipt};
+use near_primitives::receipt::{ActionReceipt, Receipt, ReceiptEnum};
 use near_primitives::transaction::{
     Action, AddKeyAction, DeleteAccountAction, DeleteKeyAction, DeployContractAction,
-    FunctionCallAction, StakeAction, TransferAction,
+    FunctionCallAction, SignedDelegateAction, StakeAction, TransferAction,
+};
+use near_primitives::types::{
+    AccountId, Balance, BlockHeight, BlockHeightDelta, EpochInfoProvider, ValidatorStake,
 };
-use near_primitives::types::{AccountId, Balance, BlockHeight, BlockHeightDelta, ValidatorStake};
 use near_primitives::utils::{
     is_valid_sub_account_id, is_valid_top_level_account_id, key_for_access_key,
 };
@@ -21,16 +24,19 @@ use near_store::{
     StorageError, TrieUpdate,
 };
 use near_vm_logic::types::PromiseResult;
-use near_vm_logic::VMContext;
+use near_vm_logic::{ReturnData, VMConfig, VMContext};
 
-use crate::config::{safe_add_gas, RuntimeConfig};
+use crate::config::{safe_add_gas, total_deposit, vm_gas_profile, RuntimeConfig};
 use crate::ext::RuntimeExt;
+use crate::verifier::check_function_call_permission;
 use crate::{ActionResult, ApplyState};
 use near_crypto::key_conversion::convert_public_key;
 use near_crypto::PublicKey;
-use near_primitives::errors::{ActionError, ActionErrorKind, RuntimeError};
+use near_primitives::errors::{
+    ActionError, ActionErrorKind, ExternalError, InvalidAccessKeyError, RuntimeError,
+};
 use near_vm_errors::{CompilationError, FunctionCallError};
-use near_vm_runner::VMError;
+use near_vm_runner::{precompile_contract, CompiledContractCache, VMError};
 
 /// Number of epochs it takes to unstake.
 const NUM_UNSTAKING_EPOCHS: u64 = 3;
@@ -121,6 +127,7 @@ pub(crate) fn action_function_call(
     action_hash: &CryptoHash,
     config: &RuntimeConfig,
     is_last_action: bool,
+    epoch_info_provider: &dyn EpochInfoProvider,
 ) -> Result<(), RuntimeError> {
     let code = match get_code_with_cache(state_update, account_id, &account) {
         Ok(Some(code)) => code,
@@ -150,6 +157,8 @@ pub(crate) fn action_function_call(
         &action_receipt.signer_public_key,
         action_receipt.gas_price,
         action_hash,
+        &apply_state.epoch_id,
+        epoch_info_provider,
     );
     // Output data receipts are ignored if the function call is not the last action in the batch.
     let output_data_receivers: Vec<_> = if is_last_action {
@@ -168,6 +177,7 @@ pub(crate) fn action_function_call(
         input: function_call.args.clone(),
         block_index: apply_state.block_index,
         block_timestamp: apply_state.block_timestamp,
+        epoch_height: apply_state.epoch_height,
         account_balance: account.amount,
         account_locked_balance: account.locked,
         storage_usage: account.storage_usage,
@@ -187,15 +197,16 @@ pub(crate) fn action_function_call(
         &config.wasm_config,
         &config.transaction_costs,
         promise_results,
+        apply_state.cache.as_ref().map(|cache| cache.as_ref()),
     );
     let execution_succeeded = match err {
         Some(VMError::FunctionCallError(err)) => {
             result.result = Err(ActionErrorKind::FunctionCallError(err).into());
             false
         }
-        Some(VMError::ExternalError(storage)) => {
-            let err: StorageError =
-                borsh::BorshDeserialize::try_from_slice(&storage).expect("Borsh cannot fail");
+        Some(VMError::ExternalError(serialized_error)) => {
+            let err: ExternalError = borsh::BorshDeserialize::try_from_slice(&serialized_error)
+                .expect("Borsh cannot fail");
             return Err(err.into());
         }
         Some(VMError::InconsistentStateError(err)) => {
@@ -213,6 +224,7 @@ pub(crate) fn action_function_call(
         // `FunctionCall`s error.
         result.gas_used = safe_add_gas(result.gas_used, outcome.used_gas)?;
         result.logs.extend(outcome.logs.into_iter());
+        result.profile.merge(vm_gas_profile(outcome.profile));
         if execution_succeeded {
             account.amount = outcome.balance;
             account.storage_usage = outcome.storage_usage;
@@ -321,9 +333,12 @@ pub(crate) fn action_create_account(
 
 pub(crate) fn action_deploy_contract(
     state_update: &mut TrieUpdate,
+    apply_state: &ApplyState,
     account: &mut Account,
     account_id: &AccountId,
     deploy_contract: &DeployContractAction,
+    wasm_config: &VMConfig,
+    precompiler: &ContractPrecompiler,
 ) -> Result<(), StorageError> {
     let code = ContractCode::new(deploy_contract.code.clone());
     let prev_code = get_code(state_update, account_id)?;
@@ -343,10 +358,88 @@ pub(crate) fn action_deploy_contract(
             ))
         })?;
     account.code_hash = code.get_hash();
+    if let Some(cache) = apply_state.cache.as_ref() {
+        precompiler.precompile(&code, wasm_config, cache.clone());
+    }
     set_code(state_update, &account_id, &code);
     Ok(())
 }
 
+const POISONED_LOCK_ERR: &str = "The lock was poisoned.";
+
+struct PrecompileTask {
+    code_hash: CryptoHash,
+    code: Vec<u8>,
+    wasm_config: VMConfig,
+    cache: Arc<dyn CompiledContractCache>,
+}
+
+/// Compiles deployed contracts on background threads and stores them in the persistent cache,
+/// so the first call to a contract doesn't have to wait for its compilation.
+/// The threads stop once the precompiler is dropped.
+pub(crate) struct ContractPrecompiler {
+    /// Queue of contracts waiting to be compiled, `None` if precompilation is disabled.
+    queue: Option<Mutex<SyncSender<PrecompileTask>>>,
+}
+
+impl ContractPrecompiler {
+    /// Start `num_threads` compiling threads. Zero threads disables precompilation.
+    pub(crate) fn new(num_threads: usize, queue_size: usize) -> Self {
+        if num_threads == 0 {
+            return ContractPrecompiler { queue: None };
+        }
+        let (sender, receiver) = sync_channel::<PrecompileTask>(queue_size);
+        let receiver = Arc::new(Mutex::new(receiver));
+        for index in 0..num_threads {
+            let receiver = receiver.clone();
+            std::thread::Builder::new()
+                .name(format!("precompile-{}", index))
+                .spawn(move || loop {
+                    let task = match receiver.lock().expect(POISONED_LOCK_ERR).recv() {
+                        Ok(task) => task,
+                        Err(_) => break,
+                    };
+                    if let Err(err) = precompile_contract(
+                        task.code_hash.as_ref(),
+                        &task.code,
+                        &task.wasm_config,
+                        task.cache.as_ref(),
+                    ) {
+                        debug!(target: "runtime", "Failed to precompile contract {}: {:?}", task.code_hash, err);
+                    }
+                })
+                .expect("Failed to spawn the contract precompilation thread");
+        }
+        ContractPrecompiler { queue: Some(Mutex::new(sender)) }
+    }
+
+    /// Queue the contract to be compiled by the background threads. Contracts deployed while
+    /// the queue is full are compiled on their first call instead.
+    fn precompile(
+        &self,
+        code: &ContractCode,
+        wasm_config: &VMConfig,
+        cache: Arc<dyn CompiledContractCache>,
+    ) {
+        let queue = match self.queue.as_ref() {
+            Some(queue) => queue,
+            None => return,
+        };
+        let task = PrecompileTask {
+            code_hash: code.get_hash(),
+            code: code.code.clone(),
+            wasm_config: wasm_config.clone(),
+            cache,
+        };
+        if let Err(err) = queue.lock().expect(POISONED_LOCK_ERR).try_send(task) {
+            let code_hash = match err {
+                TrySendError::Full(task) | TrySendError::Disconnected(task) => task.code_hash,
+            };
+            debug!(target: "runtime", "Skipping precompilation of contract {}: queue is full", code_hash);
+        }
+    }
+}
+
 pub(crate) fn action_delete_account(
     state_update: &mut TrieUpdate,
     account: &mut Option<Account>,
@@ -440,6 +533,84 @@ pub(crate) fn action_add_key(
     Ok(())
 }
 
+pub(crate) fn action_delegate(
+    state_update: &mut TrieUpdate,
+    account: &mut Account,
+    action_receipt: &ActionReceipt,
+    result: &mut ActionResult,
+    account_id: &AccountId,
+    signed_delegate_action: &SignedDelegateAction,
+) -> Result<(), RuntimeError> {
+    // The signature, the sender and the expiration are checked with the transaction.
+    let delegate_action = &signed_delegate_action.delegate_action;
+    let mut access_key =
+        match get_access_key(state_update, account_id, &delegate_action.public_key)? {
+            Some(access_key) => access_key,
+            None => {
+                result.result = Err(ActionErrorKind::DelegateActionAccessKeyError(
+                    InvalidAccessKeyError::AccessKeyNotFound {
+                        account_id: account_id.clone(),
+                        public_key: delegate_action.public_key.clone(),
+                    },
+                )
+                .into());
+                return Ok(());
+            }
+        };
+    if delegate_action.nonce <= access_key.nonce {
+        result.result = Err(ActionErrorKind::DelegateActionInvalidNonce {
+            delegate_nonce: delegate_action.nonce,
+            ak_nonce: access_key.nonce,
+        }
+        .into());
+        return Ok(());
+    }
+    if let AccessKeyPermission::FunctionCall(ref function_call_permission) = access_key.permission {
+        if let Err(e) = check_function_call_permission(
+            function_call_permission,
+            &delegate_action.receiver_id,
+            &delegate_action.actions,
+        ) {
+            result.result = Err(ActionErrorKind::DelegateActionAccessKeyError(e).into());
+            return Ok(());
+        }
+    }
+    let deposit = total_deposit(&delegate_action.actions)?;
+    account.amount = match account.amount.checked_sub(deposit) {
+        Some(amount) => amount,
+        None => {
+            result.result = Err(ActionErrorKind::DelegateActionNotEnoughBalance {
+                sender_id: account_id.clone(),
+                balance: account.amount,
+                deposit,
+            }
+            .into());
+            return Ok(());
+        }
+    };
+    access_key.nonce = delegate_action.nonce;
+    set_access_key(state_update, account_id, &delegate_action.public_key, &access_key);
+
+    // The inner actions are executed on behalf of the sender. The gas is paid by the signer of the
+    // transaction that relayed them, while the deposits are paid by the sender, so the refunds of
+    // the deposits go back to the sender as the predecessor of the receipt.
+    result.new_receipts.push(Receipt {
+        predecessor_id: account_id.clone(),
+        receiver_id: delegate_action.receiver_id.clone(),
+        receipt_id: CryptoHash::default(),
+        receipt: ReceiptEnum::Action(ActionReceipt {
+            signer_id: action_receipt.signer_id.clone(),
+            signer_public_key: action_receipt.signer_public_key.clone(),
+            gas_price: action_receipt.gas_price,
+            output_data_receivers: vec![],
+            input_data_ids: vec![],
+            actions: delegate_action.actions.clone(),
+        }),
+    });
+    result.result = Ok(ReturnData::ReceiptIndex(0));
+    Ok(())
+}
+
 pub(crate) fn check_actor_permissions(
     action: &Action,
     apply_state: &ApplyState,
@@ -476,7 +647,10 @@ pub(crate) fn check_actor_permissions(
                 .into());
             }
         }
-        Action::CreateAccount(_) | Action::FunctionCall(_) | Action::Transfer(_) => (),
+        Action::CreateAccount(_)
+        | Action::FunctionCall(_)
+        | Action::Transfer(_)
+        | Action::Delegate(_) => (),
     };
     Ok(())
 }
@@ -501,7 +675,8 @@ pub(crate) fn check_account_existence(
         | Action::Stake(_)
         | Action::AddKey(_)
         | Action::DeleteKey(_)
-        | Action::DeleteAccount(_) => {
+        | Action::DeleteAccount(_)
+        | Action::Delegate(_) => {
             if account.is_none() {
                 return Err(ActionErrorKind::AccountDoesNotExist {
                     account_id: account_id.clone(),
@@ -1,7 +1,7 @@
 use near_crypto::PublicKey;
 use near_primitives::account::{AccessKey, Account};
-use near_primitives::types::{AccountId, BlockHeight, MerkleHash};
-use near_primitives::views::ViewStateResult;
+use near_primitives::types::{AccountId, BlockHeight, EpochHeight, EpochId, MerkleHash};
+use near_primitives::views::{CallerContext, ViewStateResult};
 
 /// Adapter for querying runtime.
 pub trait ViewRuntimeAdapter {
@@ -16,9 +16,12 @@ pub trait ViewRuntimeAdapter {
         state_root: MerkleHash,
         height: BlockHeight,
         block_timestamp: u64,
+        epoch_height: EpochHeight,
+        epoch_id: &EpochId,
         contract_id: &AccountId,
         method_name: &str,
         args: &[u8],
+        caller: &CallerContext,
         logs: &mut Vec<String>,
     ) -> Result<Vec<u8>, Box<dyn std::error::Error>>;
 
@@ -5,7 +5,8 @@ use cached::{cached_key, SizedCache};
 
 use near_primitives::contract::ContractCode;
 use near_primitives::hash::CryptoHash;
-use near_store::StorageError;
+use near_store::{ColCachedContractCode, StorageError, Store};
+use near_vm_runner::CompiledContractCache;
 
 /// Cache size in number of cached modules to hold.
 #[cfg(not(feature = "no_cache"))]
@@ -39,3 +40,20 @@ pub(crate) fn get_code(
         Arc::new(code)
     }))
 }
+
+/// Persistent cache of compiled contracts in the node storage.
+pub struct StoreCompiledContractCache {
+    pub store: Arc<Store>,
+}
+
+impl CompiledContractCache for StoreCompiledContractCache {
+    fn put(&self, key: &[u8], value: &[u8]) -> Result<(), std::io::Error> {
+        let mut store_update = self.store.store_update();
+        store_update.set(ColCachedContractCode, key, value);
+        store_update.commit()
+    }
+
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, std::io::Error> {
+        self.store.get(ColCachedContractCode, key)
+    }
+}
@@ -2,10 +2,11 @@
 use near_primitives::account::AccessKeyPermission;
 use near_primitives::errors::IntegerOverflowError;
 use near_primitives::transaction::{
-    Action, AddKeyAction, DeployContractAction, FunctionCallAction, Transaction,
+    Action, AddKeyAction, DeployContractAction, FunctionCallAction, GasProfile, Transaction,
 };
 use near_primitives::types::{Balance, Gas};
 use near_runtime_fees::RuntimeFeesConfig;
+use near_vm_logic::ProfileData;
 
 // Just re-exporting RuntimeConfig for backwards compatibility.
 pub use near_runtime_configs::RuntimeConfig;
@@ -47,51 +48,65 @@ pub fn total_send_fees(
     sender_is_receiver: bool,
     actions: &[Action],
 ) -> Result<Gas, IntegerOverflowError> {
-    let cfg = &config.action_creation_config;
     let mut result = 0;
     for action in actions {
-        use Action::*;
-        let delta = match action {
-            CreateAccount(_) => cfg.create_account_cost.send_fee(sender_is_receiver),
-            DeployContract(DeployContractAction { code }) => {
-                let num_bytes = code.len() as u64;
-                cfg.deploy_contract_cost.send_fee(sender_is_receiver)
-                    + cfg.deploy_contract_cost_per_byte.send_fee(sender_is_receiver) * num_bytes
-            }
-            FunctionCall(FunctionCallAction { method_name, args, .. }) => {
-                let num_bytes = method_name.as_bytes().len() as u64 + args.len() as u64;
-                cfg.function_call_cost.send_fee(sender_is_receiver)
-                    + cfg.function_call_cost_per_byte.send_fee(sender_is_receiver) * num_bytes
-            }
-            Transfer(_) => cfg.transfer_cost.send_fee(sender_is_receiver),
-            Stake(_) => cfg.stake_cost.send_fee(sender_is_receiver),
-            AddKey(AddKeyAction { access_key, .. }) => match &access_key.permission {
-                AccessKeyPermission::FunctionCall(call_perm) => {
-                    let num_bytes = call_perm
-                        .method_names
-                        .iter()
-                        // Account for null-terminating characters.
-                        .map(|name| name.as_bytes().len() as u64 + 1)
-                        .sum::<u64>();
-                    cfg.add_key_cost.function_call_cost.send_fee(sender_is_receiver)
-                        + num_bytes
-                            * cfg
-                                .add_key_cost
-                                .function_call_cost_per_byte
-                                .send_fee(sender_is_receiver)
-                }
-                AccessKeyPermission::FullAccess => {
-                    cfg.add_key_cost.full_access_cost.send_fee(sender_is_receiver)
-                }
-            },
-            DeleteKey(_) => cfg.delete_key_cost.send_fee(sender_is_receiver),
-            DeleteAccount(_) => cfg.delete_account_cost.send_fee(sender_is_receiver),
-        };
+        let delta = send_fee(config, sender_is_receiver, action);
         result = safe_add_gas(result, delta)?;
     }
     Ok(result)
 }
 
+/// Gas that needs to be burnt to send the given action.
+pub fn send_fee(config: &RuntimeFeesConfig, sender_is_receiver: bool, action: &Action) -> Gas {
+    let cfg = &config.action_creation_config;
+    use Action::*;
+    match action {
+        CreateAccount(_) => cfg.create_account_cost.send_fee(sender_is_receiver),
+        DeployContract(DeployContractAction { code }) => {
+            let num_bytes = code.len() as u64;
+            cfg.deploy_contract_cost.send_fee(sender_is_receiver)
+                + cfg.deploy_contract_cost_per_byte.send_fee(sender_is_receiver) * num_bytes
+        }
+        FunctionCall(FunctionCallAction { method_name, args, .. }) => {
+            let num_bytes = method_name.as_bytes().len() as u64 + args.len() as u64;
+            cfg.function_call_cost.send_fee(sender_is_receiver)
+                + cfg.function_call_cost_per_byte.send_fee(sender_is_receiver) * num_bytes
+        }
+        Transfer(_) => cfg.transfer_cost.send_fee(sender_is_receiver),
+        Stake(_) => cfg.stake_cost.send_fee(sender_is_receiver),
+        AddKey(AddKeyAction { access_key, .. }) => match &access_key.permission {
+            AccessKeyPermission::FunctionCall(call_perm) => {
+                let num_bytes = call_perm
+                    .method_names
+                    .iter()
+                    // Account for null-terminating characters.
+                    .map(|name| name.as_bytes().len() as u64 + 1)
+                    .sum::<u64>();
+                cfg.add_key_cost.function_call_cost.send_fee(sender_is_receiver)
+                    + num_bytes
+                        * cfg.add_key_cost.function_call_cost_per_byte.send_fee(sender_is_receiver)
+            }
+            AccessKeyPermission::FullAccess => {
+                cfg.add_key_cost.full_access_cost.send_fee(sender_is_receiver)
+            }
+        },
+        DeleteKey(_) => cfg.delete_key_cost.send_fee(sender_is_receiver),
+        DeleteAccount(_) => cfg.delete_account_cost.send_fee(sender_is_receiver),
+        Delegate(signed_delegate_action) => {
+            // The relayer also pays for sending the receipt with the inner actions.
+            let delegate_action = &signed_delegate_action.delegate_action;
+            let inner_sender_is_receiver = delegate_action.sender_id == delegate_action.receiver_id;
+            cfg.delegate_cost.send_fee(sender_is_receiver)
+                + config.action_receipt_creation_config.send_fee(inner_sender_is_receiver)
+                + delegate_action
+                    .actions
+                    .iter()
+                    .map(|action| send_fee(config, inner_sender_is_receiver, action))
+                    .sum::<Gas>()
+        }
+    }
+}
+
 pub fn exec_fee(config: &RuntimeFeesConfig, action: &Action) -> Gas {
     let cfg = &config.action_creation_config;
     use Action::*;
@@ -124,6 +139,18 @@ pub fn exec_fee(config: &RuntimeFeesConfig, action: &Action) -> Gas {
         },
         DeleteKey(_) => cfg.delete_key_cost.exec_fee(),
         DeleteAccount(_) => cfg.delete_account_cost.exec_fee(),
+        Delegate(signed_delegate_action) => {
+            // Includes the execution fees of the receipt with the inner actions, which are
+            // prepaid when the delegate action is executed.
+            cfg.delegate_cost.exec_fee()
+                + config.action_receipt_creation_config.exec_fee()
+                + signed_delegate_action
+                    .delegate_action
+                    .actions
+                    .iter()
+                    .map(|action| exec_fee(config, action))
+                    .sum::<Gas>()
+        }
     }
 }
 /// Returns transaction costs for a given transaction.
@@ -146,6 +173,56 @@ pub fn tx_cost(
     Ok(TransactionCost { gas_burnt, gas_used, total_cost })
 }
 
+/// Name of the fee of the given action in gas profiles. Matches the names of `ActionCosts` for
+/// the actions that can be created by contracts.
+pub fn action_cost_name(action: &Action) -> &'static str {
+    use Action::*;
+    match action {
+        CreateAccount(_) => "create_account",
+        DeployContract(_) => "deploy_contract",
+        FunctionCall(_) => "function_call",
+        Transfer(_) => "transfer",
+        Stake(_) => "stake",
+        AddKey(_) => "add_key",
+        DeleteKey(_) => "delete_key",
+        DeleteAccount(_) => "delete_account",
+        Delegate(_) => "delegate",
+    }
+}
+
+/// Breakdown of the gas burnt for converting the given transaction into a receipt.
+pub fn tx_gas_profile(
+    config: &RuntimeFeesConfig,
+    transaction: &Transaction,
+    sender_is_receiver: bool,
+) -> GasProfile {
+    let mut profile = GasProfile::default();
+    profile.add_action_cost(
+        "new_receipt",
+        config.action_receipt_creation_config.send_fee(sender_is_receiver),
+    );
+    for action in transaction.actions.iter() {
+        profile.add_action_cost(
+            action_cost_name(action),
+            send_fee(config, sender_is_receiver, action),
+        );
+    }
+    profile
+}
+
+/// Converts the profile of a contract execution into a gas profile of the outcome.
+pub fn vm_gas_profile(profile: ProfileData) -> GasProfile {
+    let mut result = GasProfile { wasm_gas: profile.wasm_gas, ..Default::default() };
+    // `ExtCosts` and `ActionCosts` variants are named after the costs they represent.
+    for (cost, gas_used) in profile.ext_costs {
+        result.add_host_cost(&format!("{:?}", cost), gas_used);
+    }
+    for (action, gas_used) in profile.action_costs {
+        result.add_action_cost(&format!("{:?}", action), gas_used);
+    }
+    result
+}
+
 /// Total sum of gas that would need to be burnt before we start executing the given actions.
 pub fn total_exec_fees(
     config: &RuntimeFeesConfig,
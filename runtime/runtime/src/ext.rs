@@ -4,17 +4,17 @@ use std::iter::Peekable;
 use borsh::BorshDeserialize;
 use near_crypto::PublicKey;
 use near_primitives::account::{AccessKey, AccessKeyPermission, FunctionCallPermission};
-use near_primitives::errors::StorageError;
+use near_primitives::errors::ExternalError;
 use near_primitives::hash::CryptoHash;
 use near_primitives::receipt::{ActionReceipt, DataReceiver, Receipt, ReceiptEnum};
 use near_primitives::transaction::{
     Action, AddKeyAction, CreateAccountAction, DeleteAccountAction, DeleteKeyAction,
     DeployContractAction, FunctionCallAction, StakeAction, TransferAction,
 };
-use near_primitives::types::{AccountId, Balance};
+use near_primitives::types::{AccountId, Balance, EpochId, EpochInfoProvider};
 use near_primitives::utils::{create_nonce_with_nonce, prefix_for_data};
 use near_store::{TrieUpdate, TrieUpdateIterator, TrieUpdateValuePtr};
-use near_vm_logic::{External, HostError, VMLogicError, ValuePtr};
+use near_vm_logic::{crypto, External, HostError, VMLogicError, ValuePtr};
 use sha3::{Keccak256, Keccak512};
 
 pub struct RuntimeExt<'a> {
@@ -28,6 +28,8 @@ pub struct RuntimeExt<'a> {
     gas_price: Balance,
     base_data_id: &'a CryptoHash,
     data_count: u64,
+    epoch_id: &'a EpochId,
+    epoch_info_provider: &'a dyn EpochInfoProvider,
 }
 
 pub struct RuntimeExtValuePtr<'a>(TrieUpdateValuePtr<'a>);
@@ -50,6 +52,8 @@ impl<'a> RuntimeExt<'a> {
         signer_public_key: &'a PublicKey,
         gas_price: Balance,
         base_data_id: &'a CryptoHash,
+        epoch_id: &'a EpochId,
+        epoch_info_provider: &'a dyn EpochInfoProvider,
     ) -> Self {
         RuntimeExt {
             trie_update,
@@ -62,6 +66,8 @@ impl<'a> RuntimeExt<'a> {
             gas_price,
             base_data_id,
             data_count: 0,
+            epoch_id,
+            epoch_info_provider,
         }
     }
 
@@ -99,10 +105,9 @@ impl<'a> RuntimeExt<'a> {
     }
 }
 
-fn wrap_error(error: StorageError) -> VMLogicError {
-    // TODO(#2010): Wrap StorageError into ExternalError.
+fn wrap_error(error: impl Into<ExternalError>) -> VMLogicError {
     VMLogicError::ExternalError(
-        borsh::BorshSerialize::try_to_vec(&error).expect("Borsh serialize cannot fail"),
+        borsh::BorshSerialize::try_to_vec(&error.into()).expect("Borsh serialize cannot fail"),
     )
 }
 
@@ -382,6 +387,27 @@ impl<'a> External for RuntimeExt<'a> {
         Ok(res.to_vec())
     }
 
+    fn ripemd160(&self, data: &[u8]) -> ExtResult<Vec<u8>> {
+        Ok(crypto::ripemd160(data))
+    }
+
+    fn blake2b(&self, data: &[u8]) -> ExtResult<Vec<u8>> {
+        Ok(crypto::blake2b(data))
+    }
+
+    fn ed25519_verify(
+        &self,
+        signature: &[u8],
+        message: &[u8],
+        public_key: &[u8],
+    ) -> ExtResult<bool> {
+        Ok(crypto::ed25519_verify(signature, message, public_key))
+    }
+
+    fn ecrecover(&self, hash: &[u8], signature: &[u8], v: u8) -> ExtResult<Option<Vec<u8>>> {
+        Ok(crypto::ecrecover(hash, signature, v))
+    }
+
     fn get_touched_nodes_count(&self) -> u64 {
         self.trie_update.trie.counter.get()
     }
@@ -5,6 +5,8 @@ use std::sync::Arc;
 
 use borsh::{BorshDeserialize, BorshSerialize};
 use log::debug;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use near_crypto::PublicKey;
 use near_primitives::account::{AccessKey, Account};
@@ -15,11 +17,12 @@ use near_primitives::receipt::{ActionReceipt, DataReceipt, Receipt, ReceiptEnum,
 use near_primitives::serialize::from_base64;
 use near_primitives::state_record::StateRecord;
 use near_primitives::transaction::{
-    Action, ExecutionOutcome, ExecutionOutcomeWithId, ExecutionStatus, LogEntry, SignedTransaction,
+    Action, ExecutionOutcome, ExecutionOutcomeWithId, ExecutionStatus, GasProfile, LogEntry,
+    SignedTransaction,
 };
 use near_primitives::types::{
-    AccountId, Balance, BlockHeight, BlockHeightDelta, Gas, Nonce, StateChangeCause, StateChanges,
-    StateRoot, ValidatorStake,
+    AccountId, Balance, BlockHeight, BlockHeightDelta, EpochHeight, EpochId, EpochInfoProvider,
+    Gas, Nonce, StateChangeCause, StateChanges, StateRoot, ValidatorStake,
 };
 use near_primitives::utils::col::DELAYED_RECEIPT_INDICES;
 use near_primitives::utils::{
@@ -29,21 +32,23 @@ use near_primitives::utils::{
 };
 use near_store::{
     get, get_account, get_receipt, get_received_data, set, set_access_key, set_account, set_code,
-    set_receipt, set_received_data, StorageError, StoreUpdate, Trie, TrieChanges, TrieUpdate,
+    set_receipt, set_received_data, ForkedChanges, StorageError, StoreUpdate, Trie, TrieChanges,
+    TrieUpdate,
 };
 use near_vm_logic::types::PromiseResult;
 use near_vm_logic::ReturnData;
+use near_vm_runner::CompiledContractCache;
 #[cfg(feature = "costs_counting")]
 pub use near_vm_runner::EXT_COSTS_COUNTER;
 
 use crate::actions::*;
 use crate::balance_checker::check_balance;
 use crate::config::{
-    exec_fee, safe_add_balance, safe_add_gas, safe_gas_to_balance, total_deposit, total_exec_fees,
-    total_prepaid_gas, RuntimeConfig,
+    action_cost_name, exec_fee, safe_add_balance, safe_add_gas, safe_gas_to_balance, total_deposit,
+    total_exec_fees, total_prepaid_gas, tx_gas_profile, RuntimeConfig,
 };
-use crate::verifier::validate_receipt;
 pub use crate::verifier::verify_and_charge_transaction;
+use crate::verifier::{charge_transaction, validate_receipt};
 
 mod actions;
 pub mod adapter;
@@ -57,7 +62,7 @@ mod verifier;
 
 const EXPECT_ACCOUNT_EXISTS: &str = "account exists, checked above";
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ApplyState {
     /// Currently building block height.
     // TODO #1903 pub block_height: BlockHeight,
@@ -68,9 +73,15 @@ pub struct ApplyState {
     pub gas_price: Balance,
     /// A block timestamp
     pub block_timestamp: u64,
+    /// The id of the current epoch.
+    pub epoch_id: EpochId,
+    /// The current epoch height.
+    pub epoch_height: EpochHeight,
     /// Gas limit for a given chunk.
     /// If None is given, assumes there is no gas limit.
     pub gas_limit: Option<Gas>,
+    /// Persistent cache of compiled contracts.
+    pub cache: Option<Arc<dyn CompiledContractCache>>,
 }
 
 /// Contains information to update validators accounts at the first block of a new epoch.
@@ -95,7 +106,7 @@ pub struct VerificationResult {
     pub validator_reward: Balance,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, PartialEq)]
 pub struct ApplyStats {
     pub total_rent_paid: Balance,
     pub total_validator_reward: Balance,
@@ -103,16 +114,79 @@ pub struct ApplyStats {
     pub total_balance_slashed: Balance,
 }
 
+impl ApplyStats {
+    fn merge(&mut self, other: ApplyStats) -> Result<(), RuntimeError> {
+        self.total_rent_paid = safe_add_balance(self.total_rent_paid, other.total_rent_paid)?;
+        self.total_validator_reward =
+            safe_add_balance(self.total_validator_reward, other.total_validator_reward)?;
+        self.total_balance_burnt =
+            safe_add_balance(self.total_balance_burnt, other.total_balance_burnt)?;
+        self.total_balance_slashed =
+            safe_add_balance(self.total_balance_slashed, other.total_balance_slashed)?;
+        Ok(())
+    }
+}
+
 pub struct ApplyResult {
     pub state_root: StateRoot,
     pub trie_changes: TrieChanges,
     pub validator_proposals: Vec<ValidatorStake>,
     pub outgoing_receipts: Vec<Receipt>,
     pub outcomes: Vec<ExecutionOutcomeWithId>,
+    /// Gas profiles of the outcomes by outcome id. Refunds are not profiled.
+    pub gas_profiles: Vec<(CryptoHash, GasProfile)>,
     pub key_value_changes: StateChanges,
     pub stats: ApplyStats,
 }
 
+/// Result of executing a single receipt on a fork of the state, see `Runtime::execute_receipts`.
+struct ReceiptExecution {
+    result: Result<Option<ExecutionOutcomeWithId>, RuntimeError>,
+    changes: ForkedChanges,
+    outgoing_receipts: Vec<Receipt>,
+    validator_proposals: Vec<ValidatorStake>,
+    gas_profiles: Vec<(CryptoHash, GasProfile)>,
+    stats: ApplyStats,
+}
+
+/// Maximum number of rounds of receipts executed by `Runtime::simulate_transaction`.
+pub const MAX_SIMULATION_ROUNDS: usize = 16;
+
+/// How `Runtime::apply_to_state_update` processes the chunk.
+#[derive(Clone, Copy)]
+enum ApplyMode {
+    /// Applying a chunk of a block.
+    Chunk,
+    /// Simulating a transaction. The delayed receipts are not processed, and the signatures of
+    /// the transactions are only checked if `verify_signature` is set.
+    Simulation { verify_signature: bool },
+}
+
+/// Everything `Runtime::apply_to_state_update` produces besides the state changes.
+struct ApplyOutcomes {
+    validator_proposals: Vec<ValidatorStake>,
+    outgoing_receipts: Vec<Receipt>,
+    outcomes: Vec<ExecutionOutcomeWithId>,
+    gas_profiles: Vec<(CryptoHash, GasProfile)>,
+    stats: ApplyStats,
+    /// Receipts left over by a simulation that reached the gas limit. When applying a chunk they
+    /// are delayed instead.
+    postponed_receipts: Vec<Receipt>,
+}
+
+/// Result of a transaction simulation, see `Runtime::simulate_transaction`.
+pub struct SimulationResult {
+    /// Outcomes of the transaction and of all the executed receipts, in the execution order.
+    pub outcomes: Vec<ExecutionOutcomeWithId>,
+    /// Gas profiles of the outcomes by outcome id.
+    pub gas_profiles: Vec<(CryptoHash, GasProfile)>,
+    /// Receipts that were not executed, because they are for the accounts that are not local
+    /// or the simulation ran out of rounds or gas.
+    pub pending_receipts: Vec<Receipt>,
+    /// State changes made by the transaction and the executed receipts.
+    pub key_value_changes: StateChanges,
+}
+
 /// Stores indices for a persistent queue for delayed receipts that didn't fit into a block.
 #[derive(Default, BorshSerialize, BorshDeserialize, Clone, PartialEq)]
 pub struct DelayedReceiptIndices {
@@ -131,6 +205,7 @@ pub struct ActionResult {
     pub logs: Vec<LogEntry>,
     pub new_receipts: Vec<Receipt>,
     pub validator_proposals: Vec<ValidatorStake>,
+    pub profile: GasProfile,
 }
 
 impl ActionResult {
@@ -148,6 +223,7 @@ impl ActionResult {
             next_result.gas_burnt_for_function_call,
         )?;
         self.gas_used = safe_add_gas(self.gas_used, next_result.gas_used)?;
+        self.profile.merge(next_result.profile);
         self.result = next_result.result;
         self.logs.append(&mut next_result.logs);
         if let Ok(ReturnData::ReceiptIndex(ref mut receipt_index)) = self.result {
@@ -175,17 +251,73 @@ impl Default for ActionResult {
             logs: vec![],
             new_receipts: vec![],
             validator_proposals: vec![],
+            profile: GasProfile::default(),
+        }
+    }
+}
+
+/// Settings of the parallel execution of receipts, see `Runtime::apply`, and of the background
+/// compilation of deployed contracts. They don't change the results of the execution.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ReceiptsExecutionConfig {
+    /// Maximum number of receipts that are executed at once. Receipts of a batch are executed
+    /// speculatively, so the receipts after the one that reaches the gas limit are wasted work.
+    pub batch_size: usize,
+    /// Number of threads that execute the receipts. Zero means one thread per CPU.
+    pub num_threads: usize,
+    /// Number of threads that compile deployed contracts. Zero disables the precompilation,
+    /// so contracts are compiled on their first call.
+    pub precompile_threads: usize,
+    /// Maximum number of deployed contracts waiting to be compiled.
+    pub precompile_queue_size: usize,
+}
+
+impl Default for ReceiptsExecutionConfig {
+    fn default() -> Self {
+        ReceiptsExecutionConfig {
+            batch_size: 64,
+            num_threads: 0,
+            precompile_threads: 2,
+            precompile_queue_size: 64,
         }
     }
 }
 
 pub struct Runtime {
     pub config: RuntimeConfig,
+    receipts_batch_size: usize,
+    /// Executes the receipts, so they don't compete with the other users of the global pool.
+    thread_pool: rayon::ThreadPool,
+    /// Compiles the contracts deployed by the executed receipts.
+    precompiler: ContractPrecompiler,
 }
 
 impl Runtime {
     pub fn new(config: RuntimeConfig) -> Self {
-        Runtime { config }
+        Self::new_with_execution_config(config, &ReceiptsExecutionConfig::default())
+    }
+
+    pub fn new_with_execution_config(
+        config: RuntimeConfig,
+        execution_config: &ReceiptsExecutionConfig,
+    ) -> Self {
+        assert!(execution_config.batch_size > 0, "Receipts batch size must be positive");
+        let thread_pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(execution_config.num_threads)
+            .thread_name(|index| format!("receipts-{}", index))
+            .build()
+            .expect("Failed to create the receipts execution thread pool");
+        let precompiler = ContractPrecompiler::new(
+            execution_config.precompile_threads,
+            execution_config.precompile_queue_size,
+        );
+        Runtime {
+            config,
+            receipts_batch_size: execution_config.batch_size,
+            thread_pool,
+            precompiler,
+        }
     }
 
     fn print_log(log: &[LogEntry]) {
@@ -216,14 +348,17 @@ impl Runtime {
         state_update: &mut TrieUpdate,
         apply_state: &ApplyState,
         signed_transaction: &SignedTransaction,
+        verify_signature: bool,
+        gas_profiles: &mut Vec<(CryptoHash, GasProfile)>,
         stats: &mut ApplyStats,
     ) -> Result<(Receipt, ExecutionOutcomeWithId), RuntimeError> {
         near_metrics::inc_counter(&metrics::TRANSACTION_PROCESSED_TOTAL);
-        match verify_and_charge_transaction(
+        match charge_transaction(
             &self.config,
             state_update,
             apply_state,
             signed_transaction,
+            verify_signature,
         ) {
             Ok(verification_result) => {
                 near_metrics::inc_counter(&metrics::TRANSACTION_PROCESSED_SUCCESSFULLY_TOTAL);
@@ -250,6 +385,14 @@ impl Runtime {
                     stats.total_validator_reward,
                     verification_result.validator_reward,
                 )?;
+                gas_profiles.push((
+                    signed_transaction.get_hash(),
+                    tx_gas_profile(
+                        &self.config.transaction_costs,
+                        transaction,
+                        transaction.signer_id == transaction.receiver_id,
+                    ),
+                ));
                 let outcome = ExecutionOutcomeWithId {
                     id: signed_transaction.get_hash(),
                     outcome: ExecutionOutcome {
@@ -281,11 +424,21 @@ impl Runtime {
         promise_results: &[PromiseResult],
         action_hash: CryptoHash,
         is_last_action: bool,
+        epoch_info_provider: &dyn EpochInfoProvider,
     ) -> Result<ActionResult, RuntimeError> {
         let mut result = ActionResult::default();
         let exec_fees = exec_fee(&self.config.transaction_costs, action);
-        result.gas_burnt += exec_fees;
+        // The execution fees of the actions relayed by a delegate action are prepaid for the
+        // receipt it creates, so only the base fee is burnt here.
+        let burnt_exec_fees = match action {
+            Action::Delegate(_) => {
+                self.config.transaction_costs.action_creation_config.delegate_cost.exec_fee()
+            }
+            _ => exec_fees,
+        };
+        result.gas_burnt += burnt_exec_fees;
         result.gas_used += exec_fees;
+        result.profile.add_action_cost(action_cost_name(action), exec_fees);
         let account_id = &receipt.receiver_id;
         // Account validation
         if let Err(e) = check_account_existence(action, account, account_id) {
@@ -320,9 +473,12 @@ impl Runtime {
                 near_metrics::inc_counter(&metrics::ACTION_DEPLOY_CONTRACT_TOTAL);
                 action_deploy_contract(
                     state_update,
+                    apply_state,
                     account.as_mut().expect(EXPECT_ACCOUNT_EXISTS),
                     &account_id,
                     deploy_contract,
+                    &self.config.wasm_config,
+                    &self.precompiler,
                 )?;
             }
             Action::FunctionCall(function_call) => {
@@ -340,6 +496,7 @@ impl Runtime {
                     &action_hash,
                     &self.config,
                     is_last_action,
+                    epoch_info_provider,
                 )?;
             }
             Action::Transfer(transfer) => {
@@ -389,6 +546,17 @@ impl Runtime {
                     delete_account,
                 )?;
             }
+            Action::Delegate(signed_delegate_action) => {
+                near_metrics::inc_counter(&metrics::ACTION_DELEGATE_TOTAL);
+                action_delegate(
+                    state_update,
+                    account.as_mut().expect(EXPECT_ACCOUNT_EXISTS),
+                    action_receipt,
+                    &mut result,
+                    account_id,
+                    signed_delegate_action,
+                )?;
+            }
         };
         Ok(result)
     }
@@ -401,7 +569,9 @@ impl Runtime {
         receipt: &Receipt,
         outgoing_receipts: &mut Vec<Receipt>,
         validator_proposals: &mut Vec<ValidatorStake>,
+        gas_profiles: &mut Vec<(CryptoHash, GasProfile)>,
         stats: &mut ApplyStats,
+        epoch_info_provider: &dyn EpochInfoProvider,
     ) -> Result<ExecutionOutcomeWithId, RuntimeError> {
         let action_receipt = match receipt.receipt {
             ReceiptEnum::Action(ref action_receipt) => action_receipt,
@@ -443,6 +613,7 @@ impl Runtime {
         let exec_fee = self.config.transaction_costs.action_receipt_creation_config.exec_fee();
         result.gas_used = exec_fee;
         result.gas_burnt = exec_fee;
+        result.profile.add_action_cost("new_receipt", exec_fee);
         // Executing actions one by one
         for (action_index, action) in action_receipt.actions.iter().enumerate() {
             let is_last_action = action_index + 1 == action_receipt.actions.len();
@@ -460,6 +631,7 @@ impl Runtime {
                     u64::max_value() - action_index as u64,
                 ),
                 is_last_action,
+                epoch_info_provider,
             )?;
             if new_result.result.is_ok() {
                 if let Err(e) = new_result.new_receipts.iter().try_for_each(|receipt| {
@@ -499,7 +671,8 @@ impl Runtime {
         }
 
         // If the receipt is a refund, then we consider it free without burnt gas.
-        if receipt.predecessor_id == system_account() {
+        let is_refund = receipt.predecessor_id == system_account();
+        if is_refund {
             result.gas_burnt = 0;
             result.gas_used = 0;
             // If the refund fails, instead of just burning tokens, we report the total number of
@@ -629,6 +802,9 @@ impl Runtime {
 
         Self::print_log(&result.logs);
 
+        if !is_refund {
+            gas_profiles.push((receipt.receipt_id, result.profile));
+        }
         Ok(ExecutionOutcomeWithId {
             id: receipt.receipt_id,
             outcome: ExecutionOutcome {
@@ -682,7 +858,9 @@ impl Runtime {
         receipt: &Receipt,
         outgoing_receipts: &mut Vec<Receipt>,
         validator_proposals: &mut Vec<ValidatorStake>,
+        gas_profiles: &mut Vec<(CryptoHash, GasProfile)>,
         stats: &mut ApplyStats,
+        epoch_info_provider: &dyn EpochInfoProvider,
     ) -> Result<Option<ExecutionOutcomeWithId>, RuntimeError> {
         let account_id = &receipt.receiver_id;
         match receipt.receipt {
@@ -738,7 +916,9 @@ impl Runtime {
                                 &ready_receipt,
                                 outgoing_receipts,
                                 validator_proposals,
+                                gas_profiles,
                                 stats,
+                                epoch_info_provider,
                             )
                             .map(Some);
                     } else {
@@ -785,7 +965,9 @@ impl Runtime {
                             receipt,
                             outgoing_receipts,
                             validator_proposals,
+                            gas_profiles,
                             stats,
+                            epoch_info_provider,
                         )
                         .map(Some);
                 } else {
@@ -929,6 +1111,8 @@ impl Runtime {
     /// new outgoing receipts, total rent paid by all the affected accounts, execution outcomes for
     /// all transactions, local action receipts (generated from transactions with signer ==
     /// receivers) and incoming action receipts.
+    /// Receipts for different receivers are executed in parallel, but the result is the same as if
+    /// they were executed one by one.
     pub fn apply(
         &self,
         trie: Arc<Trie>,
@@ -937,31 +1121,94 @@ impl Runtime {
         apply_state: &ApplyState,
         incoming_receipts: &[Receipt],
         transactions: &[SignedTransaction],
+        epoch_info_provider: &dyn EpochInfoProvider,
     ) -> Result<ApplyResult, RuntimeError> {
         let initial_state = TrieUpdate::new(trie.clone(), root);
         let mut state_update = TrieUpdate::new(trie.clone(), root);
 
+        let ApplyOutcomes {
+            validator_proposals,
+            outgoing_receipts,
+            outcomes,
+            gas_profiles,
+            stats,
+            postponed_receipts: _,
+        } = self.apply_to_state_update(
+            &mut state_update,
+            validator_accounts_update,
+            apply_state,
+            incoming_receipts,
+            transactions,
+            ApplyMode::Chunk,
+            epoch_info_provider,
+        )?;
+
+        check_balance(
+            &self.config.transaction_costs,
+            &initial_state,
+            &state_update,
+            validator_accounts_update,
+            incoming_receipts,
+            transactions,
+            &outgoing_receipts,
+            &stats,
+        )?;
+
+        state_update.commit(StateChangeCause::UpdatedDelayedReceipts);
+        // TODO: Avoid cloning.
+        let key_value_changes = state_update.committed_updates_per_cause().clone();
+
+        let trie_changes = state_update.finalize()?;
+        let state_root = trie_changes.new_root;
+        Ok(ApplyResult {
+            state_root,
+            trie_changes,
+            validator_proposals,
+            outgoing_receipts,
+            outcomes,
+            gas_profiles,
+            key_value_changes,
+            stats,
+        })
+    }
+
+    /// Processes the transactions and the receipts of a chunk the way `apply` does, on top of
+    /// the given `TrieUpdate`. The changes to the delayed receipts indices are left uncommitted.
+    /// Nothing is finalized, so the caller decides whether the changes are persisted.
+    fn apply_to_state_update(
+        &self,
+        state_update: &mut TrieUpdate,
+        validator_accounts_update: &Option<ValidatorAccountsUpdate>,
+        apply_state: &ApplyState,
+        incoming_receipts: &[Receipt],
+        transactions: &[SignedTransaction],
+        mode: ApplyMode,
+        epoch_info_provider: &dyn EpochInfoProvider,
+    ) -> Result<ApplyOutcomes, RuntimeError> {
         let mut stats = ApplyStats::default();
 
         if let Some(validator_accounts_update) = validator_accounts_update {
-            self.update_validator_accounts(
-                &mut state_update,
-                validator_accounts_update,
-                &mut stats,
-            )?;
+            self.update_validator_accounts(state_update, validator_accounts_update, &mut stats)?;
         }
 
         let mut outgoing_receipts = Vec::new();
         let mut validator_proposals = vec![];
         let mut local_receipts = vec![];
         let mut outcomes = vec![];
+        let mut gas_profiles = vec![];
+        let mut postponed_receipts = vec![];
         let mut total_gas_burnt = 0;
 
         for signed_transaction in transactions {
             let (receipt, outcome_with_id) = self.process_transaction(
-                &mut state_update,
+                state_update,
                 apply_state,
                 signed_transaction,
+                match mode {
+                    ApplyMode::Chunk => true,
+                    ApplyMode::Simulation { verify_signature } => verify_signature,
+                },
+                &mut gas_profiles,
                 &mut stats,
             )?;
             if receipt.receiver_id == signed_transaction.transaction.signer_id {
@@ -976,118 +1223,332 @@ impl Runtime {
         }
 
         let mut delayed_receipts_indices: DelayedReceiptIndices =
-            get(&state_update, DELAYED_RECEIPT_INDICES)?.unwrap_or_default();
+            get(state_update, DELAYED_RECEIPT_INDICES)?.unwrap_or_default();
         let initial_delayed_receipt_indices = delayed_receipts_indices.clone();
 
-        let mut process_receipt = |receipt: &Receipt,
-                                   state_update: &mut TrieUpdate,
+        // Receipts are executed in batches on forks of the state, see `execute_receipts`. The
+        // results are merged back one by one in the order of the serial execution, so the gas
+        // limit is checked before every receipt the same way.
+        let mut merge_execution = |state_update: &mut TrieUpdate,
+                                   execution: Option<ReceiptExecution>,
                                    total_gas_burnt: &mut Gas|
          -> Result<_, RuntimeError> {
-            self.process_receipt(
-                state_update,
-                apply_state,
-                receipt,
-                &mut outgoing_receipts,
-                &mut validator_proposals,
-                &mut stats,
-            )?
-            .into_iter()
-            .try_for_each(
-                |outcome_with_id: ExecutionOutcomeWithId| -> Result<(), RuntimeError> {
-                    *total_gas_burnt =
-                        safe_add_gas(*total_gas_burnt, outcome_with_id.outcome.gas_burnt)?;
-                    outcomes.push(outcome_with_id);
-                    Ok(())
-                },
-            )?;
+            let execution = execution
+                .expect("Only the receipts after a failed one for the same receiver are skipped");
+            let outcome_with_id = execution.result?;
+            state_update.apply_forked_changes(execution.changes);
+            outgoing_receipts.extend(execution.outgoing_receipts);
+            validator_proposals.extend(execution.validator_proposals);
+            gas_profiles.extend(execution.gas_profiles);
+            stats.merge(execution.stats)?;
+            if let Some(outcome_with_id) = outcome_with_id {
+                *total_gas_burnt =
+                    safe_add_gas(*total_gas_burnt, outcome_with_id.outcome.gas_burnt)?;
+                outcomes.push(outcome_with_id);
+            }
             Ok(())
         };
 
         let gas_limit = apply_state.gas_limit.unwrap_or(Gas::max_value());
 
         // We first process local receipts. They contain staking, local contract calls, etc.
-        for receipt in local_receipts.iter() {
-            if total_gas_burnt < gas_limit {
-                // NOTE: We don't need to validate the local receipt, because it's just validated in
-                // the `verify_and_charge_transaction`.
-                process_receipt(&receipt, &mut state_update, &mut total_gas_burnt)?;
+        for batch in local_receipts.chunks(self.receipts_batch_size) {
+            let mut executions = if total_gas_burnt < gas_limit {
+                // NOTE: We don't need to validate the local receipt, because it's just validated
+                // in the `verify_and_charge_transaction`.
+                self.execute_receipts(state_update, apply_state, batch, epoch_info_provider)
             } else {
-                Self::delay_receipt(&mut state_update, &mut delayed_receipts_indices, receipt)?;
+                vec![]
+            };
+            for (index, receipt) in batch.iter().enumerate() {
+                if total_gas_burnt < gas_limit {
+                    merge_execution(state_update, executions[index].take(), &mut total_gas_burnt)?;
+                } else {
+                    match mode {
+                        ApplyMode::Chunk => Self::delay_receipt(
+                            state_update,
+                            &mut delayed_receipts_indices,
+                            receipt,
+                        )?,
+                        // The simulation leaves the delayed receipts untouched.
+                        ApplyMode::Simulation { .. } => postponed_receipts.push(receipt.clone()),
+                    }
+                }
             }
         }
 
         // Then we process the delayed receipts. It's a backlog of receipts from the past blocks.
-        while delayed_receipts_indices.first_index < delayed_receipts_indices.next_available_index {
+        // They are not related to the simulated transaction, so the simulation skips them.
+        let process_delayed_receipts = match mode {
+            ApplyMode::Chunk => true,
+            ApplyMode::Simulation { .. } => false,
+        };
+        while process_delayed_receipts
+            && delayed_receipts_indices.first_index < delayed_receipts_indices.next_available_index
+        {
             if total_gas_burnt >= gas_limit {
                 break;
             }
-            let key = key_for_delayed_receipt(delayed_receipts_indices.first_index);
-            let receipt: Receipt = get(&state_update, &key)?.ok_or_else(|| {
-                StorageError::StorageInconsistentState(format!(
-                    "Delayed receipt #{} should be in the state",
-                    delayed_receipts_indices.first_index
-                ))
-            })?;
-
-            // Validating the delayed receipt. If it fails, it's likely the state is inconsistent.
-            validate_receipt(&self.config.wasm_config.limit_config, &receipt).map_err(|e| {
-                StorageError::StorageInconsistentState(format!(
-                    "Delayed receipt #{} in the state is invalid: {}",
-                    delayed_receipts_indices.first_index, e
-                ))
-            })?;
-
-            state_update.remove(&key);
-            // Math checked above: first_index is less than next_available_index
-            delayed_receipts_indices.first_index += 1;
-            process_receipt(&receipt, &mut state_update, &mut total_gas_burnt)?;
+            let mut batch = vec![];
+            let mut batch_error = None;
+            let mut index = delayed_receipts_indices.first_index;
+            while index < delayed_receipts_indices.next_available_index
+                && batch.len() < self.receipts_batch_size
+            {
+                match self.get_delayed_receipt(state_update, index) {
+                    Ok(receipt) => batch.push(receipt),
+                    Err(err) => {
+                        batch_error = Some(err);
+                        break;
+                    }
+                }
+                index += 1;
+            }
+            let mut executions =
+                self.execute_receipts(state_update, apply_state, &batch, epoch_info_provider);
+            for execution in executions.iter_mut() {
+                if total_gas_burnt >= gas_limit {
+                    break;
+                }
+                state_update.remove(&key_for_delayed_receipt(delayed_receipts_indices.first_index));
+                // Math checked above: first_index is less than next_available_index
+                delayed_receipts_indices.first_index += 1;
+                merge_execution(state_update, execution.take(), &mut total_gas_burnt)?;
+            }
+            if let Some(err) = batch_error {
+                if total_gas_burnt < gas_limit {
+                    return Err(err);
+                }
+            }
         }
 
         // And then we process the new incoming receipts. These are receipts from other shards.
-        for receipt in incoming_receipts.iter() {
+        for batch in incoming_receipts.chunks(self.receipts_batch_size) {
             // Validating new incoming no matter whether we have available gas or not. We don't
             // want to store invalid receipts in state as delayed.
-            validate_receipt(&self.config.wasm_config.limit_config, &receipt)
-                .map_err(RuntimeError::ReceiptValidationError)?;
-            if total_gas_burnt < gas_limit {
-                process_receipt(&receipt, &mut state_update, &mut total_gas_burnt)?;
+            let mut batch_error = None;
+            let valid_count = batch
+                .iter()
+                .position(|receipt| {
+                    match validate_receipt(&self.config.wasm_config.limit_config, receipt) {
+                        Ok(()) => false,
+                        Err(err) => {
+                            batch_error = Some(RuntimeError::ReceiptValidationError(err));
+                            true
+                        }
+                    }
+                })
+                .unwrap_or_else(|| batch.len());
+            let batch = &batch[..valid_count];
+            let mut executions = if total_gas_burnt < gas_limit {
+                self.execute_receipts(state_update, apply_state, batch, epoch_info_provider)
             } else {
-                Self::delay_receipt(&mut state_update, &mut delayed_receipts_indices, receipt)?;
+                vec![]
+            };
+            for (index, receipt) in batch.iter().enumerate() {
+                if total_gas_burnt < gas_limit {
+                    merge_execution(state_update, executions[index].take(), &mut total_gas_burnt)?;
+                } else {
+                    match mode {
+                        ApplyMode::Chunk => Self::delay_receipt(
+                            state_update,
+                            &mut delayed_receipts_indices,
+                            receipt,
+                        )?,
+                        // The simulation leaves the delayed receipts untouched.
+                        ApplyMode::Simulation { .. } => postponed_receipts.push(receipt.clone()),
+                    }
+                }
+            }
+            if let Some(err) = batch_error {
+                return Err(err);
             }
         }
 
         if delayed_receipts_indices != initial_delayed_receipt_indices {
-            set(&mut state_update, DELAYED_RECEIPT_INDICES.to_vec(), &delayed_receipts_indices);
+            set(state_update, DELAYED_RECEIPT_INDICES.to_vec(), &delayed_receipts_indices);
         }
 
-        check_balance(
-            &self.config.transaction_costs,
-            &initial_state,
-            &state_update,
-            validator_accounts_update,
-            incoming_receipts,
-            transactions,
-            &outgoing_receipts,
-            &stats,
-        )?;
-
-        state_update.commit(StateChangeCause::UpdatedDelayedReceipts);
-        // TODO: Avoid cloning.
-        let key_value_changes = state_update.committed_updates_per_cause().clone();
-
-        let trie_changes = state_update.finalize()?;
-        let state_root = trie_changes.new_root;
-        Ok(ApplyResult {
-            state_root,
-            trie_changes,
+        Ok(ApplyOutcomes {
             validator_proposals,
             outgoing_receipts,
             outcomes,
-            key_value_changes,
+            gas_profiles,
             stats,
+            postponed_receipts,
+        })
+    }
+
+    /// Executes the signed transaction on top of the given state and follows the receipts it
+    /// generates for the local accounts, as decided by `is_local_account`. The receipts are
+    /// applied the same way as `apply` does, in rounds: the local receipts produced by a round
+    /// become the incoming receipts of the next one, for at most `MAX_SIMULATION_ROUNDS` rounds.
+    /// The gas limit of `apply_state` caps the gas burnt by all the rounds together.
+    /// The receipts that are not executed are returned as pending.
+    /// The changes are kept in a `TrieUpdate` that is never finalized, so the given state stays
+    /// untouched. The changes a round leaves uncommitted are committed as
+    /// `StateChangeCause::NotWritableToDisk`, the other ones keep their causes for the caller.
+    /// The signature of the transaction is only checked if `verify_signature` is set.
+    pub fn simulate_transaction(
+        &self,
+        trie: Arc<Trie>,
+        root: CryptoHash,
+        apply_state: &ApplyState,
+        signed_transaction: &SignedTransaction,
+        verify_signature: bool,
+        is_local_account: &dyn Fn(&AccountId) -> bool,
+        epoch_info_provider: &dyn EpochInfoProvider,
+    ) -> Result<SimulationResult, RuntimeError> {
+        let mut state_update = TrieUpdate::new(trie, root);
+        let mode = ApplyMode::Simulation { verify_signature };
+        let mut outcomes = vec![];
+        let mut gas_profiles = vec![];
+        let mut pending_receipts = vec![];
+
+        let gas_limit = apply_state.gas_limit.unwrap_or(Gas::max_value());
+        let mut round_apply_state = apply_state.clone();
+        let mut total_gas_burnt: Gas = 0;
+
+        let mut transactions = vec![signed_transaction.clone()];
+        let mut incoming_receipts = vec![];
+        for _ in 0..MAX_SIMULATION_ROUNDS {
+            round_apply_state.gas_limit = Some(gas_limit - total_gas_burnt);
+            let round = self.apply_to_state_update(
+                &mut state_update,
+                &None,
+                &round_apply_state,
+                &incoming_receipts,
+                &transactions,
+                mode,
+                epoch_info_provider,
+            )?;
+            state_update.commit(StateChangeCause::NotWritableToDisk);
+            for outcome_with_id in round.outcomes.iter() {
+                total_gas_burnt = total_gas_burnt.saturating_add(outcome_with_id.outcome.gas_burnt);
+            }
+            outcomes.extend(round.outcomes);
+            gas_profiles.extend(round.gas_profiles);
+            pending_receipts.extend(round.postponed_receipts);
+            transactions.clear();
+            let (local_receipts, other_receipts): (Vec<_>, Vec<_>) = round
+                .outgoing_receipts
+                .into_iter()
+                .partition(|receipt| is_local_account(&receipt.receiver_id));
+            pending_receipts.extend(other_receipts);
+            incoming_receipts = local_receipts;
+            if incoming_receipts.is_empty() || total_gas_burnt >= gas_limit {
+                break;
+            }
+        }
+        pending_receipts.extend(incoming_receipts);
+
+        Ok(SimulationResult {
+            outcomes,
+            gas_profiles,
+            pending_receipts,
+            key_value_changes: state_update.committed_updates_per_cause().clone(),
         })
     }
 
+    /// Executes the given receipts in parallel and returns the results in the same order.
+    /// Receipts are grouped by the receiver account, since a receipt only changes the state of its
+    /// receiver. The groups are executed on separate forks of the state, and receipts within a
+    /// group are executed in order. Returns `None` for the receipts that follow a receipt that
+    /// failed with an error within the same group.
+    fn execute_receipts(
+        &self,
+        state_update: &TrieUpdate,
+        apply_state: &ApplyState,
+        receipts: &[Receipt],
+        epoch_info_provider: &dyn EpochInfoProvider,
+    ) -> Vec<Option<ReceiptExecution>> {
+        let mut groups: Vec<Vec<usize>> = vec![];
+        let mut group_by_receiver: HashMap<&AccountId, usize> = HashMap::new();
+        for (index, receipt) in receipts.iter().enumerate() {
+            match group_by_receiver.get(&receipt.receiver_id) {
+                Some(&group) => groups[group].push(index),
+                None => {
+                    group_by_receiver.insert(&receipt.receiver_id, groups.len());
+                    groups.push(vec![index]);
+                }
+            }
+        }
+
+        let group_executions: Vec<Vec<(usize, ReceiptExecution)>> =
+            self.thread_pool.install(|| {
+                groups
+                    .into_par_iter()
+                    .map(|group| {
+                        let mut fork = state_update.fork();
+                        let mut executions = vec![];
+                        for index in group {
+                            let mut outgoing_receipts = vec![];
+                            let mut validator_proposals = vec![];
+                            let mut gas_profiles = vec![];
+                            let mut stats = ApplyStats::default();
+                            let result = self.process_receipt(
+                                &mut fork,
+                                apply_state,
+                                &receipts[index],
+                                &mut outgoing_receipts,
+                                &mut validator_proposals,
+                                &mut gas_profiles,
+                                &mut stats,
+                                epoch_info_provider,
+                            );
+                            // The state of the fork is inconsistent after an error.
+                            let is_err = result.is_err();
+                            executions.push((
+                                index,
+                                ReceiptExecution {
+                                    result,
+                                    changes: fork.take_forked_changes(),
+                                    outgoing_receipts,
+                                    validator_proposals,
+                                    gas_profiles,
+                                    stats,
+                                },
+                            ));
+                            if is_err {
+                                break;
+                            }
+                        }
+                        executions
+                    })
+                    .collect()
+            });
+
+        let mut executions: Vec<Option<ReceiptExecution>> = receipts.iter().map(|_| None).collect();
+        for (index, execution) in group_executions.into_iter().flatten() {
+            executions[index] = Some(execution);
+        }
+        executions
+    }
+
+    /// Reads and validates the delayed receipt with the given index from the state.
+    fn get_delayed_receipt(
+        &self,
+        state_update: &TrieUpdate,
+        index: u64,
+    ) -> Result<Receipt, RuntimeError> {
+        let receipt: Receipt =
+            get(state_update, &key_for_delayed_receipt(index))?.ok_or_else(|| {
+                StorageError::StorageInconsistentState(format!(
+                    "Delayed receipt #{} should be in the state",
+                    index
+                ))
+            })?;
+
+        // Validating the delayed receipt. If it fails, it's likely the state is inconsistent.
+        validate_receipt(&self.config.wasm_config.limit_config, &receipt).map_err(|e| {
+            StorageError::StorageInconsistentState(format!(
+                "Delayed receipt #{} in the state is invalid: {}",
+                index, e
+            ))
+        })?;
+        Ok(receipt)
+    }
+
     // Adds the given receipt into the end of the delayed receipt queue in the state.
     fn delay_receipt(
         state_update: &mut TrieUpdate,
@@ -1263,8 +1724,10 @@ mod tests {
     use near_crypto::{InMemorySigner, KeyType, Signer};
     use near_primitives::errors::ReceiptValidationError;
     use near_primitives::hash::hash;
-    use near_primitives::transaction::TransferAction;
+    use near_primitives::test_utils::MockEpochInfoProvider;
+    use near_primitives::transaction::{DelegateAction, FunctionCallAction, TransferAction};
     use near_primitives::types::MerkleHash;
+    use near_store::get_access_key;
     use near_store::test_utils::create_trie;
     use testlib::runtime_utils::{alice_account, bob_account};
 
@@ -1334,7 +1797,10 @@ mod tests {
             epoch_length: 3,
             gas_price: GAS_PRICE,
             block_timestamp: 100,
+            epoch_id: Default::default(),
+            epoch_height: 0,
             gas_limit: Some(gas_limit),
+            cache: None,
         };
 
         (runtime, trie, root, apply_state, signer)
@@ -1343,7 +1809,9 @@ mod tests {
     #[test]
     fn test_apply_no_op() {
         let (runtime, trie, root, apply_state, _) = setup_runtime(1_000_000, 0, 10_000_000);
-        runtime.apply(trie, root, &None, &apply_state, &[], &[]).unwrap();
+        runtime
+            .apply(trie, root, &None, &apply_state, &[], &[], &MockEpochInfoProvider::default())
+            .unwrap();
     }
 
     #[test]
@@ -1370,6 +1838,7 @@ mod tests {
                 &apply_state,
                 &[Receipt::new_refund(&alice_account(), small_refund)],
                 &[],
+                &MockEpochInfoProvider::default(),
             )
             .unwrap();
     }
@@ -1389,8 +1858,17 @@ mod tests {
         // Checking n receipts delayed by 1 + 3 extra
         for i in 1..=n + 3 {
             let prev_receipts: &[Receipt] = if i == 1 { &receipts } else { &[] };
-            let apply_result =
-                runtime.apply(trie.clone(), root, &None, &apply_state, prev_receipts, &[]).unwrap();
+            let apply_result = runtime
+                .apply(
+                    trie.clone(),
+                    root,
+                    &None,
+                    &apply_state,
+                    prev_receipts,
+                    &[],
+                    &MockEpochInfoProvider::default(),
+                )
+                .unwrap();
             let (store_update, new_root) = apply_result.trie_changes.into(trie.clone()).unwrap();
             root = new_root;
             store_update.commit().unwrap();
@@ -1426,8 +1904,17 @@ mod tests {
         // Every time we'll process 3 receipts, so we need n / 3 rounded up. Then we do 3 extra.
         for i in 1..=n / 3 + 3 {
             let prev_receipts: &[Receipt] = receipt_chunks.next().unwrap_or_default();
-            let apply_result =
-                runtime.apply(trie.clone(), root, &None, &apply_state, prev_receipts, &[]).unwrap();
+            let apply_result = runtime
+                .apply(
+                    trie.clone(),
+                    root,
+                    &None,
+                    &apply_state,
+                    prev_receipts,
+                    &[],
+                    &MockEpochInfoProvider::default(),
+                )
+                .unwrap();
             let (store_update, new_root) = apply_result.trie_changes.into(trie.clone()).unwrap();
             root = new_root;
             store_update.commit().unwrap();
@@ -1472,8 +1959,17 @@ mod tests {
             apply_state.gas_limit = Some(num_receipts_per_block * receipt_gas_cost);
             let prev_receipts: &[Receipt] = receipt_chunks.next().unwrap_or_default();
             num_receipts_given += prev_receipts.len() as u64;
-            let apply_result =
-                runtime.apply(trie.clone(), root, &None, &apply_state, prev_receipts, &[]).unwrap();
+            let apply_result = runtime
+                .apply(
+                    trie.clone(),
+                    root,
+                    &None,
+                    &apply_state,
+                    prev_receipts,
+                    &[],
+                    &MockEpochInfoProvider::default(),
+                )
+                .unwrap();
             let (store_update, new_root) = apply_result.trie_changes.into(trie.clone()).unwrap();
             root = new_root;
             store_update.commit().unwrap();
@@ -1557,6 +2053,7 @@ mod tests {
                 &apply_state,
                 &receipts[0..2],
                 &local_transactions[0..4],
+                &MockEpochInfoProvider::default(),
             )
             .unwrap();
         let (store_update, root) = apply_result.trie_changes.into(trie.clone()).unwrap();
@@ -1588,6 +2085,7 @@ mod tests {
                 &apply_state,
                 &receipts[2..3],
                 &local_transactions[4..5],
+                &MockEpochInfoProvider::default(),
             )
             .unwrap();
         let (store_update, root) = apply_result.trie_changes.into(trie.clone()).unwrap();
@@ -1616,6 +2114,7 @@ mod tests {
                 &apply_state,
                 &receipts[3..4],
                 &local_transactions[5..9],
+                &MockEpochInfoProvider::default(),
             )
             .unwrap();
         let (store_update, root) = apply_result.trie_changes.into(trie.clone()).unwrap();
@@ -1639,8 +2138,17 @@ mod tests {
         // We process R#1, R#2, TX#8.
         // R#4 is added to delayed queue.
         // The new delayed queue is R#3, R#4
-        let apply_result =
-            runtime.apply(trie.clone(), root, &None, &apply_state, &receipts[4..5], &[]).unwrap();
+        let apply_result = runtime
+            .apply(
+                trie.clone(),
+                root,
+                &None,
+                &apply_state,
+                &receipts[4..5],
+                &[],
+                &MockEpochInfoProvider::default(),
+            )
+            .unwrap();
         let (store_update, root) = apply_result.trie_changes.into(trie.clone()).unwrap();
         store_update.commit().unwrap();
 
@@ -1657,8 +2165,17 @@ mod tests {
         // STEP #5. Pass no new TXs and 1 receipt R#5.
         // We process R#3, R#4, R#5.
         // The new delayed queue is empty.
-        let apply_result =
-            runtime.apply(trie.clone(), root, &None, &apply_state, &receipts[5..6], &[]).unwrap();
+        let apply_result = runtime
+            .apply(
+                trie.clone(),
+                root,
+                &None,
+                &apply_state,
+                &receipts[5..6],
+                &[],
+                &MockEpochInfoProvider::default(),
+            )
+            .unwrap();
 
         assert_eq!(
             apply_result.outcomes.iter().map(|o| o.id).collect::<Vec<_>>(),
@@ -1671,6 +2188,333 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_apply_gas_profile() {
+        let (runtime, trie, root, apply_state, signer) =
+            setup_runtime(10u128.pow(24), 0, 10u64.pow(15));
+
+        let transaction = SignedTransaction::send_money(
+            1,
+            alice_account(),
+            alice_account(),
+            &*signer,
+            10_000,
+            CryptoHash::default(),
+        );
+        let apply_result = runtime
+            .apply(
+                trie,
+                root,
+                &None,
+                &apply_state,
+                &[],
+                &[transaction],
+                &MockEpochInfoProvider::default(),
+            )
+            .unwrap();
+
+        assert_eq!(apply_result.outcomes.len(), 2);
+        let fees = &runtime.config.transaction_costs;
+        assert_eq!(apply_result.gas_profiles.len(), 2);
+        assert_eq!(apply_result.gas_profiles[0].0, apply_result.outcomes[0].id);
+        let tx_profile = apply_result.gas_profiles[0].1.clone();
+        assert_eq!(tx_profile.wasm_gas, 0);
+        assert_eq!(
+            tx_profile
+                .action_costs
+                .iter()
+                .map(|c| (c.cost.as_str(), c.gas_used))
+                .collect::<Vec<_>>(),
+            vec![
+                ("new_receipt", fees.action_receipt_creation_config.send_fee(true)),
+                ("transfer", fees.action_creation_config.transfer_cost.send_fee(true)),
+            ]
+        );
+        assert_eq!(
+            tx_profile.action_costs.iter().map(|c| c.gas_used).sum::<Gas>(),
+            apply_result.outcomes[0].outcome.gas_burnt
+        );
+        assert_eq!(apply_result.gas_profiles[1].0, apply_result.outcomes[1].id);
+        let receipt_profile = apply_result.gas_profiles[1].1.clone();
+        assert_eq!(
+            receipt_profile
+                .action_costs
+                .iter()
+                .map(|c| (c.cost.as_str(), c.gas_used))
+                .collect::<Vec<_>>(),
+            vec![
+                ("new_receipt", fees.action_receipt_creation_config.exec_fee()),
+                ("transfer", fees.action_creation_config.transfer_cost.exec_fee()),
+            ]
+        );
+        assert_eq!(
+            receipt_profile.action_costs.iter().map(|c| c.gas_used).sum::<Gas>(),
+            apply_result.outcomes[1].outcome.gas_burnt
+        );
+    }
+
+    #[test]
+    fn test_simulate_transaction() {
+        let initial_balance = 10u128.pow(24);
+        let (runtime, trie, root, mut apply_state, signer) = setup_runtime(initial_balance, 0, 1);
+
+        let send_money = |receiver_id: AccountId| {
+            let mut transaction = SignedTransaction::send_money(
+                1,
+                alice_account(),
+                receiver_id,
+                &*signer,
+                10_000,
+                CryptoHash::default(),
+            );
+            transaction.signature = signer.sign(CryptoHash::default().as_ref());
+            transaction
+        };
+        let simulate =
+            |transaction: &SignedTransaction, verify_signature: bool, apply_state: &ApplyState| {
+                runtime.simulate_transaction(
+                    trie.clone(),
+                    root,
+                    apply_state,
+                    transaction,
+                    verify_signature,
+                    &|account_id: &AccountId| *account_id == alice_account(),
+                    &MockEpochInfoProvider::default(),
+                )
+            };
+
+        // The gas burnt by the transaction exhausts the gas limit of 1, so its receipt is pending.
+        let transaction = send_money(alice_account());
+        let result = simulate(&transaction, false, &apply_state).unwrap();
+        assert_eq!(result.outcomes.len(), 1);
+        assert_eq!(result.pending_receipts.len(), 1);
+
+        apply_state.gas_limit = None;
+        assert!(simulate(&transaction, true, &apply_state).is_err());
+
+        let result = simulate(&transaction, false, &apply_state).unwrap();
+        // The transaction and its receipt.
+        assert_eq!(result.outcomes.len(), 2);
+        assert_eq!(result.outcomes[0].id, transaction.get_hash());
+        assert!(result.pending_receipts.is_empty());
+        assert!(!result.key_value_changes.is_empty());
+
+        let result = simulate(&send_money(bob_account()), false, &apply_state).unwrap();
+        assert_eq!(result.outcomes.len(), 1);
+        assert_eq!(result.pending_receipts.len(), 1);
+
+        // The state is not modified.
+        let state_update = TrieUpdate::new(trie.clone(), root);
+        let account = get_account(&state_update, &alice_account()).unwrap().unwrap();
+        assert_eq!(account.amount, initial_balance);
+    }
+
+    #[test]
+    fn test_apply_delegate_action() {
+        let initial_balance = 10u128.pow(24);
+        let (_, trie, mut root, apply_state, relayer_signer) =
+            setup_runtime(initial_balance, 0, 10u64.pow(15));
+        let mut config = RuntimeConfig::default();
+        config.delegate_action_enabled = true;
+        let runtime = Runtime::new(config);
+        let relayer_id = alice_account();
+        let sender_id = bob_account();
+        let receiver_id = eve_dot_alice_account();
+        let sender_signer = InMemorySigner::from_seed(&sender_id, KeyType::ED25519, &sender_id);
+        let mut state_update = TrieUpdate::new(trie.clone(), root);
+        set_account(&mut state_update, &sender_id, &Account::new(initial_balance, hash(&[]), 0));
+        set_account(&mut state_update, &receiver_id, &Account::new(initial_balance, hash(&[]), 0));
+        set_access_key(
+            &mut state_update,
+            &sender_id,
+            &sender_signer.public_key(),
+            &AccessKey::full_access(),
+        );
+        state_update.commit(StateChangeCause::InitialState);
+        let (store_update, new_root) = state_update.finalize().unwrap().into(trie.clone()).unwrap();
+        store_update.commit().unwrap();
+        root = new_root;
+
+        let signed_delegate_action = DelegateAction {
+            sender_id: sender_id.clone(),
+            receiver_id: receiver_id.clone(),
+            actions: vec![Action::Transfer(TransferAction { deposit: 100 })],
+            nonce: 1,
+            max_block_height: 10,
+            public_key: sender_signer.public_key(),
+        }
+        .sign(&sender_signer);
+        // Applies the relayer transaction and all the receipts it produces.
+        let relay = |runtime: &Runtime, nonce: Nonce, mut root: CryptoHash| {
+            let transaction = SignedTransaction::from_actions(
+                nonce,
+                relayer_id.clone(),
+                sender_id.clone(),
+                &*relayer_signer,
+                vec![Action::Delegate(signed_delegate_action.clone())],
+                CryptoHash::default(),
+            );
+            let mut transactions = vec![transaction];
+            let mut receipts = vec![];
+            let mut outcomes = vec![];
+            while !transactions.is_empty() || !receipts.is_empty() {
+                let apply_result = runtime
+                    .apply(
+                        trie.clone(),
+                        root,
+                        &None,
+                        &apply_state,
+                        &receipts,
+                        &transactions,
+                        &MockEpochInfoProvider::default(),
+                    )
+                    .unwrap();
+                let (store_update, new_root) =
+                    apply_result.trie_changes.into(trie.clone()).unwrap();
+                store_update.commit().unwrap();
+                root = new_root;
+                transactions.clear();
+                receipts = apply_result.outgoing_receipts;
+                outcomes.extend(apply_result.outcomes);
+            }
+            (outcomes, root)
+        };
+
+        let (outcomes, root) = relay(&runtime, 1, root);
+        // The transaction, the delegate action and the inner transfer. All the prepaid gas is
+        // used, so there are no refunds.
+        assert_eq!(outcomes.len(), 3);
+        let inner_receipt_id = match outcomes[1].outcome.status {
+            ExecutionStatus::SuccessReceiptId(receipt_id) => receipt_id,
+            ref status => panic!("unexpected status {:?}", status),
+        };
+        assert_eq!(outcomes[2].id, inner_receipt_id);
+        assert_eq!(outcomes[2].outcome.status, ExecutionStatus::SuccessValue(vec![]));
+        let state_update = TrieUpdate::new(trie.clone(), root);
+        // The sender pays the deposits of the relayed actions, but not the gas.
+        let sender = get_account(&state_update, &sender_id).unwrap().unwrap();
+        assert_eq!(sender.amount, initial_balance - 100);
+        let receiver = get_account(&state_update, &receiver_id).unwrap().unwrap();
+        assert_eq!(receiver.amount, initial_balance + 100);
+        // The relayer pays for all the burnt gas and nothing else.
+        let relayer = get_account(&state_update, &relayer_id).unwrap().unwrap();
+        let gas_burnt: Gas = outcomes.iter().map(|outcome| outcome.outcome.gas_burnt).sum();
+        assert_eq!(relayer.amount, initial_balance - gas_burnt as Balance * GAS_PRICE);
+        let access_key =
+            get_access_key(&state_update, &sender_id, &sender_signer.public_key()).unwrap();
+        assert_eq!(access_key.unwrap().nonce, 1);
+
+        // The same delegate action can't be relayed twice. The relayer gets a refund.
+        let (outcomes, _) = relay(&runtime, 2, root);
+        assert_eq!(outcomes.len(), 3);
+        assert_eq!(
+            outcomes[1].outcome.status,
+            ExecutionStatus::Failure(TxExecutionError::ActionError(ActionError {
+                index: Some(0),
+                kind: ActionErrorKind::DelegateActionInvalidNonce {
+                    delegate_nonce: 1,
+                    ak_nonce: 1
+                },
+            }))
+        );
+    }
+
+    #[test]
+    fn test_apply_receipts_in_batches() {
+        let (_, trie, root, mut apply_state, _) = setup_runtime(1_000_000, 0, 1);
+        let contracts = vec![AccountId::from("contract1.near"), AccountId::from("contract2.near")];
+        let code = ContractCode::new(
+            include_bytes!("../../near-vm-runner/tests/res/test_contract_rs.wasm").to_vec(),
+        );
+        let mut state_update = TrieUpdate::new(trie.clone(), root);
+        for account_id in &contracts {
+            set_account(&mut state_update, account_id, &Account::new(10u128.pow(24), code.hash, 0));
+            set_code(&mut state_update, account_id, &code);
+        }
+        state_update.commit(StateChangeCause::InitialState);
+        let (store_update, root) = state_update.finalize().unwrap().into(trie.clone()).unwrap();
+        store_update.commit().unwrap();
+
+        let receivers =
+            vec![contracts[0].clone(), alice_account(), contracts[1].clone(), "none.near".into()];
+        let receipts = (0..24u64)
+            .map(|i| {
+                let receiver_id = receivers[i as usize % receivers.len()].clone();
+                let action = if contracts.contains(&receiver_id) {
+                    let args = [i % 3, i].iter().flat_map(|x| x.to_le_bytes().to_vec()).collect();
+                    Action::FunctionCall(FunctionCallAction {
+                        method_name: "write_key_value".to_string(),
+                        args,
+                        gas: 10u64.pow(13),
+                        deposit: 0,
+                    })
+                } else {
+                    Action::Transfer(TransferAction { deposit: 1000 + Balance::from(i) })
+                };
+                Receipt {
+                    predecessor_id: bob_account(),
+                    receiver_id,
+                    receipt_id: create_nonce_with_nonce(&CryptoHash::default(), i),
+                    receipt: ReceiptEnum::Action(ActionReceipt {
+                        signer_id: bob_account(),
+                        signer_public_key: PublicKey::empty(KeyType::ED25519),
+                        gas_price: GAS_PRICE,
+                        output_data_receivers: vec![],
+                        input_data_ids: vec![],
+                        actions: vec![action],
+                    }),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // Every batch size has to give the same results as executing the receipts one by one.
+        // The first apply hits the gas limit, so the second one processes the delayed receipts.
+        let apply_in_batches = |batch_size: usize, apply_state: &ApplyState| {
+            let runtime = Runtime::new_with_execution_config(
+                RuntimeConfig::default(),
+                &ReceiptsExecutionConfig { batch_size, num_threads: 2, ..Default::default() },
+            );
+            let mut results = vec![];
+            let mut root = root;
+            for incoming_receipts in vec![&receipts[..], &[][..]] {
+                let result = runtime
+                    .apply(
+                        trie.clone(),
+                        root,
+                        &None,
+                        apply_state,
+                        incoming_receipts,
+                        &[],
+                        &MockEpochInfoProvider::default(),
+                    )
+                    .unwrap();
+                let (store_update, new_root) = result.trie_changes.into(trie.clone()).unwrap();
+                store_update.commit().unwrap();
+                root = new_root;
+                results.push((
+                    result.state_root,
+                    result.outcomes,
+                    result.outgoing_receipts,
+                    result.validator_proposals,
+                    result.key_value_changes,
+                    result.stats,
+                ));
+            }
+            results
+        };
+
+        apply_state.gas_limit = None;
+        let total_gas_burnt: Gas =
+            apply_in_batches(1, &apply_state)[0].1.iter().map(|o| o.outcome.gas_burnt).sum();
+        apply_state.gas_limit = Some(total_gas_burnt / 2);
+        let expected = apply_in_batches(1, &apply_state);
+        assert!(expected[0].1.len() < receipts.len());
+        assert_eq!(expected[0].1.len() + expected[1].1.len(), receipts.len());
+        for batch_size in &[2, 5, 64] {
+            assert_eq!(apply_in_batches(*batch_size, &apply_state), expected);
+        }
+    }
+
     #[test]
     fn test_apply_invalid_incoming_receipts() {
         let initial_balance = 1_000_000;
@@ -1685,8 +2529,18 @@ mod tests {
         let invalid_account_id = "Invalid".to_string();
         receipts.get_mut(0).unwrap().predecessor_id = invalid_account_id.clone();
 
-        let err =
-            runtime.apply(trie.clone(), root, &None, &apply_state, &receipts, &[]).err().unwrap();
+        let err = runtime
+            .apply(
+                trie.clone(),
+                root,
+                &None,
+                &apply_state,
+                &receipts,
+                &[],
+                &MockEpochInfoProvider::default(),
+            )
+            .err()
+            .unwrap();
         assert_eq!(
             err,
             RuntimeError::ReceiptValidationError(ReceiptValidationError::InvalidPredecessorId {
@@ -1720,7 +2574,18 @@ mod tests {
         let (store_update, root) = trie_changes.into(trie.clone()).unwrap();
         store_update.commit().unwrap();
 
-        let err = runtime.apply(trie.clone(), root, &None, &apply_state, &[], &[]).err().unwrap();
+        let err = runtime
+            .apply(
+                trie.clone(),
+                root,
+                &None,
+                &apply_state,
+                &[],
+                &[],
+                &MockEpochInfoProvider::default(),
+            )
+            .err()
+            .unwrap();
         assert_eq!(
             err,
             RuntimeError::StorageError(StorageError::StorageInconsistentState(format!(
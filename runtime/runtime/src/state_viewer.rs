@@ -8,9 +8,9 @@ use near_crypto::{KeyType, PublicKey};
 use near_primitives::account::{AccessKey, Account};
 use near_primitives::hash::CryptoHash;
 use near_primitives::serialize::to_base64;
-use near_primitives::types::{AccountId, BlockHeight};
+use near_primitives::types::{AccountId, BlockHeight, EpochHeight, EpochId, EpochInfoProvider};
 use near_primitives::utils::{is_valid_account_id, prefix_for_data};
-use near_primitives::views::{StateItem, ViewStateResult};
+use near_primitives::views::{CallerContext, StateItem, ViewStateResult};
 use near_runtime_fees::RuntimeFeesConfig;
 use near_store::{get_access_key, get_account, TrieUpdate};
 use near_vm_logic::{ReturnData, VMConfig, VMContext};
@@ -87,9 +87,13 @@ impl TrieViewer {
         mut state_update: TrieUpdate,
         block_height: BlockHeight,
         block_timestamp: u64,
+        epoch_height: EpochHeight,
+        epoch_id: &EpochId,
+        epoch_info_provider: &dyn EpochInfoProvider,
         contract_id: &AccountId,
         method_name: &str,
         args: &[u8],
+        caller: &CallerContext,
         logs: &mut Vec<String>,
     ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
         let now = Instant::now();
@@ -102,9 +106,16 @@ impl TrieViewer {
         let code = get_code_with_cache(&state_update, contract_id, &account)?.ok_or_else(|| {
             format!("cannot find contract code for account {}", contract_id.clone())
         })?;
-        // TODO(#1015): Add ability to pass public key and originator_id
-        let originator_id = contract_id;
-        let public_key = PublicKey::empty(KeyType::ED25519);
+        let originator_id = caller.signer_account_id.as_ref().unwrap_or(contract_id);
+        let predecessor_id = caller.predecessor_account_id.as_ref().unwrap_or(originator_id);
+        for account_id in &[originator_id, predecessor_id] {
+            if !is_valid_account_id(account_id) {
+                return Err(format!("Caller account ID {:?} is not valid", account_id).into());
+            }
+        }
+        let public_key =
+            caller.signer_public_key.clone().unwrap_or_else(|| PublicKey::empty(KeyType::ED25519));
+        let attached_deposit = caller.attached_deposit.unwrap_or(0);
         let (outcome, err) = {
             let empty_hash = CryptoHash::default();
             let mut runtime_ext = RuntimeExt::new(
@@ -114,20 +125,23 @@ impl TrieViewer {
                 &public_key,
                 0,
                 &empty_hash,
+                epoch_id,
+                epoch_info_provider,
             );
 
             let context = VMContext {
                 current_account_id: contract_id.clone(),
                 signer_account_id: originator_id.clone(),
                 signer_account_pk: public_key.try_to_vec().expect("Failed to serialize"),
-                predecessor_account_id: originator_id.clone(),
+                predecessor_account_id: predecessor_id.clone(),
                 input: args.to_owned(),
                 block_index: block_height,
                 block_timestamp,
+                epoch_height,
                 account_balance: account.amount,
                 account_locked_balance: account.locked,
                 storage_usage: account.storage_usage,
-                attached_deposit: 0,
+                attached_deposit,
                 prepaid_gas: 0,
                 random_seed: root.as_ref().into(),
                 is_view: true,
@@ -143,6 +157,7 @@ impl TrieViewer {
                 &VMConfig::default(),
                 &RuntimeFeesConfig::default(),
                 &[],
+                None,
             )
         };
         let elapsed = now.elapsed();
@@ -176,6 +191,7 @@ impl TrieViewer {
 
 #[cfg(test)]
 mod tests {
+    use near_primitives::test_utils::MockEpochInfoProvider;
     use near_primitives::types::StateChangeCause;
     use near_primitives::utils::key_for_data;
     use near_primitives::views::StateItem;
@@ -194,9 +210,13 @@ mod tests {
             root,
             1,
             1,
+            0,
+            &EpochId::default(),
+            &MockEpochInfoProvider::default(),
             &AccountId::from("test.contract"),
             "run_test",
             &[],
+            &CallerContext::default(),
             &mut logs,
         );
 
@@ -212,9 +232,13 @@ mod tests {
             root,
             1,
             1,
+            0,
+            &EpochId::default(),
+            &MockEpochInfoProvider::default(),
             &"bad!contract".to_string(),
             "run_test",
             &[],
+            &CallerContext::default(),
             &mut logs,
         );
 
@@ -230,9 +254,13 @@ mod tests {
             root,
             1,
             1,
+            0,
+            &EpochId::default(),
+            &MockEpochInfoProvider::default(),
             &alice_account(),
             "run_test_with_storage_change",
             &[],
+            &CallerContext::default(),
             &mut logs,
         );
         // run_test tries to change storage, so it should fail
@@ -248,14 +276,84 @@ mod tests {
             root,
             1,
             1,
+            0,
+            &EpochId::default(),
+            &MockEpochInfoProvider::default(),
             &AccountId::from("test.contract"),
             "sum_with_input",
             &args,
+            &CallerContext::default(),
             &mut logs,
         );
         assert_eq!(view_call_result.unwrap(), 3u64.to_le_bytes().to_vec());
     }
 
+    #[test]
+    fn test_view_call_with_caller_context() {
+        let call = |method_name: &str, caller: CallerContext| {
+            let (viewer, root) = get_test_trie_viewer();
+            let mut logs = vec![];
+            viewer
+                .call_function(
+                    root,
+                    1,
+                    1,
+                    0,
+                    &EpochId::default(),
+                    &MockEpochInfoProvider::default(),
+                    &AccountId::from("test.contract"),
+                    method_name,
+                    &[],
+                    &caller,
+                    &mut logs,
+                )
+                .unwrap()
+        };
+
+        assert_eq!(call("ext_predecessor_account_id", CallerContext::default()), b"test.contract");
+        let signer_only =
+            CallerContext { signer_account_id: Some(alice_account()), ..Default::default() };
+        assert_eq!(call("ext_signer_id", signer_only.clone()), alice_account().as_bytes());
+        assert_eq!(call("ext_predecessor_account_id", signer_only), alice_account().as_bytes());
+        let caller = CallerContext {
+            signer_account_id: Some(alice_account()),
+            predecessor_account_id: Some(AccountId::from("bob.near")),
+            signer_public_key: Some(PublicKey::empty(KeyType::SECP256K1)),
+            attached_deposit: Some(10),
+        };
+        assert_eq!(call("ext_signer_id", caller.clone()), alice_account().as_bytes());
+        assert_eq!(call("ext_predecessor_account_id", caller.clone()), b"bob.near");
+        assert_eq!(
+            call("ext_signer_pk", caller),
+            PublicKey::empty(KeyType::SECP256K1).try_to_vec().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_view_call_bad_caller_id() {
+        let (viewer, root) = get_test_trie_viewer();
+
+        let mut logs = vec![];
+        let result = viewer.call_function(
+            root,
+            1,
+            1,
+            0,
+            &EpochId::default(),
+            &MockEpochInfoProvider::default(),
+            &AccountId::from("test.contract"),
+            "run_test",
+            &[],
+            &CallerContext {
+                predecessor_account_id: Some("bad!caller".to_string()),
+                ..Default::default()
+            },
+            &mut logs,
+        );
+
+        assert!(result.is_err());
+    }
+
     #[test]
     fn test_view_state() {
         let (_, trie, root) = get_runtime_and_trie();
@@ -310,9 +408,13 @@ mod tests {
                 root,
                 1,
                 1,
+                0,
+                &EpochId::default(),
+                &MockEpochInfoProvider::default(),
                 &AccountId::from("test.contract"),
                 "panic_after_logging",
                 &[],
+                &CallerContext::default(),
                 &mut logs,
             )
             .unwrap_err();
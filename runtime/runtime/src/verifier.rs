@@ -3,7 +3,7 @@ use crate::config::{
     safe_gas_to_balance, total_prepaid_gas, tx_cost, RuntimeConfig, TransactionCost,
 };
 use crate::{ApplyState, VerificationResult};
-use near_primitives::account::AccessKeyPermission;
+use near_primitives::account::{AccessKeyPermission, FunctionCallPermission};
 use near_primitives::errors::{
     ActionsValidationError, InvalidAccessKeyError, InvalidTxError, ReceiptValidationError,
     RuntimeError,
@@ -11,8 +11,9 @@ use near_primitives::errors::{
 use near_primitives::receipt::{ActionReceipt, DataReceipt, Receipt, ReceiptEnum};
 use near_primitives::transaction::{
     Action, AddKeyAction, DeleteAccountAction, DeployContractAction, FunctionCallAction,
-    SignedTransaction,
+    SignedDelegateAction, SignedTransaction, Transaction,
 };
+use near_primitives::types::AccountId;
 use near_primitives::utils::is_valid_account_id;
 use near_store::{get_access_key, get_account, set_access_key, set_account, TrieUpdate};
 use near_vm_logic::VMLimitConfig;
@@ -24,6 +25,18 @@ pub fn verify_and_charge_transaction(
     state_update: &mut TrieUpdate,
     apply_state: &ApplyState,
     signed_transaction: &SignedTransaction,
+) -> Result<VerificationResult, RuntimeError> {
+    charge_transaction(config, state_update, apply_state, signed_transaction, true)
+}
+
+/// Same as `verify_and_charge_transaction`, but the signature is only checked if
+/// `verify_signature` is set. Unsigned transactions can only be used for simulations.
+pub(crate) fn charge_transaction(
+    config: &RuntimeConfig,
+    state_update: &mut TrieUpdate,
+    apply_state: &ApplyState,
+    signed_transaction: &SignedTransaction,
+    verify_signature: bool,
 ) -> Result<VerificationResult, RuntimeError> {
     let transaction = &signed_transaction.transaction;
     let signer_id = &transaction.signer_id;
@@ -37,15 +50,17 @@ pub fn verify_and_charge_transaction(
         .into());
     }
 
-    if !signed_transaction
-        .signature
-        .verify(signed_transaction.get_hash().as_ref(), &transaction.public_key)
+    if verify_signature
+        && !signed_transaction
+            .signature
+            .verify(signed_transaction.get_hash().as_ref(), &transaction.public_key)
     {
         return Err(InvalidTxError::InvalidSignature.into());
     }
 
     validate_actions(&config.wasm_config.limit_config, &transaction.actions)
         .map_err(|e| InvalidTxError::ActionsValidation(e))?;
+    verify_delegate_actions(config, apply_state, transaction, verify_signature)?;
 
     let mut signer = match get_account(state_update, signer_id)? {
         Some(signer) => signer,
@@ -110,48 +125,13 @@ pub fn verify_and_charge_transaction(
     }
 
     if let AccessKeyPermission::FunctionCall(ref function_call_permission) = access_key.permission {
-        if transaction.actions.len() != 1 {
-            return Err(InvalidTxError::InvalidAccessKeyError(
-                InvalidAccessKeyError::RequiresFullAccess,
-            )
-            .into());
-        }
-        if let Some(Action::FunctionCall(ref function_call)) = transaction.actions.get(0) {
-            if function_call.deposit > 0 {
-                return Err(InvalidTxError::InvalidAccessKeyError(
-                    InvalidAccessKeyError::DepositWithFunctionCall,
-                )
-                .into());
-            }
-            if transaction.receiver_id != function_call_permission.receiver_id {
-                return Err(InvalidTxError::InvalidAccessKeyError(
-                    InvalidAccessKeyError::ReceiverMismatch {
-                        tx_receiver: transaction.receiver_id.clone(),
-                        ak_receiver: function_call_permission.receiver_id.clone(),
-                    },
-                )
-                .into());
-            }
-            if !function_call_permission.method_names.is_empty()
-                && function_call_permission
-                    .method_names
-                    .iter()
-                    .all(|method_name| &function_call.method_name != method_name)
-            {
-                return Err(InvalidTxError::InvalidAccessKeyError(
-                    InvalidAccessKeyError::MethodNameMismatch {
-                        method_name: function_call.method_name.clone(),
-                    },
-                )
-                .into());
-            }
-        } else {
-            return Err(InvalidTxError::InvalidAccessKeyError(
-                InvalidAccessKeyError::RequiresFullAccess,
-            )
-            .into());
-        }
-    };
+        check_function_call_permission(
+            function_call_permission,
+            &transaction.receiver_id,
+            &transaction.actions,
+        )
+        .map_err(InvalidTxError::InvalidAccessKeyError)?;
+    }
 
     set_access_key(state_update, &signer_id, &transaction.public_key, &access_key);
     set_account(state_update, &signer_id, &signer);
@@ -162,6 +142,80 @@ pub fn verify_and_charge_transaction(
     Ok(VerificationResult { gas_burnt, gas_used, rent_paid, validator_reward })
 }
 
+/// Checks the delegate actions of the transaction: their signatures, expiration and senders.
+/// The access keys of the senders can belong to other shards, so their nonces and permissions are
+/// checked when the delegate actions are executed. The signatures are only checked if
+/// `verify_signature` is set.
+fn verify_delegate_actions(
+    config: &RuntimeConfig,
+    apply_state: &ApplyState,
+    transaction: &Transaction,
+    verify_signature: bool,
+) -> Result<(), InvalidTxError> {
+    for action in &transaction.actions {
+        let signed_delegate_action = match action {
+            Action::Delegate(signed_delegate_action) => signed_delegate_action,
+            _ => continue,
+        };
+        if !config.delegate_action_enabled {
+            return Err(InvalidTxError::DelegateActionNotEnabled);
+        }
+        let delegate_action = &signed_delegate_action.delegate_action;
+        if delegate_action.sender_id != transaction.receiver_id {
+            return Err(InvalidTxError::DelegateActionSenderDoesNotMatchReceiver {
+                sender_id: delegate_action.sender_id.clone(),
+                receiver_id: transaction.receiver_id.clone(),
+            });
+        }
+        if apply_state.block_index > delegate_action.max_block_height {
+            return Err(InvalidTxError::DelegateActionExpired {
+                max_block_height: delegate_action.max_block_height,
+                block_height: apply_state.block_index,
+            });
+        }
+        if verify_signature && !signed_delegate_action.verify() {
+            return Err(InvalidTxError::DelegateActionInvalidSignature);
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the given actions sent to `receiver_id` are allowed by a function call access key.
+/// Such key only allows a single function call without a deposit to one of its methods.
+pub(crate) fn check_function_call_permission(
+    function_call_permission: &FunctionCallPermission,
+    receiver_id: &AccountId,
+    actions: &[Action],
+) -> Result<(), InvalidAccessKeyError> {
+    if actions.len() != 1 {
+        return Err(InvalidAccessKeyError::RequiresFullAccess);
+    }
+    if let Some(Action::FunctionCall(ref function_call)) = actions.get(0) {
+        if function_call.deposit > 0 {
+            return Err(InvalidAccessKeyError::DepositWithFunctionCall);
+        }
+        if receiver_id != &function_call_permission.receiver_id {
+            return Err(InvalidAccessKeyError::ReceiverMismatch {
+                tx_receiver: receiver_id.clone(),
+                ak_receiver: function_call_permission.receiver_id.clone(),
+            });
+        }
+        if !function_call_permission.method_names.is_empty()
+            && function_call_permission
+                .method_names
+                .iter()
+                .all(|method_name| &function_call.method_name != method_name)
+        {
+            return Err(InvalidAccessKeyError::MethodNameMismatch {
+                method_name: function_call.method_name.clone(),
+            });
+        }
+        Ok(())
+    } else {
+        Err(InvalidAccessKeyError::RequiresFullAccess)
+    }
+}
+
 /// Validates a given receipt. Checks validity of the predecessor and receiver account IDs and
 /// the validity of the Action or Data receipt.
 pub(crate) fn validate_receipt(
@@ -274,6 +328,7 @@ pub fn validate_action(
         Action::AddKey(a) => validate_add_key_action(limit_config, a),
         Action::DeleteKey(_) => Ok(()),
         Action::DeleteAccount(a) => validate_delete_account_action(a),
+        Action::Delegate(a) => validate_delegate_action(limit_config, a),
     }
 }
 
@@ -365,6 +420,28 @@ fn validate_delete_account_action(
     Ok(())
 }
 
+/// Validates `SignedDelegateAction`. Checks that the `sender_id` and `receiver_id` are valid
+/// account IDs and validates the inner actions, which can't contain other delegate actions.
+fn validate_delegate_action(
+    limit_config: &VMLimitConfig,
+    action: &SignedDelegateAction,
+) -> Result<(), ActionsValidationError> {
+    let delegate_action = &action.delegate_action;
+    for account_id in &[&delegate_action.sender_id, &delegate_action.receiver_id] {
+        if !is_valid_account_id(account_id) {
+            return Err(ActionsValidationError::InvalidAccountId {
+                account_id: account_id.to_string(),
+            });
+        }
+    }
+    for inner_action in &delegate_action.actions {
+        if let Action::Delegate(_) = inner_action {
+            return Err(ActionsValidationError::NestedDelegateAction);
+        }
+    }
+    validate_actions(limit_config, &delegate_action.actions)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -374,7 +451,7 @@ mod tests {
     use near_primitives::hash::{hash, CryptoHash};
     use near_primitives::receipt::DataReceiver;
     use near_primitives::transaction::{
-        CreateAccountAction, DeleteKeyAction, StakeAction, TransferAction,
+        CreateAccountAction, DelegateAction, DeleteKeyAction, StakeAction, TransferAction,
     };
     use near_primitives::types::{Balance, Gas, MerkleHash, StateChangeCause};
     use near_store::test_utils::create_trie;
@@ -417,7 +494,10 @@ mod tests {
             epoch_length: 3,
             gas_price: 100,
             block_timestamp: 100,
+            epoch_id: Default::default(),
+            epoch_height: 0,
             gas_limit: Some(gas_limit),
+            cache: None,
         };
         (signer, TrieUpdate::new(trie.clone(), root), apply_state)
     }
@@ -1073,6 +1153,90 @@ mod tests {
         );
     }
 
+    /// Transaction from alice relaying a delegate action of `sender_id` that is signed by
+    /// `delegate_signer` for the key of bob.
+    fn delegate_transaction(
+        signer: &InMemorySigner,
+        sender_id: AccountId,
+        max_block_height: u64,
+        delegate_signer: &InMemorySigner,
+    ) -> SignedTransaction {
+        let signed_delegate_action = DelegateAction {
+            sender_id,
+            receiver_id: eve_dot_alice_account(),
+            actions: vec![Action::Transfer(TransferAction { deposit: 100 })],
+            nonce: 1,
+            max_block_height,
+            public_key: InMemorySigner::from_seed(&bob_account(), KeyType::ED25519, "bob")
+                .public_key(),
+        }
+        .sign(delegate_signer);
+        SignedTransaction::from_actions(
+            1,
+            alice_account(),
+            bob_account(),
+            signer,
+            vec![Action::Delegate(signed_delegate_action)],
+            CryptoHash::default(),
+        )
+    }
+
+    #[test]
+    fn test_validate_transaction_delegate_action() {
+        let mut config = RuntimeConfig::default();
+        let (signer, mut state_update, mut apply_state) =
+            setup_common(TESTING_INIT_BALANCE, 0, 10_000_000, Some(AccessKey::full_access()));
+        let bob_signer = InMemorySigner::from_seed(&bob_account(), KeyType::ED25519, "bob");
+        let transaction = delegate_transaction(&signer, bob_account(), 10, &bob_signer);
+
+        assert_eq!(
+            verify_and_charge_transaction(&config, &mut state_update, &apply_state, &transaction)
+                .expect_err("expected an error"),
+            RuntimeError::InvalidTxError(InvalidTxError::DelegateActionNotEnabled),
+        );
+
+        config.delegate_action_enabled = true;
+        assert_eq!(
+            verify_and_charge_transaction(
+                &config,
+                &mut state_update,
+                &apply_state,
+                &delegate_transaction(&signer, eve_dot_alice_account(), 10, &bob_signer),
+            )
+            .expect_err("expected an error"),
+            RuntimeError::InvalidTxError(
+                InvalidTxError::DelegateActionSenderDoesNotMatchReceiver {
+                    sender_id: eve_dot_alice_account(),
+                    receiver_id: bob_account(),
+                }
+            ),
+        );
+        assert_eq!(
+            verify_and_charge_transaction(
+                &config,
+                &mut state_update,
+                &apply_state,
+                &delegate_transaction(&signer, bob_account(), 10, &*signer),
+            )
+            .expect_err("expected an error"),
+            RuntimeError::InvalidTxError(InvalidTxError::DelegateActionInvalidSignature),
+        );
+
+        apply_state.block_index = 11;
+        assert_eq!(
+            verify_and_charge_transaction(&config, &mut state_update, &apply_state, &transaction)
+                .expect_err("expected an error"),
+            RuntimeError::InvalidTxError(InvalidTxError::DelegateActionExpired {
+                max_block_height: 10,
+                block_height: 11,
+            }),
+        );
+
+        apply_state.block_index = 10;
+        verify_and_charge_transaction(&config, &mut state_update, &apply_state, &transaction)
+            .expect("valid transaction");
+    }
+
     // Receipts
 
     #[test]
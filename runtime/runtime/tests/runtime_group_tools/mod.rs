@@ -4,6 +4,7 @@ use near_primitives::hash::{hash, CryptoHash};
 use near_primitives::receipt::Receipt;
 use near_primitives::serialize::to_base64;
 use near_primitives::state_record::StateRecord;
+use near_primitives::test_utils::MockEpochInfoProvider;
 use near_primitives::transaction::{ExecutionOutcomeWithId, SignedTransaction};
 use near_primitives::types::{Balance, MerkleHash};
 use near_primitives::views::AccountView;
@@ -57,7 +58,10 @@ impl StandaloneRuntime {
             epoch_length: 4,
             gas_price: 100,
             block_timestamp: 0,
+            epoch_id: Default::default(),
+            epoch_height: 0,
             gas_limit: None,
+            cache: None,
         };
 
         Self { apply_state, runtime, trie, signer, root: root }
@@ -70,7 +74,15 @@ impl StandaloneRuntime {
     ) -> (Vec<Receipt>, Vec<ExecutionOutcomeWithId>) {
         let apply_result = self
             .runtime
-            .apply(self.trie.clone(), self.root, &None, &self.apply_state, receipts, transactions)
+            .apply(
+                self.trie.clone(),
+                self.root,
+                &None,
+                &self.apply_state,
+                receipts,
+                transactions,
+                &MockEpochInfoProvider::default(),
+            )
             .unwrap();
 
         let (store_update, root) = apply_result.trie_changes.into(self.trie.clone()).unwrap();
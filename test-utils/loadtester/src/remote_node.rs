@@ -1,4 +1,3 @@
-use reqwest::Client as AsyncClient;
 use std::format;
 use std::net::SocketAddr;
 use std::sync::{Arc, RwLock};
@@ -6,27 +5,22 @@ use std::thread;
 use std::time::{Duration, Instant};
 
 use borsh::BorshSerialize;
-use futures::{future, future::BoxFuture, FutureExt, TryFutureExt};
+use futures::{future::BoxFuture, FutureExt};
 use near_crypto::{InMemorySigner, KeyType, PublicKey};
-use near_jsonrpc::client::message::Message;
+use near_jsonrpc::client::blocking::BlockingJsonRpcClient;
+use near_jsonrpc::client::ChunkId;
 use near_primitives::hash::CryptoHash;
 use near_primitives::serialize::to_base64;
 use near_primitives::transaction::SignedTransaction;
-use near_primitives::types::{AccountId, Nonce};
-use near_primitives::views::AccessKeyView;
-use reqwest::blocking::Client as SyncClient;
-use std::convert::TryInto;
+use near_primitives::types::{AccountId, BlockId, Nonce};
+use near_primitives::views::{AccessKeyView, FinalExecutionStatus};
 use testlib::user::rpc_user::RpcUser;
 use testlib::user::User;
 
 use log::{debug, info};
 
-const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
 /// Maximum number of blocks that can be fetched through a single RPC request.
 pub const MAX_BLOCKS_FETCH: u64 = 1;
-const VALUE_NOT_STR_ERR: &str = "Value is not str";
-const VALUE_NOT_ARR_ERR: &str = "Value is not array";
-const VALUE_NOT_NUM_ERR: &str = "Value is not number";
 
 /// Maximum number of times we retry a single RPC.
 const MAX_RETRIES_PER_RPC: usize = 10;
@@ -41,8 +35,7 @@ pub struct RemoteNode {
     pub signers: Vec<Arc<InMemorySigner>>,
     pub nonces: Vec<Nonce>,
     pub url: String,
-    sync_client: SyncClient,
-    async_client: Arc<AsyncClient>,
+    client: BlockingJsonRpcClient,
 }
 
 pub fn wait<F, T>(mut f: F) -> T
@@ -111,20 +104,8 @@ impl RemoteNode {
             .collect();
         let nonces = vec![0; signers.len()];
 
-        let sync_client = SyncClient::builder()
-            .use_rustls_tls()
-            .connect_timeout(CONNECT_TIMEOUT)
-            .build()
-            .unwrap();
-
-        let async_client = Arc::new(
-            AsyncClient::builder()
-                .use_rustls_tls()
-                .connect_timeout(CONNECT_TIMEOUT)
-                .build()
-                .unwrap(),
-        );
-        let mut result = Self { addr, signers, nonces, url, sync_client, async_client };
+        let client = BlockingJsonRpcClient::new(&url);
+        let mut result = Self { addr, signers, nonces, url, client };
 
         // Wait for the node to be up.
         wait(|| result.health_ok());
@@ -171,54 +152,34 @@ impl RemoteNode {
     }
 
     fn health_ok(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let url = format!("{}{}", self.url, "/status");
-        Ok(self.sync_client.get(url.as_str()).send().map(|_| ())?)
+        Ok(self.client.call(|client| client.health())?)
     }
 
-    /// Sends transaction using `broadcast_tx_sync` using non-blocking Futures.
+    /// Sends transaction using `broadcast_tx_async` on the blocking thread pool, so that the
+    /// caller's executor isn't blocked.
     pub fn add_transaction_async(
         &self,
         transaction: SignedTransaction,
     ) -> BoxFuture<'static, Result<String, String>> {
         let bytes = transaction.try_to_vec().unwrap();
-        let params = (to_base64(&bytes),);
-        let message = Message::request(
-            "broadcast_tx_async".to_string(),
-            Some(serde_json::to_value(&params).unwrap()),
-        );
-        self.async_client
-            .post(self.url.as_str())
-            .json(&message)
-            .send()
-            .and_then(|r| r.json::<serde_json::Value>())
-            .map_err(|err| format!("{}", err))
-            .and_then(|j| {
-                future::ready(
-                    j["result"]
-                        .as_str()
-                        .map(|s| s.to_string())
-                        .ok_or(VALUE_NOT_STR_ERR.to_string()),
-                )
-            })
-            .boxed()
+        let client = self.client.clone();
+        tokio::task::spawn_blocking(move || {
+            client
+                .call(|client| client.broadcast_tx_async(to_base64(&bytes)))
+                .map_err(|err| err.to_string())
+        })
+        .map(|result| result.map_err(|err| err.to_string()).and_then(|result| result))
+        .boxed()
     }
 
-    /// Sends transactions using `broadcast_tx_sync` using blocking code. Return hash of
+    /// Sends transactions using `broadcast_tx_async` using blocking code. Return hash of
     /// the transaction.
     pub fn add_transaction(
         &self,
         transaction: SignedTransaction,
     ) -> Result<String, Box<dyn std::error::Error>> {
         let bytes = transaction.try_to_vec().unwrap();
-        let params = (to_base64(&bytes),);
-        let message = Message::request(
-            "broadcast_tx_async".to_string(),
-            Some(serde_json::to_value(&params).unwrap()),
-        );
-        let result: serde_json::Value =
-            self.sync_client.post(self.url.as_str()).json(&message).send()?.json()?;
-
-        Ok(result["result"].as_str().ok_or(VALUE_NOT_STR_ERR)?.to_owned())
+        Ok(self.client.call(|client| client.broadcast_tx_async(to_base64(&bytes)))?)
     }
 
     pub fn add_transaction_committed(
@@ -226,75 +187,55 @@ impl RemoteNode {
         transaction: SignedTransaction,
     ) -> Result<(), Box<dyn std::error::Error>> {
         let bytes = transaction.try_to_vec().unwrap();
-        let params = (to_base64(&bytes),);
-        let message = Message::request(
-            "broadcast_tx_commit".to_string(),
-            Some(serde_json::to_value(&params).unwrap()),
-        );
-        let result: serde_json::Value =
-            self.sync_client.post(self.url.as_str()).json(&message).send()?.json()?;
-        info!("{:?}", result);
+        let outcome = self.client.call(|client| client.broadcast_tx_commit(to_base64(&bytes)))?;
+        info!("{:?}", outcome);
         Ok(())
     }
 
     /// Returns () if transaction is completed
-    pub fn transaction_committed(&self, hash: &String) -> Result<(), Box<dyn std::error::Error>> {
-        let params = (hash,);
-        let message =
-            Message::request("tx".to_string(), Some(serde_json::to_value(&params).unwrap()));
-
-        let response: serde_json::Value =
-            self.sync_client.post(self.url.as_str()).json(&message).send()?.json()?;
-
-        let status = response["result"]["status"].as_str().ok_or(VALUE_NOT_STR_ERR)?.to_owned();
-        if status == "Completed" {
+    pub fn transaction_committed(
+        &self,
+        hash: &String,
+        signer_id: &AccountId,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let outcome = self.client.call(|client| client.tx(hash.clone(), signer_id.clone()))?;
+        if let FinalExecutionStatus::SuccessValue(_) = outcome.status {
             debug!("txn completed: {}", hash);
             Ok(())
         } else {
-            Err(format!("txn not completed: {} status: {}", hash, status).into())
+            Err(format!("txn not completed: {} status: {:?}", hash, outcome.status).into())
         }
     }
 
     pub fn get_current_height(&self) -> Result<u64, Box<dyn std::error::Error>> {
-        let url = format!("{}{}", self.url, "/status");
-        let response: serde_json::Value = self.sync_client.get(url.as_str()).send()?.json()?;
-        Ok(response["sync_info"]["latest_block_height"].as_u64().ok_or(VALUE_NOT_NUM_ERR)?)
+        let status = self.client.call(|client| client.status())?;
+        Ok(status.sync_info.latest_block_height)
     }
 
     pub fn get_current_block_hash(&self) -> Result<CryptoHash, Box<dyn std::error::Error>> {
-        let url = format!("{}{}", self.url, "/status");
-        let response: serde_json::Value = self.sync_client.get(url.as_str()).send()?.json()?;
-        Ok(response["sync_info"]["latest_block_hash"]
-            .as_str()
-            .ok_or(VALUE_NOT_STR_ERR)?
-            .to_string()
-            .try_into()?)
+        let status = self.client.call(|client| client.status())?;
+        Ok(status.sync_info.latest_block_hash)
     }
 
+    /// Returns the number of transactions in the chunks of the block at the given height.
     pub fn get_transactions(&self, height: u64) -> Result<u64, Box<dyn std::error::Error>> {
-        let params = (height,);
-        let message =
-            Message::request("block".to_string(), Some(serde_json::to_value(&params).unwrap()));
-        let response: serde_json::Value = self
-            .sync_client
-            .post(&format!("http://{}", &self.addr.to_string()))
-            .json(&message)
-            .send()?
-            .json()?;
-
-        Ok(response["result"]["transactions"].as_array().ok_or(VALUE_NOT_ARR_ERR)?.len() as u64)
+        let block = self.client.call(|client| client.block_by_id(BlockId::Height(height)))?;
+        let mut num_transactions = 0;
+        for chunk_header in block.chunks {
+            let chunk_id = ChunkId::Hash(chunk_header.chunk_hash);
+            let chunk = self.client.call(|client| client.chunk(chunk_id))?;
+            num_transactions += chunk.transactions.len() as u64;
+        }
+        Ok(num_transactions)
     }
 
     pub fn peer_node_addrs(&self) -> Result<Vec<String>, Box<dyn std::error::Error>> {
-        let url = format!("{}{}", self.url, "/status");
-        let response: serde_json::Value = self.sync_client.get(url.as_str()).send()?.json()?;
-        Ok(response["active_peers"]
-            .as_array()
-            .ok_or(VALUE_NOT_ARR_ERR)?
+        let network_info = self.client.call(|client| client.network_info())?;
+        Ok(network_info
+            .active_peers
             .into_iter()
-            .map(|active_peer| {
-                let mut socket_addr =
-                    active_peer["addr"].as_str().unwrap().parse::<SocketAddr>().unwrap();
+            .filter_map(|active_peer| active_peer.addr)
+            .map(|mut socket_addr| {
                 socket_addr.set_port(3030);
                 socket_addr.to_string()
             })
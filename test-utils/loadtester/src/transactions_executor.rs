@@ -28,12 +28,12 @@ impl Executor {
             for tx in transactions {
                 let hash = wait(|| n.write().unwrap().add_transaction(tx.clone()));
                 debug!("txn to deploy contract submitted: {}", &hash);
-                hashes.push(hash);
+                hashes.push((hash, tx.transaction.signer_id));
             }
             // Wait for them to propagate.
             wait(|| {
-                for h in &hashes {
-                    try_wait(|| n.write().unwrap().transaction_committed(h))?;
+                for (h, signer_id) in &hashes {
+                    try_wait(|| n.write().unwrap().transaction_committed(h, signer_id))?;
                 }
                 Ok(())
             });
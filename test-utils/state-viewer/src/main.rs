@@ -311,7 +311,7 @@ fn main() {
         .get_matches();
 
     let home_dir = matches.value_of("home").map(|dir| Path::new(dir)).unwrap();
-    let mut near_config = load_config(home_dir);
+    let mut near_config = load_config(home_dir).expect("Invalid config");
 
     let store = create_store(&get_store_path(&home_dir));
 
@@ -3,13 +3,12 @@ use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
 
-use actix::System;
 use borsh::BorshSerialize;
-use futures::{Future, TryFutureExt};
 
 use near_client::StatusResponse;
 use near_crypto::{PublicKey, Signer};
-use near_jsonrpc::client::{new_client, JsonRpcClient};
+use near_jsonrpc::client::blocking::BlockingJsonRpcClient;
+use near_jsonrpc::client::JsonRpcError;
 use near_jsonrpc::ServerError;
 use near_primitives::hash::CryptoHash;
 use near_primitives::receipt::Receipt;
@@ -27,37 +26,34 @@ use crate::user::User;
 pub struct RpcUser {
     account_id: AccountId,
     signer: Arc<dyn Signer>,
-    addr: String,
+    client: BlockingJsonRpcClient,
 }
 
-impl RpcUser {
-    fn actix<F, Fut, R>(&self, f: F) -> R
-    where
-        Fut: Future<Output = R> + 'static,
-        F: FnOnce(JsonRpcClient) -> Fut + 'static,
-    {
-        let addr = self.addr.clone();
-        System::new("actix")
-            .block_on(async move { f(new_client(&format!("http://{}", addr))).await })
+/// Transaction errors are reported by the node as `ServerError`s.
+fn server_error(err: JsonRpcError) -> ServerError {
+    match err {
+        JsonRpcError::Server(err) => err,
+        err => panic!("Unexpected error {}", err),
     }
+}
 
+impl RpcUser {
     pub fn new(addr: &str, account_id: AccountId, signer: Arc<dyn Signer>) -> RpcUser {
-        RpcUser { account_id, addr: addr.to_owned(), signer }
+        let client = BlockingJsonRpcClient::new(&format!("http://{}", addr));
+        RpcUser { account_id, signer, client }
     }
 
     pub fn get_status(&self) -> Option<StatusResponse> {
-        self.actix(|mut client| client.status()).ok()
+        self.client.call(|client| client.status()).ok()
     }
 
     pub fn query(&self, path: String, data: &[u8]) -> Result<QueryResponse, String> {
         let data = to_base(data);
-        self.actix(move |mut client| {
-            client.query_by_path(path, data).map_err(|err| err.to_string())
-        })
+        self.client.call(|client| client.query_by_path(path, data)).map_err(|err| err.to_string())
     }
 
     pub fn validators(&self, block_id: MaybeBlockId) -> Result<EpochValidatorInfo, String> {
-        self.actix(move |mut client| client.validators(block_id).map_err(|err| err.to_string()))
+        self.client.call(|client| client.validators(block_id)).map_err(|err| err.to_string())
     }
 }
 
@@ -72,14 +68,9 @@ impl User for RpcUser {
 
     fn add_transaction(&self, transaction: SignedTransaction) -> Result<(), ServerError> {
         let bytes = transaction.try_to_vec().unwrap();
-        let _ = self
-            .actix(move |mut client| client.broadcast_tx_async(to_base64(&bytes)))
-            .map_err(|err| {
-                serde_json::from_value::<ServerError>(
-                    err.data.expect("server error must carry data"),
-                )
-                .expect("deserialize server error must be ok")
-            })?;
+        self.client
+            .call(|client| client.broadcast_tx_async(to_base64(&bytes)))
+            .map_err(server_error)?;
         Ok(())
     }
 
@@ -88,16 +79,13 @@ impl User for RpcUser {
         transaction: SignedTransaction,
     ) -> Result<FinalExecutionOutcomeView, ServerError> {
         let bytes = transaction.try_to_vec().unwrap();
-        let result = self.actix(move |mut client| client.broadcast_tx_commit(to_base64(&bytes)));
+        let result = self.client.call(|client| client.broadcast_tx_commit(to_base64(&bytes)));
         // Wait for one more block, to make sure all nodes actually apply the state transition.
         let height = self.get_best_height().unwrap();
         while height == self.get_best_height().unwrap() {
             thread::sleep(Duration::from_millis(50));
         }
-        match result {
-            Ok(outcome) => Ok(outcome),
-            Err(err) => Err(serde_json::from_value::<ServerError>(err.data.unwrap()).unwrap()),
-        }
+        result.map_err(server_error)
     }
 
     fn add_receipt(&self, _receipt: Receipt) -> Result<(), ServerError> {
@@ -114,7 +102,8 @@ impl User for RpcUser {
     }
 
     fn get_block(&self, height: BlockHeight) -> Option<BlockView> {
-        self.actix(move |mut client| client.block(BlockQueryInfo::BlockId(BlockId::Height(height))))
+        self.client
+            .call(|client| client.block(BlockQueryInfo::BlockId(BlockId::Height(height))))
             .ok()
     }
 
@@ -125,7 +114,7 @@ impl User for RpcUser {
     fn get_transaction_final_result(&self, hash: &CryptoHash) -> FinalExecutionOutcomeView {
         let account_id = self.account_id.clone();
         let hash = *hash;
-        self.actix(move |mut client| client.tx((&hash).into(), account_id)).unwrap()
+        self.client.call(|client| client.tx((&hash).into(), account_id)).unwrap()
     }
 
     fn get_state_root(&self) -> CryptoHash {
@@ -7,6 +7,7 @@ use near_jsonrpc::ServerError;
 use near_primitives::errors::{RuntimeError, TxExecutionError};
 use near_primitives::hash::CryptoHash;
 use near_primitives::receipt::Receipt;
+use near_primitives::test_utils::MockEpochInfoProvider;
 use near_primitives::transaction::SignedTransaction;
 use near_primitives::types::{AccountId, BlockHeightDelta, MerkleHash};
 use near_primitives::views::{
@@ -77,13 +78,22 @@ impl RuntimeUser {
             let mut client = self.client.write().expect(POISONED_LOCK_ERR);
             let apply_result = client
                 .runtime
-                .apply(client.trie.clone(), client.state_root, &None, &apply_state, &receipts, &txs)
+                .apply(
+                    client.trie.clone(),
+                    client.state_root,
+                    &None,
+                    &apply_state,
+                    &receipts,
+                    &txs,
+                    &MockEpochInfoProvider::default(),
+                )
                 .map_err(|e| match e {
                     RuntimeError::InvalidTxError(e) => {
                         ServerError::TxExecutionError(TxExecutionError::InvalidTxError(e))
                     }
                     RuntimeError::BalanceMismatchError(e) => panic!("{}", e),
                     RuntimeError::StorageError(e) => panic!("Storage error {:?}", e),
+                    RuntimeError::ValidatorError(e) => panic!("Validator error {:?}", e),
                     RuntimeError::UnexpectedIntegerOverflow => {
                         panic!("UnexpectedIntegerOverflow error")
                     }
@@ -113,8 +123,11 @@ impl RuntimeUser {
             block_index: 0,
             block_timestamp: 0,
             epoch_length: client.epoch_length,
+            epoch_id: Default::default(),
+            epoch_height: 0,
             gas_price: MIN_GAS_PRICE,
             gas_limit: None,
+            cache: None,
         }
     }
 
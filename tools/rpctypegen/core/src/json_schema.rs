@@ -0,0 +1,547 @@
+//! JSON schemas of the RPC request and response types. The schemas follow the `serde`
+//! representation of the types and reference each other through `#/components/schemas/`, so
+//! they can be used as the components of the OpenRPC description of the RPC API.
+
+use std::collections::BTreeMap;
+
+use serde_json::{json, Map, Value};
+use syn::{
+    Attribute, Data, DataEnum, DataStruct, DeriveInput, Fields, GenericArgument, Lit, Meta,
+    NestedMeta, PathArguments, Type,
+};
+
+pub const SCHEMA_REF_PREFIX: &str = "#/components/schemas/";
+
+/// Types that don't have schemas of their own: primitives, type aliases and types serialized
+/// as strings.
+fn known_type_schema(name: &str) -> Option<Value> {
+    match name {
+        "bool" => Some(json!({ "type": "boolean" })),
+        "u8" | "u16" | "u32" | "u64" | "u128" | "usize" | "i8" | "i16" | "i32" | "i64" | "i128"
+        | "isize" | "BlockHeight" | "BlockHeightDelta" | "EpochHeight" | "Gas" | "Nonce"
+        | "NumBlocks" | "NumSeats" | "NumShards" | "ShardId" | "StorageUsage" | "ValidatorId" => {
+            Some(json!({ "type": "integer" }))
+        }
+        "f32" | "f64" => Some(json!({ "type": "number" })),
+        "String" | "str" | "char" | "AccountId" | "Base64" | "ChunkHash" | "CryptoHash"
+        | "DateTime" | "EpochId" | "FunctionArgs" | "IpSubnet" | "MerkleHash" | "PeerId"
+        | "PublicKey" | "Signature" | "SocketAddr" | "StateRoot" | "StoreKey" => {
+            Some(json!({ "type": "string" }))
+        }
+        "ChallengesResult" => {
+            Some(json!({ "type": "array", "items": schema_ref("SlashedValidator") }))
+        }
+        "MaybeBlockId" => Some(nullable(schema_ref("BlockId"))),
+        "MerklePath" => Some(json!({ "type": "array", "items": schema_ref("MerklePathItem") })),
+        "StateChanges" => Some(json!({
+            "type": "object",
+            "additionalProperties": {
+                "type": "array",
+                "items": {
+                    "type": "array",
+                    "items": [
+                        schema_ref("StateChangeCause"),
+                        nullable(json!({ "type": "array", "items": { "type": "integer" } })),
+                    ],
+                },
+            },
+        })),
+        "TrieProofPath" => Some(json!({ "type": "array", "items": { "type": "string" } })),
+        _ => None,
+    }
+}
+
+/// Schemas of the fields serialized with the helpers of `near_primitives::serialize`.
+fn serialize_with_schema(with: &str) -> Option<Value> {
+    match with {
+        "u128_dec_format" | "u128_dec_format_compatible" | "base64_format" => {
+            Some(json!({ "type": "string" }))
+        }
+        "option_u128_dec_format" | "option_base64_format" => {
+            Some(nullable(json!({ "type": "string" })))
+        }
+        _ => None,
+    }
+}
+
+#[derive(Default)]
+struct SerdeAttrs {
+    rename: Option<String>,
+    rename_all: Option<String>,
+    tag: Option<String>,
+    content: Option<String>,
+    with: Option<String>,
+    untagged: bool,
+    flatten: bool,
+    skip: bool,
+    /// The field may be missing from the serialized object.
+    optional: bool,
+}
+
+fn serde_attrs(attrs: &[Attribute]) -> SerdeAttrs {
+    let mut result = SerdeAttrs::default();
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("serde")) {
+        let nested = match attr.parse_meta() {
+            Ok(Meta::List(list)) => list.nested,
+            _ => continue,
+        };
+        for meta in nested {
+            match meta {
+                NestedMeta::Meta(Meta::NameValue(name_value)) => {
+                    let value = match name_value.lit {
+                        Lit::Str(value) => value.value(),
+                        _ => continue,
+                    };
+                    let name = match name_value.path.get_ident() {
+                        Some(name) => name.to_string(),
+                        None => continue,
+                    };
+                    match name.as_str() {
+                        "rename" => result.rename = Some(value),
+                        "rename_all" => result.rename_all = Some(value),
+                        "tag" => result.tag = Some(value),
+                        "content" => result.content = Some(value),
+                        "with" | "serialize_with" => result.with = Some(value),
+                        "default" | "skip_serializing_if" => result.optional = true,
+                        _ => {}
+                    }
+                }
+                NestedMeta::Meta(Meta::Path(path)) => match path.get_ident() {
+                    Some(name) if name == "untagged" => result.untagged = true,
+                    Some(name) if name == "flatten" => result.flatten = true,
+                    Some(name) if name == "skip" || name == "skip_serializing" => {
+                        result.skip = true
+                    }
+                    Some(name) if name == "default" => result.optional = true,
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+    }
+    result
+}
+
+/// Joins the lines of the doc comments.
+fn description(attrs: &[Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("doc"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(Meta::NameValue(name_value)) => match name_value.lit {
+                Lit::Str(line) => Some(line.value().trim().to_string()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join("\n"))
+    }
+}
+
+fn with_description(mut schema: Value, attrs: &[Attribute]) -> Value {
+    if let (Some(description), Value::Object(object)) = (description(attrs), &mut schema) {
+        object.insert("description".to_string(), Value::String(description));
+    }
+    schema
+}
+
+fn nullable(schema: Value) -> Value {
+    json!({ "anyOf": [schema, { "type": "null" }] })
+}
+
+fn schema_ref(name: &str) -> Value {
+    json!({ "$ref": format!("{}{}", SCHEMA_REF_PREFIX, name) })
+}
+
+/// Applies the `rename_all` rule of `serde` to a variant name (`PascalCase`) or a field name
+/// (`snake_case`).
+fn rename(name: &str, rule: &str, is_variant: bool) -> String {
+    let snake_case = if is_variant {
+        let mut snake_case = String::new();
+        for (i, ch) in name.chars().enumerate() {
+            if ch.is_uppercase() && i > 0 {
+                snake_case.push('_');
+            }
+            snake_case.extend(ch.to_lowercase());
+        }
+        snake_case
+    } else {
+        name.to_string()
+    };
+    match rule {
+        "lowercase" if is_variant => name.to_lowercase(),
+        "UPPERCASE" if is_variant => name.to_uppercase(),
+        "lowercase" | "snake_case" => snake_case,
+        "UPPERCASE" | "SCREAMING_SNAKE_CASE" => snake_case.to_uppercase(),
+        "kebab-case" => snake_case.replace('_', "-"),
+        "SCREAMING-KEBAB-CASE" => snake_case.replace('_', "-").to_uppercase(),
+        "camelCase" | "PascalCase" => {
+            let mut result = String::new();
+            for (i, part) in snake_case.split('_').enumerate() {
+                let mut chars = part.chars();
+                if let Some(first) = chars.next() {
+                    if i == 0 && rule == "camelCase" {
+                        result.extend(first.to_lowercase());
+                    } else {
+                        result.extend(first.to_uppercase());
+                    }
+                    result.extend(chars);
+                }
+            }
+            result
+        }
+        _ => name.to_string(),
+    }
+}
+
+fn generic_args(arguments: &PathArguments) -> Vec<&Type> {
+    match arguments {
+        PathArguments::AngleBracketed(arguments) => arguments
+            .args
+            .iter()
+            .filter_map(|argument| match argument {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+fn is_option(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => {
+            path.path.segments.last().map_or(false, |segment| segment.ident == "Option")
+        }
+        _ => false,
+    }
+}
+
+fn type_schema(ty: &Type) -> Value {
+    match ty {
+        Type::Path(path) => {
+            let segment = match path.path.segments.last() {
+                Some(segment) => segment,
+                None => return json!({}),
+            };
+            let name = segment.ident.to_string();
+            let args = generic_args(&segment.arguments);
+            match (name.as_str(), args.as_slice()) {
+                ("Option", [inner]) => nullable(type_schema(inner)),
+                ("Box", [inner]) | ("Arc", [inner]) | ("Rc", [inner]) => type_schema(inner),
+                ("Vec", [inner]) | ("VecDeque", [inner]) => {
+                    json!({ "type": "array", "items": type_schema(inner) })
+                }
+                ("HashSet", [inner]) | ("BTreeSet", [inner]) => {
+                    json!({ "type": "array", "items": type_schema(inner), "uniqueItems": true })
+                }
+                ("HashMap", [_, value]) | ("BTreeMap", [_, value]) => {
+                    json!({ "type": "object", "additionalProperties": type_schema(value) })
+                }
+                _ => known_type_schema(&name).unwrap_or_else(|| schema_ref(&name)),
+            }
+        }
+        Type::Reference(reference) => type_schema(&reference.elem),
+        Type::Paren(paren) => type_schema(&paren.elem),
+        Type::Group(group) => type_schema(&group.elem),
+        Type::Slice(slice) => json!({ "type": "array", "items": type_schema(&slice.elem) }),
+        Type::Array(array) => json!({ "type": "array", "items": type_schema(&array.elem) }),
+        Type::Tuple(tuple) if tuple.elems.is_empty() => json!({ "type": "null" }),
+        Type::Tuple(tuple) => tuple_schema(tuple.elems.iter()),
+        _ => json!({}),
+    }
+}
+
+fn tuple_schema<'a>(types: impl ExactSizeIterator<Item = &'a Type>) -> Value {
+    let len = types.len();
+    let items: Vec<Value> = types.map(type_schema).collect();
+    json!({ "type": "array", "items": items, "minItems": len, "maxItems": len })
+}
+
+fn field_schema(ty: &Type, attrs: &SerdeAttrs) -> Value {
+    attrs
+        .with
+        .as_ref()
+        .and_then(|with| serialize_with_schema(with))
+        .unwrap_or_else(|| type_schema(ty))
+}
+
+/// Schema of the named fields, with the schemas of the flattened fields merged into it.
+fn named_fields_schema(fields: &Fields, rename_all: Option<&String>) -> Value {
+    let mut properties = Map::new();
+    let mut required = vec![];
+    let mut flattened = vec![];
+    for field in fields.iter() {
+        let attrs = serde_attrs(&field.attrs);
+        if attrs.skip {
+            continue;
+        }
+        let schema = with_description(field_schema(&field.ty, &attrs), &field.attrs);
+        if attrs.flatten {
+            flattened.push(schema);
+            continue;
+        }
+        let field_name = field.ident.as_ref().expect("named fields must have ident").to_string();
+        let name = match (&attrs.rename, rename_all) {
+            (Some(name), _) => name.clone(),
+            (None, Some(rule)) => rename(&field_name, rule, false),
+            (None, None) => field_name,
+        };
+        if !attrs.optional && !is_option(&field.ty) {
+            required.push(Value::String(name.clone()));
+        }
+        properties.insert(name, schema);
+    }
+    let mut schema = json!({ "type": "object", "properties": properties });
+    if !required.is_empty() {
+        schema["required"] = Value::Array(required);
+    }
+    if flattened.is_empty() {
+        schema
+    } else {
+        flattened.insert(0, schema);
+        json!({ "allOf": flattened })
+    }
+}
+
+fn fields_schema(fields: &Fields, rename_all: Option<&String>) -> Option<Value> {
+    match fields {
+        Fields::Named(_) => Some(named_fields_schema(fields, rename_all)),
+        Fields::Unnamed(unnamed) if unnamed.unnamed.len() == 1 => {
+            let field = &unnamed.unnamed[0];
+            Some(field_schema(&field.ty, &serde_attrs(&field.attrs)))
+        }
+        Fields::Unnamed(unnamed) => Some(tuple_schema(unnamed.unnamed.iter().map(|f| &f.ty))),
+        Fields::Unit => None,
+    }
+}
+
+fn enum_schema(data: &DataEnum, attrs: &SerdeAttrs) -> Value {
+    let mut unit_variants = vec![];
+    let mut schemas = vec![];
+    for variant in data.variants.iter() {
+        let variant_attrs = serde_attrs(&variant.attrs);
+        if variant_attrs.skip {
+            continue;
+        }
+        let variant_name = variant.ident.to_string();
+        let name = match (&variant_attrs.rename, &attrs.rename_all) {
+            (Some(name), _) => name.clone(),
+            (None, Some(rule)) => rename(&variant_name, rule, true),
+            (None, None) => variant_name,
+        };
+        let content = fields_schema(&variant.fields, variant_attrs.rename_all.as_ref());
+        let tag_schema = |tag: &String| {
+            json!({
+                "type": "object",
+                "properties": { tag.clone(): { "enum": [name] } },
+                "required": [tag],
+            })
+        };
+        let schema = if attrs.untagged {
+            content.unwrap_or_else(|| json!({ "type": "null" }))
+        } else {
+            match (&attrs.tag, &attrs.content, content) {
+                (Some(tag), None, None) => tag_schema(tag),
+                (Some(tag), None, Some(mut content)) => {
+                    if let Fields::Named(_) = variant.fields {
+                        content["properties"][tag] = json!({ "enum": [name] });
+                        let mut required = vec![Value::String(tag.clone())];
+                        required
+                            .extend(content["required"].as_array().cloned().unwrap_or_default());
+                        content["required"] = Value::Array(required);
+                        content
+                    } else {
+                        json!({ "allOf": [content, tag_schema(tag)] })
+                    }
+                }
+                (Some(tag), Some(content_name), content) => {
+                    let mut schema = tag_schema(tag);
+                    if let Some(content) = content {
+                        schema["properties"][content_name] = content;
+                        schema["required"] = json!([tag, content_name]);
+                    }
+                    schema
+                }
+                (None, _, None) => {
+                    unit_variants.push((name, description(&variant.attrs)));
+                    continue;
+                }
+                (None, _, Some(content)) => json!({
+                    "type": "object",
+                    "properties": { name.clone(): content },
+                    "required": [name],
+                    "additionalProperties": false,
+                }),
+            }
+        };
+        schemas.push(with_description(schema, &variant.attrs));
+    }
+    if schemas.is_empty() {
+        let names: Vec<String> = unit_variants.into_iter().map(|(name, _)| name).collect();
+        return json!({ "type": "string", "enum": names });
+    }
+    for (name, description) in unit_variants {
+        let mut schema = json!({ "type": "string", "enum": [name] });
+        if let Some(description) = description {
+            schema["description"] = Value::String(description);
+        }
+        schemas.push(schema);
+    }
+    if attrs.untagged {
+        json!({ "anyOf": schemas })
+    } else {
+        json!({ "oneOf": schemas })
+    }
+}
+
+/// Adds the JSON schema of the given type to the schemas by the type name.
+pub fn parse_json_schema(schemas: &mut BTreeMap<String, Value>, input: &DeriveInput) {
+    let attrs = serde_attrs(&input.attrs);
+    let schema = match &input.data {
+        Data::Struct(DataStruct { ref fields, .. }) => {
+            fields_schema(fields, attrs.rename_all.as_ref())
+                .unwrap_or_else(|| json!({ "type": "null" }))
+        }
+        Data::Enum(data) => enum_schema(data, &attrs),
+        Data::Union(_) => {
+            panic!("Unions are not supported");
+        }
+    };
+    schemas.insert(input.ident.to_string(), with_description(schema, &input.attrs));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quote::quote;
+
+    fn schema_of(input: proc_macro2::TokenStream) -> Value {
+        let mut schemas = BTreeMap::default();
+        let input: DeriveInput = syn::parse2(input).unwrap();
+        parse_json_schema(&mut schemas, &input);
+        schemas.remove(&input.ident.to_string()).unwrap()
+    }
+
+    #[test]
+    fn struct_fields() {
+        let schema = schema_of(quote! {
+            /// Gas price.
+            pub struct GasPriceView {
+                #[serde(with = "u128_dec_format")]
+                pub gas_price: Balance,
+                pub block_height: Option<BlockHeight>,
+                #[serde(skip)]
+                pub cached: bool,
+                pub header: BlockHeaderView,
+            }
+        });
+        assert_eq!(
+            schema,
+            json!({
+                "type": "object",
+                "properties": {
+                    "gas_price": { "type": "string" },
+                    "block_height": { "anyOf": [{ "type": "integer" }, { "type": "null" }] },
+                    "header": { "$ref": "#/components/schemas/BlockHeaderView" }
+                },
+                "required": ["gas_price", "header"],
+                "description": "Gas price."
+            })
+        );
+    }
+
+    #[test]
+    fn externally_tagged_enum() {
+        let schema = schema_of(quote! {
+            pub enum FinalExecutionStatus {
+                NotStarted,
+                Failure(TxExecutionError),
+            }
+        });
+        assert_eq!(
+            schema,
+            json!({
+                "oneOf": [
+                    {
+                        "type": "object",
+                        "properties": {
+                            "Failure": { "$ref": "#/components/schemas/TxExecutionError" }
+                        },
+                        "required": ["Failure"],
+                        "additionalProperties": false
+                    },
+                    { "type": "string", "enum": ["NotStarted"] }
+                ]
+            })
+        );
+        let schema = schema_of(quote! {
+            pub enum Finality {
+                #[serde(rename = "optimistic")]
+                None,
+                #[serde(rename = "final")]
+                NFG,
+            }
+        });
+        assert_eq!(schema, json!({ "type": "string", "enum": ["optimistic", "final"] }));
+    }
+
+    #[test]
+    fn internally_tagged_enum() {
+        let schema = schema_of(quote! {
+            #[serde(tag = "request_type", rename_all = "snake_case")]
+            pub enum QueryRequest {
+                ViewAccount { account_id: AccountId },
+            }
+        });
+        assert_eq!(
+            schema,
+            json!({
+                "oneOf": [{
+                    "type": "object",
+                    "properties": {
+                        "account_id": { "type": "string" },
+                        "request_type": { "enum": ["view_account"] }
+                    },
+                    "required": ["request_type", "account_id"]
+                }]
+            })
+        );
+    }
+
+    #[test]
+    fn untagged_enum_and_flatten() {
+        let schema = schema_of(quote! {
+            #[serde(untagged)]
+            pub enum BlockId {
+                Height(BlockHeight),
+                Hash(CryptoHash),
+            }
+        });
+        assert_eq!(schema, json!({ "anyOf": [{ "type": "integer" }, { "type": "string" }] }));
+        let schema = schema_of(quote! {
+            pub struct QueryResponse {
+                #[serde(flatten)]
+                pub kind: QueryResponseKind,
+                pub block_height: BlockHeight,
+            }
+        });
+        assert_eq!(
+            schema,
+            json!({
+                "allOf": [
+                    {
+                        "type": "object",
+                        "properties": { "block_height": { "type": "integer" } },
+                        "required": ["block_height"]
+                    },
+                    { "$ref": "#/components/schemas/QueryResponseKind" }
+                ]
+            })
+        );
+    }
+}
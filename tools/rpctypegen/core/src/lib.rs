@@ -6,6 +6,10 @@ use syn::{Data, DataEnum, DataStruct, DeriveInput, Fields, FieldsNamed, FieldsUn
 
 use serde::{Deserialize, Serialize};
 
+mod json_schema;
+
+pub use json_schema::{parse_json_schema, SCHEMA_REF_PREFIX};
+
 #[derive(Default, Debug, Deserialize, Serialize)]
 pub struct ErrorType {
     /// A type name of the error
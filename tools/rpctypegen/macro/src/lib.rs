@@ -1,23 +1,29 @@
 extern crate proc_macro;
 extern crate proc_macro2;
 
-use near_rpc_error_core::{parse_error_type, ErrorType};
+use near_rpc_error_core::{parse_error_type, parse_json_schema, ErrorType};
 use proc_macro::TokenStream;
 use serde::{Deserialize, Serialize};
-#[cfg(feature = "dump_errors_schema")]
 use serde_json::Value;
 use std::cell::RefCell;
 use std::collections::BTreeMap;
 use syn::{parse_macro_input, DeriveInput};
 
 thread_local!(static SCHEMA: RefCell<Schema> = RefCell::new(Schema::default()));
+thread_local!(static RPC_SCHEMA: RefCell<RpcSchema> = RefCell::new(RpcSchema::default()));
 
 #[derive(Default, Debug, Deserialize, Serialize)]
 struct Schema {
     pub schema: BTreeMap<String, ErrorType>,
 }
 
-#[cfg(feature = "dump_errors_schema")]
+/// JSON schemas of the RPC request and response types by the type name.
+#[derive(Default, Debug, Deserialize, Serialize)]
+struct RpcSchema {
+    pub schemas: BTreeMap<String, Value>,
+}
+
+#[cfg(any(feature = "dump_errors_schema", feature = "dump_rpc_schema"))]
 fn merge(a: &mut Value, b: &Value) {
     match (a, b) {
         (&mut Value::Object(ref mut a), &Value::Object(ref b)) => {
@@ -31,25 +37,36 @@ fn merge(a: &mut Value, b: &Value) {
     }
 }
 
+/// Merges the schema into the one already dumped to the file by the other crates.
+#[cfg(any(feature = "dump_errors_schema", feature = "dump_rpc_schema"))]
+fn dump_schema<T: Serialize>(filename: &str, schema: &T) {
+    let schema_json = serde_json::to_value(schema).expect("Schema serialize failed");
+    let new_schema_json = if let Ok(data) = std::fs::read(filename) {
+        // merge to the existing file
+        let mut existing_schema = serde_json::from_slice::<Value>(&data)
+            .unwrap_or_else(|_| panic!("cannot deserialize {}", filename));
+        merge(&mut existing_schema, &schema_json);
+        existing_schema
+    } else {
+        schema_json
+    };
+    let new_schema_json_string =
+        serde_json::to_string_pretty(&new_schema_json).expect("schema serialization failed");
+    std::fs::write(filename, new_schema_json_string).expect("Unable to save the schema file");
+}
+
 #[cfg(feature = "dump_errors_schema")]
 impl Drop for Schema {
     fn drop(&mut self) {
         // std::env::var("CARGO_TARGET_DIR") doesn't exists
-        let filename = "./target/rpc_errors_schema.json";
-        let schema_json = serde_json::to_value(self).expect("Schema serialize failed");
-        let new_schema_json = if let Ok(data) = std::fs::read(filename) {
-            // merge to the existing file
-            let mut existing_schema = serde_json::from_slice::<Value>(&data)
-                .expect("cannot deserialize target/existing_schema.json");
-            merge(&mut existing_schema, &schema_json);
-            existing_schema
-        } else {
-            schema_json
-        };
-        let new_schema_json_string = serde_json::to_string_pretty(&new_schema_json)
-            .expect("error schema serialization failed");
-        std::fs::write(filename, new_schema_json_string)
-            .expect("Unable to save the errors schema file");
+        dump_schema("./target/rpc_errors_schema.json", self);
+    }
+}
+
+#[cfg(feature = "dump_rpc_schema")]
+impl Drop for RpcSchema {
+    fn drop(&mut self) {
+        dump_schema("./target/rpc_schema.json", self);
     }
 }
 
@@ -62,3 +79,15 @@ pub fn rpc_error(input: TokenStream) -> TokenStream {
     });
     TokenStream::new()
 }
+
+/// Collects the JSON schema of an RPC request or response type, which is dumped to
+/// `target/rpc_schema.json` with the `dump_rpc_schema` feature.
+#[proc_macro_derive(RpcSchema)]
+pub fn rpc_schema(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    RPC_SCHEMA.with(|s| {
+        parse_json_schema(&mut s.borrow_mut().schemas, &input);
+    });
+    TokenStream::new()
+}